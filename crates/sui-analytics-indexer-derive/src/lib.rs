@@ -9,9 +9,17 @@ use syn::{parse_macro_input, Data, DeriveInput, Fields};
 pub fn schema_derive(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     let struct_name = &input.ident;
-    let (schema, getter_implementation) = match &input.data {
+    let (schema, types, getter_implementation) = match &input.data {
         Data::Struct(data_struct) => match &data_struct.fields {
             Fields::Named(fields) => {
+                let types: Vec<_> = fields
+                    .named
+                    .iter()
+                    .map(|field| {
+                        let field_type = &field.ty;
+                        quote! { <#field_type as ParquetColumn>::TYPE }
+                    })
+                    .collect();
                 let (schema_iter, getter_iter): (Vec<_>, Vec<_>) = fields
                     .named
                     .iter()
@@ -27,7 +35,7 @@ pub fn schema_derive(input: TokenStream) -> TokenStream {
                         )
                     })
                     .unzip();
-                (schema_iter.join(", "), getter_iter.join("\n"))
+                (schema_iter.join(", "), types, getter_iter.join("\n"))
             }
             _ => panic!("not supported struct for parquet serialization"),
         },
@@ -42,6 +50,10 @@ pub fn schema_derive(input: TokenStream) -> TokenStream {
                 vec![#schema_tokens]
             }
 
+            fn types() -> Vec<ParquetType> {
+                vec![#(#types),*]
+            }
+
             fn get_column(&self, idx: usize) -> ParquetValue {
                 #getter_implementation_tokens
                 panic!("not supported column {:?}", idx);
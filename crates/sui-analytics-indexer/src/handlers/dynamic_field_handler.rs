@@ -0,0 +1,283 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{anyhow, Result};
+use fastcrypto::encoding::{Base64, Encoding};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+use sui_indexer::framework::Handler;
+use sui_json_rpc_types::SuiMoveValue;
+use sui_package_resolver::Resolver;
+use sui_rest_api::{CheckpointData, CheckpointTransaction};
+use sui_types::base_types::ObjectID;
+use sui_types::dynamic_field::{
+    extract_field_from_move_struct, DynamicFieldInfo, DynamicFieldType,
+};
+use sui_types::effects::TransactionEffectsAPI;
+use sui_types::object::Object;
+
+use crate::handlers::{get_move_struct, get_owner_address, AnalyticsHandler, ObjectStatusTracker};
+use crate::package_store::{LocalDBPackageStore, PackageCache};
+use crate::tables::{DynamicFieldEntry, ObjectStatus};
+use crate::FileType;
+
+pub struct DynamicFieldHandler {
+    dynamic_fields: Vec<DynamicFieldEntry>,
+    package_store: LocalDBPackageStore,
+    resolver: Resolver<PackageCache>,
+}
+
+#[async_trait::async_trait]
+impl Handler for DynamicFieldHandler {
+    fn name(&self) -> &str {
+        "dynamic_field"
+    }
+    async fn process_checkpoint(&mut self, checkpoint_data: &CheckpointData) -> Result<()> {
+        let CheckpointData {
+            checkpoint_summary,
+            transactions: checkpoint_transactions,
+            ..
+        } = checkpoint_data;
+        for checkpoint_transaction in checkpoint_transactions {
+            for object in checkpoint_transaction.output_objects.iter() {
+                self.package_store.update(object)?;
+            }
+            self.process_transaction(
+                checkpoint_summary.epoch,
+                checkpoint_summary.sequence_number,
+                checkpoint_summary.timestamp_ms,
+                checkpoint_transaction,
+            )
+            .await?;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl AnalyticsHandler<DynamicFieldEntry> for DynamicFieldHandler {
+    fn read(&mut self) -> Result<Vec<DynamicFieldEntry>> {
+        let cloned = self.dynamic_fields.clone();
+        self.dynamic_fields.clear();
+        Ok(cloned)
+    }
+
+    fn file_type(&self) -> Result<FileType> {
+        Ok(FileType::DynamicField)
+    }
+}
+
+impl DynamicFieldHandler {
+    pub fn new(store_path: &Path, rest_uri: &str) -> Self {
+        let package_store = LocalDBPackageStore::new(&store_path.join("dynamic_field"), rest_uri);
+        DynamicFieldHandler {
+            dynamic_fields: vec![],
+            package_store: package_store.clone(),
+            resolver: Resolver::new(PackageCache::new(package_store)),
+        }
+    }
+    async fn process_transaction(
+        &mut self,
+        epoch: u64,
+        checkpoint: u64,
+        timestamp_ms: u64,
+        checkpoint_transaction: &CheckpointTransaction,
+    ) -> Result<()> {
+        let transaction_digest = checkpoint_transaction.transaction.digest().base58_encode();
+        let effects = &checkpoint_transaction.effects;
+        let object_status_tracker = ObjectStatusTracker::new(effects);
+        let output_objects: BTreeMap<ObjectID, &Object> = checkpoint_transaction
+            .output_objects
+            .iter()
+            .map(|object| (object.id(), object))
+            .collect();
+        // created and mutated fields
+        for object in checkpoint_transaction.output_objects.iter() {
+            let object_status = object_status_tracker
+                .get_object_status(&object.id())
+                .expect("Object must be in output objects");
+            self.process_field(
+                epoch,
+                checkpoint,
+                timestamp_ms,
+                &transaction_digest,
+                object,
+                object_status,
+                &output_objects,
+            )
+            .await?;
+        }
+        // deleted fields are only found in the input objects
+        let deleted: BTreeSet<ObjectID> = effects.deleted().iter().map(|r| r.0).collect();
+        for object in checkpoint_transaction.input_objects.iter() {
+            if deleted.contains(&object.id()) {
+                self.process_field(
+                    epoch,
+                    checkpoint,
+                    timestamp_ms,
+                    &transaction_digest,
+                    object,
+                    ObjectStatus::Deleted,
+                    &output_objects,
+                )
+                .await?;
+            }
+        }
+        Ok(())
+    }
+    // Dynamic field data. Only objects of type sui::dynamic_field::Field are processed.
+    // Responsible to build the dynamic field table.
+    #[allow(clippy::too_many_arguments)]
+    async fn process_field(
+        &mut self,
+        epoch: u64,
+        checkpoint: u64,
+        timestamp_ms: u64,
+        transaction_digest: &str,
+        object: &Object,
+        object_status: ObjectStatus,
+        output_objects: &BTreeMap<ObjectID, &Object>,
+    ) -> Result<()> {
+        let Some(move_object) = object.data.try_as_move() else {
+            return Ok(());
+        };
+        if !move_object.type_().is_dynamic_field() {
+            return Ok(());
+        }
+        let field_id = object.id();
+        let parent_object_id = get_owner_address(object)
+            .ok_or_else(|| anyhow!("Dynamic field {field_id} is not owned by an object"))?;
+        let struct_tag = object
+            .struct_tag()
+            .ok_or_else(|| anyhow!("Dynamic field {field_id} must have a struct tag"))?;
+        let move_struct =
+            get_move_struct(&struct_tag, move_object.contents(), &self.resolver).await?;
+        let (name_value, dynamic_field_type, object_id) =
+            DynamicFieldInfo::parse_move_object(&move_struct)?;
+        let name_type = move_object
+            .type_()
+            .try_extract_field_name(&dynamic_field_type)?;
+        let bcs_name = bcs::to_bytes(&name_value.clone().undecorate())?;
+        let (value_type, value_json) = match dynamic_field_type {
+            DynamicFieldType::DynamicField => {
+                let value_type = move_object.type_().try_extract_field_value()?;
+                let value_json =
+                    extract_field_from_move_struct(&move_struct, "value").map(|value| {
+                        SuiMoveValue::from(value.clone())
+                            .to_json_value()
+                            .to_string()
+                    });
+                (Some(value_type.to_canonical_string(true)), value_json)
+            }
+            DynamicFieldType::DynamicObject => (
+                output_objects
+                    .get(&object_id)
+                    .and_then(|child| child.data.type_())
+                    .map(|type_| type_.to_canonical_string(true)),
+                None,
+            ),
+        };
+        let entry = DynamicFieldEntry {
+            parent_object_id,
+            transaction_digest: transaction_digest.to_string(),
+            checkpoint,
+            epoch,
+            timestamp_ms,
+            field_id: field_id.to_string(),
+            field_version: object.version().value(),
+            object_status,
+            dynamic_field_type: dynamic_field_type.to_string(),
+            name_type: name_type.to_canonical_string(true),
+            name: SuiMoveValue::from(name_value.clone())
+                .to_json_value()
+                .to_string(),
+            bcs_name: Base64::encode(bcs_name),
+            object_id: object_id.to_string(),
+            value_type,
+            value_json,
+        };
+        self.dynamic_fields.push(entry);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use move_core_types::language_storage::TypeTag;
+    use sui_indexer::framework::Handler;
+    use sui_types::base_types::ObjectID;
+    use sui_types::dynamic_field::{DynamicFieldInfo, Field};
+    use sui_types::id::UID;
+    use sui_types::object::{Object, Owner};
+
+    use super::DynamicFieldHandler;
+    use crate::handlers::test_utils::{
+        add_system_packages, mock_checkpoint, mock_transaction, move_object, MockEffects,
+    };
+    use crate::handlers::AnalyticsHandler;
+
+    fn field(parent: ObjectID, name: u64, value: u64) -> Object {
+        let field = Field {
+            id: UID::new(ObjectID::random()),
+            name,
+            value,
+        };
+        move_object(
+            DynamicFieldInfo::dynamic_field_type(TypeTag::U64, TypeTag::U64),
+            bcs::to_bytes(&field).unwrap(),
+            Owner::ObjectOwner(parent.into()),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_dynamic_fields() -> anyhow::Result<()> {
+        let store = tempfile::tempdir()?;
+        let mut handler = DynamicFieldHandler::new(store.path(), "http://localhost:9000");
+        add_system_packages(&handler.package_store);
+
+        let parent = ObjectID::random();
+        let created = field(parent, 1, 10);
+        let mutated = field(parent, 2, 20);
+        let deleted = field(parent, 3, 30);
+        // not a dynamic field, so it is not reported
+        let coin = Object::new_gas_for_testing();
+        let effects = MockEffects {
+            created: vec![created.compute_object_reference()],
+            mutated: vec![
+                mutated.compute_object_reference(),
+                coin.compute_object_reference(),
+            ],
+            deleted: vec![deleted.compute_object_reference()],
+            ..Default::default()
+        };
+        let transaction = mock_transaction(
+            effects,
+            vec![mutated.clone(), deleted.clone(), coin.clone()],
+            vec![created.clone(), mutated.clone(), coin],
+        );
+        handler
+            .process_checkpoint(&mock_checkpoint(vec![transaction]))
+            .await?;
+
+        let entries = handler.read()?;
+        assert_eq!(entries.len(), 3);
+        for (entry, (object, status, name, value)) in entries.iter().zip([
+            (&created, "Created", "1", "10"),
+            (&mutated, "Mutated", "2", "20"),
+            (&deleted, "Deleted", "3", "30"),
+        ]) {
+            assert_eq!(entry.field_id, object.id().to_string());
+            assert_eq!(entry.parent_object_id, parent.to_string());
+            assert_eq!(entry.object_status.to_string(), status);
+            assert_eq!(entry.dynamic_field_type, "DynamicField");
+            assert_eq!(entry.name_type, "u64");
+            // u64 values are rendered as JSON strings
+            assert_eq!(entry.name, format!("\"{name}\""));
+            assert_eq!(entry.value_type.as_deref(), Some("u64"));
+            assert_eq!(entry.value_json, Some(format!("\"{value}\"")));
+        }
+        assert!(handler.read()?.is_empty());
+        Ok(())
+    }
+}
@@ -20,12 +20,14 @@ use crate::tables::{InputObjectKind, ObjectStatus, OwnerType};
 use crate::FileType;
 
 pub mod checkpoint_handler;
+pub mod dynamic_field_handler;
 pub mod event_handler;
 pub mod move_call_handler;
 pub mod object_handler;
 pub mod package_handler;
 pub mod transaction_handler;
 pub mod transaction_objects_handler;
+pub mod wrapped_object_handler;
 
 #[async_trait::async_trait]
 pub trait AnalyticsHandler<S>: Handler {
@@ -144,11 +146,11 @@ impl ObjectStatusTracker {
 
     fn get_object_status(&self, object_id: &ObjectID) -> Option<ObjectStatus> {
         if self.mutated.contains(object_id) {
-            Some(ObjectStatus::Created)
-        } else if self.deleted.contains(object_id) {
             Some(ObjectStatus::Mutated)
-        } else if self.created.contains(object_id) {
+        } else if self.deleted.contains(object_id) {
             Some(ObjectStatus::Deleted)
+        } else if self.created.contains(object_id) {
+            Some(ObjectStatus::Created)
         } else {
             None
         }
@@ -171,3 +173,147 @@ async fn get_move_struct<T: PackageStore>(
     }?;
     Ok(move_struct)
 }
+
+#[cfg(test)]
+pub(crate) mod test_utils {
+    use move_core_types::language_storage::StructTag;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use sui_framework::BuiltInFramework;
+    use sui_rest_api::{CheckpointData, CheckpointTransaction};
+    use sui_types::base_types::{random_object_ref, ObjectRef, SuiAddress};
+    use sui_types::digests::TransactionDigest;
+    use sui_types::effects::TransactionEffects;
+    use sui_types::execution_status::ExecutionStatus;
+    use sui_types::gas::GasCostSummary;
+    use sui_types::messages_checkpoint::CheckpointContents;
+    use sui_types::object::{MoveObject, Object, Owner, OBJECT_START_VERSION};
+    use sui_types::utils::{
+        create_fake_transaction, make_committee_key, mock_certified_checkpoint,
+    };
+
+    use crate::package_store::LocalDBPackageStore;
+
+    /// Object references reported by the effects of a mock transaction.
+    #[derive(Default)]
+    pub(crate) struct MockEffects {
+        pub(crate) created: Vec<ObjectRef>,
+        pub(crate) mutated: Vec<ObjectRef>,
+        pub(crate) unwrapped: Vec<ObjectRef>,
+        pub(crate) deleted: Vec<ObjectRef>,
+        pub(crate) unwrapped_then_deleted: Vec<ObjectRef>,
+        pub(crate) wrapped: Vec<ObjectRef>,
+    }
+
+    impl MockEffects {
+        pub(crate) fn build(self) -> TransactionEffects {
+            let with_owner = |refs: Vec<ObjectRef>| {
+                refs.into_iter()
+                    .map(|obj_ref| (obj_ref, Owner::AddressOwner(SuiAddress::ZERO)))
+                    .collect()
+            };
+            TransactionEffects::new_from_execution_v1(
+                ExecutionStatus::Success,
+                0,
+                GasCostSummary::default(),
+                vec![],
+                vec![],
+                TransactionDigest::random(),
+                with_owner(self.created),
+                with_owner(self.mutated),
+                with_owner(self.unwrapped),
+                self.deleted,
+                self.unwrapped_then_deleted,
+                self.wrapped,
+                (random_object_ref(), Owner::AddressOwner(SuiAddress::ZERO)),
+                None,
+                vec![],
+            )
+        }
+    }
+
+    pub(crate) fn mock_transaction(
+        effects: MockEffects,
+        input_objects: Vec<Object>,
+        output_objects: Vec<Object>,
+    ) -> CheckpointTransaction {
+        CheckpointTransaction {
+            transaction: create_fake_transaction(),
+            effects: effects.build(),
+            events: None,
+            input_objects,
+            output_objects,
+        }
+    }
+
+    pub(crate) fn mock_checkpoint(transactions: Vec<CheckpointTransaction>) -> CheckpointData {
+        let (keys, committee) = make_committee_key(&mut StdRng::from_seed([0; 32]));
+        CheckpointData {
+            checkpoint_summary: mock_certified_checkpoint(keys.iter(), committee, 1),
+            checkpoint_contents: CheckpointContents::new_with_digests_only_for_tests(vec![]),
+            transactions,
+        }
+    }
+
+    /// An object of a type without `store`, such as a dynamic field, which cannot be transferred
+    /// publicly.
+    pub(crate) fn move_object(type_: StructTag, contents: Vec<u8>, owner: Owner) -> Object {
+        let move_object =
+            MoveObject::new_for_testing(type_.into(), false, OBJECT_START_VERSION, contents);
+        Object::new_move(move_object, owner, TransactionDigest::genesis_marker())
+    }
+
+    /// Seed the package store with the system packages, so that types can be resolved without a
+    /// full node.
+    pub(crate) fn add_system_packages(package_store: &LocalDBPackageStore) {
+        for package in BuiltInFramework::genesis_objects() {
+            package_store.update(&package).unwrap();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sui_types::base_types::{random_object_ref, ObjectRef};
+
+    use super::test_utils::MockEffects;
+    use super::ObjectStatusTracker;
+
+    fn status(tracker: &ObjectStatusTracker, obj_ref: &ObjectRef) -> Option<String> {
+        tracker
+            .get_object_status(&obj_ref.0)
+            .map(|status| status.to_string())
+    }
+
+    #[test]
+    fn test_object_status() {
+        let (created, mutated, unwrapped, deleted, unwrapped_then_deleted) = (
+            random_object_ref(),
+            random_object_ref(),
+            random_object_ref(),
+            random_object_ref(),
+            random_object_ref(),
+        );
+        let effects = MockEffects {
+            created: vec![created],
+            mutated: vec![mutated],
+            unwrapped: vec![unwrapped],
+            deleted: vec![deleted],
+            unwrapped_then_deleted: vec![unwrapped_then_deleted],
+            ..Default::default()
+        }
+        .build();
+        let tracker = ObjectStatusTracker::new(&effects);
+
+        assert_eq!(status(&tracker, &created).as_deref(), Some("Created"));
+        assert_eq!(status(&tracker, &mutated).as_deref(), Some("Mutated"));
+        // unwrapped objects existed before the transaction, so they count as mutated
+        assert_eq!(status(&tracker, &unwrapped).as_deref(), Some("Mutated"));
+        assert_eq!(status(&tracker, &deleted).as_deref(), Some("Deleted"));
+        assert_eq!(
+            status(&tracker, &unwrapped_then_deleted).as_deref(),
+            Some("Deleted")
+        );
+        assert_eq!(status(&tracker, &random_object_ref()), None);
+    }
+}
@@ -0,0 +1,312 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Result;
+use move_core_types::annotated_value::{MoveStruct, MoveValue};
+use move_core_types::language_storage::StructTag;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+use sui_indexer::framework::Handler;
+use sui_package_resolver::Resolver;
+use sui_rest_api::{CheckpointData, CheckpointTransaction};
+use sui_types::base_types::ObjectID;
+use sui_types::effects::TransactionEffectsAPI;
+use sui_types::id::UID;
+use sui_types::object::Object;
+
+use crate::handlers::{get_move_struct, AnalyticsHandler};
+use crate::package_store::{LocalDBPackageStore, PackageCache};
+use crate::tables::{WrappedObjectEntry, WrappedObjectStatus};
+use crate::FileType;
+
+pub struct WrappedObjectHandler {
+    wrapped_objects: Vec<WrappedObjectEntry>,
+    package_store: LocalDBPackageStore,
+    resolver: Resolver<PackageCache>,
+}
+
+// Where a wrapped object was found in the output objects of a transaction.
+struct WrappedObjectLocation {
+    root_object_id: ObjectID,
+    root_object_version: u64,
+    json_path: String,
+    struct_tag: StructTag,
+}
+
+#[async_trait::async_trait]
+impl Handler for WrappedObjectHandler {
+    fn name(&self) -> &str {
+        "wrapped_object"
+    }
+    async fn process_checkpoint(&mut self, checkpoint_data: &CheckpointData) -> Result<()> {
+        let CheckpointData {
+            checkpoint_summary,
+            transactions: checkpoint_transactions,
+            ..
+        } = checkpoint_data;
+        for checkpoint_transaction in checkpoint_transactions {
+            for object in checkpoint_transaction.output_objects.iter() {
+                self.package_store.update(object)?;
+            }
+            self.process_transaction(
+                checkpoint_summary.epoch,
+                checkpoint_summary.sequence_number,
+                checkpoint_summary.timestamp_ms,
+                checkpoint_transaction,
+            )
+            .await?;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl AnalyticsHandler<WrappedObjectEntry> for WrappedObjectHandler {
+    fn read(&mut self) -> Result<Vec<WrappedObjectEntry>> {
+        let cloned = self.wrapped_objects.clone();
+        self.wrapped_objects.clear();
+        Ok(cloned)
+    }
+
+    fn file_type(&self) -> Result<FileType> {
+        Ok(FileType::WrappedObject)
+    }
+}
+
+impl WrappedObjectHandler {
+    pub fn new(store_path: &Path, rest_uri: &str) -> Self {
+        let package_store = LocalDBPackageStore::new(&store_path.join("wrapped_object"), rest_uri);
+        WrappedObjectHandler {
+            wrapped_objects: vec![],
+            package_store: package_store.clone(),
+            resolver: Resolver::new(PackageCache::new(package_store)),
+        }
+    }
+    async fn process_transaction(
+        &mut self,
+        epoch: u64,
+        checkpoint: u64,
+        timestamp_ms: u64,
+        checkpoint_transaction: &CheckpointTransaction,
+    ) -> Result<()> {
+        let transaction_digest = checkpoint_transaction.transaction.digest().base58_encode();
+        let effects = &checkpoint_transaction.effects;
+        let new_entry =
+            |object_id: ObjectID, version: u64, status: WrappedObjectStatus| WrappedObjectEntry {
+                object_id: object_id.to_string(),
+                version,
+                transaction_digest: transaction_digest.clone(),
+                checkpoint,
+                epoch,
+                timestamp_ms,
+                status,
+                root_object_id: None,
+                root_object_version: None,
+                json_path: None,
+                struct_tag: None,
+            };
+
+        let wrapped = effects.wrapped();
+        let mut locations = BTreeMap::new();
+        if !wrapped.is_empty() {
+            let wrapped_ids: BTreeSet<ObjectID> = wrapped.iter().map(|r| r.0).collect();
+            for object in checkpoint_transaction.output_objects.iter() {
+                self.find_wrapped_objects(object, &wrapped_ids, &mut locations)
+                    .await?;
+            }
+        }
+        for (object_id, version, _) in wrapped {
+            let mut entry = new_entry(object_id, version.value(), WrappedObjectStatus::Wrapped);
+            if let Some(location) = locations.remove(&object_id) {
+                entry.root_object_id = Some(location.root_object_id.to_string());
+                entry.root_object_version = Some(location.root_object_version);
+                entry.json_path = Some(location.json_path);
+                entry.struct_tag = Some(location.struct_tag.to_string());
+            }
+            self.wrapped_objects.push(entry);
+        }
+
+        for ((object_id, version, _), _) in effects.unwrapped() {
+            let mut entry = new_entry(object_id, version.value(), WrappedObjectStatus::Unwrapped);
+            entry.struct_tag = checkpoint_transaction
+                .output_objects
+                .iter()
+                .find(|object| object.id() == object_id)
+                .and_then(|object| object.struct_tag())
+                .map(|tag| tag.to_string());
+            self.wrapped_objects.push(entry);
+        }
+
+        for (object_id, version, _) in effects.unwrapped_then_deleted() {
+            self.wrapped_objects.push(new_entry(
+                object_id,
+                version.value(),
+                WrappedObjectStatus::UnwrappedThenDeleted,
+            ));
+        }
+        Ok(())
+    }
+    // Look for the objects in `wrapped_ids` inside `object`, recording where they are found.
+    async fn find_wrapped_objects(
+        &self,
+        object: &Object,
+        wrapped_ids: &BTreeSet<ObjectID>,
+        locations: &mut BTreeMap<ObjectID, WrappedObjectLocation>,
+    ) -> Result<()> {
+        let Some((tag, contents)) = object
+            .struct_tag()
+            .and_then(|tag| object.data.try_as_move().map(|mo| (tag, mo.contents())))
+        else {
+            return Ok(());
+        };
+        let move_struct = get_move_struct(&tag, contents, &self.resolver).await?;
+        let mut found = vec![];
+        find_in_struct(&move_struct, "$".to_string(), wrapped_ids, &mut found);
+        for (object_id, json_path, struct_tag) in found {
+            locations.insert(
+                object_id,
+                WrappedObjectLocation {
+                    root_object_id: object.id(),
+                    root_object_version: object.version().value(),
+                    json_path,
+                    struct_tag,
+                },
+            );
+        }
+        Ok(())
+    }
+}
+
+fn find_in_struct(
+    move_struct: &MoveStruct,
+    path: String,
+    wrapped_ids: &BTreeSet<ObjectID>,
+    found: &mut Vec<(ObjectID, String, StructTag)>,
+) {
+    if let Some(object_id) = struct_object_id(move_struct) {
+        if wrapped_ids.contains(&object_id) {
+            found.push((object_id, path.clone(), move_struct.type_.clone()));
+        }
+    }
+    for (name, value) in move_struct.fields.iter() {
+        find_in_value(value, format!("{path}.{name}"), wrapped_ids, found);
+    }
+}
+
+fn find_in_value(
+    value: &MoveValue,
+    path: String,
+    wrapped_ids: &BTreeSet<ObjectID>,
+    found: &mut Vec<(ObjectID, String, StructTag)>,
+) {
+    match value {
+        MoveValue::Struct(move_struct) => find_in_struct(move_struct, path, wrapped_ids, found),
+        MoveValue::Vector(values) => {
+            for (idx, value) in values.iter().enumerate() {
+                find_in_value(value, format!("{path}[{idx}]"), wrapped_ids, found);
+            }
+        }
+        _ => (),
+    }
+}
+
+// Id of a struct with the `key` ability, whose first field is `id: UID`.
+fn struct_object_id(move_struct: &MoveStruct) -> Option<ObjectID> {
+    let (name, MoveValue::Struct(uid)) = move_struct.fields.first()? else {
+        return None;
+    };
+    if name.as_str() != "id" || uid.type_ != UID::type_() {
+        return None;
+    }
+    // UID has a single id: ID field, which has a single bytes: address field
+    let (_, MoveValue::Struct(id)) = uid.fields.first()? else {
+        return None;
+    };
+    match id.fields.first()? {
+        (_, MoveValue::Address(address)) => Some(ObjectID::from(*address)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use move_core_types::language_storage::TypeTag;
+    use sui_indexer::framework::Handler;
+    use sui_types::base_types::{random_object_ref, ObjectID};
+    use sui_types::dynamic_field::{DynamicFieldInfo, Field};
+    use sui_types::gas_coin::GasCoin;
+    use sui_types::id::UID;
+    use sui_types::object::{Object, Owner};
+
+    use super::WrappedObjectHandler;
+    use crate::handlers::test_utils::{
+        add_system_packages, mock_checkpoint, mock_transaction, move_object, MockEffects,
+    };
+    use crate::handlers::AnalyticsHandler;
+
+    #[tokio::test]
+    async fn test_wrapped_objects() -> anyhow::Result<()> {
+        let store = tempfile::tempdir()?;
+        let mut handler = WrappedObjectHandler::new(store.path(), "http://localhost:9000");
+        add_system_packages(&handler.package_store);
+
+        // a coin wrapped as the value of a dynamic field
+        let wrapped = Object::new_gas_for_testing();
+        let field = Field {
+            id: UID::new(ObjectID::random()),
+            name: 0u64,
+            value: GasCoin::new(wrapped.id(), 100),
+        };
+        let root = move_object(
+            DynamicFieldInfo::dynamic_field_type(
+                TypeTag::U64,
+                TypeTag::Struct(Box::new(GasCoin::type_())),
+            ),
+            bcs::to_bytes(&field)?,
+            Owner::ObjectOwner(ObjectID::random().into()),
+        );
+        let unwrapped = Object::new_gas_for_testing();
+        let unwrapped_then_deleted = random_object_ref();
+        let effects = MockEffects {
+            created: vec![root.compute_object_reference()],
+            unwrapped: vec![unwrapped.compute_object_reference()],
+            unwrapped_then_deleted: vec![unwrapped_then_deleted],
+            wrapped: vec![wrapped.compute_object_reference()],
+            ..Default::default()
+        };
+        let transaction = mock_transaction(
+            effects,
+            vec![wrapped.clone()],
+            vec![root.clone(), unwrapped.clone()],
+        );
+        handler
+            .process_checkpoint(&mock_checkpoint(vec![transaction]))
+            .await?;
+
+        let entries = handler.read()?;
+        assert_eq!(entries.len(), 3);
+
+        let entry = &entries[0];
+        assert_eq!(entry.object_id, wrapped.id().to_string());
+        assert_eq!(entry.status.to_string(), "Wrapped");
+        assert_eq!(entry.root_object_id, Some(root.id().to_string()));
+        assert_eq!(entry.root_object_version, Some(root.version().value()));
+        assert_eq!(entry.json_path.as_deref(), Some("$.value"));
+        assert_eq!(entry.struct_tag, Some(GasCoin::type_().to_string()));
+
+        let entry = &entries[1];
+        assert_eq!(entry.object_id, unwrapped.id().to_string());
+        assert_eq!(entry.status.to_string(), "Unwrapped");
+        assert_eq!(entry.root_object_id, None);
+        assert_eq!(entry.struct_tag, Some(GasCoin::type_().to_string()));
+
+        let entry = &entries[2];
+        assert_eq!(entry.object_id, unwrapped_then_deleted.0.to_string());
+        assert_eq!(entry.status.to_string(), "UnwrappedThenDeleted");
+        assert_eq!(entry.struct_tag, None);
+
+        assert!(handler.read()?.is_empty());
+        Ok(())
+    }
+}
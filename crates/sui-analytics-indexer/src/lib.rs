@@ -4,7 +4,7 @@
 use std::ops::Range;
 use std::path::PathBuf;
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use clap::*;
 use num_enum::IntoPrimitive;
 use num_enum::TryFromPrimitive;
@@ -25,19 +25,25 @@ use sui_types::messages_checkpoint::CheckpointSequenceNumber;
 use crate::analytics_metrics::AnalyticsMetrics;
 use crate::analytics_processor::AnalyticsProcessor;
 use crate::handlers::checkpoint_handler::CheckpointHandler;
+use crate::handlers::dynamic_field_handler::DynamicFieldHandler;
 use crate::handlers::event_handler::EventHandler;
 use crate::handlers::move_call_handler::MoveCallHandler;
 use crate::handlers::object_handler::ObjectHandler;
 use crate::handlers::package_handler::PackageHandler;
 use crate::handlers::transaction_handler::TransactionHandler;
 use crate::handlers::transaction_objects_handler::TransactionObjectsHandler;
+use crate::handlers::wrapped_object_handler::WrappedObjectHandler;
 use crate::handlers::AnalyticsHandler;
 use crate::tables::{
-    CheckpointEntry, EventEntry, InputObjectKind, MoveCallEntry, MovePackageEntry, ObjectEntry,
-    ObjectStatus, OwnerType, TransactionEntry, TransactionObjectEntry,
+    CheckpointEntry, DynamicFieldEntry, EventEntry, InputObjectKind, MoveCallEntry,
+    MovePackageEntry, ObjectEntry, ObjectStatus, OwnerType, TransactionEntry,
+    TransactionObjectEntry, WrappedObjectEntry, WrappedObjectStatus,
 };
+use crate::writers::avro_writer::AvroWriter;
 use crate::writers::csv_writer::CSVWriter;
+use crate::writers::jsonl_writer::JsonLinesWriter;
 use crate::writers::parquet_writer::ParquetWriter;
+use crate::writers::sqlite_writer::SqliteWriter;
 use crate::writers::AnalyticsWriter;
 
 pub mod analytics_metrics;
@@ -56,6 +62,9 @@ const EVENT_DIR_PREFIX: &str = "events";
 const TRANSACTION_OBJECT_DIR_PREFIX: &str = "transaction_objects";
 const MOVE_CALL_PREFIX: &str = "move_call";
 const MOVE_PACKAGE_PREFIX: &str = "move_package";
+const DYNAMIC_FIELD_PREFIX: &str = "dynamic_field";
+const WRAPPED_OBJECT_PREFIX: &str = "wrapped_object";
+const SQLITE_DB_NAME: &str = "analytics.sqlite";
 
 #[derive(Parser, Clone, Debug)]
 #[clap(
@@ -94,6 +103,14 @@ pub struct AnalyticsIndexerConfig {
     // File format to store data in i.e. csv, parquet, etc
     #[clap(long, value_enum, default_value = "csv", global = true)]
     pub file_format: FileFormat,
+    // Compression to use for the files i.e. snappy, gzip, etc. Defaults to snappy for parquet
+    // and avro, and to no compression for csv and jsonl. Avro files don't support gzip
+    #[clap(long, value_enum, global = true)]
+    pub compression: Option<FileCompression>,
+    // Sqlite database the sqlite file format writes rows into, with a table per file type.
+    // Defaults to analytics.sqlite in the checkpoint directory
+    #[clap(long, global = true)]
+    pub sqlite_path: Option<PathBuf>,
     // Type of data to write i.e. checkpoint, object, transaction, etc
     #[clap(long, value_enum, long, global = true)]
     pub file_type: FileType,
@@ -127,6 +144,9 @@ pub struct AnalyticsIndexerConfig {
 pub enum FileFormat {
     CSV = 0,
     PARQUET = 1,
+    AVRO = 2,
+    JSONL = 3,
+    SQLITE = 4,
 }
 
 impl FileFormat {
@@ -134,8 +154,29 @@ impl FileFormat {
         match self {
             FileFormat::CSV => "csv",
             FileFormat::PARQUET => "parquet",
+            FileFormat::AVRO => "avro",
+            FileFormat::JSONL => "jsonl",
+            FileFormat::SQLITE => "sqlite",
         }
     }
+
+    /// Compression used when none is configured
+    pub fn default_compression(&self) -> FileCompression {
+        match self {
+            FileFormat::CSV | FileFormat::JSONL | FileFormat::SQLITE => FileCompression::None,
+            FileFormat::PARQUET | FileFormat::AVRO => FileCompression::Snappy,
+        }
+    }
+}
+
+#[derive(
+    Copy, Clone, Debug, Eq, PartialEq, strum_macros::Display, ValueEnum, Serialize, Deserialize,
+)]
+pub enum FileCompression {
+    None,
+    Snappy,
+    Gzip,
+    Zstd,
 }
 
 #[derive(
@@ -160,9 +201,24 @@ pub enum FileType {
     Event,
     MoveCall,
     MovePackage,
+    DynamicField,
+    WrappedObject,
 }
 
 impl FileType {
+    /// Name of the table rows of this type are stored in
+    pub fn table_name(&self) -> String {
+        self.dir_prefix().to_string()
+    }
+
+    /// Column holding the checkpoint sequence number of each row
+    pub fn checkpoint_column(&self) -> &str {
+        match self {
+            FileType::Checkpoint => "sequence_number",
+            _ => "checkpoint",
+        }
+    }
+
     pub fn dir_prefix(&self) -> Path {
         match self {
             FileType::Checkpoint => Path::from(CHECKPOINT_DIR_PREFIX),
@@ -172,6 +228,8 @@ impl FileType {
             FileType::Event => Path::from(EVENT_DIR_PREFIX),
             FileType::MoveCall => Path::from(MOVE_CALL_PREFIX),
             FileType::MovePackage => Path::from(MOVE_PACKAGE_PREFIX),
+            FileType::DynamicField => Path::from(DYNAMIC_FIELD_PREFIX),
+            FileType::WrappedObject => Path::from(WRAPPED_OBJECT_PREFIX),
         }
     }
 
@@ -261,9 +319,58 @@ impl From<Option<InputObjectKind>> for ParquetValue {
     }
 }
 
+impl From<WrappedObjectStatus> for ParquetValue {
+    fn from(value: WrappedObjectStatus) -> Self {
+        Self::Str(value.to_string())
+    }
+}
+
+// Type of a table column. Writers which need a schema up front take it from here rather than
+// from the values of the rows.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ParquetType {
+    U64,
+    Str,
+    Bool,
+    I64,
+    OptionU64,
+    OptionStr,
+}
+
+// Rust types which can be stored in a table column.
+pub trait ParquetColumn: Into<ParquetValue> {
+    const TYPE: ParquetType;
+}
+
+macro_rules! parquet_columns {
+    ($($rust_type:ty => $parquet_type:ident),* $(,)?) => {
+        $(
+            impl ParquetColumn for $rust_type {
+                const TYPE: ParquetType = ParquetType::$parquet_type;
+            }
+        )*
+    };
+}
+
+parquet_columns!(
+    u64 => U64,
+    i64 => I64,
+    String => Str,
+    bool => Bool,
+    Option<u64> => OptionU64,
+    Option<String> => OptionStr,
+    OwnerType => Str,
+    ObjectStatus => Str,
+    Option<ObjectStatus> => OptionStr,
+    Option<InputObjectKind> => OptionStr,
+    WrappedObjectStatus => Str,
+);
+
 pub trait ParquetSchema {
     fn schema() -> Vec<String>;
 
+    fn types() -> Vec<ParquetType>;
+
     fn get_column(&self, idx: usize) -> ParquetValue;
 }
 
@@ -530,31 +637,120 @@ pub async fn make_move_call_processor(
     .await
 }
 
+pub async fn make_dynamic_field_processor(
+    config: AnalyticsIndexerConfig,
+    metrics: AnalyticsMetrics,
+) -> Result<Processor> {
+    let handler: Box<dyn AnalyticsHandler<DynamicFieldEntry>> = Box::new(DynamicFieldHandler::new(
+        &config.package_cache_path,
+        &config.rest_url,
+    ));
+    let starting_checkpoint_seq_num =
+        get_starting_checkpoint_seq_num(config.clone(), FileType::DynamicField).await?;
+    let writer = make_writer::<DynamicFieldEntry>(
+        config.clone(),
+        FileType::DynamicField,
+        starting_checkpoint_seq_num,
+    )?;
+    Processor::new::<DynamicFieldEntry>(
+        handler,
+        writer,
+        starting_checkpoint_seq_num,
+        metrics,
+        config,
+    )
+    .await
+}
+
+pub async fn make_wrapped_object_processor(
+    config: AnalyticsIndexerConfig,
+    metrics: AnalyticsMetrics,
+) -> Result<Processor> {
+    let handler: Box<dyn AnalyticsHandler<WrappedObjectEntry>> = Box::new(
+        WrappedObjectHandler::new(&config.package_cache_path, &config.rest_url),
+    );
+    let starting_checkpoint_seq_num =
+        get_starting_checkpoint_seq_num(config.clone(), FileType::WrappedObject).await?;
+    let writer = make_writer::<WrappedObjectEntry>(
+        config.clone(),
+        FileType::WrappedObject,
+        starting_checkpoint_seq_num,
+    )?;
+    Processor::new::<WrappedObjectEntry>(
+        handler,
+        writer,
+        starting_checkpoint_seq_num,
+        metrics,
+        config,
+    )
+    .await
+}
+
 pub fn make_writer<S: Serialize + ParquetSchema>(
     config: AnalyticsIndexerConfig,
     file_type: FileType,
     starting_checkpoint_seq_num: u64,
 ) -> Result<Box<dyn AnalyticsWriter<S>>> {
+    let compression = config
+        .compression
+        .unwrap_or_else(|| config.file_format.default_compression());
     Ok(match config.file_format {
-        FileFormat::CSV => Box::new(CSVWriter::new(
+        FileFormat::CSV => {
+            if compression != FileCompression::None {
+                return Err(anyhow!("Compression is not supported for csv files"));
+            }
+            Box::new(CSVWriter::new(
+                &config.checkpoint_dir,
+                file_type,
+                starting_checkpoint_seq_num,
+            )?)
+        }
+        FileFormat::PARQUET => Box::new(ParquetWriter::new(
             &config.checkpoint_dir,
             file_type,
             starting_checkpoint_seq_num,
+            compression,
         )?),
-        FileFormat::PARQUET => Box::new(ParquetWriter::new(
+        FileFormat::AVRO => Box::new(AvroWriter::new(
+            &config.checkpoint_dir,
+            file_type,
+            starting_checkpoint_seq_num,
+            compression,
+        )?),
+        FileFormat::JSONL => Box::new(JsonLinesWriter::new(
             &config.checkpoint_dir,
             file_type,
             starting_checkpoint_seq_num,
+            compression,
         )?),
+        FileFormat::SQLITE => {
+            if compression != FileCompression::None {
+                return Err(anyhow!("Compression is not supported for sqlite"));
+            }
+            Box::new(SqliteWriter::new(
+                &sqlite_db_path(&config),
+                file_type,
+                starting_checkpoint_seq_num,
+            )?)
+        }
     })
 }
 
+fn sqlite_db_path(config: &AnalyticsIndexerConfig) -> PathBuf {
+    config
+        .sqlite_path
+        .clone()
+        .unwrap_or_else(|| config.checkpoint_dir.join(SQLITE_DB_NAME))
+}
+
 pub async fn get_starting_checkpoint_seq_num(
     config: AnalyticsIndexerConfig,
     file_type: FileType,
 ) -> Result<u64> {
     let checkpoint = if let Some(starting_checkpoint_seq_num) = config.starting_checkpoint_seq_num {
         starting_checkpoint_seq_num
+    } else if config.file_format == FileFormat::SQLITE {
+        SqliteWriter::next_checkpoint_seq_num(&sqlite_db_path(&config), file_type)?
     } else {
         read_store_for_checkpoint(config.remote_store_config.clone(), file_type).await?
     };
@@ -573,5 +769,7 @@ pub async fn make_analytics_processor(
         FileType::TransactionObjects => make_transaction_objects_processor(config, metrics).await,
         FileType::MoveCall => make_move_call_processor(config, metrics).await,
         FileType::MovePackage => make_move_package_processor(config, metrics).await,
+        FileType::DynamicField => make_dynamic_field_processor(config, metrics).await,
+        FileType::WrappedObject => make_wrapped_object_processor(config, metrics).await,
     }
 }
@@ -0,0 +1,20 @@
+CREATE TABLE IF NOT EXISTS chaindata.DYNAMIC_FIELD
+(
+    parent_object_id   STRING        NOT NULL,
+    transaction_digest STRING        NOT NULL,
+    checkpoint         INT64         NOT NULL,
+    epoch              INT64         NOT NULL,
+    timestamp_ms       INT64         NOT NULL,
+    field_id           STRING        NOT NULL,
+    field_version      INT64         NOT NULL,
+    object_status      STRING        NOT NULL,
+    dynamic_field_type STRING        NOT NULL,
+    name_type          STRING        NOT NULL,
+    name               JSON          NOT NULL,
+    bcs_name           STRING        NOT NULL,
+    object_id          STRING        NOT NULL,
+    value_type         STRING,
+    value_json         JSON
+)
+PARTITION BY RANGE_BUCKET(epoch, GENERATE_ARRAY(0, 100000, 10))
+CLUSTER BY parent_object_id, field_id, field_version
@@ -0,0 +1,16 @@
+CREATE TABLE IF NOT EXISTS chaindata.WRAPPED_OBJECT
+(
+    object_id           STRING        NOT NULL,
+    version             INT64         NOT NULL,
+    transaction_digest  STRING        NOT NULL,
+    checkpoint          INT64         NOT NULL,
+    epoch               INT64         NOT NULL,
+    timestamp_ms        INT64         NOT NULL,
+    status              STRING        NOT NULL,
+    root_object_id      STRING,
+    root_object_version INT64,
+    json_path           STRING,
+    struct_tag          STRING
+)
+PARTITION BY RANGE_BUCKET(epoch, GENERATE_ARRAY(0, 100000, 10))
+CLUSTER BY object_id, version
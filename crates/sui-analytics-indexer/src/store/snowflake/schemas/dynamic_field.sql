@@ -0,0 +1,60 @@
+CREATE OR REPLACE TABLE DYNAMIC_FIELD
+(
+    parent_object_id   STRING        NOT NULL,
+    transaction_digest STRING        NOT NULL,
+    checkpoint         NUMBER(20, 0) NOT NULL,
+    epoch              NUMBER(20, 0) NOT NULL,
+    timestamp_ms       NUMBER(20, 0) NOT NULL,
+    field_id           STRING        NOT NULL,
+    field_version      NUMBER(20, 0) NOT NULL,
+    object_status      STRING        NOT NULL,
+    dynamic_field_type STRING        NOT NULL,
+    name_type          STRING        NOT NULL,
+    name               VARIANT       NOT NULL,
+    bcs_name           STRING        NOT NULL,
+    object_id          STRING        NOT NULL,
+    value_type         STRING,
+    value_json         VARIANT
+) STAGE_FILE_FORMAT = parquet_format
+    STAGE_COPY_OPTIONS =
+(
+    ABORT_STATEMENT
+)
+    ENABLE_SCHEMA_EVOLUTION = TRUE
+    CLUSTER BY
+(
+    timestamp_ms
+);
+
+// Define the dynamic field stage
+CREATE OR REPLACE STAGE dynamic_field_parquet_stage
+    URL = '&{checkpoints_bucket}/dynamic_field/'
+    STORAGE_INTEGRATION = checkpoints_data_loader
+    FILE_FORMAT = parquet_format;
+
+// Set up the dynamic field auto ingestion pipe
+CREATE
+    OR REPLACE PIPE dynamic_field_pipe
+    AUTO_INGEST = true
+    INTEGRATION = 'CHECKPOINTS_DATA_LOADER_NOTIFICATION'
+    AS
+        copy into DYNAMIC_FIELD (parent_object_id, transaction_digest, checkpoint, epoch, timestamp_ms, field_id,
+                                 field_version, object_status, dynamic_field_type, name_type, name, bcs_name,
+                                 object_id, value_type, value_json)
+            from (SELECT t.$1:parent_object_id       as parent_object_id,
+                         t.$1:transaction_digest     as transaction_digest,
+                         t.$1:checkpoint             as checkpoint,
+                         t.$1:epoch                  as epoch,
+                         t.$1:timestamp_ms           as timestamp_ms,
+                         t.$1:field_id               as field_id,
+                         t.$1:field_version          as field_version,
+                         t.$1:object_status          as object_status,
+                         t.$1:dynamic_field_type     as dynamic_field_type,
+                         t.$1:name_type              as name_type,
+                         parse_json(t.$1:name)       as name,
+                         t.$1:bcs_name               as bcs_name,
+                         t.$1:object_id              as object_id,
+                         t.$1:value_type             as value_type,
+                         parse_json(t.$1:value_json) as value_json
+                  from @dynamic_field_parquet_stage (file_format => 'parquet_format', pattern => '.*[.]parquet') t)
+            file_format = parquet_format;
@@ -4,7 +4,7 @@ CREATE STORAGE INTEGRATION checkpoints_data_loader
     TYPE = EXTERNAL_STAGE
     STORAGE_PROVIDER = GCS
     ENABLED = TRUE
-    STORAGE_ALLOWED_LOCATIONS = ('&{checkpoints_bucket}/checkpoints', '&{checkpoints_bucket}/events','&{checkpoints_bucket}/move_call.sql','&{checkpoints_bucket}/move_package','&{checkpoints_bucket}/objects','&{checkpoints_bucket}/transaction_objects','&{checkpoints_bucket}/transactions');
+    STORAGE_ALLOWED_LOCATIONS = ('&{checkpoints_bucket}/checkpoints', '&{checkpoints_bucket}/events','&{checkpoints_bucket}/move_call.sql','&{checkpoints_bucket}/move_package','&{checkpoints_bucket}/objects','&{checkpoints_bucket}/transaction_objects','&{checkpoints_bucket}/transactions','&{checkpoints_bucket}/dynamic_field','&{checkpoints_bucket}/wrapped_object');
 
 // This sets up pubsub_subscription_id as the pubsub topic subscriber id
 CREATE NOTIFICATION INTEGRATION checkpoints_data_loader_notification
@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 #![allow(dead_code)]
 
-use crate::{ParquetSchema, ParquetValue};
+use crate::{ParquetColumn, ParquetSchema, ParquetType, ParquetValue};
 use serde::Serialize;
 use strum_macros::Display;
 use sui_analytics_indexer_derive::SerializeParquet;
@@ -141,6 +141,15 @@ pub enum ObjectStatus {
     Deleted,
 }
 
+// Used in the wrapped object table to identify what happened to a wrapped object in a
+// transaction.
+#[derive(Serialize, Clone, Display)]
+pub enum WrappedObjectStatus {
+    Wrapped,
+    Unwrapped,
+    UnwrappedThenDeleted,
+}
+
 // Object owner information.
 #[derive(Serialize, Clone, Display)]
 pub enum OwnerType {
@@ -233,3 +242,51 @@ pub(crate) struct MovePackageEntry {
     // txn publishing the package
     pub(crate) transaction_digest: String,
 }
+
+// A dynamic field (or dynamic object field) written or deleted by a transaction.
+// Dynamic fields identity is via `field_id` and `field_version`.
+#[derive(Serialize, Clone, SerializeParquet)]
+pub(crate) struct DynamicFieldEntry {
+    // indexes
+    pub(crate) parent_object_id: String,
+    pub(crate) transaction_digest: String,
+    pub(crate) checkpoint: u64,
+    pub(crate) epoch: u64,
+    pub(crate) timestamp_ms: u64,
+    // the sui::dynamic_field::Field object
+    pub(crate) field_id: String,
+    pub(crate) field_version: u64,
+    pub(crate) object_status: ObjectStatus,
+    // DynamicField or DynamicObject
+    pub(crate) dynamic_field_type: String,
+    // field name
+    pub(crate) name_type: String,
+    pub(crate) name: String,
+    // We represent them in base64 encoding so they work with the csv.
+    pub(crate) bcs_name: String,
+    // field value. For dynamic object fields this is the child object, its type is only
+    // known if the child object is written by the same transaction.
+    pub(crate) object_id: String,
+    pub(crate) value_type: Option<String>,
+    pub(crate) value_json: Option<String>,
+}
+
+// An object being wrapped into, or unwrapped from, another object.
+// For wrapped objects, `root_object_id` is the top level object it was wrapped into and
+// `json_path` its location in that object.
+#[derive(Serialize, Clone, SerializeParquet)]
+pub(crate) struct WrappedObjectEntry {
+    // indexes
+    pub(crate) object_id: String,
+    pub(crate) version: u64,
+    pub(crate) transaction_digest: String,
+    pub(crate) checkpoint: u64,
+    pub(crate) epoch: u64,
+    pub(crate) timestamp_ms: u64,
+    // wrapping info
+    pub(crate) status: WrappedObjectStatus,
+    pub(crate) root_object_id: Option<String>,
+    pub(crate) root_object_version: Option<u64>,
+    pub(crate) json_path: Option<String>,
+    pub(crate) struct_tag: Option<String>,
+}
@@ -0,0 +1,283 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{AnalyticsWriter, FileCompression, FileFormat, FileType};
+use crate::{ParquetSchema, ParquetType, ParquetValue};
+use anyhow::{anyhow, Result};
+use apache_avro::types::Value;
+use apache_avro::{Codec, Decimal, Schema, Writer};
+use serde::Serialize;
+use serde_json::json;
+use std::fs::File;
+use std::fs::{create_dir_all, remove_file};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use sui_types::base_types::EpochId;
+
+use sui_storage::object_store::util::path_to_filesystem;
+
+// Save table entries to avro files.
+pub(crate) struct AvroWriter {
+    root_dir_path: PathBuf,
+    file_type: FileType,
+    epoch: EpochId,
+    checkpoint_range: Range<u64>,
+    codec: Codec,
+    rows: Vec<Vec<ParquetValue>>,
+}
+
+impl AvroWriter {
+    pub(crate) fn new(
+        root_dir_path: &Path,
+        file_type: FileType,
+        start_checkpoint_seq_num: u64,
+        compression: FileCompression,
+    ) -> Result<Self> {
+        let checkpoint_range = start_checkpoint_seq_num..u64::MAX;
+        let codec = match compression {
+            FileCompression::None => Codec::Null,
+            FileCompression::Snappy => Codec::Snappy,
+            FileCompression::Zstd => Codec::Zstandard,
+            FileCompression::Gzip => {
+                return Err(anyhow!("Gzip compression is not supported for avro files"))
+            }
+        };
+        Ok(Self {
+            root_dir_path: root_dir_path.to_path_buf(),
+            file_type,
+            epoch: 0,
+            checkpoint_range,
+            codec,
+            rows: vec![],
+        })
+    }
+
+    fn file(&self) -> Result<File> {
+        let file_path = path_to_filesystem(
+            self.root_dir_path.clone(),
+            &self
+                .file_type
+                .file_path(FileFormat::AVRO, self.epoch, self.checkpoint_range.clone()),
+        )?;
+        create_dir_all(file_path.parent().ok_or(anyhow!("Bad directory path"))?)?;
+        if file_path.exists() {
+            remove_file(&file_path)?;
+        }
+        Ok(File::create(&file_path)?)
+    }
+
+    // Avro schema of a table with the given columns.
+    fn schema(&self, columns: &[String], types: &[ParquetType]) -> Result<Schema> {
+        // avro has no unsigned types, u64 columns are stored as decimals so that values above
+        // i64::MAX are preserved
+        let u64_type = json!({
+            "type": "bytes",
+            "logicalType": "decimal",
+            "precision": 20,
+            "scale": 0,
+        });
+        let fields: Vec<_> = columns
+            .iter()
+            .zip(types.iter())
+            .map(|(name, type_)| {
+                let type_ = match type_ {
+                    ParquetType::U64 => u64_type.clone(),
+                    ParquetType::I64 => json!("long"),
+                    ParquetType::Str => json!("string"),
+                    ParquetType::Bool => json!("boolean"),
+                    ParquetType::OptionU64 => json!(["null", u64_type]),
+                    ParquetType::OptionStr => json!(["null", "string"]),
+                };
+                json!({ "name": name, "type": type_ })
+            })
+            .collect();
+        Ok(Schema::parse(&json!({
+            "type": "record",
+            "name": self.file_type.dir_prefix().to_string(),
+            "fields": fields,
+        }))?)
+    }
+}
+
+// Big-endian two's complement bytes of `value`, with a leading zero byte so it is never negative.
+fn u64_to_decimal(value: u64) -> Value {
+    let mut bytes = vec![0u8];
+    bytes.extend_from_slice(&value.to_be_bytes());
+    Value::Decimal(Decimal::from(bytes))
+}
+
+fn to_avro_value(value: ParquetValue) -> Value {
+    match value {
+        ParquetValue::U64(value) => u64_to_decimal(value),
+        ParquetValue::I64(value) => Value::Long(value),
+        ParquetValue::Str(value) => Value::String(value),
+        ParquetValue::Bool(value) => Value::Boolean(value),
+        ParquetValue::OptionU64(value) => match value {
+            Some(value) => Value::Union(1, Box::new(u64_to_decimal(value))),
+            None => Value::Union(0, Box::new(Value::Null)),
+        },
+        ParquetValue::OptionStr(value) => match value {
+            Some(value) => Value::Union(1, Box::new(Value::String(value))),
+            None => Value::Union(0, Box::new(Value::Null)),
+        },
+    }
+}
+
+impl<S: Serialize + ParquetSchema> AnalyticsWriter<S> for AvroWriter {
+    fn file_format(&self) -> Result<FileFormat> {
+        Ok(FileFormat::AVRO)
+    }
+
+    fn write(&mut self, rows: &[S]) -> Result<()> {
+        let num_columns = S::schema().len();
+        for row in rows {
+            self.rows
+                .push((0..num_columns).map(|idx| row.get_column(idx)).collect());
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self, end_checkpoint_seq_num: u64) -> Result<bool> {
+        if self.rows.is_empty() {
+            return Ok(false);
+        }
+        self.checkpoint_range.end = end_checkpoint_seq_num;
+        let columns = S::schema();
+        let schema = self.schema(&columns, &S::types())?;
+        let mut writer = Writer::with_codec(&schema, self.file()?, self.codec);
+        for row in std::mem::take(&mut self.rows) {
+            writer.append(Value::Record(
+                columns
+                    .iter()
+                    .cloned()
+                    .zip(row.into_iter().map(to_avro_value))
+                    .collect(),
+            ))?;
+        }
+        writer.flush()?;
+        Ok(true)
+    }
+
+    fn reset(&mut self, epoch_num: EpochId, start_checkpoint_seq_num: u64) -> Result<()> {
+        self.checkpoint_range.start = start_checkpoint_seq_num;
+        self.checkpoint_range.end = u64::MAX;
+        self.epoch = epoch_num;
+        self.rows = vec![];
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::{anyhow, Result};
+    use apache_avro::types::Value;
+    use apache_avro::Reader;
+    use std::collections::BTreeMap;
+    use std::fs::File;
+
+    use sui_storage::object_store::util::path_to_filesystem;
+
+    use super::AvroWriter;
+    use crate::writers::test_utils::{test_entries, TestEntry};
+    use crate::writers::AnalyticsWriter;
+    use crate::{FileCompression, FileFormat, FileType};
+
+    fn decode_u64(value: &Value) -> Result<u64> {
+        let Value::Decimal(decimal) = value else {
+            return Err(anyhow!("Expected a decimal, found {value:?}"));
+        };
+        let bytes = Vec::<u8>::try_from(decimal)?;
+        let (sign, value) = bytes.split_at(bytes.len() - 8);
+        assert!(sign.iter().all(|byte| *byte == 0));
+        Ok(u64::from_be_bytes(value.try_into()?))
+    }
+
+    fn field<'a>(fields: &'a BTreeMap<String, Value>, name: &str) -> Result<&'a Value> {
+        fields
+            .get(name)
+            .ok_or_else(|| anyhow!("Missing field {name}"))
+    }
+
+    // Optional columns are unions of null and the column type.
+    fn optional_field<'a>(
+        fields: &'a BTreeMap<String, Value>,
+        name: &str,
+    ) -> Result<Option<&'a Value>> {
+        match field(fields, name)? {
+            Value::Union(_, value) if **value == Value::Null => Ok(None),
+            Value::Union(_, value) => Ok(Some(value.as_ref())),
+            value => Err(anyhow!("Expected a union, found {value:?}")),
+        }
+    }
+
+    fn decode_entry(value: Value) -> Result<TestEntry> {
+        let Value::Record(fields) = value else {
+            return Err(anyhow!("Expected a record"));
+        };
+        let fields: BTreeMap<String, Value> = fields.into_iter().collect();
+        let (Value::Long(balance_change), Value::String(digest), Value::Boolean(end_of_epoch)) = (
+            field(&fields, "balance_change")?,
+            field(&fields, "digest")?,
+            field(&fields, "end_of_epoch")?,
+        ) else {
+            return Err(anyhow!("Unexpected record {fields:?}"));
+        };
+        Ok(TestEntry {
+            checkpoint: decode_u64(field(&fields, "checkpoint")?)?,
+            balance_change: *balance_change,
+            digest: digest.clone(),
+            end_of_epoch: *end_of_epoch,
+            gas_used: optional_field(&fields, "gas_used")?
+                .map(decode_u64)
+                .transpose()?,
+            sender: match optional_field(&fields, "sender")? {
+                Some(Value::String(sender)) => Some(sender.clone()),
+                Some(value) => return Err(anyhow!("Expected a string, found {value:?}")),
+                None => None,
+            },
+        })
+    }
+
+    #[test]
+    fn test_round_trip() -> Result<()> {
+        for compression in [
+            FileCompression::None,
+            FileCompression::Snappy,
+            FileCompression::Zstd,
+        ] {
+            let dir = tempfile::tempdir()?;
+            let mut writer = AvroWriter::new(dir.path(), FileType::Checkpoint, 5, compression)?;
+            AnalyticsWriter::<TestEntry>::write(&mut writer, &test_entries())?;
+            assert!(AnalyticsWriter::<TestEntry>::flush(&mut writer, 10)?);
+
+            let file_path = path_to_filesystem(
+                dir.path().to_path_buf(),
+                &FileType::Checkpoint.file_path(FileFormat::AVRO, 0, 5..10),
+            )?;
+            let entries = Reader::new(File::open(file_path)?)?
+                .map(|value| decode_entry(value?))
+                .collect::<Result<Vec<_>>>()?;
+            let expected = test_entries();
+            assert_eq!(entries.len(), expected.len());
+            for (entry, expected) in entries.iter().zip(expected.iter()) {
+                assert_eq!(entry.checkpoint, expected.checkpoint);
+                assert_eq!(entry.balance_change, expected.balance_change);
+                assert_eq!(entry.digest, expected.digest);
+                assert_eq!(entry.end_of_epoch, expected.end_of_epoch);
+                assert_eq!(entry.gas_used, expected.gas_used);
+                assert_eq!(entry.sender, expected.sender);
+            }
+            // nothing left to write
+            assert!(!AnalyticsWriter::<TestEntry>::flush(&mut writer, 10)?);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_gzip_is_rejected() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let result = AvroWriter::new(dir.path(), FileType::Checkpoint, 0, FileCompression::Gzip);
+        assert!(result.is_err());
+        Ok(())
+    }
+}
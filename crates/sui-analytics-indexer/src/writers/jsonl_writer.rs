@@ -0,0 +1,233 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::fs::{create_dir_all, remove_file};
+use std::io::{BufWriter, Write};
+use std::ops::Range;
+use std::path::Path;
+use std::{fs, fs::File, path::PathBuf};
+
+use anyhow::{anyhow, Result};
+use flate2::write::GzEncoder;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use sui_storage::object_store::util::path_to_filesystem;
+use sui_types::base_types::EpochId;
+
+use crate::writers::AnalyticsWriter;
+use crate::{FileCompression, FileFormat, FileType, ParquetSchema, ParquetValue};
+
+// Compressed or plain output stream of a json lines file.
+enum Encoder {
+    None(BufWriter<File>),
+    Snappy(snap::write::FrameEncoder<BufWriter<File>>),
+    Gzip(GzEncoder<BufWriter<File>>),
+    Zstd(zstd::Encoder<'static, BufWriter<File>>),
+}
+
+impl Encoder {
+    fn new(file: File, compression: FileCompression) -> Result<Self> {
+        let file = BufWriter::new(file);
+        Ok(match compression {
+            FileCompression::None => Encoder::None(file),
+            FileCompression::Snappy => Encoder::Snappy(snap::write::FrameEncoder::new(file)),
+            FileCompression::Gzip => {
+                Encoder::Gzip(GzEncoder::new(file, flate2::Compression::default()))
+            }
+            FileCompression::Zstd => Encoder::Zstd(zstd::Encoder::new(file, 0)?),
+        })
+    }
+
+    fn writer(&mut self) -> &mut dyn Write {
+        match self {
+            Encoder::None(writer) => writer,
+            Encoder::Snappy(writer) => writer,
+            Encoder::Gzip(writer) => writer,
+            Encoder::Zstd(writer) => writer,
+        }
+    }
+
+    // Write any trailing compressed data and flush the file.
+    fn finish(self) -> Result<()> {
+        let mut file = match self {
+            Encoder::None(writer) => writer,
+            Encoder::Snappy(writer) => writer.into_inner().map_err(|e| anyhow!("{e}"))?,
+            Encoder::Gzip(writer) => writer.finish()?,
+            Encoder::Zstd(writer) => writer.finish()?,
+        };
+        file.flush()?;
+        Ok(())
+    }
+}
+
+fn to_json_value(value: ParquetValue) -> Value {
+    match value {
+        ParquetValue::U64(value) => value.into(),
+        ParquetValue::I64(value) => value.into(),
+        ParquetValue::Str(value) => value.into(),
+        ParquetValue::Bool(value) => value.into(),
+        ParquetValue::OptionU64(value) => value.map_or(Value::Null, Value::from),
+        ParquetValue::OptionStr(value) => value.map_or(Value::Null, Value::from),
+    }
+}
+
+// Save table entries to newline delimited json files, one object per row with the columns
+// of the table.
+pub(crate) struct JsonLinesWriter {
+    root_dir_path: PathBuf,
+    file_type: FileType,
+    compression: FileCompression,
+    encoder: Option<Encoder>,
+    epoch: EpochId,
+    checkpoint_range: Range<u64>,
+}
+
+impl JsonLinesWriter {
+    pub(crate) fn new(
+        root_dir_path: &Path,
+        file_type: FileType,
+        start_checkpoint_seq_num: u64,
+        compression: FileCompression,
+    ) -> Result<Self> {
+        let checkpoint_range = start_checkpoint_seq_num..u64::MAX;
+        let mut writer = JsonLinesWriter {
+            root_dir_path: root_dir_path.to_path_buf(),
+            file_type,
+            compression,
+            encoder: None,
+            epoch: 0,
+            checkpoint_range,
+        };
+        writer.encoder = Some(writer.make_encoder()?);
+        Ok(writer)
+    }
+
+    fn make_encoder(&self) -> Result<Encoder> {
+        let file_path = self.file_path(self.epoch, self.checkpoint_range.clone())?;
+        create_dir_all(file_path.parent().ok_or(anyhow!("Bad directory path"))?)?;
+        if file_path.exists() {
+            remove_file(&file_path)?;
+        }
+        Encoder::new(File::create(file_path)?, self.compression)
+    }
+
+    fn file_path(&self, epoch: EpochId, range: Range<u64>) -> Result<PathBuf> {
+        path_to_filesystem(
+            self.root_dir_path.clone(),
+            &self.file_type.file_path(FileFormat::JSONL, epoch, range),
+        )
+    }
+}
+
+impl<S: Serialize + ParquetSchema> AnalyticsWriter<S> for JsonLinesWriter {
+    fn file_format(&self) -> Result<FileFormat> {
+        Ok(FileFormat::JSONL)
+    }
+
+    fn write(&mut self, rows: &[S]) -> Result<()> {
+        let writer = self
+            .encoder
+            .as_mut()
+            .ok_or(anyhow!("Writer must be reset after a flush"))?
+            .writer();
+        let columns = S::schema();
+        for row in rows {
+            let object: Map<String, Value> = columns
+                .iter()
+                .enumerate()
+                .map(|(idx, column)| (column.clone(), to_json_value(row.get_column(idx))))
+                .collect();
+            serde_json::to_writer(&mut *writer, &object)?;
+            writer.write_all(b"\n")?;
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self, end_checkpoint_seq_num: u64) -> Result<bool> {
+        let Some(encoder) = self.encoder.take() else {
+            return Ok(false);
+        };
+        encoder.finish()?;
+        let old_file_path = self.file_path(self.epoch, self.checkpoint_range.clone())?;
+        let new_file_path = self.file_path(
+            self.epoch,
+            self.checkpoint_range.start..end_checkpoint_seq_num,
+        )?;
+        fs::rename(old_file_path, new_file_path)?;
+        Ok(true)
+    }
+
+    fn reset(&mut self, epoch_num: EpochId, start_checkpoint_seq_num: u64) -> Result<()> {
+        self.checkpoint_range.start = start_checkpoint_seq_num;
+        self.checkpoint_range.end = u64::MAX;
+        self.epoch = epoch_num;
+        self.encoder = Some(self.make_encoder()?);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use flate2::read::GzDecoder;
+    use serde_json::{json, Value};
+    use std::fs::File;
+    use std::io::{BufRead, BufReader, Read};
+
+    use sui_storage::object_store::util::path_to_filesystem;
+
+    use super::JsonLinesWriter;
+    use crate::writers::test_utils::{test_entries, TestEntry};
+    use crate::writers::AnalyticsWriter;
+    use crate::{FileCompression, FileFormat, FileType};
+
+    #[test]
+    fn test_round_trip() -> Result<()> {
+        for compression in [FileCompression::None, FileCompression::Gzip] {
+            let dir = tempfile::tempdir()?;
+            let mut writer =
+                JsonLinesWriter::new(dir.path(), FileType::Checkpoint, 5, compression)?;
+            AnalyticsWriter::<TestEntry>::write(&mut writer, &test_entries())?;
+            assert!(AnalyticsWriter::<TestEntry>::flush(&mut writer, 10)?);
+
+            let file_path = path_to_filesystem(
+                dir.path().to_path_buf(),
+                &FileType::Checkpoint.file_path(FileFormat::JSONL, 0, 5..10),
+            )?;
+            let file = File::open(file_path)?;
+            let reader: Box<dyn Read> = match compression {
+                FileCompression::Gzip => Box::new(GzDecoder::new(file)),
+                _ => Box::new(file),
+            };
+            let rows = BufReader::new(reader)
+                .lines()
+                .map(|line| Ok(serde_json::from_str(&line?)?))
+                .collect::<Result<Vec<Value>>>()?;
+            assert_eq!(
+                rows,
+                vec![
+                    json!({
+                        "checkpoint": 1,
+                        "balance_change": -5,
+                        "digest": "first",
+                        "end_of_epoch": false,
+                        "gas_used": u64::MAX,
+                        "sender": null,
+                    }),
+                    json!({
+                        "checkpoint": u64::MAX,
+                        "balance_change": i64::MIN,
+                        "digest": "second",
+                        "end_of_epoch": true,
+                        "gas_used": null,
+                        "sender": "0x1",
+                    }),
+                ]
+            );
+            // the writer must be reset before it takes more rows
+            assert!(!AnalyticsWriter::<TestEntry>::flush(&mut writer, 10)?);
+        }
+        Ok(())
+    }
+}
@@ -6,8 +6,11 @@ use anyhow::Result;
 use serde::Serialize;
 use sui_types::base_types::EpochId;
 
+pub mod avro_writer;
 pub mod csv_writer;
+pub mod jsonl_writer;
 pub mod parquet_writer;
+pub mod sqlite_writer;
 
 pub trait AnalyticsWriter<S: Serialize + ParquetSchema>: Send + Sync + 'static {
     /// File format i.e. csv, parquet, etc
@@ -19,3 +22,44 @@ pub trait AnalyticsWriter<S: Serialize + ParquetSchema>: Send + Sync + 'static {
     /// Reset internal state with given epoch and checkpoint sequence number
     fn reset(&mut self, epoch_num: EpochId, start_checkpoint_seq_num: u64) -> Result<()>;
 }
+
+#[cfg(test)]
+pub(crate) mod test_utils {
+    use serde::Serialize;
+    use sui_analytics_indexer_derive::SerializeParquet;
+
+    use crate::{ParquetColumn, ParquetSchema, ParquetType, ParquetValue};
+
+    // A row with a column of every supported type.
+    #[derive(Serialize, Clone, SerializeParquet)]
+    pub(crate) struct TestEntry {
+        pub(crate) checkpoint: u64,
+        pub(crate) balance_change: i64,
+        pub(crate) digest: String,
+        pub(crate) end_of_epoch: bool,
+        pub(crate) gas_used: Option<u64>,
+        pub(crate) sender: Option<String>,
+    }
+
+    // Rows with values at the edges of each type, which must survive a round trip unchanged.
+    pub(crate) fn test_entries() -> Vec<TestEntry> {
+        vec![
+            TestEntry {
+                checkpoint: 1,
+                balance_change: -5,
+                digest: "first".to_string(),
+                end_of_epoch: false,
+                gas_used: Some(u64::MAX),
+                sender: None,
+            },
+            TestEntry {
+                checkpoint: u64::MAX,
+                balance_change: i64::MIN,
+                digest: "second".to_string(),
+                end_of_epoch: true,
+                gas_used: None,
+                sender: Some("0x1".to_string()),
+            },
+        ]
+    }
+}
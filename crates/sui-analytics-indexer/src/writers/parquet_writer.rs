@@ -1,7 +1,7 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{AnalyticsWriter, FileFormat, FileType};
+use crate::{AnalyticsWriter, FileCompression, FileFormat, FileType};
 use crate::{ParquetSchema, ParquetValue};
 use anyhow::{anyhow, Result};
 use arrow_array::{ArrayRef, BooleanArray, Int64Array, RecordBatch, StringArray, UInt64Array};
@@ -14,7 +14,7 @@ use std::sync::Arc;
 use sui_types::base_types::EpochId;
 
 use parquet::arrow::ArrowWriter;
-use parquet::basic::Compression;
+use parquet::basic::{Compression, GzipLevel, ZstdLevel};
 use parquet::file::properties::WriterProperties;
 use sui_storage::object_store::util::path_to_filesystem;
 
@@ -24,6 +24,7 @@ pub(crate) struct ParquetWriter {
     file_type: FileType,
     epoch: EpochId,
     checkpoint_range: Range<u64>,
+    compression: Compression,
     data: Vec<Vec<ParquetValue>>,
 }
 
@@ -32,13 +33,21 @@ impl ParquetWriter {
         root_dir_path: &Path,
         file_type: FileType,
         start_checkpoint_seq_num: u64,
+        compression: FileCompression,
     ) -> Result<Self> {
         let checkpoint_range = start_checkpoint_seq_num..u64::MAX;
+        let compression = match compression {
+            FileCompression::None => Compression::UNCOMPRESSED,
+            FileCompression::Snappy => Compression::SNAPPY,
+            FileCompression::Gzip => Compression::GZIP(GzipLevel::default()),
+            FileCompression::Zstd => Compression::ZSTD(ZstdLevel::default()),
+        };
         Ok(Self {
             root_dir_path: root_dir_path.to_path_buf(),
             file_type,
             epoch: 0,
             checkpoint_range,
+            compression,
             data: vec![],
         })
     }
@@ -112,7 +121,7 @@ impl<S: Serialize + ParquetSchema> AnalyticsWriter<S> for ParquetWriter {
         let batch = RecordBatch::try_from_iter(S::schema().iter().zip(batch_data.into_iter()))?;
 
         let properties = WriterProperties::builder()
-            .set_compression(Compression::SNAPPY)
+            .set_compression(self.compression)
             .build();
 
         let mut writer = ArrowWriter::try_new(self.file()?, batch.schema(), Some(properties))?;
@@ -0,0 +1,330 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::ops::Range;
+use std::path::Path;
+use std::sync::Mutex;
+
+use anyhow::Result;
+use rusqlite::types::Value;
+use rusqlite::{params, params_from_iter, Connection};
+use serde::Serialize;
+
+use sui_types::base_types::EpochId;
+
+use crate::writers::AnalyticsWriter;
+use crate::{FileFormat, FileType, ParquetSchema, ParquetType, ParquetValue};
+
+// Save table entries to a local sqlite database, one table per file type.
+// Rows are buffered and written when flushed, in a single transaction which first deletes
+// any rows previously written for the same checkpoints. Processing a range of checkpoints
+// again is therefore idempotent.
+pub(crate) struct SqliteWriter {
+    connection: Mutex<Connection>,
+    file_type: FileType,
+    epoch: EpochId,
+    checkpoint_range: Range<u64>,
+    rows: Vec<Vec<ParquetValue>>,
+}
+
+impl SqliteWriter {
+    pub(crate) fn new(
+        db_path: &Path,
+        file_type: FileType,
+        start_checkpoint_seq_num: u64,
+    ) -> Result<Self> {
+        let connection = Self::open(db_path)?;
+        Ok(Self {
+            connection: Mutex::new(connection),
+            file_type,
+            epoch: 0,
+            checkpoint_range: start_checkpoint_seq_num..u64::MAX,
+            rows: vec![],
+        })
+    }
+
+    fn open(db_path: &Path) -> Result<Connection> {
+        let connection = Connection::open(db_path)?;
+        // checkpoint ranges written for each file type
+        connection.execute_batch(
+            "CREATE TABLE IF NOT EXISTS checkpoint_ranges (
+                file_type        TEXT    NOT NULL,
+                epoch            INTEGER NOT NULL,
+                start_checkpoint INTEGER NOT NULL,
+                end_checkpoint   INTEGER NOT NULL,
+                PRIMARY KEY (file_type, start_checkpoint)
+            );",
+        )?;
+        Ok(connection)
+    }
+
+    // Checkpoint to resume from for the given file type, i.e. the end of the last range written.
+    pub(crate) fn next_checkpoint_seq_num(db_path: &Path, file_type: FileType) -> Result<u64> {
+        let connection = Self::open(db_path)?;
+        let end: Option<i64> = connection.query_row(
+            "SELECT MAX(end_checkpoint) FROM checkpoint_ranges WHERE file_type = ?1",
+            params![file_type.table_name()],
+            |row| row.get(0),
+        )?;
+        Ok(end.unwrap_or(0) as u64)
+    }
+
+    // Create the table for this file type from its schema.
+    fn create_table(
+        connection: &Connection,
+        table: &str,
+        checkpoint_column: &str,
+        columns: &[String],
+        types: &[ParquetType],
+    ) -> Result<()> {
+        let column_definitions: Vec<_> = columns
+            .iter()
+            .zip(types.iter())
+            .map(|(name, type_)| {
+                let type_ = match type_ {
+                    ParquetType::U64 | ParquetType::I64 => "INTEGER NOT NULL",
+                    ParquetType::Str => "TEXT NOT NULL",
+                    ParquetType::Bool => "BOOLEAN NOT NULL",
+                    ParquetType::OptionU64 => "INTEGER",
+                    ParquetType::OptionStr => "TEXT",
+                };
+                format!("{} {type_}", quote_identifier(name))
+            })
+            .collect();
+        connection.execute_batch(&format!(
+            "CREATE TABLE IF NOT EXISTS {} ({});
+            CREATE INDEX IF NOT EXISTS {} ON {} ({});",
+            quote_identifier(table),
+            column_definitions.join(", "),
+            quote_identifier(&format!("{table}_{checkpoint_column}")),
+            quote_identifier(table),
+            quote_identifier(checkpoint_column),
+        ))?;
+        Ok(())
+    }
+}
+
+// Table and column names can't be bound as parameters, so they are quoted instead.
+fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+fn to_sql_value(value: ParquetValue) -> Value {
+    // sqlite integers are signed, the rare u64 which doesn't fit is kept as text
+    let from_u64 = |value: u64| match i64::try_from(value) {
+        Ok(value) => Value::Integer(value),
+        Err(_) => Value::Text(value.to_string()),
+    };
+    match value {
+        ParquetValue::U64(value) => from_u64(value),
+        ParquetValue::I64(value) => Value::Integer(value),
+        ParquetValue::Str(value) => Value::Text(value),
+        ParquetValue::Bool(value) => Value::Integer(value.into()),
+        ParquetValue::OptionU64(value) => value.map(from_u64).unwrap_or(Value::Null),
+        ParquetValue::OptionStr(value) => value.map(Value::Text).unwrap_or(Value::Null),
+    }
+}
+
+impl<S: Serialize + ParquetSchema> AnalyticsWriter<S> for SqliteWriter {
+    fn file_format(&self) -> Result<FileFormat> {
+        Ok(FileFormat::SQLITE)
+    }
+
+    fn write(&mut self, rows: &[S]) -> Result<()> {
+        let num_columns = S::schema().len();
+        for row in rows {
+            self.rows
+                .push((0..num_columns).map(|idx| row.get_column(idx)).collect());
+        }
+        Ok(())
+    }
+
+    // Always returns false as there is no file to upload.
+    fn flush(&mut self, end_checkpoint_seq_num: u64) -> Result<bool> {
+        self.checkpoint_range.end = end_checkpoint_seq_num;
+        let table = self.file_type.table_name();
+        let quoted_table = quote_identifier(&table);
+        let checkpoint_column = self.file_type.checkpoint_column();
+        let quoted_checkpoint_column = quote_identifier(checkpoint_column);
+        let columns = S::schema();
+        let start = self.checkpoint_range.start as i64;
+        let end = self.checkpoint_range.end as i64;
+        let rows = std::mem::take(&mut self.rows);
+
+        let mut connection = self.connection.lock().unwrap();
+        let transaction = connection.transaction()?;
+        Self::create_table(
+            &transaction,
+            &table,
+            checkpoint_column,
+            &columns,
+            &S::types(),
+        )?;
+        transaction.execute(
+            &format!(
+                "DELETE FROM {quoted_table}
+                WHERE {quoted_checkpoint_column} >= ?1 AND {quoted_checkpoint_column} < ?2"
+            ),
+            params![start, end],
+        )?;
+        if !rows.is_empty() {
+            let quoted_columns: Vec<_> =
+                columns.iter().map(|name| quote_identifier(name)).collect();
+            let placeholders: Vec<_> = (1..=columns.len()).map(|idx| format!("?{idx}")).collect();
+            let mut insert = transaction.prepare(&format!(
+                "INSERT INTO {quoted_table} ({}) VALUES ({})",
+                quoted_columns.join(", "),
+                placeholders.join(", ")
+            ))?;
+            for row in rows {
+                insert.execute(params_from_iter(row.into_iter().map(to_sql_value)))?;
+            }
+        }
+        transaction.execute(
+            "DELETE FROM checkpoint_ranges
+            WHERE file_type = ?1 AND start_checkpoint < ?3 AND end_checkpoint > ?2",
+            params![table, start, end],
+        )?;
+        transaction.execute(
+            "INSERT INTO checkpoint_ranges (file_type, epoch, start_checkpoint, end_checkpoint)
+            VALUES (?1, ?2, ?3, ?4)",
+            params![table, self.epoch as i64, start, end],
+        )?;
+        transaction.commit()?;
+        Ok(false)
+    }
+
+    fn reset(&mut self, epoch_num: EpochId, start_checkpoint_seq_num: u64) -> Result<()> {
+        self.checkpoint_range.start = start_checkpoint_seq_num;
+        self.checkpoint_range.end = u64::MAX;
+        self.epoch = epoch_num;
+        self.rows = vec![];
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use rusqlite::Connection;
+    use std::path::Path;
+
+    use super::{quote_identifier, SqliteWriter};
+    use crate::writers::test_utils::TestEntry;
+    use crate::writers::AnalyticsWriter;
+    use crate::FileType;
+
+    fn entry(checkpoint: u64, digest: &str) -> TestEntry {
+        TestEntry {
+            checkpoint,
+            balance_change: -1,
+            digest: digest.to_string(),
+            end_of_epoch: false,
+            gas_used: Some(checkpoint * 10),
+            sender: None,
+        }
+    }
+
+    fn write(db_path: &Path, start: u64, end: u64, entries: &[TestEntry]) -> Result<()> {
+        let mut writer = SqliteWriter::new(db_path, FileType::Transaction, start)?;
+        AnalyticsWriter::<TestEntry>::write(&mut writer, entries)?;
+        assert!(!AnalyticsWriter::<TestEntry>::flush(&mut writer, end)?);
+        Ok(())
+    }
+
+    fn read(db_path: &Path) -> Result<Vec<(u64, String, Option<u64>)>> {
+        let connection = Connection::open(db_path)?;
+        let mut statement = connection.prepare(
+            "SELECT checkpoint, digest, gas_used FROM transactions ORDER BY checkpoint, digest",
+        )?;
+        let rows = statement
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(rows)
+    }
+
+    #[test]
+    fn test_write_rows() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let db_path = dir.path().join("analytics.sqlite");
+        write(&db_path, 0, 2, &[entry(0, "a"), entry(1, "b")])?;
+        write(&db_path, 2, 4, &[entry(3, "c")])?;
+
+        assert_eq!(
+            read(&db_path)?,
+            vec![
+                (0, "a".to_string(), Some(0)),
+                (1, "b".to_string(), Some(10)),
+                (3, "c".to_string(), Some(30)),
+            ]
+        );
+        assert_eq!(
+            SqliteWriter::next_checkpoint_seq_num(&db_path, FileType::Transaction)?,
+            4
+        );
+        // nothing was written for other file types
+        assert_eq!(
+            SqliteWriter::next_checkpoint_seq_num(&db_path, FileType::Event)?,
+            0
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_reprocessing_is_idempotent() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let db_path = dir.path().join("analytics.sqlite");
+        write(&db_path, 0, 2, &[entry(0, "a"), entry(1, "b")])?;
+        write(&db_path, 2, 4, &[entry(2, "c"), entry(3, "d")])?;
+
+        // processing the second range again replaces its rows instead of duplicating them
+        write(&db_path, 2, 4, &[entry(2, "c"), entry(3, "d")])?;
+        assert_eq!(read(&db_path)?.len(), 4);
+
+        // and a range with fewer rows drops the rows it no longer produces
+        write(&db_path, 2, 4, &[entry(3, "e")])?;
+        assert_eq!(
+            read(&db_path)?,
+            vec![
+                (0, "a".to_string(), Some(0)),
+                (1, "b".to_string(), Some(10)),
+                (3, "e".to_string(), Some(30)),
+            ]
+        );
+
+        let connection = Connection::open(&db_path)?;
+        let ranges: i64 = connection.query_row(
+            "SELECT COUNT(*) FROM checkpoint_ranges WHERE file_type = 'transactions'",
+            [],
+            |row| row.get(0),
+        )?;
+        assert_eq!(ranges, 2);
+        assert_eq!(
+            SqliteWriter::next_checkpoint_seq_num(&db_path, FileType::Transaction)?,
+            4
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_empty_range_creates_table() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let db_path = dir.path().join("analytics.sqlite");
+        write(&db_path, 0, 2, &[])?;
+        assert!(read(&db_path)?.is_empty());
+        assert_eq!(
+            SqliteWriter::next_checkpoint_seq_num(&db_path, FileType::Transaction)?,
+            2
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_quote_identifier() {
+        assert_eq!(quote_identifier("transactions"), "\"transactions\"");
+        assert_eq!(
+            quote_identifier("a\"; DROP TABLE b; --"),
+            "\"a\"\"; DROP TABLE b; --\""
+        );
+    }
+}
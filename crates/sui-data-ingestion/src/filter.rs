@@ -0,0 +1,110 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use move_core_types::language_storage::StructTag;
+use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, DisplayFromStr};
+use std::collections::BTreeSet;
+use sui_types::base_types::{ObjectID, SuiAddress};
+use sui_types::full_checkpoint_content::{CheckpointData, CheckpointTransaction};
+use sui_types::object::Object;
+use sui_types::transaction::TransactionDataAPI;
+
+/// Declarative filter applied to checkpoints before they are dispatched to the workers of a pool.
+/// A transaction is kept if it matches any of the non-empty criteria, an empty filter keeps
+/// every transaction. When object types are set, only the input and output objects of those
+/// types are kept in the remaining transactions.
+/// Checkpoint summary and contents are always passed through unchanged.
+#[serde_as]
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct CheckpointFilter {
+    /// Transactions calling into, emitting events from or touching objects of these packages
+    pub packages: BTreeSet<ObjectID>,
+    /// Transactions emitting events of these types.
+    /// A type without type parameters matches all of its instantiations.
+    #[serde_as(as = "Vec<DisplayFromStr>")]
+    pub event_types: Vec<StructTag>,
+    /// Transactions sent by these addresses
+    pub senders: BTreeSet<SuiAddress>,
+    /// Transactions touching objects of these types, matched like `event_types`
+    #[serde_as(as = "Vec<DisplayFromStr>")]
+    pub object_types: Vec<StructTag>,
+}
+
+impl CheckpointFilter {
+    pub fn is_empty(&self) -> bool {
+        self.packages.is_empty()
+            && self.event_types.is_empty()
+            && self.senders.is_empty()
+            && self.object_types.is_empty()
+    }
+
+    pub fn apply(&self, mut checkpoint: CheckpointData) -> CheckpointData {
+        if self.is_empty() {
+            return checkpoint;
+        }
+        checkpoint
+            .transactions
+            .retain(|transaction| self.matches(transaction));
+        if !self.object_types.is_empty() {
+            for transaction in checkpoint.transactions.iter_mut() {
+                transaction
+                    .input_objects
+                    .retain(|object| self.matches_object_type(object));
+                transaction
+                    .output_objects
+                    .retain(|object| self.matches_object_type(object));
+            }
+        }
+        checkpoint
+    }
+
+    fn matches(&self, transaction: &CheckpointTransaction) -> bool {
+        let data = transaction.transaction.data().transaction_data();
+        if self.senders.contains(&data.sender()) {
+            return true;
+        }
+        if data
+            .move_calls()
+            .into_iter()
+            .any(|(package, _, _)| self.packages.contains(package))
+        {
+            return true;
+        }
+        let events = transaction.events.iter().flat_map(|events| &events.data);
+        for event in events {
+            if self.packages.contains(&event.package_id)
+                || self.packages.contains(&ObjectID::from(event.type_.address))
+                || matches_any_type(&self.event_types, &event.type_)
+            {
+                return true;
+            }
+        }
+        transaction
+            .input_objects
+            .iter()
+            .chain(&transaction.output_objects)
+            .any(|object| {
+                self.matches_object_type(object)
+                    || object.struct_tag().map_or(false, |tag| {
+                        self.packages.contains(&ObjectID::from(tag.address))
+                    })
+            })
+    }
+
+    fn matches_object_type(&self, object: &Object) -> bool {
+        object
+            .struct_tag()
+            .map_or(false, |tag| matches_any_type(&self.object_types, &tag))
+    }
+}
+
+fn matches_any_type(types: &[StructTag], tag: &StructTag) -> bool {
+    types.iter().any(|type_| {
+        type_.address == tag.address
+            && type_.module == tag.module
+            && type_.name == tag.name
+            && (type_.type_params.is_empty() || type_.type_params == tag.type_params)
+    })
+}
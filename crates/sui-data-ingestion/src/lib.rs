@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 mod executor;
+mod filter;
 mod metrics;
 mod progress_store;
 mod reader;
@@ -11,7 +12,15 @@ mod worker_pool;
 mod workers;
 
 pub use executor::IndexerExecutor;
+pub use filter::CheckpointFilter;
 pub use metrics::DataIngestionMetrics;
-pub use progress_store::{DynamoDBProgressStore, FileProgressStore};
+pub use progress_store::{
+    DynamoDBProgressStore, FileProgressStore, PostgresProgressStore, ProgressStore,
+    RocksDBProgressStore, TransactionalProgressStore,
+};
 pub use worker_pool::WorkerPool;
-pub use workers::{S3TaskConfig, S3Worker, Worker};
+pub use workers::{
+    EventRecord, FileQueueTaskConfig, FileQueueWorker, KafkaTaskConfig, KafkaWorker, KeyedRecord,
+    RecordKey, RecordSerialization, S3TaskConfig, S3Worker, TransactionRecord, TransactionalWorker,
+    TransactionalWorkerAdapter, Worker,
+};
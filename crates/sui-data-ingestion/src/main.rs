@@ -3,18 +3,26 @@
 
 use anyhow::Result;
 use prometheus::Registry;
-use serde::{Deserialize, Serialize};
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize};
 use std::env;
 use std::path::PathBuf;
-use sui_data_ingestion::{DataIngestionMetrics, DynamoDBProgressStore, S3TaskConfig, S3Worker};
-use sui_data_ingestion::{IndexerExecutor, WorkerPool};
+use sui_data_ingestion::{
+    CheckpointFilter, DataIngestionMetrics, DynamoDBProgressStore, FileQueueTaskConfig,
+    FileQueueWorker, KafkaTaskConfig, KafkaWorker, S3TaskConfig, S3Worker, Worker,
+};
+use sui_data_ingestion::{
+    IndexerExecutor, PostgresProgressStore, ProgressStore, RocksDBProgressStore, WorkerPool,
+};
 use tokio::signal;
 use tokio::sync::oneshot;
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
-#[serde(rename_all = "lowercase")]
+#[serde(rename_all = "snake_case")]
 enum Task {
     S3(S3TaskConfig),
+    Kafka(KafkaTaskConfig),
+    FileQueue(FileQueueTaskConfig),
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -22,21 +30,33 @@ struct TaskConfig {
     #[serde(flatten)]
     task: Task,
     concurrency: usize,
+    #[serde(default)]
+    filter: CheckpointFilter,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
-#[serde(rename_all = "lowercase")]
-struct ProgressStoreConfig {
-    pub aws_access_key_id: String,
-    pub aws_secret_access_key: String,
-    pub aws_region: String,
-    pub table_name: String,
+#[serde(tag = "type", rename_all = "lowercase")]
+enum ProgressStoreConfig {
+    DynamoDB {
+        aws_access_key_id: String,
+        aws_secret_access_key: String,
+        aws_region: String,
+        table_name: String,
+    },
+    Postgres {
+        database_url: String,
+        table_name: String,
+    },
+    RocksDB {
+        path: PathBuf,
+    },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct IndexerConfig {
     path: PathBuf,
     tasks: Vec<TaskConfig>,
+    #[serde(deserialize_with = "deserialize_progress_store")]
     progress_store: ProgressStoreConfig,
     #[serde(default = "default_metrics_host")]
     metrics_host: String,
@@ -44,6 +64,19 @@ struct IndexerConfig {
     metrics_port: u16,
 }
 
+/// Configs written before progress stores were tagged only describe DynamoDB stores, so a
+/// missing `type` defaults to `dynamodb`.
+fn deserialize_progress_store<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<ProgressStoreConfig, D::Error> {
+    let mut config = serde_yaml::Mapping::deserialize(deserializer)?;
+    let tag = serde_yaml::Value::from("type");
+    if !config.contains_key(&tag) {
+        config.insert(tag, serde_yaml::Value::from("dynamodb"));
+    }
+    serde_yaml::from_value(serde_yaml::Value::Mapping(config)).map_err(D::Error::custom)
+}
+
 fn default_metrics_host() -> String {
     "127.0.0.1".to_string()
 }
@@ -90,21 +123,129 @@ async fn main() -> Result<()> {
     mysten_metrics::init_metrics(&registry);
     let metrics = DataIngestionMetrics::new(&registry);
 
-    let progress_store = DynamoDBProgressStore::new(
-        &config.progress_store.aws_access_key_id,
-        &config.progress_store.aws_secret_access_key,
-        config.progress_store.aws_region,
-        config.progress_store.table_name,
-    )
-    .await;
+    match config.progress_store.clone() {
+        ProgressStoreConfig::DynamoDB {
+            aws_access_key_id,
+            aws_secret_access_key,
+            aws_region,
+            table_name,
+        } => {
+            let progress_store = DynamoDBProgressStore::new(
+                &aws_access_key_id,
+                &aws_secret_access_key,
+                aws_region,
+                table_name,
+            )
+            .await;
+            run(config, progress_store, metrics, exit_receiver).await
+        }
+        ProgressStoreConfig::Postgres {
+            database_url,
+            table_name,
+        } => {
+            let progress_store = PostgresProgressStore::new(database_url, table_name).await?;
+            run(config, progress_store, metrics, exit_receiver).await
+        }
+        ProgressStoreConfig::RocksDB { path } => {
+            let progress_store = RocksDBProgressStore::new(&path)?;
+            run(config, progress_store, metrics, exit_receiver).await
+        }
+    }
+}
+
+async fn run<P: ProgressStore>(
+    config: IndexerConfig,
+    progress_store: P,
+    metrics: DataIngestionMetrics,
+    exit_receiver: oneshot::Receiver<()>,
+) -> Result<()> {
     let mut executor = IndexerExecutor::new(progress_store, metrics);
     for task_config in config.tasks {
-        let worker = match task_config.task {
-            Task::S3(s3_config) => S3Worker::new(s3_config).await,
-        };
-        let worker_pool = WorkerPool::new(worker, task_config.concurrency);
-        executor.register(worker_pool).await?;
+        let TaskConfig {
+            task,
+            concurrency,
+            filter,
+        } = task_config;
+        match task {
+            Task::S3(s3_config) => {
+                let worker = S3Worker::new(s3_config).await;
+                register(&mut executor, worker, concurrency, filter).await?
+            }
+            Task::Kafka(kafka_config) => {
+                let worker = KafkaWorker::new(kafka_config).await?;
+                register(&mut executor, worker, concurrency, filter).await?
+            }
+            Task::FileQueue(file_queue_config) => {
+                let worker = FileQueueWorker::new(file_queue_config)?;
+                register(&mut executor, worker, concurrency, filter).await?
+            }
+        }
     }
     executor.run(config.path, exit_receiver).await?;
     Ok(())
 }
+
+async fn register<P: ProgressStore, W: Worker + 'static>(
+    executor: &mut IndexerExecutor<P>,
+    worker: W,
+    concurrency: usize,
+    filter: CheckpointFilter,
+) -> Result<()> {
+    let worker_pool = WorkerPool::new(worker, concurrency).with_filter(filter);
+    executor.register(worker_pool).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{IndexerConfig, ProgressStoreConfig};
+
+    #[test]
+    fn untagged_progress_store_is_dynamodb() {
+        let config: IndexerConfig = serde_yaml::from_str(
+            r#"
+path: /tmp/checkpoints
+tasks: []
+progress_store:
+  aws_access_key_id: id
+  aws_secret_access_key: secret
+  aws_region: us-west-2
+  table_name: progress
+"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            config.progress_store,
+            ProgressStoreConfig::DynamoDB { table_name, .. } if table_name == "progress"
+        ));
+    }
+
+    #[test]
+    fn tagged_progress_store() {
+        let config: IndexerConfig = serde_yaml::from_str(
+            r#"
+path: /tmp/checkpoints
+tasks: []
+progress_store:
+  type: postgres
+  database_url: postgres://localhost/ingestion
+  table_name: progress
+"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            config.progress_store,
+            ProgressStoreConfig::Postgres { table_name, .. } if table_name == "progress"
+        ));
+
+        let config: Result<IndexerConfig, _> = serde_yaml::from_str(
+            r#"
+path: /tmp/checkpoints
+tasks: []
+progress_store:
+  type: rocksdb
+  database_url: postgres://localhost/ingestion
+"#,
+        );
+        assert!(config.is_err());
+    }
+}
@@ -7,8 +7,12 @@ use std::collections::HashMap;
 use sui_types::messages_checkpoint::CheckpointSequenceNumber;
 mod dynamodb;
 mod file;
+mod postgres;
+mod rocks;
 pub use dynamodb::DynamoDBProgressStore;
 pub use file::FileProgressStore;
+pub use postgres::PostgresProgressStore;
+pub use rocks::RocksDBProgressStore;
 
 pub type ExecutorProgress = HashMap<String, CheckpointSequenceNumber>;
 
@@ -22,6 +26,29 @@ pub trait ProgressStore: Send {
     ) -> Result<()>;
 }
 
+/// Progress store which can record a processed checkpoint in the same transaction as the
+/// output of the worker that processed it, so a checkpoint is never applied twice.
+/// The executor watermark only moves on checkpoints which were committed, checkpoints above it
+/// which were committed before a restart are reported by `is_processed` and skipped.
+#[async_trait]
+pub trait TransactionalProgressStore: Send + Sync {
+    type Transaction: Send;
+
+    async fn begin(&self) -> Result<Self::Transaction>;
+    /// Atomically applies the transaction and marks the checkpoint as processed for the task
+    async fn commit(
+        &self,
+        transaction: Self::Transaction,
+        task_name: &str,
+        checkpoint_number: CheckpointSequenceNumber,
+    ) -> Result<()>;
+    async fn is_processed(
+        &self,
+        task_name: &str,
+        checkpoint_number: CheckpointSequenceNumber,
+    ) -> Result<bool>;
+}
+
 pub struct ProgressStoreWrapper<P> {
     progress_store: P,
     pending_state: ExecutorProgress,
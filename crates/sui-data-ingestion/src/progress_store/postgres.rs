@@ -0,0 +1,97 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::progress_store::ProgressStore;
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use diesel::r2d2::{ConnectionManager, Pool};
+use diesel::sql_types::{BigInt, Text};
+use diesel::{OptionalExtension, PgConnection, QueryableByName, RunQueryDsl};
+use sui_types::messages_checkpoint::CheckpointSequenceNumber;
+
+/// Progress store keeping the watermark of every task in a single Postgres table,
+/// one row per task name.
+#[derive(Clone)]
+pub struct PostgresProgressStore {
+    pool: Pool<ConnectionManager<PgConnection>>,
+    table_name: String,
+}
+
+#[derive(QueryableByName)]
+struct Watermark {
+    #[diesel(sql_type = BigInt)]
+    checkpoint_number: i64,
+}
+
+impl PostgresProgressStore {
+    pub async fn new(database_url: String, table_name: String) -> Result<Self> {
+        // the table name can't be bound as a parameter, so only plain identifiers are accepted
+        let mut chars = table_name.chars();
+        if !chars
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            || !chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        {
+            bail!("Invalid progress store table name {table_name:?}");
+        }
+        let query = format!(
+            "CREATE TABLE IF NOT EXISTS {} (
+                task_name         TEXT   PRIMARY KEY,
+                checkpoint_number BIGINT NOT NULL
+            )",
+            table_name
+        );
+        let pool = tokio::task::spawn_blocking(move || {
+            let pool = Pool::builder()
+                .max_size(1)
+                .build(ConnectionManager::<PgConnection>::new(database_url))?;
+            diesel::sql_query(query).execute(&mut pool.get()?)?;
+            Ok::<_, anyhow::Error>(pool)
+        })
+        .await??;
+        Ok(Self { pool, table_name })
+    }
+}
+
+#[async_trait]
+impl ProgressStore for PostgresProgressStore {
+    async fn load(&mut self, task_name: String) -> Result<CheckpointSequenceNumber> {
+        let pool = self.pool.clone();
+        let query = format!(
+            "SELECT checkpoint_number FROM {} WHERE task_name = $1",
+            self.table_name
+        );
+        let watermark = tokio::task::spawn_blocking(move || {
+            diesel::sql_query(query)
+                .bind::<Text, _>(task_name)
+                .get_result::<Watermark>(&mut pool.get()?)
+                .optional()
+                .map_err(anyhow::Error::from)
+        })
+        .await??;
+        Ok(watermark
+            .map(|watermark| watermark.checkpoint_number as CheckpointSequenceNumber)
+            .unwrap_or_default())
+    }
+    async fn save(
+        &mut self,
+        task_name: String,
+        checkpoint_number: CheckpointSequenceNumber,
+    ) -> Result<()> {
+        let pool = self.pool.clone();
+        let query = format!(
+            "INSERT INTO {} (task_name, checkpoint_number) VALUES ($1, $2)
+            ON CONFLICT (task_name) DO UPDATE SET checkpoint_number = EXCLUDED.checkpoint_number",
+            self.table_name
+        );
+        tokio::task::spawn_blocking(move || {
+            diesel::sql_query(query)
+                .bind::<Text, _>(task_name)
+                .bind::<BigInt, _>(checkpoint_number as i64)
+                .execute(&mut pool.get()?)
+                .map_err(anyhow::Error::from)
+        })
+        .await??;
+        Ok(())
+    }
+}
@@ -0,0 +1,145 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::progress_store::{ProgressStore, TransactionalProgressStore};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use rocksdb::{BoundColumnFamily, Options, WriteBatch, DB};
+use std::path::Path;
+use std::sync::Arc;
+use sui_types::messages_checkpoint::CheckpointSequenceNumber;
+
+const WATERMARKS_CF: &str = "watermarks";
+const PROCESSED_CHECKPOINTS_CF: &str = "processed_checkpoints";
+
+/// Progress store backed by a local RocksDB instance.
+/// Workers wrapped in a `TransactionalWorkerAdapter` write their output to the same database
+/// through a `WriteBatch`, which is committed together with the processed checkpoint.
+#[derive(Clone)]
+pub struct RocksDBProgressStore {
+    db: Arc<DB>,
+}
+
+impl RocksDBProgressStore {
+    pub fn new(path: &Path) -> Result<Self> {
+        let mut options = Options::default();
+        options.create_if_missing(true);
+        options.create_missing_column_families(true);
+        let mut column_families = DB::list_cf(&options, path).unwrap_or_default();
+        for name in [WATERMARKS_CF, PROCESSED_CHECKPOINTS_CF] {
+            if !column_families.iter().any(|cf| cf == name) {
+                column_families.push(name.to_string());
+            }
+        }
+        let db = DB::open_cf(&options, path, column_families)?;
+        Ok(Self { db: Arc::new(db) })
+    }
+
+    /// Underlying database, used by workers to create their own column families
+    pub fn db(&self) -> Arc<DB> {
+        self.db.clone()
+    }
+
+    fn cf(&self, name: &str) -> Result<Arc<BoundColumnFamily<'_>>> {
+        self.db
+            .cf_handle(name)
+            .ok_or_else(|| anyhow!("missing column family {}", name))
+    }
+
+    fn processed_checkpoint_key(
+        task_name: &str,
+        checkpoint_number: CheckpointSequenceNumber,
+    ) -> Vec<u8> {
+        let mut key = task_name.as_bytes().to_vec();
+        key.push(0);
+        key.extend_from_slice(&checkpoint_number.to_be_bytes());
+        key
+    }
+
+    fn watermark(&self, task_name: &str) -> Result<CheckpointSequenceNumber> {
+        let watermark = self.db.get_cf(&self.cf(WATERMARKS_CF)?, task_name)?;
+        Ok(match watermark {
+            Some(bytes) => CheckpointSequenceNumber::from_be_bytes(
+                bytes
+                    .as_slice()
+                    .try_into()
+                    .map_err(|_| anyhow!("corrupted watermark for task {}", task_name))?,
+            ),
+            None => 0,
+        })
+    }
+}
+
+#[async_trait]
+impl ProgressStore for RocksDBProgressStore {
+    async fn load(&mut self, task_name: String) -> Result<CheckpointSequenceNumber> {
+        let mut watermark = self.watermark(&task_name)?;
+        // checkpoints committed after the last saved watermark
+        while self.is_processed(&task_name, watermark).await? {
+            watermark += 1;
+        }
+        Ok(watermark)
+    }
+
+    async fn save(
+        &mut self,
+        task_name: String,
+        checkpoint_number: CheckpointSequenceNumber,
+    ) -> Result<()> {
+        let mut batch = WriteBatch::default();
+        batch.put_cf(
+            &self.cf(WATERMARKS_CF)?,
+            &task_name,
+            checkpoint_number.to_be_bytes(),
+        );
+        // processed checkpoints below the watermark are no longer needed
+        batch.delete_range_cf(
+            &self.cf(PROCESSED_CHECKPOINTS_CF)?,
+            Self::processed_checkpoint_key(&task_name, 0),
+            Self::processed_checkpoint_key(&task_name, checkpoint_number),
+        );
+        self.db.write(batch)?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl TransactionalProgressStore for RocksDBProgressStore {
+    type Transaction = WriteBatch;
+
+    async fn begin(&self) -> Result<WriteBatch> {
+        Ok(WriteBatch::default())
+    }
+
+    async fn commit(
+        &self,
+        mut transaction: WriteBatch,
+        task_name: &str,
+        checkpoint_number: CheckpointSequenceNumber,
+    ) -> Result<()> {
+        transaction.put_cf(
+            &self.cf(PROCESSED_CHECKPOINTS_CF)?,
+            Self::processed_checkpoint_key(task_name, checkpoint_number),
+            b"",
+        );
+        self.db.write(transaction)?;
+        Ok(())
+    }
+
+    async fn is_processed(
+        &self,
+        task_name: &str,
+        checkpoint_number: CheckpointSequenceNumber,
+    ) -> Result<bool> {
+        if checkpoint_number < self.watermark(task_name)? {
+            return Ok(true);
+        }
+        Ok(self
+            .db
+            .get_pinned_cf(
+                &self.cf(PROCESSED_CHECKPOINTS_CF)?,
+                Self::processed_checkpoint_key(task_name, checkpoint_number),
+            )?
+            .is_some())
+    }
+}
@@ -1,31 +1,46 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::progress_store::ExecutorProgress;
+use crate::progress_store::{ExecutorProgress, ProgressStore, TransactionalProgressStore};
 use crate::reader::ENV_VAR_LOCAL_READ_TIMEOUT_MS;
-use crate::workers::Worker;
-use crate::{DataIngestionMetrics, FileProgressStore, IndexerExecutor, WorkerPool};
+use crate::workers::{TransactionalWorker, TransactionalWorkerAdapter, Worker};
+use crate::{
+    CheckpointFilter, DataIngestionMetrics, EventRecord, FileProgressStore, FileQueueTaskConfig,
+    FileQueueWorker, IndexerExecutor, RecordKey, RecordSerialization, RocksDBProgressStore,
+    TransactionRecord, WorkerPool,
+};
 use anyhow::Result;
 use async_trait::async_trait;
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::StructTag;
 use prometheus::Registry;
 use rand::prelude::StdRng;
 use rand::SeedableRng;
+use rocksdb::WriteBatch;
 use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 use std::time::Duration;
 use sui_storage::blob::{Blob, BlobEncoding};
-use sui_types::crypto::KeypairTraits;
-use sui_types::full_checkpoint_content::CheckpointData;
+use sui_types::base_types::{random_object_ref, ObjectID, SuiAddress};
+use sui_types::crypto::{get_key_pair, AccountKeyPair, KeypairTraits};
+use sui_types::effects::{TransactionEffects, TransactionEvents};
+use sui_types::event::Event;
+use sui_types::full_checkpoint_content::{CheckpointData, CheckpointTransaction};
 use sui_types::gas::GasCostSummary;
 use sui_types::messages_checkpoint::{
     CertifiedCheckpointSummary, CheckpointContents, CheckpointSequenceNumber, CheckpointSummary,
     SignedCheckpointSummary,
 };
-use sui_types::utils::make_committee_key;
+use sui_types::object::Object;
+use sui_types::transaction::{TransactionData, TransactionDataAPI};
+use sui_types::utils::{make_committee_key, to_sender_signed_transaction};
 use tempfile::NamedTempFile;
 use tokio::sync::oneshot;
 
-async fn add_worker_pool<W: Worker + 'static>(
-    indexer: &mut IndexerExecutor<FileProgressStore>,
+async fn add_worker_pool<W: Worker + 'static, P: ProgressStore>(
+    indexer: &mut IndexerExecutor<P>,
     worker: W,
     concurrency: usize,
 ) -> Result<()> {
@@ -34,8 +49,8 @@ async fn add_worker_pool<W: Worker + 'static>(
     Ok(())
 }
 
-async fn run(
-    indexer: IndexerExecutor<FileProgressStore>,
+async fn run<P: ProgressStore + 'static>(
+    indexer: IndexerExecutor<P>,
     path: Option<PathBuf>,
     duration: Option<Duration>,
 ) -> Result<ExecutorProgress> {
@@ -74,6 +89,34 @@ impl Worker for TestWorker {
     }
 }
 
+#[derive(Clone, Default)]
+struct TestTransactionalWorker {
+    processed: Arc<AtomicUsize>,
+    fail: bool,
+}
+
+#[async_trait]
+impl TransactionalWorker<RocksDBProgressStore> for TestTransactionalWorker {
+    async fn process_checkpoint(
+        &self,
+        checkpoint: &CheckpointData,
+        transaction: &mut WriteBatch,
+    ) -> Result<()> {
+        self.processed.fetch_add(1, Ordering::SeqCst);
+        transaction.put(
+            checkpoint.checkpoint_summary.sequence_number.to_be_bytes(),
+            b"processed",
+        );
+        if self.fail {
+            anyhow::bail!("failed to process checkpoint");
+        }
+        Ok(())
+    }
+    fn name(&self) -> &'static str {
+        "transactional_test"
+    }
+}
+
 #[tokio::test]
 async fn empty_pools() {
     let bundle = create_executor_bundle();
@@ -100,6 +143,285 @@ async fn basic_flow() {
     assert_eq!(result.unwrap().get(TestWorker.name()), Some(&20));
 }
 
+#[tokio::test]
+async fn transactional_flow() {
+    let mut progress_store = RocksDBProgressStore::new(&temp_dir()).unwrap();
+    let worker = TestTransactionalWorker::default();
+    let mut executor = IndexerExecutor::new(
+        progress_store.clone(),
+        DataIngestionMetrics::new(&Registry::new()),
+    );
+    let adapter = TransactionalWorkerAdapter::new(worker.clone(), progress_store.clone());
+    add_worker_pool(&mut executor, adapter.clone(), 5)
+        .await
+        .unwrap();
+    let path = temp_dir();
+    for checkpoint_number in 0..20 {
+        let bytes = mock_checkpoint_data_bytes(checkpoint_number);
+        std::fs::write(path.join(format!("{}.chk", checkpoint_number)), bytes).unwrap();
+    }
+    let result = run(executor, Some(path), Some(Duration::from_secs(1))).await;
+    assert_eq!(result.unwrap().get(worker.name()), Some(&20));
+    assert_eq!(worker.processed.load(Ordering::SeqCst), 20);
+    let db = progress_store.db();
+    for checkpoint_number in 0..20u64 {
+        assert!(db.get(checkpoint_number.to_be_bytes()).unwrap().is_some());
+    }
+
+    // committed checkpoints are never processed again
+    adapter
+        .process_checkpoint(mock_checkpoint_data(5))
+        .await
+        .unwrap();
+    assert_eq!(worker.processed.load(Ordering::SeqCst), 20);
+
+    // a checkpoint committed ahead of the saved watermark is picked up on restart
+    adapter
+        .process_checkpoint(mock_checkpoint_data(20))
+        .await
+        .unwrap();
+    assert_eq!(
+        progress_store
+            .load(worker.name().to_string())
+            .await
+            .unwrap(),
+        21
+    );
+
+    // output of a failed attempt is never applied
+    let failing_worker = TestTransactionalWorker {
+        fail: true,
+        ..worker.clone()
+    };
+    let failing_adapter = TransactionalWorkerAdapter::new(failing_worker, progress_store.clone());
+    assert!(failing_adapter
+        .process_checkpoint(mock_checkpoint_data(21))
+        .await
+        .is_err());
+    assert!(db.get(21u64.to_be_bytes()).unwrap().is_none());
+    assert!(!progress_store
+        .is_processed(worker.name(), 21)
+        .await
+        .unwrap());
+}
+
+async fn assert_named_tasks_progress<P: ProgressStore>(mut progress_store: P) {
+    assert_eq!(progress_store.load("first".to_string()).await.unwrap(), 0);
+    progress_store.save("first".to_string(), 10).await.unwrap();
+    progress_store.save("second".to_string(), 3).await.unwrap();
+    progress_store.save("first".to_string(), 12).await.unwrap();
+    assert_eq!(progress_store.load("first".to_string()).await.unwrap(), 12);
+    assert_eq!(progress_store.load("second".to_string()).await.unwrap(), 3);
+}
+
+#[tokio::test]
+async fn rocksdb_progress_store() {
+    let path = temp_dir();
+    assert_named_tasks_progress(RocksDBProgressStore::new(&path).unwrap()).await;
+    // progress survives reopening the database
+    let mut progress_store = RocksDBProgressStore::new(&path).unwrap();
+    assert_eq!(progress_store.load("first".to_string()).await.unwrap(), 12);
+}
+
+#[cfg(feature = "pg_integration")]
+#[tokio::test]
+async fn postgres_progress_store() {
+    let pg_host = std::env::var("POSTGRES_HOST").unwrap_or_else(|_| "localhost".into());
+    let pg_port = std::env::var("POSTGRES_PORT").unwrap_or_else(|_| "32770".into());
+    let pw = std::env::var("POSTGRES_PASSWORD").unwrap_or_else(|_| "postgrespw".into());
+    let database_url = format!("postgres://postgres:{pw}@{pg_host}:{pg_port}");
+    let table_name = format!("progress_store_{}", rand::random::<u32>());
+    assert_named_tasks_progress(
+        crate::PostgresProgressStore::new(database_url, table_name)
+            .await
+            .unwrap(),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn postgres_progress_store_rejects_invalid_table_name() {
+    for table_name in [
+        "",
+        "1progress",
+        "progress; DROP TABLE users",
+        "\"progress\"",
+    ] {
+        let result = crate::PostgresProgressStore::new(
+            "postgres://localhost".to_string(),
+            table_name.into(),
+        )
+        .await;
+        assert!(result
+            .err()
+            .unwrap()
+            .to_string()
+            .contains("Invalid progress store table name"));
+    }
+}
+
+#[test]
+fn kafka_partition_for_key() {
+    // hashes from the murmur2 tests of the Kafka clients, masked to positive values
+    for (key, hash) in [
+        ("21", 1173551340),
+        ("foobar", 1357151166),
+        ("a-little-bit-long-string", 1161502112),
+        ("a-little-bit-longer-string", 661178819),
+        (
+            "lkjh234lh9fiuh90y23oiuhsafujhadof229phr9h19h89h8",
+            2088585677,
+        ),
+        ("abc", 479470107),
+    ] {
+        assert_eq!(
+            crate::workers::partition_for_key(key.as_bytes(), 1 << 31),
+            hash
+        );
+    }
+    assert_eq!(crate::workers::partition_for_key(b"foobar", 12), 6);
+    assert_eq!(crate::workers::partition_for_key(b"foobar", 1), 0);
+}
+
+#[test]
+fn checkpoint_filter() {
+    let event_type = StructTag::from_str("0x3::test::TestEvent").unwrap();
+    let (first, first_sender) = mock_transaction(Some(event_type.clone()));
+    let (second, second_sender) = mock_transaction(None);
+    let mut checkpoint = mock_checkpoint_data(0);
+    checkpoint.transactions = vec![first, second];
+    let filtered_senders = |filter: CheckpointFilter| -> Vec<SuiAddress> {
+        filter
+            .apply(checkpoint.clone())
+            .transactions
+            .iter()
+            .map(|transaction| transaction.transaction.data().transaction_data().sender())
+            .collect()
+    };
+
+    assert_eq!(
+        filtered_senders(CheckpointFilter::default()),
+        vec![first_sender, second_sender]
+    );
+    let filter = CheckpointFilter {
+        senders: [second_sender].into(),
+        ..Default::default()
+    };
+    assert_eq!(filtered_senders(filter), vec![second_sender]);
+    let filter = CheckpointFilter {
+        event_types: vec![event_type],
+        ..Default::default()
+    };
+    assert_eq!(filtered_senders(filter), vec![first_sender]);
+    let filter = CheckpointFilter {
+        packages: [ObjectID::from_str("0x3").unwrap()].into(),
+        ..Default::default()
+    };
+    assert_eq!(filtered_senders(filter), vec![first_sender]);
+
+    // types without type parameters match any instantiation
+    let filter: CheckpointFilter =
+        serde_yaml::from_str("object_types: [\"0x2::coin::Coin\"]").unwrap();
+    assert_eq!(filtered_senders(filter), vec![first_sender, second_sender]);
+    let filter: CheckpointFilter =
+        serde_yaml::from_str("object_types: [\"0x2::coin::Coin<0x3::test::TEST>\"]").unwrap();
+    assert!(filtered_senders(filter).is_empty());
+
+    // objects not matching the object types are dropped
+    let filter = CheckpointFilter {
+        senders: [first_sender].into(),
+        object_types: vec![StructTag::from_str("0x3::test::Object").unwrap()],
+        ..Default::default()
+    };
+    let filtered = filter.apply(checkpoint.clone());
+    assert_eq!(filtered.transactions.len(), 1);
+    assert!(filtered.transactions[0].output_objects.is_empty());
+}
+
+#[tokio::test]
+async fn file_queue_worker() {
+    let path = temp_dir();
+    let worker = FileQueueWorker::new(FileQueueTaskConfig {
+        path: path.clone(),
+        key: RecordKey::Sender,
+        serialization: RecordSerialization::Bcs,
+    })
+    .unwrap();
+    let event_type = StructTag::from_str("0x3::test::TestEvent").unwrap();
+    let (transaction, sender) = mock_transaction(Some(event_type.clone()));
+    let mut checkpoint = mock_checkpoint_data(7);
+    checkpoint.transactions = vec![transaction.clone(), mock_transaction(None).0];
+    worker.process_checkpoint(checkpoint).await.unwrap();
+
+    let transactions =
+        FileQueueWorker::read_records(&path.join("transactions").join("7.records")).unwrap();
+    assert_eq!(transactions.len(), 2);
+    assert_eq!(transactions[0].key, sender.to_string().into_bytes());
+    let record: TransactionRecord = bcs::from_bytes(&transactions[0].value).unwrap();
+    assert_eq!(record.checkpoint, 7);
+    assert_eq!(
+        record.transaction.transaction.digest(),
+        transaction.transaction.digest()
+    );
+
+    let events = FileQueueWorker::read_records(&path.join("events").join("7.records")).unwrap();
+    assert_eq!(events.len(), 1);
+    let record: EventRecord = bcs::from_bytes(&events[0].value).unwrap();
+    assert_eq!(record.transaction_digest, *transaction.transaction.digest());
+    assert_eq!(record.event.type_, event_type);
+}
+
+#[cfg(feature = "kafka_integration")]
+#[tokio::test]
+async fn kafka_worker() {
+    use crate::{KafkaTaskConfig, KafkaWorker};
+    use rskafka::client::partition::UnknownTopicHandling;
+    use rskafka::client::ClientBuilder;
+
+    let brokers = std::env::var("KAFKA_BROKERS").unwrap_or_else(|_| "localhost:9092".into());
+    let brokers: Vec<String> = brokers.split(',').map(String::from).collect();
+    let suffix = rand::random::<u32>();
+    let transactions_topic = format!("transactions_{suffix}");
+    let events_topic = format!("events_{suffix}");
+    let client = ClientBuilder::new(brokers.clone()).build().await.unwrap();
+    let controller = client.controller_client().unwrap();
+    for topic in [&transactions_topic, &events_topic] {
+        controller
+            .create_topic(topic.clone(), 1, 1, 5_000)
+            .await
+            .unwrap();
+    }
+
+    let worker = KafkaWorker::new(KafkaTaskConfig {
+        brokers,
+        transactions_topic: transactions_topic.clone(),
+        events_topic,
+        partitions: 1,
+        key: RecordKey::TransactionDigest,
+        serialization: RecordSerialization::Json,
+    })
+    .await
+    .unwrap();
+    let (transaction, _) = mock_transaction(None);
+    let mut checkpoint = mock_checkpoint_data(3);
+    checkpoint.transactions = vec![transaction.clone()];
+    worker.process_checkpoint(checkpoint).await.unwrap();
+
+    let partition = client
+        .partition_client(transactions_topic, 0, UnknownTopicHandling::Error)
+        .await
+        .unwrap();
+    let (records, _) = partition
+        .fetch_records(0, 1..1_000_000, 1_000)
+        .await
+        .unwrap();
+    assert_eq!(records.len(), 1);
+    assert_eq!(
+        records[0].record.key,
+        Some(transaction.transaction.digest().to_string().into_bytes())
+    );
+}
+
 fn temp_dir() -> std::path::PathBuf {
     tempfile::tempdir()
         .expect("Failed to open temporary directory")
@@ -119,12 +441,44 @@ fn create_executor_bundle() -> ExecutorBundle {
     }
 }
 
+fn mock_transaction(event_type: Option<StructTag>) -> (CheckpointTransaction, SuiAddress) {
+    let (sender, keypair): (_, AccountKeyPair) = get_key_pair();
+    let data =
+        TransactionData::new_transfer_sui(sender, sender, None, random_object_ref(), 1000, 1);
+    let events = event_type.map(|type_| TransactionEvents {
+        data: vec![Event {
+            package_id: ObjectID::from(type_.address),
+            transaction_module: Identifier::new("test").unwrap(),
+            sender,
+            type_,
+            contents: vec![],
+        }],
+    });
+    let transaction = CheckpointTransaction {
+        transaction: to_sender_signed_transaction(data, &keypair),
+        effects: TransactionEffects::default(),
+        events,
+        input_objects: vec![],
+        output_objects: vec![Object::with_id_owner_for_testing(
+            ObjectID::random(),
+            sender,
+        )],
+    };
+    (transaction, sender)
+}
+
 const RNG_SEED: [u8; 32] = [
     21, 23, 199, 200, 234, 250, 252, 178, 94, 15, 202, 178, 62, 186, 88, 137, 233, 192, 130, 157,
     179, 179, 65, 9, 31, 249, 221, 123, 225, 112, 199, 247,
 ];
 
 fn mock_checkpoint_data_bytes(seq_number: CheckpointSequenceNumber) -> Vec<u8> {
+    Blob::encode(&mock_checkpoint_data(seq_number), BlobEncoding::Bcs)
+        .unwrap()
+        .to_bytes()
+}
+
+fn mock_checkpoint_data(seq_number: CheckpointSequenceNumber) -> CheckpointData {
     let mut rng = StdRng::from_seed(RNG_SEED);
     let (keys, committee) = make_committee_key(&mut rng);
     let contents = CheckpointContents::new_with_digests_only_for_tests(vec![]);
@@ -147,13 +501,10 @@ fn mock_checkpoint_data_bytes(seq_number: CheckpointSequenceNumber) -> Vec<u8> {
         })
         .collect();
 
-    let checkpoint_data = CheckpointData {
+    CheckpointData {
         checkpoint_summary: CertifiedCheckpointSummary::new(summary, sign_infos, &committee)
             .unwrap(),
         checkpoint_contents: contents,
         transactions: vec![],
-    };
-    Blob::encode(&checkpoint_data, BlobEncoding::Bcs)
-        .unwrap()
-        .to_bytes()
+    }
 }
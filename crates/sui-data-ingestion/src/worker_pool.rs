@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::executor::MAX_CHECKPOINTS_IN_PROGRESS;
+use crate::filter::CheckpointFilter;
 use crate::workers::Worker;
 use mysten_metrics::spawn_monitored_task;
 use std::collections::HashSet;
@@ -16,6 +17,7 @@ pub struct WorkerPool<W: Worker> {
     pub task_name: String,
     concurrency: usize,
     worker: Arc<W>,
+    filter: CheckpointFilter,
 }
 
 impl<W: Worker + 'static> WorkerPool<W> {
@@ -24,8 +26,16 @@ impl<W: Worker + 'static> WorkerPool<W> {
             task_name: worker.name().to_string(),
             concurrency,
             worker: Arc::new(worker),
+            filter: CheckpointFilter::default(),
         }
     }
+
+    /// Only dispatches the transactions and objects selected by `filter` to the workers
+    pub fn with_filter(mut self, filter: CheckpointFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
     pub async fn run(
         self,
         mut current_checkpoint_number: CheckpointSequenceNumber,
@@ -86,7 +96,7 @@ impl<W: Worker + 'static> WorkerPool<W> {
                             }
                             let worker_id = (sequence_number % self.concurrency as u64) as usize;
                             debug!("received checkpoint for processing {} for workflow {}", sequence_number, self.task_name);
-                            workers[worker_id].0.send(checkpoint).await.expect("failed to dispatch a task");
+                            workers[worker_id].0.send(self.filter.apply(checkpoint)).await.expect("failed to dispatch a task");
                         }
                         Err(_) => break,
                     }
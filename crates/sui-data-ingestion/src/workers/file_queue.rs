@@ -0,0 +1,107 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::workers::records::{checkpoint_records, KeyedRecord, RecordKey, RecordSerialization};
+use crate::Worker;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use sui_types::full_checkpoint_content::CheckpointData;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct FileQueueTaskConfig {
+    pub path: PathBuf,
+    #[serde(default)]
+    pub key: RecordKey,
+    #[serde(default)]
+    pub serialization: RecordSerialization,
+}
+
+/// Local queue sink writing the records of every checkpoint to the `transactions` and `events`
+/// subdirectories of a directory, one `{checkpoint}.records` file per checkpoint.
+/// Files are written atomically, consumers process them in checkpoint order and remove them.
+/// Each record is framed as a little endian u32 length followed by the key, and the same for
+/// the value.
+#[derive(Clone)]
+pub struct FileQueueWorker {
+    path: PathBuf,
+    key: RecordKey,
+    serialization: RecordSerialization,
+}
+
+impl FileQueueWorker {
+    pub fn new(config: FileQueueTaskConfig) -> Result<Self> {
+        for stream in ["transactions", "events"] {
+            fs::create_dir_all(config.path.join(stream))?;
+        }
+        Ok(Self {
+            path: config.path,
+            key: config.key,
+            serialization: config.serialization,
+        })
+    }
+
+    /// Reads back the records of a file written by the worker
+    pub fn read_records(path: &Path) -> Result<Vec<KeyedRecord>> {
+        let bytes = fs::read(path)?;
+        let mut remaining = bytes.as_slice();
+        let mut records = vec![];
+        while !remaining.is_empty() {
+            let (Some(key), Some(value)) = (read_field(&mut remaining), read_field(&mut remaining))
+            else {
+                return Err(anyhow!("truncated record file {}", path.display()));
+            };
+            records.push(KeyedRecord {
+                key: key.to_vec(),
+                value: value.to_vec(),
+            });
+        }
+        Ok(records)
+    }
+
+    fn write_records(&self, stream: &str, file_name: &str, records: &[KeyedRecord]) -> Result<()> {
+        let mut bytes = vec![];
+        for record in records {
+            for field in [&record.key, &record.value] {
+                bytes.extend_from_slice(&(field.len() as u32).to_le_bytes());
+                bytes.extend_from_slice(field);
+            }
+        }
+        let directory = self.path.join(stream);
+        let tmp_path = directory.join(format!("{}.tmp", file_name));
+        fs::write(&tmp_path, bytes)?;
+        fs::rename(tmp_path, directory.join(file_name))?;
+        Ok(())
+    }
+}
+
+fn read_field<'a>(remaining: &mut &'a [u8]) -> Option<&'a [u8]> {
+    if remaining.len() < 4 {
+        return None;
+    }
+    let (length, rest) = remaining.split_at(4);
+    let length = u32::from_le_bytes(length.try_into().ok()?) as usize;
+    if rest.len() < length {
+        return None;
+    }
+    let (field, rest) = rest.split_at(length);
+    *remaining = rest;
+    Some(field)
+}
+
+#[async_trait]
+impl Worker for FileQueueWorker {
+    async fn process_checkpoint(&self, checkpoint: CheckpointData) -> Result<()> {
+        let file_name = format!("{}.records", checkpoint.checkpoint_summary.sequence_number);
+        let records = checkpoint_records(checkpoint, self.key, self.serialization)?;
+        self.write_records("transactions", &file_name, &records.transactions)?;
+        self.write_records("events", &file_name, &records.events)?;
+        Ok(())
+    }
+
+    fn name(&self) -> &'static str {
+        "file_queue"
+    }
+}
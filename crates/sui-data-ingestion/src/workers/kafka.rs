@@ -0,0 +1,146 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::workers::records::{checkpoint_records, KeyedRecord, RecordKey, RecordSerialization};
+use crate::Worker;
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, TimeZone, Utc};
+use rskafka::client::partition::{Compression, PartitionClient, UnknownTopicHandling};
+use rskafka::client::ClientBuilder;
+use rskafka::record::Record;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use sui_types::full_checkpoint_content::CheckpointData;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct KafkaTaskConfig {
+    pub brokers: Vec<String>,
+    pub transactions_topic: String,
+    pub events_topic: String,
+    /// Number of partitions of both topics, records are assigned to a partition by key
+    #[serde(default = "default_partitions")]
+    pub partitions: i32,
+    #[serde(default)]
+    pub key: RecordKey,
+    #[serde(default)]
+    pub serialization: RecordSerialization,
+}
+
+fn default_partitions() -> i32 {
+    1
+}
+
+/// Publishes a record per transaction and per event to topics of a Kafka protocol broker.
+/// The records of one `process_checkpoint` call keep their order within each partition, but
+/// nothing orders calls: with a pool concurrency above one, checkpoints are processed at the same
+/// time and their records interleave in a partition. Run the pool with a concurrency of one for
+/// records ordered by checkpoint.
+#[derive(Clone)]
+pub struct KafkaWorker {
+    transactions: Arc<Vec<PartitionClient>>,
+    events: Arc<Vec<PartitionClient>>,
+    key: RecordKey,
+    serialization: RecordSerialization,
+}
+
+impl KafkaWorker {
+    pub async fn new(config: KafkaTaskConfig) -> Result<Self> {
+        let client = ClientBuilder::new(config.brokers).build().await?;
+        let mut topics = vec![];
+        for topic in [config.transactions_topic, config.events_topic] {
+            let mut partitions = vec![];
+            for partition in 0..config.partitions {
+                partitions.push(
+                    client
+                        .partition_client(topic.clone(), partition, UnknownTopicHandling::Retry)
+                        .await?,
+                );
+            }
+            topics.push(Arc::new(partitions));
+        }
+        let events = topics.pop().expect("events topic");
+        let transactions = topics.pop().expect("transactions topic");
+        Ok(Self {
+            transactions,
+            events,
+            key: config.key,
+            serialization: config.serialization,
+        })
+    }
+
+    async fn publish(
+        partitions: &[PartitionClient],
+        records: Vec<KeyedRecord>,
+        timestamp: DateTime<Utc>,
+    ) -> Result<()> {
+        let mut batches: Vec<Vec<Record>> = partitions.iter().map(|_| vec![]).collect();
+        for record in records {
+            let partition = partition_for_key(&record.key, partitions.len());
+            batches[partition].push(Record {
+                key: Some(record.key),
+                value: Some(record.value),
+                headers: BTreeMap::new(),
+                timestamp,
+            });
+        }
+        for (partition, batch) in partitions.iter().zip(batches) {
+            if !batch.is_empty() {
+                partition.produce(batch, Compression::default()).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Partition of a record key, using the same murmur2 hash as the default partitioner of the
+/// Kafka clients, so consumers can locate records by key.
+pub(crate) fn partition_for_key(key: &[u8], partitions: usize) -> usize {
+    (murmur2(key) & 0x7fffffff) as usize % partitions
+}
+
+fn murmur2(data: &[u8]) -> u32 {
+    const SEED: u32 = 0x9747b28c;
+    const M: u32 = 0x5bd1e995;
+    const R: u32 = 24;
+    let mut h = SEED ^ data.len() as u32;
+    let mut chunks = data.chunks_exact(4);
+    for chunk in &mut chunks {
+        let mut k = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        k = k.wrapping_mul(M);
+        k ^= k >> R;
+        k = k.wrapping_mul(M);
+        h = h.wrapping_mul(M);
+        h ^= k;
+    }
+    let tail = chunks.remainder();
+    if !tail.is_empty() {
+        for (idx, byte) in tail.iter().enumerate() {
+            h ^= (*byte as u32) << (8 * idx);
+        }
+        h = h.wrapping_mul(M);
+    }
+    h ^= h >> 13;
+    h = h.wrapping_mul(M);
+    h ^= h >> 15;
+    h
+}
+
+#[async_trait]
+impl Worker for KafkaWorker {
+    async fn process_checkpoint(&self, checkpoint: CheckpointData) -> Result<()> {
+        let timestamp = Utc
+            .timestamp_millis_opt(checkpoint.checkpoint_summary.timestamp_ms as i64)
+            .single()
+            .unwrap_or_default();
+        let records = checkpoint_records(checkpoint, self.key, self.serialization)?;
+        Self::publish(&self.transactions, records.transactions, timestamp).await?;
+        Self::publish(&self.events, records.events, timestamp).await?;
+        Ok(())
+    }
+
+    fn name(&self) -> &'static str {
+        "kafka"
+    }
+}
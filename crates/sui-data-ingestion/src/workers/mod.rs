@@ -4,8 +4,17 @@
 use anyhow::Result;
 use async_trait::async_trait;
 use sui_types::full_checkpoint_content::CheckpointData;
+mod file_queue;
+mod kafka;
+mod records;
 mod s3;
+mod transactional;
+pub use file_queue::{FileQueueTaskConfig, FileQueueWorker};
+pub(crate) use kafka::partition_for_key;
+pub use kafka::{KafkaTaskConfig, KafkaWorker};
+pub use records::{EventRecord, KeyedRecord, RecordKey, RecordSerialization, TransactionRecord};
 pub use s3::{S3TaskConfig, S3Worker};
+pub use transactional::{TransactionalWorker, TransactionalWorkerAdapter};
 
 #[async_trait]
 pub trait Worker: Send + Sync + Clone {
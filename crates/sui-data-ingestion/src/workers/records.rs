@@ -0,0 +1,116 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sui_types::digests::TransactionDigest;
+use sui_types::event::Event;
+use sui_types::full_checkpoint_content::{CheckpointData, CheckpointTransaction};
+use sui_types::messages_checkpoint::CheckpointSequenceNumber;
+use sui_types::transaction::TransactionDataAPI;
+
+/// Encoding of the records published by the stream workers
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RecordSerialization {
+    #[default]
+    Json,
+    Bcs,
+}
+
+/// Field used as the key of the published records.
+/// Consumers see the records sharing a key in the order they were published.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordKey {
+    #[default]
+    TransactionDigest,
+    Sender,
+    Checkpoint,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TransactionRecord {
+    pub checkpoint: CheckpointSequenceNumber,
+    pub timestamp_ms: u64,
+    pub transaction: CheckpointTransaction,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct EventRecord {
+    pub checkpoint: CheckpointSequenceNumber,
+    pub timestamp_ms: u64,
+    pub transaction_digest: TransactionDigest,
+    pub event_sequence_number: u64,
+    pub event: Event,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyedRecord {
+    pub key: Vec<u8>,
+    pub value: Vec<u8>,
+}
+
+/// Records of a checkpoint, split by stream
+#[derive(Default)]
+pub(crate) struct CheckpointRecords {
+    pub(crate) transactions: Vec<KeyedRecord>,
+    pub(crate) events: Vec<KeyedRecord>,
+}
+
+impl RecordSerialization {
+    pub fn serialize<T: Serialize>(&self, value: &T) -> Result<Vec<u8>> {
+        Ok(match self {
+            RecordSerialization::Json => serde_json::to_vec(value)?,
+            RecordSerialization::Bcs => bcs::to_bytes(value)?,
+        })
+    }
+}
+
+pub(crate) fn checkpoint_records(
+    checkpoint: CheckpointData,
+    key: RecordKey,
+    serialization: RecordSerialization,
+) -> Result<CheckpointRecords> {
+    let checkpoint_number = checkpoint.checkpoint_summary.sequence_number;
+    let timestamp_ms = checkpoint.checkpoint_summary.timestamp_ms;
+    let mut records = CheckpointRecords::default();
+    for transaction in checkpoint.transactions {
+        let transaction_digest = *transaction.transaction.digest();
+        let record_key = match key {
+            RecordKey::TransactionDigest => transaction_digest.to_string(),
+            RecordKey::Sender => transaction
+                .transaction
+                .data()
+                .transaction_data()
+                .sender()
+                .to_string(),
+            RecordKey::Checkpoint => checkpoint_number.to_string(),
+        }
+        .into_bytes();
+        let events = transaction.events.iter().flat_map(|events| &events.data);
+        for (event_sequence_number, event) in events.enumerate() {
+            let record = EventRecord {
+                checkpoint: checkpoint_number,
+                timestamp_ms,
+                transaction_digest,
+                event_sequence_number: event_sequence_number as u64,
+                event: event.clone(),
+            };
+            records.events.push(KeyedRecord {
+                key: record_key.clone(),
+                value: serialization.serialize(&record)?,
+            });
+        }
+        let record = TransactionRecord {
+            checkpoint: checkpoint_number,
+            timestamp_ms,
+            transaction,
+        };
+        records.transactions.push(KeyedRecord {
+            key: record_key,
+            value: serialization.serialize(&record)?,
+        });
+    }
+    Ok(records)
+}
@@ -0,0 +1,73 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::progress_store::TransactionalProgressStore;
+use crate::Worker;
+use anyhow::Result;
+use async_trait::async_trait;
+use sui_types::full_checkpoint_content::CheckpointData;
+
+/// Worker whose output is written into a transaction of the progress store `S`.
+/// The output is only applied if the transaction is committed, which happens atomically
+/// with marking the checkpoint as processed.
+#[async_trait]
+pub trait TransactionalWorker<S: TransactionalProgressStore>: Send + Sync + Clone {
+    async fn process_checkpoint(
+        &self,
+        checkpoint: &CheckpointData,
+        transaction: &mut S::Transaction,
+    ) -> Result<()>;
+    fn name(&self) -> &'static str;
+}
+
+/// Runs a `TransactionalWorker` in a regular `WorkerPool`, giving it exactly-once semantics.
+/// Checkpoints already committed are skipped, and a failed attempt is dropped without
+/// applying any of its output before being retried.
+#[derive(Clone)]
+pub struct TransactionalWorkerAdapter<W, S> {
+    worker: W,
+    progress_store: S,
+}
+
+impl<S, W> TransactionalWorkerAdapter<W, S>
+where
+    S: TransactionalProgressStore + Clone,
+    W: TransactionalWorker<S>,
+{
+    pub fn new(worker: W, progress_store: S) -> Self {
+        Self {
+            worker,
+            progress_store,
+        }
+    }
+}
+
+#[async_trait]
+impl<S, W> Worker for TransactionalWorkerAdapter<W, S>
+where
+    S: TransactionalProgressStore + Clone,
+    W: TransactionalWorker<S>,
+{
+    async fn process_checkpoint(&self, checkpoint: CheckpointData) -> Result<()> {
+        let task_name = self.worker.name();
+        let checkpoint_number = checkpoint.checkpoint_summary.sequence_number;
+        if self
+            .progress_store
+            .is_processed(task_name, checkpoint_number)
+            .await?
+        {
+            return Ok(());
+        }
+        let mut transaction = self.progress_store.begin().await?;
+        self.worker
+            .process_checkpoint(&checkpoint, &mut transaction)
+            .await?;
+        self.progress_store
+            .commit(transaction, task_name, checkpoint_number)
+            .await
+    }
+
+    fn name(&self) -> &'static str {
+        self.worker.name()
+    }
+}
@@ -10,7 +10,7 @@ use move_core_types::parser::parse_struct_tag;
 use rand::rngs::OsRng;
 use serde_json::json;
 use std::sync::Arc;
-use sui::client_commands::{SuiClientCommandResult, SuiClientCommands};
+use sui::client_commands::{SimulationArgs, SuiClientCommandResult, SuiClientCommands};
 use sui_core::authority::EffectsNotifyRead;
 use sui_json_rpc_types::{
     type_and_fields_from_move_struct, EventPage, SuiEvent, SuiExecutionStatus,
@@ -567,6 +567,7 @@ async fn test_full_node_sync_flood() -> Result<(), anyhow::Error> {
                             * context.get_reference_gas_price().await.unwrap(),
                         serialize_unsigned_transaction: false,
                         serialize_signed_transaction: false,
+                        simulation: SimulationArgs::default(),
                     }
                     .execute(context)
                     .await
@@ -1086,7 +1086,7 @@ pub async fn batch_transfer_gases(
 
 #[cfg(test)]
 mod tests {
-    use sui::client_commands::{SuiClientCommandResult, SuiClientCommands};
+    use sui::client_commands::{SimulationArgs, SuiClientCommandResult, SuiClientCommands};
     use sui_json_rpc_types::SuiExecutionStatus;
     use sui_sdk::wallet_context::WalletContext;
     use test_cluster::TestClusterBuilder;
@@ -1113,6 +1113,7 @@ mod tests {
             count: Some(10),
             serialize_unsigned_transaction: false,
             serialize_signed_transaction: false,
+            simulation: SimulationArgs::default(),
         }
         .execute(&mut context)
         .await
@@ -1241,6 +1242,7 @@ mod tests {
             count: Some(10),
             serialize_unsigned_transaction: false,
             serialize_signed_transaction: false,
+            simulation: SimulationArgs::default(),
         }
         .execute(&mut context)
         .await
@@ -1398,6 +1400,7 @@ mod tests {
             gas_budget: 2_000_000,
             serialize_unsigned_transaction: false,
             serialize_signed_transaction: false,
+            simulation: SimulationArgs::default(),
         }
         .execute(faucet.wallet_mut())
         .await
@@ -1523,6 +1526,7 @@ mod tests {
             count: None,
             serialize_unsigned_transaction: false,
             serialize_signed_transaction: false,
+            simulation: SimulationArgs::default(),
         }
         .execute(&mut context)
         .await;
@@ -1608,6 +1612,7 @@ mod tests {
             count: None,
             serialize_unsigned_transaction: false,
             serialize_signed_transaction: false,
+            simulation: SimulationArgs::default(),
         }
         .execute(&mut context)
         .await
@@ -1623,6 +1628,7 @@ mod tests {
                 amount: None,
                 serialize_unsigned_transaction: false,
                 serialize_signed_transaction: false,
+                simulation: SimulationArgs::default(),
             }
             .execute(&mut context)
             .await
@@ -1681,6 +1687,7 @@ mod tests {
             count: None,
             serialize_unsigned_transaction: false,
             serialize_signed_transaction: false,
+            simulation: SimulationArgs::default(),
         }
         .execute(&mut context)
         .await;
@@ -1696,6 +1703,7 @@ mod tests {
                 amount: None,
                 serialize_unsigned_transaction: false,
                 serialize_signed_transaction: false,
+                simulation: SimulationArgs::default(),
             }
             .execute(&mut context)
             .await
@@ -1815,6 +1823,7 @@ mod tests {
             count: Some(10),
             serialize_unsigned_transaction: false,
             serialize_signed_transaction: false,
+            simulation: SimulationArgs::default(),
         }
         .execute(&mut context)
         .await
@@ -1,7 +1,7 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::indexer_reader::IndexerReader;
+use crate::indexer_reader::IndexerStoreReader;
 use async_trait::async_trait;
 use jsonrpsee::core::RpcResult;
 use jsonrpsee::RpcModule;
@@ -14,18 +14,18 @@ use sui_types::balance::Supply;
 use sui_types::base_types::{ObjectID, SuiAddress};
 use sui_types::gas_coin::{GAS, TOTAL_SUPPLY_MIST};
 
-pub(crate) struct CoinReadApiV2 {
-    inner: IndexerReader,
+pub(crate) struct CoinReadApiV2<R> {
+    inner: R,
 }
 
-impl CoinReadApiV2 {
-    pub fn new(inner: IndexerReader) -> Self {
+impl<R: IndexerStoreReader> CoinReadApiV2<R> {
+    pub fn new(inner: R) -> Self {
         Self { inner }
     }
 }
 
 #[async_trait]
-impl CoinReadApiServer for CoinReadApiV2 {
+impl<R: IndexerStoreReader> CoinReadApiServer for CoinReadApiV2<R> {
     async fn get_coins(
         &self,
         owner: SuiAddress,
@@ -49,7 +49,9 @@ impl CoinReadApiServer for CoinReadApiV2 {
         };
         let mut results = self
             .inner
-            .get_owned_coins_in_blocking_task(owner, Some(coin_type), cursor, limit + 1)
+            .spawn_blocking(move |this| {
+                this.get_owned_coins(owner, Some(coin_type), cursor, limit + 1)
+            })
             .await?;
 
         let has_next_page = results.len() > limit;
@@ -80,7 +82,7 @@ impl CoinReadApiServer for CoinReadApiV2 {
         };
         let mut results = self
             .inner
-            .get_owned_coins_in_blocking_task(owner, None, cursor, limit + 1)
+            .spawn_blocking(move |this| this.get_owned_coins(owner, None, cursor, limit + 1))
             .await?;
 
         let has_next_page = results.len() > limit;
@@ -104,7 +106,10 @@ impl CoinReadApiServer for CoinReadApiV2 {
 
         let mut results = self
             .inner
-            .get_coin_balances_in_blocking_task(owner, Some(coin_type.clone()))
+            .spawn_blocking({
+                let coin_type = coin_type.clone();
+                move |this| this.get_coin_balances(owner, Some(coin_type))
+            })
             .await?;
         if results.is_empty() {
             return Ok(Balance::zero(coin_type));
@@ -114,7 +119,7 @@ impl CoinReadApiServer for CoinReadApiV2 {
 
     async fn get_all_balances(&self, owner: SuiAddress) -> RpcResult<Vec<Balance>> {
         self.inner
-            .get_coin_balances_in_blocking_task(owner, None)
+            .spawn_blocking(move |this| this.get_coin_balances(owner, None))
             .await
             .map_err(Into::into)
     }
@@ -122,7 +127,7 @@ impl CoinReadApiServer for CoinReadApiV2 {
     async fn get_coin_metadata(&self, coin_type: String) -> RpcResult<Option<SuiCoinMetadata>> {
         let coin_struct = parse_to_struct_tag(&coin_type)?;
         self.inner
-            .get_coin_metadata_in_blocking_task(coin_struct)
+            .spawn_blocking(move |this| this.get_coin_metadata(coin_struct))
             .await
             .map_err(Into::into)
     }
@@ -135,14 +140,14 @@ impl CoinReadApiServer for CoinReadApiV2 {
             })
         } else {
             self.inner
-                .get_total_supply_in_blocking_task(coin_struct)
+                .spawn_blocking(move |this| this.get_total_supply(coin_struct))
                 .await
                 .map_err(Into::into)
         }
     }
 }
 
-impl SuiRpcModule for CoinReadApiV2 {
+impl<R: IndexerStoreReader> SuiRpcModule for CoinReadApiV2<R> {
     fn rpc(self) -> RpcModule<Self> {
         self.into_rpc()
     }
@@ -1,7 +1,7 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::indexer_reader::IndexerReader;
+use crate::indexer_reader::IndexerStoreReader;
 use jsonrpsee::{core::RpcResult, RpcModule};
 use sui_json_rpc::{
     api::{validate_limit, ExtendedApiServer, QUERY_MAX_RESULT_LIMIT_CHECKPOINTS},
@@ -14,18 +14,18 @@ use sui_json_rpc_types::{
 use sui_open_rpc::Module;
 use sui_types::sui_serde::BigInt;
 
-pub(crate) struct ExtendedApiV2 {
-    inner: IndexerReader,
+pub(crate) struct ExtendedApiV2<R> {
+    inner: R,
 }
 
-impl ExtendedApiV2 {
-    pub fn new(inner: IndexerReader) -> Self {
+impl<R: IndexerStoreReader> ExtendedApiV2<R> {
+    pub fn new(inner: R) -> Self {
         Self { inner }
     }
 }
 
 #[async_trait::async_trait]
-impl ExtendedApiServer for ExtendedApiV2 {
+impl<R: IndexerStoreReader> ExtendedApiServer for ExtendedApiV2<R> {
     async fn get_epochs(
         &self,
         cursor: Option<BigInt<u64>>,
@@ -165,7 +165,7 @@ impl ExtendedApiServer for ExtendedApiV2 {
     }
 }
 
-impl SuiRpcModule for ExtendedApiV2 {
+impl<R: IndexerStoreReader> SuiRpcModule for ExtendedApiV2<R> {
     fn rpc(self) -> RpcModule<Self> {
         self.into_rpc()
     }
@@ -2,12 +2,12 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use std::collections::BTreeMap;
+use std::sync::Mutex;
 
-use crate::{errors::IndexerError, indexer_reader::IndexerReader};
+use crate::{errors::IndexerError, indexer_reader::IndexerStoreReader};
 use async_trait::async_trait;
 use jsonrpsee::{core::RpcResult, RpcModule};
 
-use cached::{proc_macro::cached, SizedCache};
 use sui_json_rpc::{
     api::GovernanceReadApiServer, governance_api::ValidatorExchangeRates, SuiRpcModule,
 };
@@ -24,12 +24,12 @@ use sui_types::{
 };
 
 #[derive(Clone)]
-pub struct GovernanceReadApiV2 {
-    inner: IndexerReader,
+pub struct GovernanceReadApiV2<R> {
+    inner: R,
 }
 
-impl GovernanceReadApiV2 {
-    pub fn new(inner: IndexerReader) -> Self {
+impl<R: IndexerStoreReader> GovernanceReadApiV2<R> {
+    pub fn new(inner: R) -> Self {
         Self { inner }
     }
 
@@ -58,7 +58,11 @@ impl GovernanceReadApiV2 {
         ids: Vec<ObjectID>,
     ) -> Result<Vec<DelegatedStake>, IndexerError> {
         let mut stakes = vec![];
-        for stored_object in self.inner.multi_get_objects_in_blocking_task(ids).await? {
+        for stored_object in self
+            .inner
+            .spawn_blocking(move |this| this.multi_get_objects(ids))
+            .await?
+        {
             let object = sui_types::object::Object::try_from(stored_object)?;
             let stake_object = StakedSui::try_from(&object)?;
             stakes.push(stake_object);
@@ -74,15 +78,17 @@ impl GovernanceReadApiV2 {
         let mut stakes = vec![];
         for stored_object in self
             .inner
-            .get_owned_objects_in_blocking_task(
-                owner,
-                Some(SuiObjectDataFilter::StructType(
-                    MoveObjectType::staked_sui().into(),
-                )),
-                None,
-                // Allow querying for up to 1000 staked objects
-                1000,
-            )
+            .spawn_blocking(move |this| {
+                this.get_owned_objects(
+                    owner,
+                    Some(SuiObjectDataFilter::StructType(
+                        MoveObjectType::staked_sui().into(),
+                    )),
+                    None,
+                    // Allow querying for up to 1000 staked objects
+                    1000,
+                )
+            })
             .await?
         {
             let object = sui_types::object::Object::try_from(stored_object)?;
@@ -167,16 +173,33 @@ impl GovernanceReadApiV2 {
     }
 }
 
+/// Exchange rates of the epoch they were last computed for.
+static EXCHANGE_RATES_CACHE: Mutex<Option<(EpochId, Vec<ValidatorExchangeRates>)>> =
+    Mutex::new(None);
+
 /// Cached exchange rates for validators for the given epoch, the cache size is 1, it will be cleared when the epoch changes.
 /// rates are in descending order by epoch.
-#[cached(
-    type = "SizedCache<EpochId, Vec<ValidatorExchangeRates>>",
-    create = "{ SizedCache::with_size(1) }",
-    convert = "{ system_state_summary.epoch }",
-    result = true
-)]
-async fn exchange_rates(
-    state: &GovernanceReadApiV2,
+async fn exchange_rates<R: IndexerStoreReader>(
+    state: &GovernanceReadApiV2<R>,
+    system_state_summary: SuiSystemStateSummary,
+) -> Result<Vec<ValidatorExchangeRates>, IndexerError> {
+    let epoch = system_state_summary.epoch;
+    let cached_rates = EXCHANGE_RATES_CACHE
+        .lock()
+        .unwrap()
+        .as_ref()
+        .filter(|(cached_epoch, _)| *cached_epoch == epoch)
+        .map(|(_, rates)| rates.clone());
+    if let Some(rates) = cached_rates {
+        return Ok(rates);
+    }
+    let rates = compute_exchange_rates(state, system_state_summary).await?;
+    *EXCHANGE_RATES_CACHE.lock().unwrap() = Some((epoch, rates.clone()));
+    Ok(rates)
+}
+
+async fn compute_exchange_rates<R: IndexerStoreReader>(
+    state: &GovernanceReadApiV2<R>,
     system_state_summary: SuiSystemStateSummary,
 ) -> Result<Vec<ValidatorExchangeRates>, IndexerError> {
     // Get validator rate tables
@@ -195,11 +218,11 @@ async fn exchange_rates(
     // Get inactive validator rate tables
     for df in state
         .inner
-        .get_dynamic_fields_in_blocking_task(
-            system_state_summary.inactive_pools_id,
-            None,
-            system_state_summary.inactive_pools_size as usize,
-        )
+        .spawn_blocking({
+            let inactive_pools_id = system_state_summary.inactive_pools_id;
+            let inactive_pools_size = system_state_summary.inactive_pools_size as usize;
+            move |this| this.get_dynamic_fields(inactive_pools_id, None, inactive_pools_size)
+        })
         .await?
     {
         let pool_id: sui_types::id::ID = bcs::from_bytes(&df.bcs_name).map_err(|e| {
@@ -233,11 +256,9 @@ async fn exchange_rates(
         let mut rates = vec![];
         for df in state
             .inner
-            .get_dynamic_fields_raw_in_blocking_task(
-                exchange_rates_id,
-                None,
-                exchange_rates_size as usize,
-            )
+            .spawn_blocking(move |this| {
+                this.get_dynamic_fields_raw(exchange_rates_id, None, exchange_rates_size as usize)
+            })
             .await?
         {
             let dynamic_field = df
@@ -262,7 +283,7 @@ async fn exchange_rates(
 }
 
 #[async_trait]
-impl GovernanceReadApiServer for GovernanceReadApiV2 {
+impl<R: IndexerStoreReader> GovernanceReadApiServer for GovernanceReadApiV2<R> {
     async fn get_stakes_by_ids(
         &self,
         staked_sui_ids: Vec<ObjectID>,
@@ -313,7 +334,7 @@ impl GovernanceReadApiServer for GovernanceReadApiV2 {
     }
 }
 
-impl SuiRpcModule for GovernanceReadApiV2 {
+impl<R: IndexerStoreReader> SuiRpcModule for GovernanceReadApiV2<R> {
     fn rpc(self) -> RpcModule<Self> {
         self.into_rpc()
     }
@@ -1,7 +1,7 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::indexer_reader::IndexerReader;
+use crate::indexer_reader::IndexerStoreReader;
 use crate::IndexerError;
 use async_trait::async_trait;
 use jsonrpsee::core::RpcResult;
@@ -25,13 +25,13 @@ use sui_types::event::EventID;
 use sui_types::object::ObjectRead;
 use sui_types::TypeTag;
 
-pub(crate) struct IndexerApiV2 {
-    inner: IndexerReader,
+pub(crate) struct IndexerApiV2<R> {
+    inner: R,
     name_service_config: NameServiceConfig,
 }
 
-impl IndexerApiV2 {
-    pub fn new(inner: IndexerReader) -> Self {
+impl<R: IndexerStoreReader> IndexerApiV2<R> {
+    pub fn new(inner: R) -> Self {
         Self {
             inner,
             // TODO allow configuring for other networks
@@ -50,7 +50,7 @@ impl IndexerApiV2 {
         let options = options.unwrap_or_default();
         let objects = self
             .inner
-            .get_owned_objects_in_blocking_task(address, filter, cursor, limit + 1)
+            .spawn_blocking(move |this| this.get_owned_objects(address, filter, cursor, limit + 1))
             .await?;
         let mut objects = self
             .inner
@@ -121,7 +121,7 @@ impl IndexerApiV2 {
 }
 
 #[async_trait]
-impl IndexerApiServer for IndexerApiV2 {
+impl<R: IndexerStoreReader> IndexerApiServer for IndexerApiV2<R> {
     async fn get_owned_objects(
         &self,
         address: SuiAddress,
@@ -150,13 +150,15 @@ impl IndexerApiServer for IndexerApiV2 {
         }
         let mut results = self
             .inner
-            .query_transaction_blocks_in_blocking_task(
-                query.filter,
-                query.options.unwrap_or_default(),
-                cursor,
-                limit + 1,
-                descending_order.unwrap_or(false),
-            )
+            .spawn_blocking(move |this| {
+                this.query_transaction_blocks(
+                    query.filter,
+                    query.options.unwrap_or_default(),
+                    cursor,
+                    limit + 1,
+                    descending_order.unwrap_or(false),
+                )
+            })
             .await
             .map_err(|e: IndexerError| anyhow::anyhow!(e))?;
 
@@ -185,7 +187,9 @@ impl IndexerApiServer for IndexerApiV2 {
         let descending_order = descending_order.unwrap_or(false);
         let mut results = self
             .inner
-            .query_events_in_blocking_task(query, cursor, limit + 1, descending_order)
+            .spawn_blocking(move |this| {
+                this.query_events(query, cursor, limit + 1, descending_order)
+            })
             .await?;
 
         let has_next_page = results.len() > limit;
@@ -210,7 +214,9 @@ impl IndexerApiServer for IndexerApiV2 {
         }
         let mut results = self
             .inner
-            .get_dynamic_fields_in_blocking_task(parent_object_id, cursor, limit + 1)
+            .spawn_blocking(move |this| {
+                this.get_dynamic_fields(parent_object_id, cursor, limit + 1)
+            })
             .await?;
 
         let has_next_page = results.len() > limit;
@@ -239,7 +245,11 @@ impl IndexerApiServer for IndexerApiV2 {
         .expect("deriving dynamic field id can't fail");
 
         let options = sui_json_rpc_types::SuiObjectDataOptions::full_content();
-        match self.inner.get_object_read_in_blocking_task(id).await? {
+        match self
+            .inner
+            .spawn_blocking(move |this| this.get_object_read(&id))
+            .await?
+        {
             sui_types::object::ObjectRead::NotExists(_)
             | sui_types::object::ObjectRead::Deleted(_) => {}
             sui_types::object::ObjectRead::Exists(object_ref, o, layout) => {
@@ -261,7 +271,7 @@ impl IndexerApiServer for IndexerApiV2 {
         .expect("deriving dynamic field id can't fail");
         match self
             .inner
-            .get_object_read_in_blocking_task(dynamic_object_field_id)
+            .spawn_blocking(move |this| this.get_object_read(&dynamic_object_field_id))
             .await?
         {
             sui_types::object::ObjectRead::NotExists(_)
@@ -300,7 +310,11 @@ impl IndexerApiServer for IndexerApiV2 {
 
         let record_id = self.name_service_config.record_field_id(&domain);
 
-        let field_record_object = match self.inner.get_object_in_blocking_task(record_id).await? {
+        let field_record_object = match self
+            .inner
+            .spawn_blocking(move |this| IndexerStoreReader::get_object(&this, &record_id, None))
+            .await?
+        {
             Some(o) => o,
             None => return Ok(None),
         };
@@ -327,7 +341,9 @@ impl IndexerApiServer for IndexerApiV2 {
 
         let field_reverse_record_object = match self
             .inner
-            .get_object_in_blocking_task(reverse_record_id)
+            .spawn_blocking(move |this| {
+                IndexerStoreReader::get_object(&this, &reverse_record_id, None)
+            })
             .await?
         {
             Some(o) => o,
@@ -357,7 +373,7 @@ impl IndexerApiServer for IndexerApiV2 {
     }
 }
 
-impl SuiRpcModule for IndexerApiV2 {
+impl<R: IndexerStoreReader> SuiRpcModule for IndexerApiV2<R> {
     fn rpc(self) -> RpcModule<Self> {
         self.into_rpc()
     }
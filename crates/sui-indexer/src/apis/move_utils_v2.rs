@@ -20,27 +20,27 @@ use sui_open_rpc::Module;
 use sui_types::base_types::ObjectID;
 use sui_types::move_package::normalize_modules;
 
-use crate::indexer_reader::IndexerReader;
+use crate::indexer_reader::IndexerStoreReader;
 
-pub struct MoveUtilsApiV2 {
-    inner: IndexerReader,
+pub struct MoveUtilsApiV2<R> {
+    inner: R,
 }
 
-impl MoveUtilsApiV2 {
-    pub fn new(inner: IndexerReader) -> Self {
+impl<R: IndexerStoreReader> MoveUtilsApiV2<R> {
+    pub fn new(inner: R) -> Self {
         Self { inner }
     }
 }
 
 #[async_trait]
-impl MoveUtilsServer for MoveUtilsApiV2 {
+impl<R: IndexerStoreReader> MoveUtilsServer for MoveUtilsApiV2<R> {
     async fn get_normalized_move_modules_by_package(
         &self,
         package_id: ObjectID,
     ) -> RpcResult<BTreeMap<String, SuiMoveNormalizedModule>> {
         let package = self
             .inner
-            .get_package_in_blocking_task(package_id)
+            .spawn_blocking(move |this| this.get_package(&package_id))
             .await
             .map_err(|e| SuiRpcInputError::GenericNotFound(e.to_string()))?
             .ok_or_else(|| {
@@ -149,7 +149,7 @@ impl MoveUtilsServer for MoveUtilsApiV2 {
     }
 }
 
-impl SuiRpcModule for MoveUtilsApiV2 {
+impl<R: IndexerStoreReader> SuiRpcModule for MoveUtilsApiV2<R> {
     fn rpc(self) -> RpcModule<Self> {
         self.into_rpc()
     }
@@ -9,7 +9,7 @@ use sui_types::error::SuiObjectResponseError;
 use sui_types::object::ObjectRead;
 
 use crate::errors::IndexerError;
-use crate::indexer_reader::IndexerReader;
+use crate::indexer_reader::IndexerStoreReader;
 use sui_json_rpc::api::{ReadApiServer, QUERY_MAX_RESULT_LIMIT};
 use sui_json_rpc::SuiRpcModule;
 use sui_json_rpc_types::{
@@ -26,12 +26,12 @@ use sui_types::sui_serde::BigInt;
 use sui_json_rpc_types::SuiLoadedChildObjectsResponse;
 
 #[derive(Clone)]
-pub(crate) struct ReadApiV2 {
-    inner: IndexerReader,
+pub(crate) struct ReadApiV2<R> {
+    inner: R,
 }
 
-impl ReadApiV2 {
-    pub fn new(inner: IndexerReader) -> Self {
+impl<R: IndexerStoreReader> ReadApiV2<R> {
+    pub fn new(inner: R) -> Self {
         Self { inner }
     }
 
@@ -62,7 +62,7 @@ impl ReadApiV2 {
 }
 
 #[async_trait]
-impl ReadApiServer for ReadApiV2 {
+impl<R: IndexerStoreReader> ReadApiServer for ReadApiV2<R> {
     async fn get_object(
         &self,
         object_id: ObjectID,
@@ -71,7 +71,7 @@ impl ReadApiServer for ReadApiV2 {
         let options = options.unwrap_or_default();
         let object_read = self
             .inner
-            .get_object_read_in_blocking_task(object_id)
+            .spawn_blocking(move |this| this.get_object_read(&object_id))
             .await?;
 
         match object_read {
@@ -168,7 +168,9 @@ impl ReadApiServer for ReadApiV2 {
         let options = options.unwrap_or_default();
         let txns = self
             .inner
-            .multi_get_transaction_block_response_in_blocking_task(digests, options)
+            .spawn_blocking(move |this| {
+                this.multi_get_transaction_block_response(&digests, options)
+            })
             .await?;
 
         Ok(txns)
@@ -252,7 +254,7 @@ impl ReadApiServer for ReadApiV2 {
 
     async fn get_events(&self, transaction_digest: TransactionDigest) -> RpcResult<Vec<SuiEvent>> {
         self.inner
-            .get_transaction_events_in_blocking_task(transaction_digest)
+            .spawn_blocking(move |this| this.get_transaction_events(transaction_digest))
             .await
             .map_err(Into::into)
     }
@@ -296,7 +298,7 @@ impl ReadApiServer for ReadApiV2 {
     }
 }
 
-impl SuiRpcModule for ReadApiV2 {
+impl<R: IndexerStoreReader> SuiRpcModule for ReadApiV2<R> {
     fn rpc(self) -> RpcModule<Self> {
         self.into_rpc()
     }
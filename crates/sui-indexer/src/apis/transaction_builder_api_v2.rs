@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use super::governance_api_v2::GovernanceReadApiV2;
-use crate::indexer_reader::IndexerReader;
+use crate::indexer_reader::IndexerStoreReader;
 use async_trait::async_trait;
 use move_core_types::language_storage::StructTag;
 use sui_json_rpc::transaction_builder_api::TransactionBuilderApi;
@@ -11,19 +11,19 @@ use sui_transaction_builder::DataReader;
 use sui_types::base_types::{ObjectID, ObjectInfo, SuiAddress};
 use sui_types::object::Object;
 
-pub(crate) struct TransactionBuilderApiV2 {
-    inner: IndexerReader,
+pub(crate) struct TransactionBuilderApiV2<R> {
+    inner: R,
 }
 
-impl TransactionBuilderApiV2 {
+impl<R: IndexerStoreReader> TransactionBuilderApiV2<R> {
     #[allow(clippy::new_ret_no_self)]
-    pub fn new(inner: IndexerReader) -> TransactionBuilderApi {
+    pub fn new(inner: R) -> TransactionBuilderApi {
         TransactionBuilderApi::new_with_data_reader(std::sync::Arc::new(Self { inner }))
     }
 }
 
 #[async_trait]
-impl DataReader for TransactionBuilderApiV2 {
+impl<R: IndexerStoreReader> DataReader for TransactionBuilderApiV2<R> {
     async fn get_owned_objects(
         &self,
         address: SuiAddress,
@@ -31,12 +31,14 @@ impl DataReader for TransactionBuilderApiV2 {
     ) -> Result<Vec<ObjectInfo>, anyhow::Error> {
         let stored_objects = self
             .inner
-            .get_owned_objects_in_blocking_task(
-                address,
-                Some(SuiObjectDataFilter::StructType(object_type)),
-                None,
-                50, // Limit the number of objects returned to 50
-            )
+            .spawn_blocking(move |this| {
+                this.get_owned_objects(
+                    address,
+                    Some(SuiObjectDataFilter::StructType(object_type)),
+                    None,
+                    50, // Limit the number of objects returned to 50
+                )
+            })
             .await?;
 
         stored_objects
@@ -57,7 +59,7 @@ impl DataReader for TransactionBuilderApiV2 {
     ) -> Result<SuiObjectResponse, anyhow::Error> {
         let result = self
             .inner
-            .get_object_read_in_blocking_task(object_id)
+            .spawn_blocking(move |this| this.get_object_read(&object_id))
             .await?;
         Ok((result, options).try_into()?)
     }
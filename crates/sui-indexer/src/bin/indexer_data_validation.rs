@@ -1,11 +1,15 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 use clap::Parser;
+use prometheus::Registry;
 use tracing::{error, info, warn};
 
-use sui_indexer::new_rpc_client;
+use sui_indexer::data_validation::{broken_ranges, DataValidator};
+use sui_indexer::metrics::IndexerMetrics;
+use sui_indexer::store::PgIndexerStoreV2;
+use sui_indexer::{new_pg_connection_pool, new_rpc_client};
 use sui_json_rpc_types::{
     CheckpointId, ObjectChange, SuiObjectDataOptions, SuiTransactionBlockResponseOptions,
 };
@@ -22,26 +26,83 @@ async fn main() -> Result<()> {
     info!("Running correctness check for indexer...");
     let config = TestConfig::parse();
     let fn_rpc_client = new_rpc_client(&config.fn_rpc_client_url).await?;
-    let indexer_rpc_client = new_rpc_client(&config.indexer_rpc_client_url).await?;
-
     let fn_latest_checkpoint = fn_rpc_client
         .read_api()
         .get_latest_checkpoint_sequence_number()
         .await?;
-    let indexer_latest_checkpoint = indexer_rpc_client
-        .read_api()
-        .get_latest_checkpoint_sequence_number()
-        .await?;
-
     let end_checkpoint = config.end_checkpoint;
-    if end_checkpoint <= fn_latest_checkpoint && end_checkpoint <= indexer_latest_checkpoint {
-        for checkpoint in config.start_checkpoint..=end_checkpoint {
-            info!("Checking checkpoint {}", checkpoint);
-            check_checkpoint(&config, &fn_rpc_client, &indexer_rpc_client, checkpoint).await?;
+    if end_checkpoint > fn_latest_checkpoint {
+        error!(
+            "End checkpoint is not available in FN, end: {}, FN latest: {}",
+            end_checkpoint, fn_latest_checkpoint
+        );
+        return Ok(());
+    }
+
+    if let Some(db_url) = &config.db_url {
+        check_db(&config, db_url).await?;
+    }
+
+    if let Some(indexer_rpc_client_url) = &config.indexer_rpc_client_url {
+        let indexer_rpc_client = new_rpc_client(indexer_rpc_client_url).await?;
+        let indexer_latest_checkpoint = indexer_rpc_client
+            .read_api()
+            .get_latest_checkpoint_sequence_number()
+            .await?;
+        if end_checkpoint <= indexer_latest_checkpoint {
+            for checkpoint in config.start_checkpoint..=end_checkpoint {
+                info!("Checking checkpoint {}", checkpoint);
+                check_checkpoint(&config, &fn_rpc_client, &indexer_rpc_client, checkpoint).await?;
+            }
+        } else {
+            error!(
+                "End checkpoint is not available in Indexer, end: {}, indexer latest: {}",
+                end_checkpoint, indexer_latest_checkpoint
+            );
         }
-    } else {
-        error!("Start checkpoint is not available in both FN and Indexer, start: {}, FN latest: {}, indexer latest: {}", 
-        end_checkpoint, fn_latest_checkpoint, indexer_latest_checkpoint);
+    }
+    Ok(())
+}
+
+/// Validate the indexer tables directly against checkpoint data from the FN,
+/// and re-ingest the broken checkpoints if requested.
+pub async fn check_db(config: &TestConfig, db_url: &str) -> Result<(), anyhow::Error> {
+    let registry = Registry::default();
+    mysten_metrics::init_metrics(&registry);
+    let metrics = IndexerMetrics::new(&registry);
+    let store = PgIndexerStoreV2::new(new_pg_connection_pool(db_url)?, metrics.clone());
+    let rest_client = sui_rest_api::Client::new(format!("{}/rest", config.fn_rpc_client_url));
+    let validator = DataValidator::new(store, rest_client, metrics);
+
+    let mismatches = validator
+        .validate(config.start_checkpoint..=config.end_checkpoint)
+        .await?;
+    for mismatch in &mismatches {
+        error!("Data mismatch found: {}", mismatch);
+    }
+    let ranges = broken_ranges(&mismatches);
+    if ranges.is_empty() {
+        info!(
+            "No mismatches found between checkpoints {} and {}",
+            config.start_checkpoint, config.end_checkpoint
+        );
+        return Ok(());
+    }
+    if !config.repair {
+        bail!(
+            "Found {} mismatches in checkpoint ranges {:?}, rerun with --repair to repair them",
+            mismatches.len(),
+            ranges
+        );
+    }
+    warn!(
+        "Found {} mismatches in checkpoint ranges {:?}",
+        mismatches.len(),
+        ranges
+    );
+    for range in ranges {
+        info!("Repairing checkpoints {:?}", range);
+        validator.repair(range).await?;
     }
     Ok(())
 }
@@ -196,7 +257,10 @@ pub struct TestConfig {
     #[clap(long)]
     pub fn_rpc_client_url: String,
     #[clap(long)]
-    pub indexer_rpc_client_url: String,
+    pub indexer_rpc_client_url: Option<String>,
+    /// Validate the indexer tables in this database against checkpoint data from the FN.
+    #[clap(long)]
+    pub db_url: Option<String>,
     #[clap(long)]
     pub start_checkpoint: u64,
     #[clap(long)]
@@ -207,4 +271,8 @@ pub struct TestConfig {
     pub check_events: bool,
     #[clap(long)]
     pub check_objects: bool,
+    /// Re-ingest the checkpoints with mismatches found in the database. Only the core tables
+    /// are repaired, tables of custom handlers need a `DataValidator` built with them.
+    #[clap(long, requires = "db_url")]
+    pub repair: bool,
 }
@@ -0,0 +1,968 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Validates the tables written by the v2 indexer against checkpoint data fetched from a
+//! fullnode, and re-ingests broken checkpoints in place.
+
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+use std::ops::RangeInclusive;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use diesel::dsl::max;
+use diesel::upsert::excluded;
+use diesel::{ExpressionMethods, OptionalExtension, PgConnection, QueryDsl, RunQueryDsl};
+use fastcrypto::encoding::{Encoding, Hex};
+use sui_rest_api::CheckpointData;
+use tokio::sync::watch;
+use tracing::info;
+
+use crate::errors::IndexerError;
+use crate::framework::{CustomDataToCommit, CustomHandlers};
+use crate::handlers::checkpoint_handler_v2::index_checkpoint;
+use crate::handlers::tx_processor::IndexingPackageCache;
+use crate::handlers::{CheckpointDataToCommit, EpochToCommit};
+use crate::metrics::IndexerMetrics;
+use crate::models_v2::checkpoints::StoredCheckpoint;
+use crate::models_v2::display::StoredDisplay;
+use crate::models_v2::epoch::StoredEpochInfo;
+use crate::models_v2::events::StoredEvent;
+use crate::models_v2::objects::{StoredHistoryObject, StoredObject};
+use crate::models_v2::packages::StoredPackage;
+use crate::models_v2::transactions::StoredTransaction;
+use crate::schema_v2::{
+    checkpoints, display, epochs, events, objects, objects_history, packages, transactions,
+    tx_calls, tx_changed_objects, tx_input_objects, tx_recipients, tx_senders,
+};
+use crate::store::diesel_macro::{read_only_blocking, transactional_blocking_with_retry};
+use crate::store::pg_indexer_store_v2::{
+    make_final_list_of_objects_to_commit, ObjectChangeToCommit,
+};
+use crate::store::PgIndexerStoreV2;
+use crate::types_v2::{IndexedEpochInfo, ObjectStatus};
+use crate::PgConnectionPool;
+
+/// Rows written per insert statement when repairing, like the store's commit chunks.
+const REPAIR_CHUNK_SIZE: usize = 1000;
+
+/// A difference between the indexer tables and the checkpoint data they were derived from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Mismatch {
+    pub checkpoint: u64,
+    pub table: &'static str,
+    pub details: String,
+}
+
+impl std::fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "checkpoint {}, table {}: {}",
+            self.checkpoint, self.table, self.details
+        )
+    }
+}
+
+pub struct DataValidator {
+    store: PgIndexerStoreV2,
+    rest_client: sui_rest_api::Client,
+    metrics: IndexerMetrics,
+    package_cache: Arc<Mutex<IndexingPackageCache>>,
+    custom_handlers: CustomHandlers,
+}
+
+impl DataValidator {
+    pub fn new(
+        store: PgIndexerStoreV2,
+        rest_client: sui_rest_api::Client,
+        metrics: IndexerMetrics,
+    ) -> Self {
+        // Nothing is committed through the pipeline, so cached packages are never evicted.
+        let (_, commit_watcher) = watch::channel(None);
+        Self {
+            store,
+            rest_client,
+            metrics,
+            package_cache: IndexingPackageCache::start(commit_watcher),
+            custom_handlers: CustomHandlers::new(),
+        }
+    }
+
+    /// Also re-ingest the data of these custom handlers when repairing, within the same
+    /// transaction as the core tables.
+    pub fn with_custom_handlers(mut self, custom_handlers: CustomHandlers) -> Self {
+        self.custom_handlers = custom_handlers;
+        self
+    }
+
+    /// Compare the indexer tables against the data of every checkpoint in `checkpoints`.
+    pub async fn validate(
+        &self,
+        checkpoints: RangeInclusive<u64>,
+    ) -> Result<Vec<Mismatch>, IndexerError> {
+        let mut mismatches = vec![];
+        for checkpoint in checkpoints {
+            let data = self.index(checkpoint).await?;
+            let pool = self.store.blocking_cp();
+            let checkpoint_mismatches =
+                tokio::task::spawn_blocking(move || validate_checkpoint(&pool, &data)).await??;
+            info!(
+                checkpoint,
+                mismatches = checkpoint_mismatches.len(),
+                "Validated checkpoint"
+            );
+            mismatches.extend(checkpoint_mismatches);
+        }
+        Ok(mismatches)
+    }
+
+    /// Re-ingest `checkpoints` in place, replacing whatever the indexer committed for them.
+    /// Checkpoints must be repaired in ascending order, as objects and epochs build on the
+    /// data of earlier checkpoints.
+    pub async fn repair(&self, checkpoints: RangeInclusive<u64>) -> Result<(), IndexerError> {
+        for checkpoint in checkpoints {
+            let data = self.fetch(checkpoint).await?;
+            let custom_data = self
+                .custom_handlers
+                .process_checkpoint(&data)
+                .map_err(|e| {
+                    IndexerError::DataTransformationError(format!(
+                        "Failed to index checkpoint {} with custom handlers: {}",
+                        checkpoint, e
+                    ))
+                })?;
+            let data = index_checkpoint(
+                self.store.clone(),
+                data,
+                self.metrics.clone(),
+                self.package_cache.clone(),
+            )
+            .await?;
+            let custom_data = self.custom_handlers.batch(vec![custom_data]);
+            self.repair_checkpoint(data, custom_data).await?;
+            info!(checkpoint, "Repaired checkpoint");
+        }
+        Ok(())
+    }
+
+    async fn fetch(&self, checkpoint: u64) -> Result<CheckpointData, IndexerError> {
+        self.rest_client
+            .get_full_checkpoint(checkpoint)
+            .await
+            .map_err(|e| {
+                IndexerError::FullNodeReadingError(format!(
+                    "Failed to fetch checkpoint {}: {}",
+                    checkpoint, e
+                ))
+            })
+    }
+
+    async fn index(&self, checkpoint: u64) -> Result<CheckpointDataToCommit, IndexerError> {
+        let data = self.fetch(checkpoint).await?;
+        index_checkpoint(
+            self.store.clone(),
+            data,
+            self.metrics.clone(),
+            self.package_cache.clone(),
+        )
+        .await
+    }
+
+    /// Replace the rows derived from the checkpoint with the ones in `data`, in a single
+    /// database transaction so an interrupted repair leaves the tables untouched and can
+    /// simply be run again. The data of the custom handlers is committed in the same
+    /// transaction.
+    async fn repair_checkpoint(
+        &self,
+        data: CheckpointDataToCommit,
+        custom_data: Vec<CustomDataToCommit>,
+    ) -> Result<(), IndexerError> {
+        let CheckpointDataToCommit {
+            checkpoint,
+            transactions,
+            events,
+            tx_indices,
+            display_updates,
+            object_changes,
+            packages,
+            epoch,
+            custom_data: _,
+        } = data;
+        let checkpoint_seq = checkpoint.sequence_number as i64;
+        let tx_range = tx_sequence_range(transactions.iter().map(|t| t.tx_sequence_number));
+
+        let checkpoint = StoredCheckpoint::from(&checkpoint);
+        let transactions = transactions
+            .iter()
+            .map(StoredTransaction::from)
+            .collect::<Vec<_>>();
+        let events = events
+            .into_iter()
+            .map(StoredEvent::from)
+            .collect::<Vec<_>>();
+        let mut senders = vec![];
+        let mut recipients = vec![];
+        let mut input_objects = vec![];
+        let mut changed_objects = vec![];
+        let mut calls = vec![];
+        for index in tx_indices {
+            let (index_senders, index_recipients, index_inputs, index_changes, index_calls) =
+                index.split();
+            senders.extend(index_senders);
+            recipients.extend(index_recipients);
+            input_objects.extend(index_inputs);
+            changed_objects.extend(index_changes);
+            calls.extend(index_calls);
+        }
+        let displays = display_updates.into_values().collect::<Vec<_>>();
+        let packages = packages
+            .into_iter()
+            .map(StoredPackage::from)
+            .collect::<Vec<_>>();
+        let object_changes = make_final_list_of_objects_to_commit(vec![object_changes]);
+        let object_ids = object_changes
+            .iter()
+            .map(|change| change.object_id().to_vec())
+            .collect::<Vec<_>>();
+        let history = object_changes
+            .into_iter()
+            .map(StoredHistoryObject::from)
+            .collect::<Vec<_>>();
+
+        let pool = self.store.blocking_cp();
+        tokio::task::spawn_blocking(move || {
+            transactional_blocking_with_retry!(
+                &pool,
+                |conn| {
+                    // Remove the rows derived from this checkpoint, including any partially
+                    // written ones.
+                    if let Some((first, last)) = tx_range {
+                        diesel::delete(
+                            transactions::table
+                                .filter(transactions::tx_sequence_number.between(first, last)),
+                        )
+                        .execute(conn)?;
+                        diesel::delete(
+                            events::table.filter(events::tx_sequence_number.between(first, last)),
+                        )
+                        .execute(conn)?;
+                        diesel::delete(
+                            tx_senders::table
+                                .filter(tx_senders::tx_sequence_number.between(first, last)),
+                        )
+                        .execute(conn)?;
+                        diesel::delete(
+                            tx_recipients::table
+                                .filter(tx_recipients::tx_sequence_number.between(first, last)),
+                        )
+                        .execute(conn)?;
+                        diesel::delete(
+                            tx_input_objects::table
+                                .filter(tx_input_objects::tx_sequence_number.between(first, last)),
+                        )
+                        .execute(conn)?;
+                        diesel::delete(
+                            tx_changed_objects::table.filter(
+                                tx_changed_objects::tx_sequence_number.between(first, last),
+                            ),
+                        )
+                        .execute(conn)?;
+                        diesel::delete(
+                            tx_calls::table
+                                .filter(tx_calls::tx_sequence_number.between(first, last)),
+                        )
+                        .execute(conn)?;
+                    }
+                    diesel::delete(
+                        objects_history::table
+                            .filter(objects_history::checkpoint_sequence_number.eq(checkpoint_seq)),
+                    )
+                    .execute(conn)?;
+                    diesel::delete(
+                        checkpoints::table.filter(checkpoints::sequence_number.eq(checkpoint_seq)),
+                    )
+                    .execute(conn)?;
+
+                    for chunk in transactions.chunks(REPAIR_CHUNK_SIZE) {
+                        diesel::insert_into(transactions::table)
+                            .values(chunk)
+                            .execute(conn)?;
+                    }
+                    for chunk in events.chunks(REPAIR_CHUNK_SIZE) {
+                        diesel::insert_into(events::table)
+                            .values(chunk)
+                            .execute(conn)?;
+                    }
+                    for chunk in senders.chunks(REPAIR_CHUNK_SIZE) {
+                        diesel::insert_into(tx_senders::table)
+                            .values(chunk)
+                            .on_conflict_do_nothing()
+                            .execute(conn)?;
+                    }
+                    for chunk in recipients.chunks(REPAIR_CHUNK_SIZE) {
+                        diesel::insert_into(tx_recipients::table)
+                            .values(chunk)
+                            .on_conflict_do_nothing()
+                            .execute(conn)?;
+                    }
+                    for chunk in input_objects.chunks(REPAIR_CHUNK_SIZE) {
+                        diesel::insert_into(tx_input_objects::table)
+                            .values(chunk)
+                            .on_conflict_do_nothing()
+                            .execute(conn)?;
+                    }
+                    for chunk in changed_objects.chunks(REPAIR_CHUNK_SIZE) {
+                        diesel::insert_into(tx_changed_objects::table)
+                            .values(chunk)
+                            .on_conflict_do_nothing()
+                            .execute(conn)?;
+                    }
+                    for chunk in calls.chunks(REPAIR_CHUNK_SIZE) {
+                        diesel::insert_into(tx_calls::table)
+                            .values(chunk)
+                            .on_conflict_do_nothing()
+                            .execute(conn)?;
+                    }
+                    repair_displays(conn, &displays)?;
+                    for chunk in packages.chunks(REPAIR_CHUNK_SIZE) {
+                        diesel::insert_into(packages::table)
+                            .values(chunk)
+                            .on_conflict(packages::package_id)
+                            .do_update()
+                            .set(packages::move_package.eq(excluded(packages::move_package)))
+                            .execute(conn)?;
+                    }
+                    for chunk in history.chunks(REPAIR_CHUNK_SIZE) {
+                        diesel::insert_into(objects_history::table)
+                            .values(chunk)
+                            .execute(conn)?;
+                    }
+                    if let Some(epoch) = &epoch {
+                        repair_epochs(conn, epoch)?;
+                    }
+                    repair_live_objects(conn, &object_ids)?;
+                    diesel::insert_into(checkpoints::table)
+                        .values(&checkpoint)
+                        .execute(conn)?;
+                    for data in &custom_data {
+                        data.commit(conn).map_err(|e| {
+                            IndexerError::PostgresWriteError(format!(
+                                "Failed to write data of custom handler {}: {}",
+                                data.handler_name(),
+                                e
+                            ))
+                        })?;
+                    }
+                    Ok::<(), IndexerError>(())
+                },
+                Duration::from_secs(60)
+            )
+        })
+        .await?
+    }
+}
+
+/// Group the checkpoints with mismatches into contiguous ranges, e.g. to repair them.
+pub fn broken_ranges(mismatches: &[Mismatch]) -> Vec<RangeInclusive<u64>> {
+    let mut checkpoints = mismatches.iter().map(|m| m.checkpoint).collect::<Vec<_>>();
+    checkpoints.sort();
+    checkpoints.dedup();
+    let mut ranges: Vec<RangeInclusive<u64>> = vec![];
+    for checkpoint in checkpoints {
+        match ranges.last_mut() {
+            Some(range) if *range.end() + 1 == checkpoint => {
+                *range = *range.start()..=checkpoint;
+            }
+            _ => ranges.push(checkpoint..=checkpoint),
+        }
+    }
+    ranges
+}
+
+fn validate_checkpoint(
+    pool: &PgConnectionPool,
+    data: &CheckpointDataToCommit,
+) -> Result<Vec<Mismatch>, IndexerError> {
+    let checkpoint_seq = data.checkpoint.sequence_number;
+    let mut mismatches = vec![];
+
+    let expected_checkpoint = StoredCheckpoint::from(&data.checkpoint);
+    let checkpoint: Option<StoredCheckpoint> = read_only_blocking!(pool, |conn| {
+        checkpoints::table
+            .filter(checkpoints::sequence_number.eq(checkpoint_seq as i64))
+            .first::<StoredCheckpoint>(conn)
+            .optional()
+    })?;
+    compare_rows(
+        &mut mismatches,
+        checkpoint_seq,
+        "checkpoints",
+        BTreeMap::from([(checkpoint_seq as i64, expected_checkpoint)]),
+        checkpoint
+            .into_iter()
+            .map(|c| (c.sequence_number, c))
+            .collect(),
+    );
+
+    if let Some((first, last)) =
+        tx_sequence_range(data.transactions.iter().map(|t| t.tx_sequence_number))
+    {
+        check_transactions(pool, data, first, last, &mut mismatches)?;
+    }
+    check_objects(pool, data, &mut mismatches)?;
+    check_displays(pool, data, &mut mismatches)?;
+    if let Some(epoch) = &data.epoch {
+        check_epochs(pool, checkpoint_seq, epoch, &mut mismatches)?;
+    }
+    Ok(mismatches)
+}
+
+fn check_transactions(
+    pool: &PgConnectionPool,
+    data: &CheckpointDataToCommit,
+    first: i64,
+    last: i64,
+    mismatches: &mut Vec<Mismatch>,
+) -> Result<(), IndexerError> {
+    let checkpoint_seq = data.checkpoint.sequence_number;
+
+    let stored_transactions: Vec<StoredTransaction> = read_only_blocking!(pool, |conn| {
+        transactions::table
+            .filter(transactions::tx_sequence_number.between(first, last))
+            .load::<StoredTransaction>(conn)
+    })?;
+    compare_rows(
+        mismatches,
+        checkpoint_seq,
+        "transactions",
+        data.transactions
+            .iter()
+            .map(StoredTransaction::from)
+            .map(|t| (t.tx_sequence_number, t))
+            .collect(),
+        stored_transactions
+            .into_iter()
+            .map(|t| (t.tx_sequence_number, t))
+            .collect(),
+    );
+
+    let stored_events: Vec<StoredEvent> = read_only_blocking!(pool, |conn| {
+        events::table
+            .filter(events::tx_sequence_number.between(first, last))
+            .load::<StoredEvent>(conn)
+    })?;
+    compare_rows(
+        mismatches,
+        checkpoint_seq,
+        "events",
+        data.events
+            .iter()
+            .cloned()
+            .map(StoredEvent::from)
+            .map(|e| ((e.tx_sequence_number, e.event_sequence_number), e))
+            .collect(),
+        stored_events
+            .into_iter()
+            .map(|e| ((e.tx_sequence_number, e.event_sequence_number), e))
+            .collect(),
+    );
+
+    let mut expected_senders = BTreeMap::new();
+    let mut expected_recipients = BTreeMap::new();
+    let mut expected_input_objects = BTreeMap::new();
+    let mut expected_changed_objects = BTreeMap::new();
+    let mut expected_calls = BTreeMap::new();
+    for index in data.tx_indices.iter().cloned() {
+        let (senders, recipients, input_objects, changed_objects, calls) = index.split();
+        expected_senders.extend(
+            senders
+                .into_iter()
+                .map(|s| ((s.tx_sequence_number, s.sender), ())),
+        );
+        expected_recipients.extend(
+            recipients
+                .into_iter()
+                .map(|r| ((r.tx_sequence_number, r.recipient), ())),
+        );
+        expected_input_objects.extend(
+            input_objects
+                .into_iter()
+                .map(|o| ((o.tx_sequence_number, o.object_id), ())),
+        );
+        expected_changed_objects.extend(
+            changed_objects
+                .into_iter()
+                .map(|o| ((o.tx_sequence_number, o.object_id), ())),
+        );
+        expected_calls.extend(
+            calls
+                .into_iter()
+                .map(|c| ((c.tx_sequence_number, c.package, c.module, c.func), ())),
+        );
+    }
+
+    let senders: Vec<(i64, Vec<u8>)> = read_only_blocking!(pool, |conn| {
+        tx_senders::table
+            .filter(tx_senders::tx_sequence_number.between(first, last))
+            .select((tx_senders::tx_sequence_number, tx_senders::sender))
+            .load(conn)
+    })?;
+    compare_rows(
+        mismatches,
+        checkpoint_seq,
+        "tx_senders",
+        expected_senders,
+        senders.into_iter().map(|row| (row, ())).collect(),
+    );
+
+    let recipients: Vec<(i64, Vec<u8>)> = read_only_blocking!(pool, |conn| {
+        tx_recipients::table
+            .filter(tx_recipients::tx_sequence_number.between(first, last))
+            .select((tx_recipients::tx_sequence_number, tx_recipients::recipient))
+            .load(conn)
+    })?;
+    compare_rows(
+        mismatches,
+        checkpoint_seq,
+        "tx_recipients",
+        expected_recipients,
+        recipients.into_iter().map(|row| (row, ())).collect(),
+    );
+
+    let input_objects: Vec<(i64, Vec<u8>)> = read_only_blocking!(pool, |conn| {
+        tx_input_objects::table
+            .filter(tx_input_objects::tx_sequence_number.between(first, last))
+            .select((
+                tx_input_objects::tx_sequence_number,
+                tx_input_objects::object_id,
+            ))
+            .load(conn)
+    })?;
+    compare_rows(
+        mismatches,
+        checkpoint_seq,
+        "tx_input_objects",
+        expected_input_objects,
+        input_objects.into_iter().map(|row| (row, ())).collect(),
+    );
+
+    let changed_objects: Vec<(i64, Vec<u8>)> = read_only_blocking!(pool, |conn| {
+        tx_changed_objects::table
+            .filter(tx_changed_objects::tx_sequence_number.between(first, last))
+            .select((
+                tx_changed_objects::tx_sequence_number,
+                tx_changed_objects::object_id,
+            ))
+            .load(conn)
+    })?;
+    compare_rows(
+        mismatches,
+        checkpoint_seq,
+        "tx_changed_objects",
+        expected_changed_objects,
+        changed_objects.into_iter().map(|row| (row, ())).collect(),
+    );
+
+    let calls: Vec<(i64, Vec<u8>, String, String)> = read_only_blocking!(pool, |conn| {
+        tx_calls::table
+            .filter(tx_calls::tx_sequence_number.between(first, last))
+            .select((
+                tx_calls::tx_sequence_number,
+                tx_calls::package,
+                tx_calls::module,
+                tx_calls::func,
+            ))
+            .load(conn)
+    })?;
+    compare_rows(
+        mismatches,
+        checkpoint_seq,
+        "tx_calls",
+        expected_calls,
+        calls.into_iter().map(|row| (row, ())).collect(),
+    );
+    Ok(())
+}
+
+fn check_objects(
+    pool: &PgConnectionPool,
+    data: &CheckpointDataToCommit,
+    mismatches: &mut Vec<Mismatch>,
+) -> Result<(), IndexerError> {
+    let checkpoint_seq = data.checkpoint.sequence_number;
+    let changes = make_final_list_of_objects_to_commit(vec![data.object_changes.clone()]);
+
+    let history: Vec<StoredHistoryObject> = read_only_blocking!(pool, |conn| {
+        objects_history::table
+            .filter(objects_history::checkpoint_sequence_number.eq(checkpoint_seq as i64))
+            .load::<StoredHistoryObject>(conn)
+    })?;
+    compare_rows(
+        mismatches,
+        checkpoint_seq,
+        "objects_history",
+        changes
+            .iter()
+            .cloned()
+            .map(StoredHistoryObject::from)
+            .map(|o| ((o.object_id.clone(), o.object_version), o))
+            .collect(),
+        history
+            .into_iter()
+            .map(|o| ((o.object_id.clone(), o.object_version), o))
+            .collect(),
+    );
+
+    // The objects table only keeps the latest version, which may come from a later checkpoint.
+    let object_ids = changes
+        .iter()
+        .map(|change| change.object_id().to_vec())
+        .collect::<Vec<_>>();
+    let live_objects: BTreeMap<Vec<u8>, StoredObject> = read_only_blocking!(pool, |conn| {
+        objects::table
+            .filter(objects::object_id.eq_any(object_ids))
+            .load::<StoredObject>(conn)
+    })?
+    .into_iter()
+    .map(|o| (o.object_id.clone(), o))
+    .collect();
+    for change in changes {
+        let live_object = live_objects.get(change.object_id());
+        let details = match (&change, live_object) {
+            (ObjectChangeToCommit::MutatedObject(expected), Some(actual))
+                if actual.object_version == expected.object_version && actual != expected =>
+            {
+                Some("differs from checkpoint data".to_string())
+            }
+            (ObjectChangeToCommit::MutatedObject(expected), Some(actual))
+                if actual.object_version < expected.object_version =>
+            {
+                Some(format!(
+                    "stale version {}, expected at least {}",
+                    actual.object_version, expected.object_version
+                ))
+            }
+            (ObjectChangeToCommit::MutatedObject(expected), None) => {
+                let latest_version = latest_history_version(pool, &expected.object_id)?;
+                (latest_version.unwrap_or_default() <= expected.object_version)
+                    .then(|| "missing".to_string())
+            }
+            (ObjectChangeToCommit::DeletedObject(expected), Some(actual))
+                if actual.object_version <= expected.object_version =>
+            {
+                Some(format!(
+                    "version {} should have been deleted at version {}",
+                    actual.object_version, expected.object_version
+                ))
+            }
+            _ => None,
+        };
+        if let Some(details) = details {
+            mismatches.push(Mismatch {
+                checkpoint: checkpoint_seq,
+                table: "objects",
+                details: format!("object {}: {}", hex(change.object_id()), details),
+            });
+        }
+    }
+    Ok(())
+}
+
+fn check_displays(
+    pool: &PgConnectionPool,
+    data: &CheckpointDataToCommit,
+    mismatches: &mut Vec<Mismatch>,
+) -> Result<(), IndexerError> {
+    if data.display_updates.is_empty() {
+        return Ok(());
+    }
+    let checkpoint_seq = data.checkpoint.sequence_number;
+    let object_types = data.display_updates.keys().cloned().collect::<Vec<_>>();
+    // Like objects, the display table only keeps the latest version of each display.
+    let stored_displays: BTreeMap<String, StoredDisplay> = read_only_blocking!(pool, |conn| {
+        display::table
+            .filter(display::object_type.eq_any(object_types))
+            .load::<StoredDisplay>(conn)
+    })?
+    .into_iter()
+    .map(|d| (d.object_type.clone(), d))
+    .collect();
+    for (object_type, expected) in &data.display_updates {
+        let details = match stored_displays.get(object_type) {
+            None => Some("missing".to_string()),
+            Some(actual) if actual.version == expected.version && actual != expected => {
+                Some("differs from checkpoint data".to_string())
+            }
+            Some(actual) if actual.version < expected.version => Some(format!(
+                "stale version {}, expected at least {}",
+                actual.version, expected.version
+            )),
+            Some(_) => None,
+        };
+        if let Some(details) = details {
+            mismatches.push(Mismatch {
+                checkpoint: checkpoint_seq,
+                table: "display",
+                details: format!("display of {}: {}", object_type, details),
+            });
+        }
+    }
+    Ok(())
+}
+
+fn check_epochs(
+    pool: &PgConnectionPool,
+    checkpoint_seq: u64,
+    epoch: &EpochToCommit,
+    mismatches: &mut Vec<Mismatch>,
+) -> Result<(), IndexerError> {
+    if let Some(last_epoch) = &epoch.last_epoch {
+        let stored = get_epoch(pool, last_epoch.epoch)?;
+        let expected = expected_epoch_end(last_epoch, stored.clone());
+        compare_rows(
+            mismatches,
+            checkpoint_seq,
+            "epochs",
+            BTreeMap::from([(expected.epoch, expected)]),
+            stored.into_iter().map(|e| (e.epoch, e)).collect(),
+        );
+    }
+    let stored = get_epoch(pool, epoch.new_epoch.epoch)?;
+    let expected = expected_epoch_beginning(&epoch.new_epoch, stored.as_ref());
+    compare_rows(
+        mismatches,
+        checkpoint_seq,
+        "epochs",
+        BTreeMap::from([(expected.epoch, expected)]),
+        stored.into_iter().map(|e| (e.epoch, e)).collect(),
+    );
+    Ok(())
+}
+
+fn repair_epochs(conn: &mut PgConnection, epoch: &EpochToCommit) -> Result<(), IndexerError> {
+    let mut rows = vec![];
+    if let Some(last_epoch) = &epoch.last_epoch {
+        let stored = epochs::table
+            .filter(epochs::epoch.eq(last_epoch.epoch as i64))
+            .first::<StoredEpochInfo>(conn)
+            .optional()?;
+        rows.push(expected_epoch_end(last_epoch, stored));
+    }
+    let stored = epochs::table
+        .filter(epochs::epoch.eq(epoch.new_epoch.epoch as i64))
+        .first::<StoredEpochInfo>(conn)
+        .optional()?;
+    rows.push(expected_epoch_beginning(&epoch.new_epoch, stored.as_ref()));
+    for row in rows {
+        diesel::delete(epochs::table.filter(epochs::epoch.eq(row.epoch))).execute(conn)?;
+        diesel::insert_into(epochs::table)
+            .values(row)
+            .execute(conn)?;
+    }
+    Ok(())
+}
+
+/// Write the display updates of the checkpoint, unless a later checkpoint already updated
+/// the display.
+fn repair_displays(
+    conn: &mut PgConnection,
+    displays: &[StoredDisplay],
+) -> Result<(), IndexerError> {
+    for expected in displays {
+        let stored = display::table
+            .filter(display::object_type.eq(expected.object_type.clone()))
+            .first::<StoredDisplay>(conn)
+            .optional()?;
+        if stored.map_or(true, |stored| stored.version <= expected.version) {
+            diesel::insert_into(display::table)
+                .values(expected)
+                .on_conflict(display::object_type)
+                .do_update()
+                .set((
+                    display::id.eq(excluded(display::id)),
+                    display::version.eq(excluded(display::version)),
+                    display::bcs.eq(excluded(display::bcs)),
+                ))
+                .execute(conn)?;
+        }
+    }
+    Ok(())
+}
+
+/// Rebuild the objects table rows of `object_ids` from their latest version in objects_history.
+fn repair_live_objects(
+    conn: &mut PgConnection,
+    object_ids: &[Vec<u8>],
+) -> Result<(), IndexerError> {
+    for object_id in object_ids {
+        let latest = objects_history::table
+            .filter(objects_history::object_id.eq(object_id.clone()))
+            .order(objects_history::object_version.desc())
+            .first::<StoredHistoryObject>(conn)
+            .optional()?;
+        diesel::delete(objects::table.filter(objects::object_id.eq(object_id.clone())))
+            .execute(conn)?;
+        if let Some(object) = latest.and_then(live_object) {
+            diesel::insert_into(objects::table)
+                .values(object)
+                .execute(conn)?;
+        }
+    }
+    Ok(())
+}
+
+fn get_epoch(pool: &PgConnectionPool, epoch: u64) -> Result<Option<StoredEpochInfo>, IndexerError> {
+    read_only_blocking!(pool, |conn| {
+        epochs::table
+            .filter(epochs::epoch.eq(epoch as i64))
+            .first::<StoredEpochInfo>(conn)
+            .optional()
+    })
+}
+
+fn latest_history_version(
+    pool: &PgConnectionPool,
+    object_id: &[u8],
+) -> Result<Option<i64>, IndexerError> {
+    let object_id = object_id.to_vec();
+    read_only_blocking!(pool, |conn| {
+        objects_history::table
+            .filter(objects_history::object_id.eq(object_id))
+            .select(max(objects_history::object_version))
+            .first::<Option<i64>>(conn)
+    })
+}
+
+/// The epochs row once the epoch ended: `stored` with the end of epoch columns overwritten.
+fn expected_epoch_end(
+    last_epoch: &IndexedEpochInfo,
+    stored: Option<StoredEpochInfo>,
+) -> StoredEpochInfo {
+    let end_info = StoredEpochInfo::from_epoch_end_info(last_epoch);
+    match stored {
+        Some(stored) => with_epoch_end_info(stored, &end_info),
+        None => end_info,
+    }
+}
+
+/// The epochs row when the epoch begins, keeping the end of epoch columns of `stored` which
+/// are written by a later checkpoint.
+fn expected_epoch_beginning(
+    new_epoch: &IndexedEpochInfo,
+    stored: Option<&StoredEpochInfo>,
+) -> StoredEpochInfo {
+    let beginning_info = StoredEpochInfo::from_epoch_beginning_info(new_epoch);
+    match stored {
+        Some(stored) => with_epoch_end_info(beginning_info, stored),
+        None => beginning_info,
+    }
+}
+
+fn with_epoch_end_info(epoch: StoredEpochInfo, end_info: &StoredEpochInfo) -> StoredEpochInfo {
+    StoredEpochInfo {
+        epoch_total_transactions: end_info.epoch_total_transactions,
+        last_checkpoint_id: end_info.last_checkpoint_id,
+        epoch_end_timestamp: end_info.epoch_end_timestamp,
+        storage_fund_reinvestment: end_info.storage_fund_reinvestment,
+        storage_charge: end_info.storage_charge,
+        storage_rebate: end_info.storage_rebate,
+        stake_subsidy_amount: end_info.stake_subsidy_amount,
+        total_gas_fees: end_info.total_gas_fees,
+        total_stake_rewards_distributed: end_info.total_stake_rewards_distributed,
+        leftover_storage_fund_inflow: end_info.leftover_storage_fund_inflow,
+        epoch_commitments: end_info.epoch_commitments.clone(),
+        ..epoch
+    }
+}
+
+fn live_object(object: StoredHistoryObject) -> Option<StoredObject> {
+    if object.object_status != ObjectStatus::Active as i16 {
+        return None;
+    }
+    Some(StoredObject {
+        object_id: object.object_id,
+        object_version: object.object_version,
+        object_digest: object.object_digest?,
+        checkpoint_sequence_number: object.checkpoint_sequence_number,
+        owner_type: object.owner_type?,
+        owner_id: object.owner_id,
+        object_type: object.object_type,
+        serialized_object: object.serialized_object?,
+        coin_type: object.coin_type,
+        coin_balance: object.coin_balance,
+        df_kind: object.df_kind,
+        df_name: object.df_name,
+        df_object_type: object.df_object_type,
+        df_object_id: object.df_object_id,
+    })
+}
+
+fn tx_sequence_range(tx_sequence_numbers: impl Iterator<Item = u64>) -> Option<(i64, i64)> {
+    tx_sequence_numbers.fold(None, |range, seq| {
+        let seq = seq as i64;
+        Some(match range {
+            Some((first, last)) => (std::cmp::min(first, seq), std::cmp::max(last, seq)),
+            None => (seq, seq),
+        })
+    })
+}
+
+/// Report rows that are missing, unexpected or different from the expected ones, by key.
+fn compare_rows<K: Ord + Debug, T: PartialEq>(
+    mismatches: &mut Vec<Mismatch>,
+    checkpoint: u64,
+    table: &'static str,
+    expected: BTreeMap<K, T>,
+    mut actual: BTreeMap<K, T>,
+) {
+    for (key, expected_row) in expected {
+        let details = match actual.remove(&key) {
+            None => "missing",
+            Some(actual_row) if actual_row != expected_row => "differs from checkpoint data",
+            Some(_) => continue,
+        };
+        mismatches.push(Mismatch {
+            checkpoint,
+            table,
+            details: format!("row {:?} {}", key, details),
+        });
+    }
+    for key in actual.into_keys() {
+        mismatches.push(Mismatch {
+            checkpoint,
+            table,
+            details: format!("unexpected row {:?}", key),
+        });
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    format!("0x{}", Hex::encode(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{broken_ranges, Mismatch};
+
+    fn mismatch(checkpoint: u64) -> Mismatch {
+        Mismatch {
+            checkpoint,
+            table: "transactions",
+            details: "missing".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_broken_ranges() {
+        assert!(broken_ranges(&[]).is_empty());
+        let mismatches = [7, 3, 4, 4, 5, 10, 8, 12]
+            .into_iter()
+            .map(mismatch)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            broken_ranges(&mismatches),
+            vec![3..=5, 7..=8, 10..=10, 12..=12]
+        );
+    }
+}
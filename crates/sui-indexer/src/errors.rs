@@ -66,6 +66,9 @@ pub enum IndexerError {
     #[error("Indexer failed to commit changes to PostgresDB with error: `{0}`")]
     PostgresWriteError(String),
 
+    #[error("Indexer failed to read or write the embedded database with error: `{0}`")]
+    EmbeddedDbError(String),
+
     #[error(transparent)]
     PostgresError(#[from] diesel::result::Error),
 
@@ -148,3 +151,9 @@ impl From<tokio::task::JoinError> for IndexerError {
         IndexerError::UncategorizedError(anyhow::Error::from(value))
     }
 }
+
+impl From<rocksdb::Error> for IndexerError {
+    fn from(value: rocksdb::Error) -> Self {
+        IndexerError::EmbeddedDbError(value.to_string())
+    }
+}
@@ -0,0 +1,139 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::any::Any;
+use std::sync::Arc;
+
+use anyhow::Result;
+use diesel::PgConnection;
+use sui_rest_api::CheckpointData;
+
+/// An application specific handler, indexing checkpoints into its own tables.
+///
+/// The data of a checkpoint is written in the same Postgres transaction as its row in the
+/// `checkpoints` table, the watermark the indexer resumes from after a restart, so the data is
+/// never ahead of or behind the watermark. The other core tables are written in earlier
+/// transactions, and may already contain checkpoints past the watermark. The handler is
+/// responsible for creating its tables, they are not managed by the indexer migrations.
+pub trait CustomHandler: Send + Sync + 'static {
+    /// The data indexed from a single checkpoint.
+    type Data: Send + 'static;
+
+    fn name(&self) -> &str;
+
+    /// Index a checkpoint. Checkpoints are processed in order of sequence number.
+    fn process_checkpoint(&self, checkpoint: &CheckpointData) -> Result<Self::Data>;
+
+    /// Write the data of a batch of consecutive checkpoints, in order of sequence number.
+    /// This is called within the transaction committing these checkpoints, and is called
+    /// again if the transaction is retried. `DataValidator::repair` also calls it for
+    /// checkpoints which were already committed, without removing their rows first, so it
+    /// should overwrite any existing rows of these checkpoints.
+    fn commit(&self, conn: &mut PgConnection, data: &[Self::Data]) -> Result<()>;
+}
+
+/// Object safe version of `CustomHandler`, so handlers with different data types can be
+/// registered together.
+trait AnyCustomHandler: Send + Sync {
+    fn name(&self) -> &str;
+    fn process_checkpoint(&self, checkpoint: &CheckpointData) -> Result<Box<dyn Any + Send>>;
+    fn batch(&self, data: Vec<Box<dyn Any + Send>>) -> Box<dyn Any + Send>;
+    fn commit(&self, conn: &mut PgConnection, batch: &(dyn Any + Send)) -> Result<()>;
+}
+
+impl<T: CustomHandler> AnyCustomHandler for T {
+    fn name(&self) -> &str {
+        CustomHandler::name(self)
+    }
+
+    fn process_checkpoint(&self, checkpoint: &CheckpointData) -> Result<Box<dyn Any + Send>> {
+        Ok(Box::new(CustomHandler::process_checkpoint(
+            self, checkpoint,
+        )?))
+    }
+
+    fn batch(&self, data: Vec<Box<dyn Any + Send>>) -> Box<dyn Any + Send> {
+        // Unwrap safe: the data was produced by `process_checkpoint` of the same handler.
+        let batch = data
+            .into_iter()
+            .map(|d| *d.downcast::<T::Data>().unwrap())
+            .collect::<Vec<_>>();
+        Box::new(batch)
+    }
+
+    fn commit(&self, conn: &mut PgConnection, batch: &(dyn Any + Send)) -> Result<()> {
+        // Unwrap safe: the batch was produced by `batch` of the same handler.
+        let batch = batch.downcast_ref::<Vec<T::Data>>().unwrap();
+        CustomHandler::commit(self, conn, batch)
+    }
+}
+
+/// The custom handlers registered with an indexer writer.
+#[derive(Clone, Default)]
+pub struct CustomHandlers {
+    handlers: Vec<Arc<dyn AnyCustomHandler>>,
+}
+
+impl CustomHandlers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn handler<T: CustomHandler>(mut self, handler: T) -> Self {
+        self.handlers.push(Arc::new(handler));
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.handlers.is_empty()
+    }
+
+    /// Index a checkpoint with every handler, in order of registration.
+    pub(crate) fn process_checkpoint(
+        &self,
+        checkpoint: &CheckpointData,
+    ) -> Result<Vec<Box<dyn Any + Send>>> {
+        self.handlers
+            .iter()
+            .map(|handler| handler.process_checkpoint(checkpoint))
+            .collect()
+    }
+
+    /// Group the data of consecutive checkpoints, as returned by `process_checkpoint`,
+    /// into one batch per handler.
+    pub(crate) fn batch(
+        &self,
+        checkpoints: Vec<Vec<Box<dyn Any + Send>>>,
+    ) -> Vec<CustomDataToCommit> {
+        let mut per_handler = self.handlers.iter().map(|_| vec![]).collect::<Vec<_>>();
+        for checkpoint in checkpoints {
+            for (data, handler_data) in per_handler.iter_mut().zip(checkpoint) {
+                data.push(handler_data);
+            }
+        }
+        self.handlers
+            .iter()
+            .zip(per_handler)
+            .map(|(handler, data)| CustomDataToCommit {
+                handler: handler.clone(),
+                batch: handler.batch(data),
+            })
+            .collect()
+    }
+}
+
+/// The data of a batch of checkpoints indexed by a custom handler, ready to be committed.
+pub struct CustomDataToCommit {
+    handler: Arc<dyn AnyCustomHandler>,
+    batch: Box<dyn Any + Send>,
+}
+
+impl CustomDataToCommit {
+    pub fn handler_name(&self) -> &str {
+        self.handler.name()
+    }
+
+    pub(crate) fn commit(&self, conn: &mut PgConnection) -> Result<()> {
+        self.handler.commit(conn, self.batch.as_ref())
+    }
+}
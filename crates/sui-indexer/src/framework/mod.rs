@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 mod builder;
+mod custom_handler;
 pub mod interface;
 
 // TODO remove the pub(crater) once indexer_v2.rs is renamed to lib.rs
@@ -9,4 +10,5 @@ pub(crate) mod fetcher;
 pub(crate) mod runner;
 
 pub use builder::IndexerBuilder;
+pub use custom_handler::{CustomDataToCommit, CustomHandler, CustomHandlers};
 pub use interface::Handler;
@@ -38,6 +38,7 @@ use sui_types::sui_system_state::{get_sui_system_state, SuiSystemStateTrait};
 
 use crate::errors::IndexerError;
 use crate::framework::interface::Handler;
+use crate::framework::CustomHandlers;
 use crate::metrics::IndexerMetrics;
 
 use crate::store::module_resolver_v2::InterimModuleResolver;
@@ -61,6 +62,7 @@ pub async fn new_handlers<S>(
     state: S,
     metrics: IndexerMetrics,
     config: &IndexerConfig,
+    custom_handlers: CustomHandlers,
 ) -> Result<CheckpointHandler<S>, IndexerError>
 where
     S: IndexerStoreV2 + Clone + Sync + Send + 'static,
@@ -86,6 +88,7 @@ where
         state_clone,
         metrics_clone,
         config_clone,
+        custom_handlers.clone(),
         indexed_checkpoint_receiver,
         tx,
     ));
@@ -95,11 +98,40 @@ where
         metrics,
         indexed_checkpoint_sender,
         package_cache: IndexingPackageCache::start(rx),
+        custom_handlers,
     };
 
     Ok(checkpoint_handler)
 }
 
+/// Index a single checkpoint outside of the ingestion pipeline, the same way
+/// `CheckpointHandler` does, e.g. to validate or repair already committed data.
+pub(crate) async fn index_checkpoint<S>(
+    state: S,
+    data: CheckpointData,
+    metrics: IndexerMetrics,
+    package_cache: Arc<Mutex<IndexingPackageCache>>,
+) -> Result<CheckpointDataToCommit, IndexerError>
+where
+    S: IndexerStoreV2 + Clone + Sync + Send + 'static,
+{
+    let packages = CheckpointHandler::<S>::index_packages(std::slice::from_ref(&data), &metrics);
+    let module_resolver = Arc::new(InterimModuleResolver::new(
+        state.module_cache(),
+        package_cache,
+        &packages,
+        metrics.clone(),
+    ));
+    CheckpointHandler::index_one_checkpoint(
+        Arc::new(state),
+        data,
+        Arc::new(metrics),
+        packages,
+        module_resolver,
+    )
+    .await
+}
+
 pub struct CheckpointHandler<S> {
     state: S,
     metrics: IndexerMetrics,
@@ -107,6 +139,7 @@ pub struct CheckpointHandler<S> {
     // Map from checkpoint sequence number and its starting transaction sequence number
     // This thing is small enough to be kept in memory
     package_cache: Arc<Mutex<IndexingPackageCache>>,
+    custom_handlers: CustomHandlers,
 }
 
 #[async_trait]
@@ -169,7 +202,7 @@ where
                 module_resolver.clone(),
             )));
         }
-        let checkpoint_data_to_commit = futures::future::join_all(tasks)
+        let mut checkpoint_data_to_commit = futures::future::join_all(tasks)
             .await
             .into_iter()
             .collect::<Result<Vec<_>, _>>()
@@ -184,6 +217,21 @@ where
             .tap_err(|e| {
                 error!("Failed to index checkpoints with error: {}", e.to_string());
             })?;
+        if !self.custom_handlers.is_empty() {
+            for (checkpoint_data, checkpoint) in
+                checkpoint_data_to_commit.iter_mut().zip(checkpoints)
+            {
+                checkpoint_data.custom_data = self
+                    .custom_handlers
+                    .process_checkpoint(checkpoint)
+                    .tap_err(|e| {
+                    error!(
+                        "Failed to index checkpoint with custom handlers with error: {}",
+                        e.to_string()
+                    );
+                })?;
+            }
+        }
         let elapsed = indexing_timer.stop_and_record();
 
         info!(
@@ -347,6 +395,7 @@ where
             object_changes,
             packages,
             epoch,
+            custom_data: vec![],
         })
     }
 
@@ -11,6 +11,7 @@ use tracing::{error, info};
 
 use sui_types::messages_checkpoint::CheckpointSequenceNumber;
 
+use crate::framework::CustomHandlers;
 use crate::metrics::IndexerMetrics;
 
 use crate::store::IndexerStoreV2;
@@ -23,6 +24,7 @@ pub async fn start_tx_checkpoint_commit_task<S>(
     state: S,
     metrics: IndexerMetrics,
     config: IndexerConfig,
+    custom_handlers: CustomHandlers,
     tx_indexing_receiver: mysten_metrics::metered_channel::Receiver<CheckpointDataToCommit>,
     commit_notifier: watch::Sender<Option<CheckpointSequenceNumber>>,
 ) where
@@ -64,6 +66,7 @@ pub async fn start_tx_checkpoint_commit_task<S>(
                     indexed_checkpoint_batch_per_epoch,
                     epoch,
                     &metrics,
+                    &custom_handlers,
                     &commit_notifier,
                 )
                 .await;
@@ -76,6 +79,7 @@ pub async fn start_tx_checkpoint_commit_task<S>(
                 indexed_checkpoint_batch_per_epoch,
                 None,
                 &metrics,
+                &custom_handlers,
                 &commit_notifier,
             )
             .await;
@@ -93,6 +97,7 @@ async fn commit_checkpoints<S>(
     indexed_checkpoint_batch: Vec<CheckpointDataToCommit>,
     epoch: Option<EpochToCommit>,
     metrics: &IndexerMetrics,
+    custom_handlers: &CustomHandlers,
     commit_notifier: &watch::Sender<Option<CheckpointSequenceNumber>>,
 ) where
     S: IndexerStoreV2 + Clone + Sync + Send + 'static,
@@ -104,6 +109,7 @@ async fn commit_checkpoints<S>(
     let mut display_updates_batch = BTreeMap::new();
     let mut object_changes_batch = vec![];
     let mut packages_batch = vec![];
+    let mut custom_data_batch = vec![];
 
     for indexed_checkpoint in indexed_checkpoint_batch {
         let CheckpointDataToCommit {
@@ -115,6 +121,7 @@ async fn commit_checkpoints<S>(
             object_changes,
             packages,
             epoch: _,
+            custom_data,
         } = indexed_checkpoint;
         checkpoint_batch.push(checkpoint);
         tx_batch.push(transactions);
@@ -123,6 +130,7 @@ async fn commit_checkpoints<S>(
         display_updates_batch.extend(display_updates.into_iter());
         object_changes_batch.push(object_changes);
         packages_batch.push(packages);
+        custom_data_batch.push(custom_data);
     }
 
     let first_checkpoint_seq = checkpoint_batch.first().as_ref().unwrap().sequence_number;
@@ -163,8 +171,11 @@ async fn commit_checkpoints<S>(
             .expect("Persisting data into DB should not fail.");
     }
 
+    // Custom handlers data is committed along with the checkpoints, so that it is
+    // never ahead of or behind the checkpoints watermark.
+    let custom_data_batch = custom_handlers.batch(custom_data_batch);
     state
-        .persist_checkpoints(checkpoint_batch)
+        .persist_checkpoints_with_custom_data(checkpoint_batch, custom_data_batch)
         .await
         .tap_err(|e| {
             error!(
@@ -6,6 +6,7 @@ pub mod checkpoint_handler_v2;
 pub mod committer;
 pub mod tx_processor;
 
+use std::any::Any;
 use std::collections::BTreeMap;
 
 use crate::{
@@ -26,6 +27,8 @@ pub struct CheckpointDataToCommit {
     pub object_changes: TransactionObjectChangesToCommit,
     pub packages: Vec<IndexedPackage>,
     pub epoch: Option<EpochToCommit>,
+    /// Data indexed by each registered custom handler, in order of registration.
+    pub custom_data: Vec<Box<dyn Any + Send>>,
 }
 
 #[derive(Clone, Debug)]
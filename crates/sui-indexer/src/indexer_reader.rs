@@ -24,6 +24,7 @@ use crate::{
     PgConnectionConfig, PgConnectionPoolConfig, PgPoolConnection,
 };
 use anyhow::{anyhow, Result};
+use async_trait::async_trait;
 use cached::proc_macro::cached;
 use cached::SizedCache;
 use diesel::{
@@ -33,6 +34,7 @@ use diesel::{
 use fastcrypto::encoding::Encoding;
 use fastcrypto::encoding::Hex;
 use itertools::{any, Itertools};
+use move_bytecode_utils::module_cache::GetModule;
 use move_core_types::annotated_value::MoveStructLayout;
 use move_core_types::language_storage::StructTag;
 use std::{
@@ -57,6 +59,7 @@ use sui_types::{
     is_system_package,
     move_package::MovePackage,
     object::{Object, ObjectRead},
+    storage::ObjectStore,
     sui_system_state::{sui_system_state_summary::SuiSystemStateSummary, SuiSystemStateTrait},
 };
 use sui_types::{coin::CoinMetadata, event::EventID};
@@ -65,6 +68,202 @@ pub const TX_SEQUENCE_NUMBER_STR: &str = "tx_sequence_number";
 pub const TRANSACTION_DIGEST_STR: &str = "transaction_digest";
 pub const EVENT_SEQUENCE_NUMBER_STR: &str = "event_sequence_number";
 
+/// Reads shared by all indexer storage backends, so the JSON-RPC reader, tools and tests can
+/// read back indexed data without depending on Postgres. `IndexerReader` implements it on top of
+/// Postgres and `EmbeddedIndexerStoreV2` on top of its local database.
+///
+/// Reads are blocking, async callers should run them through `spawn_blocking`.
+#[async_trait]
+pub trait IndexerStoreReader: GetModule + ObjectStore + Clone + Send + Sync + 'static {
+    /// Runs `f` with a clone of this reader on a thread where blocking reads are allowed.
+    async fn spawn_blocking<F, R, E>(&self, f: F) -> Result<R, E>
+    where
+        F: FnOnce(Self) -> Result<R, E> + Send + 'static,
+        R: Send + 'static,
+        E: Send + 'static;
+
+    fn get_checkpoint(
+        &self,
+        checkpoint_id: CheckpointId,
+    ) -> Result<Option<sui_json_rpc_types::Checkpoint>, IndexerError>;
+
+    fn get_latest_checkpoint(&self) -> Result<sui_json_rpc_types::Checkpoint, IndexerError>;
+
+    fn get_checkpoints(
+        &self,
+        cursor: Option<u64>,
+        limit: usize,
+        descending_order: bool,
+    ) -> Result<Vec<sui_json_rpc_types::Checkpoint>, IndexerError>;
+
+    fn get_epoch_info(&self, epoch: Option<EpochId>) -> Result<Option<EpochInfo>, IndexerError>;
+
+    fn get_latest_epoch_info_from_db(&self) -> Result<StoredEpochInfo, IndexerError>;
+
+    fn get_epochs(
+        &self,
+        cursor: Option<u64>,
+        limit: usize,
+        descending_order: bool,
+    ) -> Result<Vec<EpochInfo>, IndexerError>;
+
+    fn get_object(
+        &self,
+        object_id: &ObjectID,
+        version: Option<VersionNumber>,
+    ) -> Result<Option<Object>, IndexerError>;
+
+    fn get_object_read(&self, object_id: &ObjectID) -> Result<ObjectRead, IndexerError>;
+
+    fn multi_get_objects(
+        &self,
+        object_ids: Vec<ObjectID>,
+    ) -> Result<Vec<StoredObject>, IndexerError>;
+
+    /// Objects owned by `address` with an id greater than `cursor`, in object id order.
+    fn get_owned_objects(
+        &self,
+        address: SuiAddress,
+        filter: Option<SuiObjectDataFilter>,
+        cursor: Option<ObjectID>,
+        limit: usize,
+    ) -> Result<Vec<StoredObject>, IndexerError>;
+
+    fn get_package(&self, package_id: &ObjectID) -> Result<Option<MovePackage>, IndexerError>;
+
+    fn multi_get_transaction_block_response(
+        &self,
+        digests: &[TransactionDigest],
+        options: sui_json_rpc_types::SuiTransactionBlockResponseOptions,
+    ) -> Result<Vec<SuiTransactionBlockResponse>, IndexerError>;
+
+    fn query_transaction_blocks(
+        &self,
+        filter: Option<TransactionFilter>,
+        options: sui_json_rpc_types::SuiTransactionBlockResponseOptions,
+        cursor: Option<TransactionDigest>,
+        limit: usize,
+        is_descending: bool,
+    ) -> IndexerResult<Vec<SuiTransactionBlockResponse>>;
+
+    fn get_transaction_events(
+        &self,
+        digest: TransactionDigest,
+    ) -> Result<Vec<SuiEvent>, IndexerError>;
+
+    fn query_events(
+        &self,
+        filter: EventFilter,
+        cursor: Option<EventID>,
+        limit: usize,
+        descending_order: bool,
+    ) -> IndexerResult<Vec<SuiEvent>>;
+
+    fn get_dynamic_fields(
+        &self,
+        parent_object_id: ObjectID,
+        cursor: Option<ObjectID>,
+        limit: usize,
+    ) -> Result<Vec<DynamicFieldInfo>, IndexerError>;
+
+    fn get_dynamic_fields_raw(
+        &self,
+        parent_object_id: ObjectID,
+        cursor: Option<ObjectID>,
+        limit: usize,
+    ) -> Result<Vec<StoredObject>, IndexerError>;
+
+    fn get_display_update_event(
+        &self,
+        object_type: String,
+    ) -> Result<Option<sui_types::display::DisplayVersionUpdatedEvent>, IndexerError>;
+
+    /// Coins owned by `owner`, of type `coin_type` or of any type if it is None.
+    fn get_owned_coins(
+        &self,
+        owner: SuiAddress,
+        coin_type: Option<String>,
+        cursor: ObjectID,
+        limit: usize,
+    ) -> Result<Vec<SuiCoin>, IndexerError>;
+
+    /// Balances of `owner` per coin type, of `coin_type` only or of all types if it is None.
+    fn get_coin_balances(
+        &self,
+        owner: SuiAddress,
+        coin_type: Option<String>,
+    ) -> Result<Vec<Balance>, IndexerError>;
+
+    fn get_coin_metadata(
+        &self,
+        coin_struct: StructTag,
+    ) -> Result<Option<SuiCoinMetadata>, IndexerError>;
+
+    fn get_total_supply(&self, coin_struct: StructTag) -> Result<Supply, IndexerError>;
+
+    fn get_latest_network_metrics(&self) -> IndexerResult<NetworkMetrics>;
+
+    fn get_latest_move_call_metrics(&self) -> IndexerResult<MoveCallMetrics>;
+
+    fn get_latest_address_metrics(&self) -> IndexerResult<AddressMetrics>;
+
+    fn get_checkpoint_address_metrics(&self, checkpoint_seq: u64) -> IndexerResult<AddressMetrics>;
+
+    fn get_all_epoch_address_metrics(
+        &self,
+        descending_order: Option<bool>,
+    ) -> IndexerResult<Vec<AddressMetrics>>;
+
+    fn get_latest_sui_system_state(&self) -> Result<SuiSystemStateSummary, IndexerError> {
+        let system_state: SuiSystemStateSummary =
+            sui_types::sui_system_state::get_sui_system_state(self)?
+                .into_sui_system_state_summary();
+        Ok(system_state)
+    }
+
+    fn bcs_name_from_dynamic_field_name(
+        &self,
+        name: &DynamicFieldName,
+    ) -> Result<Vec<u8>, IndexerError> {
+        let layout =
+            move_bytecode_utils::layout::TypeLayoutBuilder::build_with_types(&name.type_, self)?;
+        let sui_json_value = sui_json::SuiJsonValue::new(name.value.clone())?;
+        let name_bcs_value = sui_json_value.to_bcs_bytes(&layout)?;
+        Ok(name_bcs_value)
+    }
+
+    async fn get_display_fields(
+        &self,
+        original_object: &sui_types::object::Object,
+        original_layout: &Option<MoveStructLayout>,
+    ) -> Result<DisplayFieldsResponse, IndexerError> {
+        let (object_type, layout) = if let Some((object_type, layout)) =
+            sui_json_rpc::read_api::get_object_type_and_struct(original_object, original_layout)
+                .map_err(|e| IndexerError::GenericError(e.to_string()))?
+        {
+            (object_type, layout)
+        } else {
+            return Ok(DisplayFieldsResponse {
+                data: None,
+                error: None,
+            });
+        };
+
+        let object_type = object_type.to_canonical_string(/* with_prefix */ true);
+        if let Some(display_object) = self
+            .spawn_blocking(move |this| this.get_display_update_event(object_type))
+            .await?
+        {
+            return sui_json_rpc::read_api::get_rendered_fields(display_object.fields, &layout)
+                .map_err(|e| IndexerError::GenericError(e.to_string()));
+        }
+        Ok(DisplayFieldsResponse {
+            data: None,
+            error: None,
+        })
+    }
+}
+
 #[derive(Clone)]
 pub struct IndexerReader {
     pool: crate::PgConnectionPool,
@@ -1280,17 +1479,6 @@ impl IndexerReader {
         Ok(objects)
     }
 
-    pub fn bcs_name_from_dynamic_field_name(
-        &self,
-        name: &DynamicFieldName,
-    ) -> Result<Vec<u8>, IndexerError> {
-        let layout =
-            move_bytecode_utils::layout::TypeLayoutBuilder::build_with_types(&name.type_, self)?;
-        let sui_json_value = sui_json::SuiJsonValue::new(name.value.clone())?;
-        let name_bcs_value = sui_json_value.to_bcs_bytes(&layout)?;
-        Ok(name_bcs_value)
-    }
-
     fn get_object_refs(
         &self,
         object_ids: Vec<Vec<u8>>,
@@ -1559,33 +1747,6 @@ impl IndexerReader {
             .collect())
     }
 
-    pub(crate) async fn get_display_fields(
-        &self,
-        original_object: &sui_types::object::Object,
-        original_layout: &Option<MoveStructLayout>,
-    ) -> Result<DisplayFieldsResponse, IndexerError> {
-        let (object_type, layout) = if let Some((object_type, layout)) =
-            sui_json_rpc::read_api::get_object_type_and_struct(original_object, original_layout)
-                .map_err(|e| IndexerError::GenericError(e.to_string()))?
-        {
-            (object_type, layout)
-        } else {
-            return Ok(DisplayFieldsResponse {
-                data: None,
-                error: None,
-            });
-        };
-
-        if let Some(display_object) = self.get_display_object_by_type(&object_type).await? {
-            return sui_json_rpc::read_api::get_rendered_fields(display_object.fields, &layout)
-                .map_err(|e| IndexerError::GenericError(e.to_string()));
-        }
-        Ok(DisplayFieldsResponse {
-            data: None,
-            error: None,
-        })
-    }
-
     pub async fn get_coin_metadata_in_blocking_task(
         &self,
         coin_struct: StructTag,
@@ -1654,6 +1815,201 @@ impl PackageCache {
     }
 }
 
+#[async_trait]
+impl IndexerStoreReader for IndexerReader {
+    async fn spawn_blocking<F, R, E>(&self, f: F) -> Result<R, E>
+    where
+        F: FnOnce(Self) -> Result<R, E> + Send + 'static,
+        R: Send + 'static,
+        E: Send + 'static,
+    {
+        IndexerReader::spawn_blocking(self, f).await
+    }
+
+    fn get_checkpoint(
+        &self,
+        checkpoint_id: CheckpointId,
+    ) -> Result<Option<sui_json_rpc_types::Checkpoint>, IndexerError> {
+        IndexerReader::get_checkpoint(self, checkpoint_id)
+    }
+
+    fn get_latest_checkpoint(&self) -> Result<sui_json_rpc_types::Checkpoint, IndexerError> {
+        IndexerReader::get_latest_checkpoint(self)
+    }
+
+    fn get_checkpoints(
+        &self,
+        cursor: Option<u64>,
+        limit: usize,
+        descending_order: bool,
+    ) -> Result<Vec<sui_json_rpc_types::Checkpoint>, IndexerError> {
+        IndexerReader::get_checkpoints(self, cursor, limit, descending_order)
+    }
+
+    fn get_epoch_info(&self, epoch: Option<EpochId>) -> Result<Option<EpochInfo>, IndexerError> {
+        IndexerReader::get_epoch_info(self, epoch)
+    }
+
+    fn get_latest_epoch_info_from_db(&self) -> Result<StoredEpochInfo, IndexerError> {
+        IndexerReader::get_latest_epoch_info_from_db(self)
+    }
+
+    fn get_epochs(
+        &self,
+        cursor: Option<u64>,
+        limit: usize,
+        descending_order: bool,
+    ) -> Result<Vec<EpochInfo>, IndexerError> {
+        IndexerReader::get_epochs(self, cursor, limit, descending_order)
+    }
+
+    fn get_object(
+        &self,
+        object_id: &ObjectID,
+        version: Option<VersionNumber>,
+    ) -> Result<Option<Object>, IndexerError> {
+        IndexerReader::get_object(self, object_id, version)
+    }
+
+    fn get_object_read(&self, object_id: &ObjectID) -> Result<ObjectRead, IndexerError> {
+        IndexerReader::get_object_read(self, object_id)
+    }
+
+    fn multi_get_objects(
+        &self,
+        object_ids: Vec<ObjectID>,
+    ) -> Result<Vec<StoredObject>, IndexerError> {
+        self.multi_get_objects_impl(object_ids)
+    }
+
+    fn get_owned_objects(
+        &self,
+        address: SuiAddress,
+        filter: Option<SuiObjectDataFilter>,
+        cursor: Option<ObjectID>,
+        limit: usize,
+    ) -> Result<Vec<StoredObject>, IndexerError> {
+        self.get_owned_objects_impl(address, filter, cursor, limit)
+    }
+
+    fn get_package(&self, package_id: &ObjectID) -> Result<Option<MovePackage>, IndexerError> {
+        IndexerReader::get_package(self, package_id)
+    }
+
+    fn multi_get_transaction_block_response(
+        &self,
+        digests: &[TransactionDigest],
+        options: sui_json_rpc_types::SuiTransactionBlockResponseOptions,
+    ) -> Result<Vec<SuiTransactionBlockResponse>, IndexerError> {
+        self.multi_get_transaction_block_response_impl(digests, options)
+    }
+
+    fn query_transaction_blocks(
+        &self,
+        filter: Option<TransactionFilter>,
+        options: sui_json_rpc_types::SuiTransactionBlockResponseOptions,
+        cursor: Option<TransactionDigest>,
+        limit: usize,
+        is_descending: bool,
+    ) -> IndexerResult<Vec<SuiTransactionBlockResponse>> {
+        self.query_transaction_blocks_impl(filter, options, cursor, limit, is_descending)
+    }
+
+    fn get_transaction_events(
+        &self,
+        digest: TransactionDigest,
+    ) -> Result<Vec<SuiEvent>, IndexerError> {
+        self.get_transaction_events_impl(digest)
+    }
+
+    fn query_events(
+        &self,
+        filter: EventFilter,
+        cursor: Option<EventID>,
+        limit: usize,
+        descending_order: bool,
+    ) -> IndexerResult<Vec<SuiEvent>> {
+        self.query_events_impl(filter, cursor, limit, descending_order)
+    }
+
+    fn get_dynamic_fields(
+        &self,
+        parent_object_id: ObjectID,
+        cursor: Option<ObjectID>,
+        limit: usize,
+    ) -> Result<Vec<DynamicFieldInfo>, IndexerError> {
+        self.get_dynamic_fields_impl(parent_object_id, cursor, limit)
+    }
+
+    fn get_dynamic_fields_raw(
+        &self,
+        parent_object_id: ObjectID,
+        cursor: Option<ObjectID>,
+        limit: usize,
+    ) -> Result<Vec<StoredObject>, IndexerError> {
+        IndexerReader::get_dynamic_fields_raw(self, parent_object_id, cursor, limit)
+    }
+
+    fn get_display_update_event(
+        &self,
+        object_type: String,
+    ) -> Result<Option<sui_types::display::DisplayVersionUpdatedEvent>, IndexerError> {
+        IndexerReader::get_display_update_event(self, object_type)
+    }
+
+    fn get_owned_coins(
+        &self,
+        owner: SuiAddress,
+        coin_type: Option<String>,
+        cursor: ObjectID,
+        limit: usize,
+    ) -> Result<Vec<SuiCoin>, IndexerError> {
+        IndexerReader::get_owned_coins(self, owner, coin_type, cursor, limit)
+    }
+
+    fn get_coin_balances(
+        &self,
+        owner: SuiAddress,
+        coin_type: Option<String>,
+    ) -> Result<Vec<Balance>, IndexerError> {
+        IndexerReader::get_coin_balances(self, owner, coin_type)
+    }
+
+    fn get_coin_metadata(
+        &self,
+        coin_struct: StructTag,
+    ) -> Result<Option<SuiCoinMetadata>, IndexerError> {
+        IndexerReader::get_coin_metadata(self, coin_struct)
+    }
+
+    fn get_total_supply(&self, coin_struct: StructTag) -> Result<Supply, IndexerError> {
+        IndexerReader::get_total_supply(self, coin_struct)
+    }
+
+    fn get_latest_network_metrics(&self) -> IndexerResult<NetworkMetrics> {
+        IndexerReader::get_latest_network_metrics(self)
+    }
+
+    fn get_latest_move_call_metrics(&self) -> IndexerResult<MoveCallMetrics> {
+        IndexerReader::get_latest_move_call_metrics(self)
+    }
+
+    fn get_latest_address_metrics(&self) -> IndexerResult<AddressMetrics> {
+        IndexerReader::get_latest_address_metrics(self)
+    }
+
+    fn get_checkpoint_address_metrics(&self, checkpoint_seq: u64) -> IndexerResult<AddressMetrics> {
+        IndexerReader::get_checkpoint_address_metrics(self, checkpoint_seq)
+    }
+
+    fn get_all_epoch_address_metrics(
+        &self,
+        descending_order: Option<bool>,
+    ) -> IndexerResult<Vec<AddressMetrics>> {
+        IndexerReader::get_all_epoch_address_metrics(self, descending_order)
+    }
+}
+
 impl move_core_types::resolver::ModuleResolver for IndexerReader {
     type Error = IndexerError;
 
@@ -6,7 +6,7 @@ use crate::apis::{
     TransactionBuilderApiV2, WriteApi,
 };
 use crate::errors::IndexerError;
-use crate::indexer_reader::IndexerReader;
+use crate::indexer_reader::{IndexerReader, IndexerStoreReader};
 use crate::metrics::IndexerMetrics;
 use crate::IndexerConfig;
 use anyhow::Result;
@@ -20,6 +20,7 @@ use tokio::runtime::Handle;
 use tracing::info;
 
 use crate::framework::fetcher::CheckpointFetcher;
+use crate::framework::CustomHandlers;
 use crate::handlers::checkpoint_handler_v2::new_handlers;
 use crate::processors_v2::processor_orchestrator_v2::ProcessorOrchestratorV2;
 use crate::store::{IndexerStoreV2, PgIndexerAnalyticalStore};
@@ -34,6 +35,24 @@ impl IndexerV2 {
         store: S,
         metrics: IndexerMetrics,
     ) -> Result<(), IndexerError> {
+        Self::start_writer_with_custom_handlers(config, store, metrics, CustomHandlers::new()).await
+    }
+
+    /// Start a writer that also runs the given custom handlers, committing their data in the
+    /// same transaction as the checkpoints they were indexed from.
+    pub async fn start_writer_with_custom_handlers<
+        S: IndexerStoreV2 + Sync + Send + Clone + 'static,
+    >(
+        config: &IndexerConfig,
+        store: S,
+        metrics: IndexerMetrics,
+        custom_handlers: CustomHandlers,
+    ) -> Result<(), IndexerError> {
+        if !custom_handlers.is_empty() && !store.supports_custom_handlers() {
+            return Err(IndexerError::NotSupportedError(
+                "Custom handlers are only supported by the Postgres store".to_string(),
+            ));
+        }
         info!(
             "Sui indexerV2 Writer (version {:?}) started...",
             env!("CARGO_PKG_VERSION")
@@ -62,7 +81,7 @@ impl IndexerV2 {
         );
         spawn_monitored_task!(fetcher.run());
 
-        let checkpoint_handler = new_handlers(store, metrics, config).await?;
+        let checkpoint_handler = new_handlers(store, metrics, config, custom_handlers).await?;
 
         crate::framework::runner::run(
             mysten_metrics::metered_channel::ReceiverStream::new(
@@ -79,13 +98,22 @@ impl IndexerV2 {
         config: &IndexerConfig,
         registry: &Registry,
         db_url: String,
+    ) -> Result<(), IndexerError> {
+        let indexer_reader = IndexerReader::new(db_url)?;
+        Self::start_reader_with_store(config, registry, indexer_reader).await
+    }
+
+    /// Start a JSON-RPC reader serving the data of the given store.
+    pub async fn start_reader_with_store<R: IndexerStoreReader>(
+        config: &IndexerConfig,
+        registry: &Registry,
+        reader: R,
     ) -> Result<(), IndexerError> {
         info!(
             "Sui indexerV2 Reader (version {:?}) started...",
             env!("CARGO_PKG_VERSION")
         );
-        let indexer_reader = IndexerReader::new(db_url)?;
-        let handle = build_json_rpc_server(registry, indexer_reader, config, None)
+        let handle = build_json_rpc_server(registry, reader, config, None)
             .await
             .expect("Json rpc server should not run into errors upon start.");
         tokio::spawn(async move { handle.stopped().await })
@@ -109,9 +137,9 @@ impl IndexerV2 {
     }
 }
 
-pub async fn build_json_rpc_server(
+pub async fn build_json_rpc_server<R: IndexerStoreReader>(
     prometheus_registry: &Registry,
-    reader: IndexerReader,
+    reader: R,
     config: &IndexerConfig,
     custom_runtime: Option<Handle>,
 ) -> Result<ServerHandle, IndexerError> {
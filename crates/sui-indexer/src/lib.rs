@@ -4,6 +4,7 @@
 
 use std::env;
 use std::net::SocketAddr;
+use std::path::PathBuf;
 use std::{collections::HashMap, time::Duration};
 
 use anyhow::{anyhow, Result};
@@ -35,6 +36,7 @@ use crate::framework::IndexerBuilder;
 use crate::handlers::checkpoint_handler::new_handlers;
 
 pub mod apis;
+pub mod data_validation;
 pub mod errors;
 pub mod framework;
 mod handlers;
@@ -118,6 +120,10 @@ pub struct IndexerConfig {
     pub skip_db_commit: bool,
     #[clap(long)]
     pub use_v2: bool,
+    /// Index into and serve from a local embedded database at this path instead of Postgres
+    /// (v2 only, the analytical worker still requires Postgres).
+    #[clap(long)]
+    pub embedded_db_path: Option<PathBuf>,
 }
 
 impl IndexerConfig {
@@ -174,6 +180,7 @@ impl Default for IndexerConfig {
             analytical_worker: false,
             skip_db_commit: false,
             use_v2: false,
+            embedded_db_path: None,
         }
     }
 }
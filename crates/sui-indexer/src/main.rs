@@ -8,6 +8,7 @@ use sui_indexer::errors::IndexerError;
 use sui_indexer::indexer_v2::IndexerV2;
 use sui_indexer::metrics::IndexerMetrics;
 use sui_indexer::start_prometheus_server;
+use sui_indexer::store::EmbeddedIndexerStoreV2;
 use sui_indexer::store::PgIndexerAnalyticalStore;
 use sui_indexer::store::PgIndexerStore;
 use sui_indexer::store::PgIndexerStoreV2;
@@ -38,6 +39,41 @@ async fn main() -> Result<(), IndexerError> {
 
     mysten_metrics::init_metrics(&registry);
 
+    if let Some(embedded_db_path) = &indexer_config.embedded_db_path {
+        if !indexer_config.use_v2 || indexer_config.analytical_worker {
+            return Err(IndexerError::NotSupportedError(
+                "The embedded database is only supported by the v2 fullnode sync and rpc server workers"
+                    .to_string(),
+            ));
+        }
+        info!("Use embedded database at {:?}", embedded_db_path);
+        if indexer_config.reset_db {
+            EmbeddedIndexerStoreV2::destroy(embedded_db_path)?;
+        }
+        // RocksDB only lets one process open the database, so the writer and the reader
+        // share the store when both run.
+        let store = EmbeddedIndexerStoreV2::new(embedded_db_path)?;
+        return match (
+            indexer_config.fullnode_sync_worker,
+            indexer_config.rpc_server_worker,
+        ) {
+            (true, true) => {
+                tokio::try_join!(
+                    IndexerV2::start_writer(&indexer_config, store.clone(), indexer_metrics),
+                    IndexerV2::start_reader_with_store(&indexer_config, &registry, store),
+                )?;
+                Ok(())
+            }
+            (true, false) => IndexerV2::start_writer(&indexer_config, store, indexer_metrics).await,
+            (false, true) => {
+                IndexerV2::start_reader_with_store(&indexer_config, &registry, store).await
+            }
+            (false, false) => Err(IndexerError::NotSupportedError(
+                "No worker is specified".to_string(),
+            )),
+        };
+    }
+
     let db_url = indexer_config.get_db_url().map_err(|e| {
         IndexerError::PgPoolConnectionError(format!(
             "Failed parsing database url with error {:?}",
@@ -2,11 +2,12 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
 use sui_types::display::DisplayVersionUpdatedEvent;
 
 use crate::schema_v2::display;
 
-#[derive(Queryable, Insertable, Debug, Clone)]
+#[derive(Queryable, Insertable, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[diesel(table_name = display)]
 pub struct StoredDisplay {
     pub object_type: String,
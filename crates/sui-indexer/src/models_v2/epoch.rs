@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use diesel::{Insertable, Queryable};
+use serde::{Deserialize, Serialize};
 
 use crate::errors::IndexerError;
 use crate::schema_v2::epochs;
@@ -9,7 +10,7 @@ use crate::types_v2::IndexedEpochInfo;
 use sui_json_rpc_types::{EndOfEpochInfo, EpochInfo};
 use sui_types::sui_system_state::sui_system_state_summary::SuiSystemStateSummary;
 
-#[derive(Queryable, Insertable, Debug, Clone, Default)]
+#[derive(Queryable, Insertable, Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 #[diesel(table_name = epochs)]
 pub struct StoredEpochInfo {
     pub epoch: i64,
@@ -7,6 +7,7 @@ use diesel::prelude::*;
 use move_bytecode_utils::module_cache::GetModule;
 use move_core_types::annotated_value::MoveStruct;
 use move_core_types::identifier::Identifier;
+use serde::{Deserialize, Serialize};
 
 use sui_json_rpc_types::{SuiEvent, SuiMoveStruct};
 use sui_types::base_types::{ObjectID, SuiAddress};
@@ -19,7 +20,9 @@ use crate::errors::IndexerError;
 use crate::schema_v2::events;
 use crate::types_v2::IndexedEvent;
 
-#[derive(Queryable, QueryableByName, Insertable, Debug, Clone)]
+#[derive(
+    Queryable, QueryableByName, Insertable, Debug, Clone, PartialEq, Serialize, Deserialize,
+)]
 #[diesel(table_name = events)]
 pub struct StoredEvent {
     #[diesel(sql_type = diesel::sql_types::BigInt)]
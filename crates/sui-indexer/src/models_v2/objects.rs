@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use sui_json_rpc::coin_api::parse_to_struct_tag;
 
@@ -39,7 +40,17 @@ pub struct ObjectRefColumn {
 // NOTE: please add updating statement like below in pg_indexer_store_v2.rs,
 // if new columns are added here:
 // objects::epoch.eq(excluded(objects::epoch))
-#[derive(Queryable, Insertable, Debug, Identifiable, Clone, QueryableByName)]
+#[derive(
+    Queryable,
+    Insertable,
+    Debug,
+    Identifiable,
+    Clone,
+    QueryableByName,
+    PartialEq,
+    Serialize,
+    Deserialize,
+)]
 #[diesel(table_name = objects, primary_key(object_id))]
 pub struct StoredObject {
     pub object_id: Vec<u8>,
@@ -60,7 +71,17 @@ pub struct StoredObject {
     pub df_object_id: Option<Vec<u8>>,
 }
 
-#[derive(Queryable, Insertable, Debug, Identifiable, Clone, QueryableByName)]
+#[derive(
+    Queryable,
+    Insertable,
+    Debug,
+    Identifiable,
+    Clone,
+    QueryableByName,
+    PartialEq,
+    Serialize,
+    Deserialize,
+)]
 #[diesel(table_name = objects_history, primary_key(object_id, object_version, checkpoint_sequence_number))]
 pub struct StoredHistoryObject {
     pub object_id: Vec<u8>,
@@ -140,6 +161,28 @@ impl From<StoredDeletedObject> for StoredDeletedHistoryObject {
     }
 }
 
+impl From<StoredDeletedObject> for StoredHistoryObject {
+    fn from(o: StoredDeletedObject) -> Self {
+        Self {
+            object_id: o.object_id,
+            object_version: o.object_version,
+            object_status: ObjectStatus::WrappedOrDeleted as i16,
+            object_digest: None,
+            checkpoint_sequence_number: o.checkpoint_sequence_number,
+            owner_type: None,
+            owner_id: None,
+            object_type: None,
+            serialized_object: None,
+            coin_type: None,
+            coin_balance: None,
+            df_kind: None,
+            df_name: None,
+            df_object_type: None,
+            df_object_id: None,
+        }
+    }
+}
+
 impl From<IndexedObject> for StoredObject {
     fn from(o: IndexedObject) -> Self {
         Self {
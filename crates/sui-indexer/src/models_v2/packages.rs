@@ -5,8 +5,9 @@ use crate::schema_v2::packages;
 use crate::types_v2::IndexedPackage;
 
 use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
 
-#[derive(Queryable, Insertable, Clone, Debug, Identifiable)]
+#[derive(Queryable, Insertable, Clone, Debug, Identifiable, Serialize, Deserialize)]
 #[diesel(table_name = packages, primary_key(package_id))]
 pub struct StoredPackage {
     pub package_id: Vec<u8>,
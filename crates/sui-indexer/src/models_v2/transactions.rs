@@ -1,6 +1,7 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
 
 use move_bytecode_utils::module_cache::GetModule;
 use sui_json_rpc_types::BalanceChange;
@@ -22,7 +23,9 @@ use crate::types_v2::IndexedObjectChange;
 use crate::types_v2::IndexedTransaction;
 use crate::types_v2::IndexerResult;
 
-#[derive(Clone, Debug, Queryable, Insertable, QueryableByName)]
+#[derive(
+    Clone, Debug, Queryable, Insertable, QueryableByName, PartialEq, Serialize, Deserialize,
+)]
 #[diesel(table_name = transactions)]
 pub struct StoredTransaction {
     pub tx_sequence_number: i64,
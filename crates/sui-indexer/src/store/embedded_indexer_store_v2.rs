@@ -0,0 +1,1434 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::ops::Bound;
+use std::path::Path;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use move_binary_format::CompiledModule;
+use move_bytecode_utils::module_cache::{GetModule, SyncModuleCache};
+use move_core_types::language_storage::{ModuleId, StructTag};
+use rocksdb::{BoundColumnFamily, Direction, IteratorMode, Options, WriteBatch, DB};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use tracing::info;
+
+use sui_json_rpc_types::{
+    AddressMetrics, Balance, Checkpoint, CheckpointId, Coin as SuiCoin, EpochInfo, EventFilter,
+    MoveCallMetrics, NetworkMetrics, SuiCoinMetadata, SuiEvent, SuiObjectDataFilter,
+    SuiTransactionBlockEffectsAPI, SuiTransactionBlockResponse, SuiTransactionBlockResponseOptions,
+    TransactionFilter,
+};
+use sui_types::balance::Supply;
+use sui_types::base_types::{ObjectID, SequenceNumber, SuiAddress, VersionNumber};
+use sui_types::coin::{CoinMetadata, TreasuryCap};
+use sui_types::committee::EpochId;
+use sui_types::digests::TransactionDigest;
+use sui_types::display::DisplayVersionUpdatedEvent;
+use sui_types::dynamic_field::DynamicFieldInfo;
+use sui_types::error::SuiError;
+use sui_types::event::EventID;
+use sui_types::is_system_package;
+use sui_types::move_package::MovePackage;
+use sui_types::object::{Object, ObjectRead};
+
+use crate::errors::IndexerError;
+use crate::handlers::{EpochToCommit, TransactionObjectChangesToCommit};
+use crate::indexer_reader::IndexerStoreReader;
+use crate::models_v2::checkpoints::StoredCheckpoint;
+use crate::models_v2::display::StoredDisplay;
+use crate::models_v2::epoch::StoredEpochInfo;
+use crate::models_v2::events::StoredEvent;
+use crate::models_v2::objects::{CoinBalance, StoredHistoryObject, StoredObject};
+use crate::models_v2::packages::StoredPackage;
+use crate::models_v2::transactions::StoredTransaction;
+use crate::store::module_resolver_v2::EmbeddedStoreModuleResolver;
+use crate::types_v2::{
+    IndexedCheckpoint, IndexedEvent, IndexedPackage, IndexedTransaction, IndexerResult, OwnerType,
+    TxIndex,
+};
+
+use super::pg_indexer_store_v2::{make_final_list_of_objects_to_commit, ObjectChangeToCommit};
+use super::IndexerStoreV2;
+
+const CHECKPOINTS_CF: &str = "checkpoints";
+const CHECKPOINT_DIGESTS_CF: &str = "checkpoint_digests";
+const TRANSACTIONS_CF: &str = "transactions";
+const TRANSACTION_DIGESTS_CF: &str = "transaction_digests";
+const TX_INDICES_CF: &str = "tx_indices";
+const EVENTS_CF: &str = "events";
+const OBJECTS_CF: &str = "objects";
+const OBJECTS_HISTORY_CF: &str = "objects_history";
+const PACKAGES_CF: &str = "packages";
+const EPOCHS_CF: &str = "epochs";
+const DISPLAY_CF: &str = "display";
+
+const COLUMN_FAMILIES: [&str; 11] = [
+    CHECKPOINTS_CF,
+    CHECKPOINT_DIGESTS_CF,
+    TRANSACTIONS_CF,
+    TRANSACTION_DIGESTS_CF,
+    TX_INDICES_CF,
+    EVENTS_CF,
+    OBJECTS_CF,
+    OBJECTS_HISTORY_CF,
+    PACKAGES_CF,
+    EPOCHS_CF,
+    DISPLAY_CF,
+];
+
+// Key prefixes of the tx_indices column family, one per Postgres tx_* table.
+const TX_SENDERS_PREFIX: u8 = 0;
+const TX_RECIPIENTS_PREFIX: u8 = 1;
+const TX_INPUT_OBJECTS_PREFIX: u8 = 2;
+const TX_CHANGED_OBJECTS_PREFIX: u8 = 3;
+const TX_CALLS_PREFIX: u8 = 4;
+
+/// An `IndexerStoreV2` backed by a local RocksDB instance, for running an indexer in dev and CI
+/// without a Postgres server. Rows are stored as BCS-encoded diesel models, keyed so that
+/// sequence numbers sort in order.
+///
+/// Only the tx_indices are indexed, so reads filtering objects or events scan their column
+/// family, which is fine for the size of dev and CI databases.
+#[derive(Clone)]
+pub struct EmbeddedIndexerStoreV2 {
+    db: Arc<DB>,
+    module_cache: Arc<SyncModuleCache<EmbeddedStoreModuleResolver>>,
+}
+
+impl EmbeddedIndexerStoreV2 {
+    pub fn new(path: &Path) -> Result<Self, IndexerError> {
+        let mut options = Options::default();
+        options.create_if_missing(true);
+        options.create_missing_column_families(true);
+        let db = Arc::new(DB::open_cf(&options, path, COLUMN_FAMILIES)?);
+        let module_cache = Arc::new(SyncModuleCache::new(EmbeddedStoreModuleResolver::new(
+            db.clone(),
+        )));
+        Ok(Self { db, module_cache })
+    }
+
+    /// Removes all indexed data at `path`, the embedded equivalent of `reset_database`.
+    pub fn destroy(path: &Path) -> Result<(), IndexerError> {
+        Ok(DB::destroy(&Options::default(), path)?)
+    }
+
+    fn cf(&self, name: &str) -> Result<Arc<BoundColumnFamily<'_>>, IndexerError> {
+        cf(&self.db, name)
+    }
+
+    fn get<T: DeserializeOwned>(
+        &self,
+        cf_name: &str,
+        key: impl AsRef<[u8]>,
+    ) -> Result<Option<T>, IndexerError> {
+        get(&self.db, cf_name, key)
+    }
+
+    fn last<T: DeserializeOwned>(&self, cf_name: &str) -> Result<Option<T>, IndexerError> {
+        self.db
+            .iterator_cf(&self.cf(cf_name)?, IteratorMode::End)
+            .next()
+            .transpose()?
+            .map(|(_, value)| decode(&value))
+            .transpose()
+    }
+
+    fn get_latest_tx_checkpoint_sequence_number(&self) -> Result<Option<u64>, IndexerError> {
+        Ok(self
+            .last::<StoredCheckpoint>(CHECKPOINTS_CF)?
+            .map(|checkpoint| checkpoint.sequence_number as u64))
+    }
+
+    fn get_stored_object(
+        &self,
+        object_id: &ObjectID,
+        version: Option<SequenceNumber>,
+    ) -> Result<Option<StoredObject>, IndexerError> {
+        let object = self.get::<StoredObject>(OBJECTS_CF, object_id)?;
+        Ok(object.filter(|o| version.map_or(true, |v| o.object_version == v.value() as i64)))
+    }
+
+    // Note: here we treat Deleted as NotExists too
+    fn get_object_read(
+        &self,
+        object_id: ObjectID,
+        version: Option<SequenceNumber>,
+    ) -> Result<ObjectRead, IndexerError> {
+        match self.get_stored_object(&object_id, version)? {
+            None => Ok(ObjectRead::NotExists(object_id)),
+            Some(obj) => obj.try_into_object_read(self.module_cache.as_ref()),
+        }
+    }
+
+    fn get_tx_sequence_number(
+        &self,
+        digest: &TransactionDigest,
+    ) -> Result<Option<u64>, IndexerError> {
+        self.get::<u64>(TRANSACTION_DIGESTS_CF, digest.inner())
+    }
+
+    /// Visits the rows of `cf_name` in key order, or in reverse key order if `descending`,
+    /// starting right after `cursor`, for as long as `f` returns true.
+    fn scan(
+        &self,
+        cf_name: &str,
+        cursor: Option<&[u8]>,
+        descending: bool,
+        mut f: impl FnMut(&[u8], &[u8]) -> Result<bool, IndexerError>,
+    ) -> Result<(), IndexerError> {
+        let mode = match (cursor, descending) {
+            (Some(cursor), false) => IteratorMode::From(cursor, Direction::Forward),
+            (Some(cursor), true) => IteratorMode::From(cursor, Direction::Reverse),
+            (None, false) => IteratorMode::Start,
+            (None, true) => IteratorMode::End,
+        };
+        for item in self.db.iterator_cf(&self.cf(cf_name)?, mode) {
+            let (key, value) = item?;
+            if Some(key.as_ref()) == cursor {
+                continue;
+            }
+            if !f(&key, &value)? {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Up to `limit` rows of `cf_name` after `cursor` that pass `filter`.
+    fn scan_page<T: DeserializeOwned>(
+        &self,
+        cf_name: &str,
+        cursor: Option<&[u8]>,
+        limit: usize,
+        descending: bool,
+        filter: impl Fn(&T) -> bool,
+    ) -> Result<Vec<T>, IndexerError> {
+        let mut rows = vec![];
+        if limit == 0 {
+            return Ok(rows);
+        }
+        self.scan(cf_name, cursor, descending, |_, value| {
+            let row = decode(value)?;
+            if filter(&row) {
+                rows.push(row);
+            }
+            Ok(rows.len() < limit)
+        })?;
+        Ok(rows)
+    }
+
+    /// Sequence numbers of the transactions indexed under `values` in the tx_indices of kind
+    /// `prefix`.
+    fn get_indexed_tx_sequence_numbers(
+        &self,
+        prefix: u8,
+        values: &[&[u8]],
+    ) -> Result<BTreeSet<u64>, IndexerError> {
+        let key_prefix = tx_index_key_prefix(prefix, values);
+        let mut tx_sequence_numbers = BTreeSet::new();
+        self.scan(TX_INDICES_CF, Some(&key_prefix), false, |key, _| {
+            if !key.starts_with(&key_prefix) {
+                return Ok(false);
+            }
+            tx_sequence_numbers.insert(tx_sequence_number_of_index_key(key)?);
+            Ok(true)
+        })?;
+        Ok(tx_sequence_numbers)
+    }
+
+    fn get_transaction_block_responses(
+        &self,
+        tx_sequence_numbers: impl IntoIterator<Item = u64>,
+        options: &SuiTransactionBlockResponseOptions,
+    ) -> Result<Vec<SuiTransactionBlockResponse>, IndexerError> {
+        let mut responses = vec![];
+        for tx_sequence_number in tx_sequence_numbers {
+            let Some(stored_tx) =
+                self.get::<StoredTransaction>(TRANSACTIONS_CF, tx_sequence_number.to_be_bytes())?
+            else {
+                continue;
+            };
+            responses.push(
+                stored_tx
+                    .try_into_sui_transaction_block_response(options, self.module_cache.as_ref())?,
+            );
+        }
+        Ok(responses)
+    }
+
+    /// The single object of type `object_type` created by the transaction that published
+    /// `package_id`, such as the `CoinMetadata` or the `TreasuryCap` of a coin.
+    fn get_single_object_from_package_publish(
+        &self,
+        package_id: ObjectID,
+        object_type: String,
+    ) -> Result<Option<Object>, IndexerError> {
+        let publish_tx_sequence_number = if is_system_package(package_id) {
+            Some(0)
+        } else {
+            match IndexerStoreReader::get_object(self, &package_id, None)? {
+                Some(package) => self.get_tx_sequence_number(&package.previous_transaction)?,
+                // The coin package does not exist.
+                None => None,
+            }
+        };
+        let Some(publish_tx_sequence_number) = publish_tx_sequence_number else {
+            return Ok(None);
+        };
+        let Some(publish_tx) = self
+            .get::<StoredTransaction>(TRANSACTIONS_CF, publish_tx_sequence_number.to_be_bytes())?
+        else {
+            return Ok(None);
+        };
+
+        let mut objects = vec![];
+        for created in publish_tx.try_into_sui_transaction_effects()?.created() {
+            if let Some(object) = self.get_stored_object(&created.object_id(), None)? {
+                if object.object_type.as_deref() == Some(object_type.as_str()) {
+                    objects.push(object);
+                }
+            }
+        }
+        if objects.len() > 1 {
+            // We expect there to be only one object of this type created by the package.
+            tracing::error!(
+                "There are more than one objects found for type {}",
+                object_type
+            );
+            return Ok(None);
+        }
+        objects.pop().map(Object::try_from).transpose()
+    }
+
+    fn persist_objects(
+        &self,
+        object_changes: Vec<TransactionObjectChangesToCommit>,
+    ) -> Result<(), IndexerError> {
+        let objects_cf = self.cf(OBJECTS_CF)?;
+        let mut batch = WriteBatch::default();
+        for object in make_final_list_of_objects_to_commit(object_changes) {
+            match object {
+                ObjectChangeToCommit::MutatedObject(o) => {
+                    batch.put_cf(&objects_cf, &o.object_id, encode(&o)?);
+                }
+                ObjectChangeToCommit::DeletedObject(o) => {
+                    batch.delete_cf(&objects_cf, &o.object_id);
+                }
+            }
+        }
+        let len = batch.len();
+        self.db.write(batch)?;
+        info!("Persisted {} objects", len);
+        Ok(())
+    }
+
+    fn persist_object_history(
+        &self,
+        object_changes: Vec<TransactionObjectChangesToCommit>,
+    ) -> Result<(), IndexerError> {
+        let objects_history_cf = self.cf(OBJECTS_HISTORY_CF)?;
+        let mut batch = WriteBatch::default();
+        for object in make_final_list_of_objects_to_commit(object_changes) {
+            let history_object = StoredHistoryObject::from(object);
+            let key = object_version_key(
+                &history_object.object_id,
+                history_object.object_version as u64,
+            );
+            batch.put_cf(&objects_history_cf, key, encode(&history_object)?);
+        }
+        let len = batch.len();
+        self.db.write(batch)?;
+        info!("Persisted {} objects history", len);
+        Ok(())
+    }
+
+    fn persist_checkpoints(&self, checkpoints: Vec<IndexedCheckpoint>) -> Result<(), IndexerError> {
+        let checkpoints_cf = self.cf(CHECKPOINTS_CF)?;
+        let checkpoint_digests_cf = self.cf(CHECKPOINT_DIGESTS_CF)?;
+        let mut batch = WriteBatch::default();
+        for checkpoint in &checkpoints {
+            let stored_checkpoint = StoredCheckpoint::from(checkpoint);
+            batch.put_cf(
+                &checkpoints_cf,
+                checkpoint.sequence_number.to_be_bytes(),
+                encode(&stored_checkpoint)?,
+            );
+            batch.put_cf(
+                &checkpoint_digests_cf,
+                checkpoint.checkpoint_digest.inner(),
+                encode(&checkpoint.sequence_number)?,
+            );
+        }
+        self.db.write(batch)?;
+        info!(
+            "Persisted {} checkpoints, last checkpoint: {:?}",
+            checkpoints.len(),
+            checkpoints.last().map(|c| c.sequence_number)
+        );
+        Ok(())
+    }
+
+    fn persist_transactions(
+        &self,
+        transactions: Vec<IndexedTransaction>,
+    ) -> Result<(), IndexerError> {
+        let transactions_cf = self.cf(TRANSACTIONS_CF)?;
+        let transaction_digests_cf = self.cf(TRANSACTION_DIGESTS_CF)?;
+        let mut batch = WriteBatch::default();
+        for transaction in &transactions {
+            batch.put_cf(
+                &transactions_cf,
+                transaction.tx_sequence_number.to_be_bytes(),
+                encode(&StoredTransaction::from(transaction))?,
+            );
+            batch.put_cf(
+                &transaction_digests_cf,
+                transaction.tx_digest.inner(),
+                encode(&transaction.tx_sequence_number)?,
+            );
+        }
+        self.db.write(batch)?;
+        info!("Persisted {} transactions", transactions.len());
+        Ok(())
+    }
+
+    fn persist_tx_indices(&self, indices: Vec<TxIndex>) -> Result<(), IndexerError> {
+        let tx_indices_cf = self.cf(TX_INDICES_CF)?;
+        let mut batch = WriteBatch::default();
+        for index in indices {
+            let (senders, recipients, input_objects, changed_objects, calls) = index.split();
+            for sender in senders {
+                let key = tx_index_key(
+                    TX_SENDERS_PREFIX,
+                    &[sender.sender.as_slice()],
+                    sender.tx_sequence_number,
+                );
+                batch.put_cf(&tx_indices_cf, key, b"");
+            }
+            for recipient in recipients {
+                let key = tx_index_key(
+                    TX_RECIPIENTS_PREFIX,
+                    &[recipient.recipient.as_slice()],
+                    recipient.tx_sequence_number,
+                );
+                batch.put_cf(&tx_indices_cf, key, b"");
+            }
+            for object in input_objects {
+                let key = tx_index_key(
+                    TX_INPUT_OBJECTS_PREFIX,
+                    &[object.object_id.as_slice()],
+                    object.tx_sequence_number,
+                );
+                batch.put_cf(&tx_indices_cf, key, b"");
+            }
+            for object in changed_objects {
+                let key = tx_index_key(
+                    TX_CHANGED_OBJECTS_PREFIX,
+                    &[object.object_id.as_slice()],
+                    object.tx_sequence_number,
+                );
+                batch.put_cf(&tx_indices_cf, key, b"");
+            }
+            for call in calls {
+                let key = tx_index_key(
+                    TX_CALLS_PREFIX,
+                    &[
+                        call.package.as_slice(),
+                        call.module.as_bytes(),
+                        call.func.as_bytes(),
+                    ],
+                    call.tx_sequence_number,
+                );
+                batch.put_cf(&tx_indices_cf, key, b"");
+            }
+        }
+        let len = batch.len();
+        self.db.write(batch)?;
+        info!("Persisted {} tx_indices rows", len);
+        Ok(())
+    }
+
+    fn persist_events(&self, events: Vec<IndexedEvent>) -> Result<(), IndexerError> {
+        let events_cf = self.cf(EVENTS_CF)?;
+        let mut batch = WriteBatch::default();
+        let len = events.len();
+        for event in events {
+            let key = event_key(event.tx_sequence_number, event.event_sequence_number);
+            batch.put_cf(&events_cf, key, encode(&StoredEvent::from(event))?);
+        }
+        self.db.write(batch)?;
+        info!("Persisted {} events", len);
+        Ok(())
+    }
+
+    fn persist_displays(
+        &self,
+        display_updates: BTreeMap<String, StoredDisplay>,
+    ) -> Result<(), IndexerError> {
+        let display_cf = self.cf(DISPLAY_CF)?;
+        let mut batch = WriteBatch::default();
+        for (object_type, display) in &display_updates {
+            batch.put_cf(&display_cf, object_type, encode(display)?);
+        }
+        self.db.write(batch)?;
+        Ok(())
+    }
+
+    fn persist_packages(&self, packages: Vec<IndexedPackage>) -> Result<(), IndexerError> {
+        let packages_cf = self.cf(PACKAGES_CF)?;
+        let mut batch = WriteBatch::default();
+        let len = packages.len();
+        for package in packages {
+            let stored_package = StoredPackage::from(package);
+            batch.put_cf(
+                &packages_cf,
+                &stored_package.package_id,
+                encode(&stored_package)?,
+            );
+        }
+        self.db.write(batch)?;
+        info!("Persisted {} packages", len);
+        Ok(())
+    }
+
+    fn persist_epoch(&self, epoch: EpochToCommit) -> Result<(), IndexerError> {
+        let epochs_cf = self.cf(EPOCHS_CF)?;
+        let mut batch = WriteBatch::default();
+        if let Some(last_epoch) = &epoch.last_epoch {
+            let last_epoch_id = last_epoch.epoch;
+            let end_info = StoredEpochInfo::from_epoch_end_info(last_epoch);
+            info!(last_epoch_id, "Persisting epoch end data: {:?}", end_info);
+            // Keep the epoch beginning info (validators, first_checkpoint_id, ...) if we
+            // have it and only fill in the epoch end info.
+            let stored_epoch =
+                match self.get::<StoredEpochInfo>(EPOCHS_CF, last_epoch_id.to_be_bytes())? {
+                    Some(stored_epoch) => StoredEpochInfo {
+                        epoch_total_transactions: end_info.epoch_total_transactions,
+                        last_checkpoint_id: end_info.last_checkpoint_id,
+                        epoch_end_timestamp: end_info.epoch_end_timestamp,
+                        storage_fund_reinvestment: end_info.storage_fund_reinvestment,
+                        storage_charge: end_info.storage_charge,
+                        storage_rebate: end_info.storage_rebate,
+                        stake_subsidy_amount: end_info.stake_subsidy_amount,
+                        total_gas_fees: end_info.total_gas_fees,
+                        total_stake_rewards_distributed: end_info.total_stake_rewards_distributed,
+                        leftover_storage_fund_inflow: end_info.leftover_storage_fund_inflow,
+                        epoch_commitments: end_info.epoch_commitments,
+                        ..stored_epoch
+                    },
+                    None => end_info,
+                };
+            batch.put_cf(
+                &epochs_cf,
+                last_epoch_id.to_be_bytes(),
+                encode(&stored_epoch)?,
+            );
+        }
+        let epoch_id = epoch.new_epoch.epoch;
+        if self
+            .get::<StoredEpochInfo>(EPOCHS_CF, epoch_id.to_be_bytes())?
+            .is_none()
+        {
+            info!(epoch_id, "Persisting epoch beginning info");
+            let new_epoch = StoredEpochInfo::from_epoch_beginning_info(&epoch.new_epoch);
+            batch.put_cf(&epochs_cf, epoch_id.to_be_bytes(), encode(&new_epoch)?);
+        }
+        self.db.write(batch)?;
+        Ok(())
+    }
+
+    fn get_network_total_transactions_by_end_of_epoch(
+        &self,
+        epoch: u64,
+    ) -> Result<u64, IndexerError> {
+        // Checkpoints are keyed by sequence number, so the first checkpoint of `epoch` found
+        // when scanning backwards is its last one.
+        for item in self
+            .db
+            .iterator_cf(&self.cf(CHECKPOINTS_CF)?, IteratorMode::End)
+        {
+            let (_, value) = item?;
+            let checkpoint: StoredCheckpoint = decode(&value)?;
+            if checkpoint.epoch as u64 == epoch {
+                return Ok(checkpoint.network_total_transactions as u64);
+            }
+            if (checkpoint.epoch as u64) < epoch {
+                break;
+            }
+        }
+        Ok(0)
+    }
+
+    async fn execute_in_blocking_worker<F, R>(&self, f: F) -> Result<R, IndexerError>
+    where
+        F: FnOnce(Self) -> Result<R, IndexerError> + Send + 'static,
+        R: Send + 'static,
+    {
+        let this = self.clone();
+        let current_span = tracing::Span::current();
+        tokio::task::spawn_blocking(move || {
+            let _guard = current_span.enter();
+            f(this)
+        })
+        .await
+        .map_err(Into::into)
+        .and_then(std::convert::identity)
+    }
+}
+
+#[async_trait]
+impl IndexerStoreV2 for EmbeddedIndexerStoreV2 {
+    type ModuleCache = SyncModuleCache<EmbeddedStoreModuleResolver>;
+
+    async fn get_latest_tx_checkpoint_sequence_number(&self) -> Result<Option<u64>, IndexerError> {
+        self.execute_in_blocking_worker(|this| this.get_latest_tx_checkpoint_sequence_number())
+            .await
+    }
+
+    async fn get_object_read(
+        &self,
+        object_id: ObjectID,
+        version: Option<SequenceNumber>,
+    ) -> Result<ObjectRead, IndexerError> {
+        self.execute_in_blocking_worker(move |this| this.get_object_read(object_id, version))
+            .await
+    }
+
+    async fn persist_objects(
+        &self,
+        object_changes: Vec<TransactionObjectChangesToCommit>,
+    ) -> Result<(), IndexerError> {
+        self.execute_in_blocking_worker(move |this| this.persist_objects(object_changes))
+            .await
+    }
+
+    async fn persist_object_history(
+        &self,
+        object_changes: Vec<TransactionObjectChangesToCommit>,
+    ) -> Result<(), IndexerError> {
+        self.execute_in_blocking_worker(move |this| this.persist_object_history(object_changes))
+            .await
+    }
+
+    async fn persist_checkpoints(
+        &self,
+        checkpoints: Vec<IndexedCheckpoint>,
+    ) -> Result<(), IndexerError> {
+        self.execute_in_blocking_worker(move |this| this.persist_checkpoints(checkpoints))
+            .await
+    }
+
+    async fn persist_transactions(
+        &self,
+        transactions: Vec<IndexedTransaction>,
+    ) -> Result<(), IndexerError> {
+        self.execute_in_blocking_worker(move |this| this.persist_transactions(transactions))
+            .await
+    }
+
+    async fn persist_tx_indices(&self, indices: Vec<TxIndex>) -> Result<(), IndexerError> {
+        self.execute_in_blocking_worker(move |this| this.persist_tx_indices(indices))
+            .await
+    }
+
+    async fn persist_events(&self, events: Vec<IndexedEvent>) -> Result<(), IndexerError> {
+        self.execute_in_blocking_worker(move |this| this.persist_events(events))
+            .await
+    }
+
+    async fn persist_displays(
+        &self,
+        display_updates: BTreeMap<String, StoredDisplay>,
+    ) -> Result<(), IndexerError> {
+        self.execute_in_blocking_worker(move |this| this.persist_displays(display_updates))
+            .await
+    }
+
+    async fn persist_packages(&self, packages: Vec<IndexedPackage>) -> Result<(), IndexerError> {
+        self.execute_in_blocking_worker(move |this| this.persist_packages(packages))
+            .await
+    }
+
+    async fn persist_epoch(&self, epoch: EpochToCommit) -> Result<(), IndexerError> {
+        self.execute_in_blocking_worker(move |this| this.persist_epoch(epoch))
+            .await
+    }
+
+    async fn advance_epoch(&self, _epoch: EpochToCommit) -> Result<(), IndexerError> {
+        // There are no epoch partitions to maintain in the embedded database.
+        Ok(())
+    }
+
+    async fn get_network_total_transactions_by_end_of_epoch(
+        &self,
+        epoch: u64,
+    ) -> Result<u64, IndexerError> {
+        self.execute_in_blocking_worker(move |this| {
+            this.get_network_total_transactions_by_end_of_epoch(epoch)
+        })
+        .await
+    }
+
+    fn module_cache(&self) -> Arc<Self::ModuleCache> {
+        self.module_cache.clone()
+    }
+}
+
+#[async_trait]
+impl IndexerStoreReader for EmbeddedIndexerStoreV2 {
+    async fn spawn_blocking<F, R, E>(&self, f: F) -> Result<R, E>
+    where
+        F: FnOnce(Self) -> Result<R, E> + Send + 'static,
+        R: Send + 'static,
+        E: Send + 'static,
+    {
+        let this = self.clone();
+        let current_span = tracing::Span::current();
+        tokio::task::spawn_blocking(move || {
+            let _guard = current_span.enter();
+            f(this)
+        })
+        .await
+        .expect("propagate any panics")
+    }
+
+    fn get_checkpoint(
+        &self,
+        checkpoint_id: CheckpointId,
+    ) -> Result<Option<Checkpoint>, IndexerError> {
+        let sequence_number = match checkpoint_id {
+            CheckpointId::SequenceNumber(seq) => seq,
+            CheckpointId::Digest(digest) => {
+                match self.get::<u64>(CHECKPOINT_DIGESTS_CF, digest.inner())? {
+                    Some(seq) => seq,
+                    None => return Ok(None),
+                }
+            }
+        };
+        self.get::<StoredCheckpoint>(CHECKPOINTS_CF, sequence_number.to_be_bytes())?
+            .map(Checkpoint::try_from)
+            .transpose()
+    }
+
+    fn get_latest_checkpoint(&self) -> Result<Checkpoint, IndexerError> {
+        let stored_checkpoint = self
+            .last::<StoredCheckpoint>(CHECKPOINTS_CF)?
+            .ok_or_else(|| IndexerError::EmbeddedDbError("No checkpoint indexed yet".into()))?;
+        Checkpoint::try_from(stored_checkpoint)
+    }
+
+    fn get_checkpoints(
+        &self,
+        cursor: Option<u64>,
+        limit: usize,
+        descending_order: bool,
+    ) -> Result<Vec<Checkpoint>, IndexerError> {
+        let cursor = cursor.map(u64::to_be_bytes);
+        self.scan_page::<StoredCheckpoint>(
+            CHECKPOINTS_CF,
+            cursor.as_ref().map(|c| c.as_slice()),
+            limit,
+            descending_order,
+            |_| true,
+        )?
+        .into_iter()
+        .map(Checkpoint::try_from)
+        .collect()
+    }
+
+    fn get_epoch_info(&self, epoch: Option<EpochId>) -> Result<Option<EpochInfo>, IndexerError> {
+        let stored_epoch = match epoch {
+            Some(epoch) => self.get::<StoredEpochInfo>(EPOCHS_CF, epoch.to_be_bytes())?,
+            None => self.last::<StoredEpochInfo>(EPOCHS_CF)?,
+        };
+        stored_epoch.map(EpochInfo::try_from).transpose()
+    }
+
+    fn get_latest_epoch_info_from_db(&self) -> Result<StoredEpochInfo, IndexerError> {
+        self.last::<StoredEpochInfo>(EPOCHS_CF)?
+            .ok_or_else(|| IndexerError::EmbeddedDbError("No epoch indexed yet".into()))
+    }
+
+    fn get_epochs(
+        &self,
+        cursor: Option<u64>,
+        limit: usize,
+        descending_order: bool,
+    ) -> Result<Vec<EpochInfo>, IndexerError> {
+        let cursor = cursor.map(u64::to_be_bytes);
+        self.scan_page::<StoredEpochInfo>(
+            EPOCHS_CF,
+            cursor.as_ref().map(|c| c.as_slice()),
+            limit,
+            descending_order,
+            |_| true,
+        )?
+        .into_iter()
+        .map(EpochInfo::try_from)
+        .collect()
+    }
+
+    fn get_object(
+        &self,
+        object_id: &ObjectID,
+        version: Option<VersionNumber>,
+    ) -> Result<Option<Object>, IndexerError> {
+        let Some(version) = version else {
+            return self
+                .get_stored_object(object_id, None)?
+                .map(Object::try_from)
+                .transpose();
+        };
+        let key = object_version_key(object_id.as_ref(), version.value());
+        let Some(history_object) = self.get::<StoredHistoryObject>(OBJECTS_HISTORY_CF, key)? else {
+            return Ok(None);
+        };
+        history_object
+            .serialized_object
+            .map(|bytes| decode::<Object>(&bytes))
+            .transpose()
+    }
+
+    fn get_object_read(&self, object_id: &ObjectID) -> Result<ObjectRead, IndexerError> {
+        EmbeddedIndexerStoreV2::get_object_read(self, *object_id, None)
+    }
+
+    fn multi_get_objects(
+        &self,
+        object_ids: Vec<ObjectID>,
+    ) -> Result<Vec<StoredObject>, IndexerError> {
+        let mut objects = vec![];
+        for object_id in object_ids {
+            if let Some(object) = self.get_stored_object(&object_id, None)? {
+                objects.push(object);
+            }
+        }
+        Ok(objects)
+    }
+
+    fn get_owned_objects(
+        &self,
+        address: SuiAddress,
+        filter: Option<SuiObjectDataFilter>,
+        cursor: Option<ObjectID>,
+        limit: usize,
+    ) -> Result<Vec<StoredObject>, IndexerError> {
+        let (included_types, excluded_types) = object_type_filter(filter)?;
+        let owner = address.to_vec();
+        let cursor = cursor.map(|cursor| cursor.to_vec());
+        self.scan_page(
+            OBJECTS_CF,
+            cursor.as_deref(),
+            limit,
+            false,
+            |o: &StoredObject| {
+                let object_type = o.object_type.as_deref().unwrap_or_default();
+                is_owned_by(o, OwnerType::Address, &owner)
+                    && included_types.as_ref().map_or(true, |types| {
+                        types.iter().any(|t| object_type.starts_with(t.as_str()))
+                    })
+                    && !excluded_types
+                        .iter()
+                        .any(|t| object_type.starts_with(t.as_str()))
+            },
+        )
+    }
+
+    fn get_package(&self, package_id: &ObjectID) -> Result<Option<MovePackage>, IndexerError> {
+        get_embedded_package(&self.db, package_id)
+    }
+
+    fn multi_get_transaction_block_response(
+        &self,
+        digests: &[TransactionDigest],
+        options: SuiTransactionBlockResponseOptions,
+    ) -> Result<Vec<SuiTransactionBlockResponse>, IndexerError> {
+        let mut tx_sequence_numbers = vec![];
+        for digest in digests {
+            if let Some(tx_sequence_number) = self.get_tx_sequence_number(digest)? {
+                tx_sequence_numbers.push(tx_sequence_number);
+            }
+        }
+        self.get_transaction_block_responses(tx_sequence_numbers, &options)
+    }
+
+    fn query_transaction_blocks(
+        &self,
+        filter: Option<TransactionFilter>,
+        options: SuiTransactionBlockResponseOptions,
+        cursor: Option<TransactionDigest>,
+        limit: usize,
+        is_descending: bool,
+    ) -> IndexerResult<Vec<SuiTransactionBlockResponse>> {
+        let cursor = cursor
+            .map(|digest| {
+                self.get_tx_sequence_number(&digest)?.ok_or_else(|| {
+                    IndexerError::InvalidArgumentError(format!(
+                        "Cursor transaction {digest} not found"
+                    ))
+                })
+            })
+            .transpose()?;
+        let tx_sequence_numbers = match filter {
+            None => {
+                let cursor = cursor.map(u64::to_be_bytes);
+                let stored_txes = self.scan_page::<StoredTransaction>(
+                    TRANSACTIONS_CF,
+                    cursor.as_ref().map(|c| c.as_slice()),
+                    limit,
+                    is_descending,
+                    |_| true,
+                )?;
+                return stored_txes
+                    .into_iter()
+                    .map(|stored_tx| {
+                        stored_tx.try_into_sui_transaction_block_response(
+                            &options,
+                            self.module_cache.as_ref(),
+                        )
+                    })
+                    .collect();
+            }
+            Some(TransactionFilter::Checkpoint(seq)) => {
+                let Some(checkpoint) =
+                    self.get::<StoredCheckpoint>(CHECKPOINTS_CF, seq.to_be_bytes())?
+                else {
+                    return Ok(vec![]);
+                };
+                let end = checkpoint.network_total_transactions as u64;
+                let start = end - checkpoint.tx_digests.len() as u64;
+                (start..end).collect()
+            }
+            Some(TransactionFilter::MoveFunction {
+                package,
+                module,
+                function,
+            }) => match (module, function) {
+                (Some(module), Some(function)) => self.get_indexed_tx_sequence_numbers(
+                    TX_CALLS_PREFIX,
+                    &[package.as_ref(), module.as_bytes(), function.as_bytes()],
+                )?,
+                (Some(module), None) => self.get_indexed_tx_sequence_numbers(
+                    TX_CALLS_PREFIX,
+                    &[package.as_ref(), module.as_bytes()],
+                )?,
+                (None, Some(_)) => {
+                    return Err(IndexerError::InvalidArgumentError(
+                        "Function cannot be present wihtout Module.".into(),
+                    ));
+                }
+                (None, None) => {
+                    self.get_indexed_tx_sequence_numbers(TX_CALLS_PREFIX, &[package.as_ref()])?
+                }
+            },
+            Some(TransactionFilter::InputObject(object_id)) => self
+                .get_indexed_tx_sequence_numbers(TX_INPUT_OBJECTS_PREFIX, &[object_id.as_ref()])?,
+            Some(TransactionFilter::ChangedObject(object_id)) => self
+                .get_indexed_tx_sequence_numbers(
+                    TX_CHANGED_OBJECTS_PREFIX,
+                    &[object_id.as_ref()],
+                )?,
+            Some(TransactionFilter::FromAddress(from_address)) => {
+                self.get_indexed_tx_sequence_numbers(TX_SENDERS_PREFIX, &[from_address.as_ref()])?
+            }
+            Some(TransactionFilter::ToAddress(to_address)) => {
+                self.get_indexed_tx_sequence_numbers(TX_RECIPIENTS_PREFIX, &[to_address.as_ref()])?
+            }
+            Some(TransactionFilter::FromAndToAddress { from, to }) => {
+                let sent =
+                    self.get_indexed_tx_sequence_numbers(TX_SENDERS_PREFIX, &[from.as_ref()])?;
+                let received =
+                    self.get_indexed_tx_sequence_numbers(TX_RECIPIENTS_PREFIX, &[to.as_ref()])?;
+                sent.intersection(&received).copied().collect()
+            }
+            Some(TransactionFilter::FromOrToAddress { addr }) => {
+                let mut tx_sequence_numbers =
+                    self.get_indexed_tx_sequence_numbers(TX_SENDERS_PREFIX, &[addr.as_ref()])?;
+                tx_sequence_numbers.extend(
+                    self.get_indexed_tx_sequence_numbers(TX_RECIPIENTS_PREFIX, &[addr.as_ref()])?,
+                );
+                tx_sequence_numbers
+            }
+            Some(
+                TransactionFilter::TransactionKind(_) | TransactionFilter::TransactionKindIn(_),
+            ) => {
+                return Err(IndexerError::NotSupportedError(
+                    "TransactionKind filter is not supported.".into(),
+                ));
+            }
+        };
+
+        let page = page_sequence_numbers(&tx_sequence_numbers, cursor, limit, is_descending);
+        self.get_transaction_block_responses(page, &options)
+    }
+
+    fn get_transaction_events(
+        &self,
+        digest: TransactionDigest,
+    ) -> Result<Vec<SuiEvent>, IndexerError> {
+        let Some(tx_sequence_number) = self.get_tx_sequence_number(&digest)? else {
+            return Ok(vec![]);
+        };
+        let prefix = tx_sequence_number.to_be_bytes();
+        let mut events = vec![];
+        for item in self.db.iterator_cf(
+            &self.cf(EVENTS_CF)?,
+            IteratorMode::From(&prefix, Direction::Forward),
+        ) {
+            let (key, value) = item?;
+            if !key.starts_with(&prefix) {
+                break;
+            }
+            let stored_event: StoredEvent = decode(&value)?;
+            events.push(stored_event.try_into_sui_event(self.module_cache.as_ref())?);
+        }
+        Ok(events)
+    }
+
+    fn query_events(
+        &self,
+        filter: EventFilter,
+        cursor: Option<EventID>,
+        limit: usize,
+        descending_order: bool,
+    ) -> IndexerResult<Vec<SuiEvent>> {
+        if let EventFilter::Transaction(tx_digest) = filter {
+            if cursor.map_or(false, |cursor| cursor.tx_digest != tx_digest) {
+                return Err(IndexerError::InvalidArgumentError(
+                    "Cursor tx_digest does not match the tx_digest in the query.".into(),
+                ));
+            }
+            let mut events = self.get_transaction_events(tx_digest)?;
+            if let Some(cursor) = cursor {
+                events.retain(|event| {
+                    if descending_order {
+                        event.id.event_seq < cursor.event_seq
+                    } else {
+                        event.id.event_seq > cursor.event_seq
+                    }
+                });
+            }
+            if descending_order {
+                events.reverse();
+            }
+            events.truncate(limit);
+            return Ok(events);
+        }
+        if matches!(
+            filter,
+            EventFilter::MoveEventField { .. }
+                | EventFilter::All(_)
+                | EventFilter::Any(_)
+                | EventFilter::And(_, _)
+                | EventFilter::Or(_, _)
+                | EventFilter::TimeRange { .. }
+        ) {
+            return Err(IndexerError::NotSupportedError(
+                "This type of EventFilter is not supported.".into(),
+            ));
+        }
+
+        let cursor = cursor
+            .map(
+                |EventID {
+                     tx_digest,
+                     event_seq,
+                 }| {
+                    let tx_sequence_number =
+                        self.get_tx_sequence_number(&tx_digest)?.ok_or_else(|| {
+                            IndexerError::InvalidArgumentError(format!(
+                                "Cursor transaction {tx_digest} not found"
+                            ))
+                        })?;
+                    Ok::<_, IndexerError>(event_key(tx_sequence_number, event_seq))
+                },
+            )
+            .transpose()?;
+        let stored_events = self.scan_page(
+            EVENTS_CF,
+            cursor.as_deref(),
+            limit,
+            descending_order,
+            |event: &StoredEvent| event_matches(&filter, event),
+        )?;
+        stored_events
+            .into_iter()
+            .map(|event| event.try_into_sui_event(self.module_cache.as_ref()))
+            .collect()
+    }
+
+    fn get_dynamic_fields(
+        &self,
+        parent_object_id: ObjectID,
+        cursor: Option<ObjectID>,
+        limit: usize,
+    ) -> Result<Vec<DynamicFieldInfo>, IndexerError> {
+        let mut dynamic_fields = vec![];
+        for object in self.get_dynamic_fields_raw(parent_object_id, cursor, limit)? {
+            let df_object_id = object.df_object_id.clone().ok_or_else(|| {
+                IndexerError::PersistentStorageDataCorruptionError(format!(
+                    "Dynamic field has empty df_object_id column for parent object {}",
+                    parent_object_id
+                ))
+            })?;
+            let is_dynamic_object_field = df_object_id != object.object_id;
+            let mut dynamic_field =
+                object.try_into_expectant_dynamic_field_info(self.module_cache.as_ref())?;
+            // The version and digest of a dynamic object field are the ones of the object.
+            if is_dynamic_object_field {
+                if let Some(df_object) = self.get::<StoredObject>(OBJECTS_CF, &df_object_id)? {
+                    let (_, version, digest) = df_object.get_object_ref()?;
+                    dynamic_field.version = version;
+                    dynamic_field.digest = digest;
+                }
+            }
+            dynamic_fields.push(dynamic_field);
+        }
+        Ok(dynamic_fields)
+    }
+
+    fn get_dynamic_fields_raw(
+        &self,
+        parent_object_id: ObjectID,
+        cursor: Option<ObjectID>,
+        limit: usize,
+    ) -> Result<Vec<StoredObject>, IndexerError> {
+        let parent = parent_object_id.to_vec();
+        let cursor = cursor.map(|cursor| cursor.to_vec());
+        self.scan_page(
+            OBJECTS_CF,
+            cursor.as_deref(),
+            limit,
+            false,
+            |o: &StoredObject| is_owned_by(o, OwnerType::Object, &parent),
+        )
+    }
+
+    fn get_display_update_event(
+        &self,
+        object_type: String,
+    ) -> Result<Option<DisplayVersionUpdatedEvent>, IndexerError> {
+        let Some(stored_display) = self.get::<StoredDisplay>(DISPLAY_CF, object_type)? else {
+            return Ok(None);
+        };
+        Ok(Some(stored_display.to_display_update_event()?))
+    }
+
+    fn get_owned_coins(
+        &self,
+        owner: SuiAddress,
+        coin_type: Option<String>,
+        cursor: ObjectID,
+        limit: usize,
+    ) -> Result<Vec<SuiCoin>, IndexerError> {
+        let owner = owner.to_vec();
+        let cursor = cursor.to_vec();
+        let mut coins = vec![];
+        self.scan(OBJECTS_CF, Some(&cursor), false, |_, value| {
+            let object: StoredObject = decode(value)?;
+            if is_owned_by(&object, OwnerType::Address, &owner)
+                && object.coin_type.is_some()
+                && (coin_type.is_none() || object.coin_type == coin_type)
+            {
+                coins.push(object);
+            }
+            Ok(true)
+        })?;
+        coins.sort_by(|a, b| (&a.coin_type, &a.object_id).cmp(&(&b.coin_type, &b.object_id)));
+        coins
+            .into_iter()
+            .take(limit)
+            .map(SuiCoin::try_from)
+            .collect()
+    }
+
+    fn get_coin_balances(
+        &self,
+        owner: SuiAddress,
+        coin_type: Option<String>,
+    ) -> Result<Vec<Balance>, IndexerError> {
+        let owner = owner.to_vec();
+        // Number of coins and total balance per coin type.
+        let mut balances = BTreeMap::<String, (i64, i64)>::new();
+        self.scan(OBJECTS_CF, None, false, |_, value| {
+            let object: StoredObject = decode(value)?;
+            if !is_owned_by(&object, OwnerType::Address, &owner) {
+                return Ok(true);
+            }
+            if let Some(object_coin_type) = object.coin_type {
+                if coin_type.is_none() || coin_type.as_ref() == Some(&object_coin_type) {
+                    let (coin_num, coin_balance) = balances.entry(object_coin_type).or_default();
+                    *coin_num += 1;
+                    *coin_balance += object.coin_balance.unwrap_or_default();
+                }
+            }
+            Ok(true)
+        })?;
+        balances
+            .into_iter()
+            .map(|(coin_type, (coin_num, coin_balance))| {
+                Balance::try_from(CoinBalance {
+                    coin_type,
+                    coin_num,
+                    coin_balance,
+                })
+            })
+            .collect()
+    }
+
+    fn get_coin_metadata(
+        &self,
+        coin_struct: StructTag,
+    ) -> Result<Option<SuiCoinMetadata>, IndexerError> {
+        let package_id = coin_struct.address.into();
+        let coin_metadata_type =
+            CoinMetadata::type_(coin_struct).to_canonical_string(/* with_prefix */ true);
+        let metadata_object =
+            self.get_single_object_from_package_publish(package_id, coin_metadata_type)?;
+        Ok(metadata_object.and_then(|v| SuiCoinMetadata::try_from(v).ok()))
+    }
+
+    fn get_total_supply(&self, coin_struct: StructTag) -> Result<Supply, IndexerError> {
+        let package_id = coin_struct.address.into();
+        let treasury_cap_type =
+            TreasuryCap::type_(coin_struct).to_canonical_string(/* with_prefix */ true);
+        let treasury_cap_object = self
+            .get_single_object_from_package_publish(package_id, treasury_cap_type.clone())?
+            .ok_or(IndexerError::GenericError(format!(
+                "Cannot find treasury cap for type {}",
+                treasury_cap_type
+            )))?;
+        Ok(TreasuryCap::try_from(treasury_cap_object)?.total_supply)
+    }
+
+    fn get_latest_network_metrics(&self) -> IndexerResult<NetworkMetrics> {
+        Err(metrics_not_supported())
+    }
+
+    fn get_latest_move_call_metrics(&self) -> IndexerResult<MoveCallMetrics> {
+        Err(metrics_not_supported())
+    }
+
+    fn get_latest_address_metrics(&self) -> IndexerResult<AddressMetrics> {
+        Err(metrics_not_supported())
+    }
+
+    fn get_checkpoint_address_metrics(
+        &self,
+        _checkpoint_seq: u64,
+    ) -> IndexerResult<AddressMetrics> {
+        Err(metrics_not_supported())
+    }
+
+    fn get_all_epoch_address_metrics(
+        &self,
+        _descending_order: Option<bool>,
+    ) -> IndexerResult<Vec<AddressMetrics>> {
+        Err(metrics_not_supported())
+    }
+}
+
+impl GetModule for EmbeddedIndexerStoreV2 {
+    type Error = anyhow::Error;
+    type Item = Arc<CompiledModule>;
+
+    fn get_module_by_id(&self, id: &ModuleId) -> Result<Option<Arc<CompiledModule>>, Self::Error> {
+        self.module_cache.get_module_by_id(id)
+    }
+}
+
+impl sui_types::storage::ObjectStore for EmbeddedIndexerStoreV2 {
+    fn get_object(&self, object_id: &ObjectID) -> Result<Option<Object>, SuiError> {
+        IndexerStoreReader::get_object(self, object_id, None)
+            .map_err(|e| SuiError::GenericStorageError(e.to_string()))
+    }
+
+    fn get_object_by_key(
+        &self,
+        object_id: &ObjectID,
+        version: VersionNumber,
+    ) -> Result<Option<Object>, SuiError> {
+        IndexerStoreReader::get_object(self, object_id, Some(version))
+            .map_err(|e| SuiError::GenericStorageError(e.to_string()))
+    }
+}
+
+pub(crate) fn get_embedded_package(
+    db: &DB,
+    package_id: &ObjectID,
+) -> Result<Option<MovePackage>, IndexerError> {
+    let Some(stored_package) = get::<StoredPackage>(db, PACKAGES_CF, package_id)? else {
+        return Ok(None);
+    };
+    let move_package =
+        bcs::from_bytes::<MovePackage>(&stored_package.move_package).map_err(|e| {
+            IndexerError::PersistentStorageDataCorruptionError(format!(
+                "Error deserializing move package. Error: {}",
+                e
+            ))
+        })?;
+    Ok(Some(move_package))
+}
+
+fn cf<'a>(db: &'a DB, name: &str) -> Result<Arc<BoundColumnFamily<'a>>, IndexerError> {
+    db.cf_handle(name)
+        .ok_or_else(|| IndexerError::EmbeddedDbError(format!("Missing column family {}", name)))
+}
+
+fn get<T: DeserializeOwned>(
+    db: &DB,
+    cf_name: &str,
+    key: impl AsRef<[u8]>,
+) -> Result<Option<T>, IndexerError> {
+    db.get_cf(&cf(db, cf_name)?, key)?
+        .map(|value| decode(&value))
+        .transpose()
+}
+
+fn encode<T: Serialize>(value: &T) -> Result<Vec<u8>, IndexerError> {
+    bcs::to_bytes(value).map_err(|e| IndexerError::SerdeError(e.to_string()))
+}
+
+fn decode<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, IndexerError> {
+    bcs::from_bytes(bytes).map_err(|e| {
+        IndexerError::PersistentStorageDataCorruptionError(format!(
+            "Failed to decode value from the embedded database: {}",
+            e
+        ))
+    })
+}
+
+fn object_version_key(object_id: &[u8], version: u64) -> Vec<u8> {
+    let mut key = object_id.to_vec();
+    key.extend_from_slice(&version.to_be_bytes());
+    key
+}
+
+fn event_key(tx_sequence_number: u64, event_sequence_number: u64) -> Vec<u8> {
+    let mut key = tx_sequence_number.to_be_bytes().to_vec();
+    key.extend_from_slice(&event_sequence_number.to_be_bytes());
+    key
+}
+
+/// Key of a tx_indices row: the index kind, the indexed values (each null terminated so
+/// variable length values don't run into each other), then the tx sequence number.
+fn tx_index_key(prefix: u8, values: &[&[u8]], tx_sequence_number: i64) -> Vec<u8> {
+    let mut key = tx_index_key_prefix(prefix, values);
+    key.extend_from_slice(&(tx_sequence_number as u64).to_be_bytes());
+    key
+}
+
+/// Common prefix of the keys of the tx_indices rows of kind `prefix` for `values`.
+fn tx_index_key_prefix(prefix: u8, values: &[&[u8]]) -> Vec<u8> {
+    let mut key = vec![prefix];
+    for value in values {
+        key.extend_from_slice(value);
+        key.push(0);
+    }
+    key
+}
+
+fn tx_sequence_number_of_index_key(key: &[u8]) -> Result<u64, IndexerError> {
+    key.len()
+        .checked_sub(8)
+        .and_then(|start| key[start..].try_into().ok())
+        .map(u64::from_be_bytes)
+        .ok_or_else(|| {
+            IndexerError::PersistentStorageDataCorruptionError(format!(
+                "Malformed tx_indices key {:?}",
+                key
+            ))
+        })
+}
+
+/// The first `limit` of `sequence_numbers` after `cursor`, in ascending or descending order.
+fn page_sequence_numbers(
+    sequence_numbers: &BTreeSet<u64>,
+    cursor: Option<u64>,
+    limit: usize,
+    descending: bool,
+) -> Vec<u64> {
+    let after_cursor = cursor.map_or(Bound::Unbounded, Bound::Excluded);
+    if descending {
+        sequence_numbers
+            .range((Bound::Unbounded, after_cursor))
+            .rev()
+            .take(limit)
+            .copied()
+            .collect()
+    } else {
+        sequence_numbers
+            .range((after_cursor, Bound::Unbounded))
+            .take(limit)
+            .copied()
+            .collect()
+    }
+}
+
+fn is_owned_by(object: &StoredObject, owner_type: OwnerType, owner_id: &[u8]) -> bool {
+    object.owner_type == owner_type as i16 && object.owner_id.as_deref() == Some(owner_id)
+}
+
+/// The object type prefixes an owned objects filter requires, if any, and the ones it
+/// excludes. Like `IndexerReader`, only struct type filters and their `MatchAny` and
+/// `MatchNone` combinations are supported.
+fn object_type_filter(
+    filter: Option<SuiObjectDataFilter>,
+) -> Result<(Option<Vec<String>>, Vec<String>), IndexerError> {
+    fn struct_type(filter: &SuiObjectDataFilter) -> Result<String, IndexerError> {
+        match filter {
+            SuiObjectDataFilter::StructType(struct_tag) => {
+                Ok(struct_tag.to_canonical_string(/* with_prefix */ true))
+            }
+            _ => Err(IndexerError::InvalidArgumentError(
+                "Invalid filter type. Only struct, MatchAny and MatchNone of struct filters are supported.".into(),
+            )),
+        }
+    }
+
+    Ok(match filter {
+        None => (None, vec![]),
+        Some(SuiObjectDataFilter::MatchAny(filters)) => (
+            Some(filters.iter().map(struct_type).collect::<Result<_, _>>()?),
+            vec![],
+        ),
+        Some(SuiObjectDataFilter::MatchNone(filters)) => (
+            None,
+            filters.iter().map(struct_type).collect::<Result<_, _>>()?,
+        ),
+        Some(filter) => (Some(vec![struct_type(&filter)?]), vec![]),
+    })
+}
+
+/// Whether `event` passes `filter`, one of the filters `query_events` scans the events for.
+fn event_matches(filter: &EventFilter, event: &StoredEvent) -> bool {
+    match filter {
+        EventFilter::Sender(sender) => event
+            .senders
+            .iter()
+            .any(|s| s.as_deref() == Some(sender.as_ref())),
+        EventFilter::Package(package_id) => event.package == package_id.to_vec(),
+        EventFilter::MoveModule { package, module } => {
+            event.package == package.to_vec() && event.module == module.as_str()
+        }
+        EventFilter::MoveEventType(struct_tag) => {
+            event.event_type == struct_tag.to_canonical_string(/* with_prefix */ true)
+        }
+        EventFilter::MoveEventModule { package, module } => event.event_type.starts_with(&format!(
+            "{}::{}::",
+            package.to_hex_uncompressed(),
+            module
+        )),
+        // Handled or rejected by `query_events` before scanning.
+        _ => false,
+    }
+}
+
+fn metrics_not_supported() -> IndexerError {
+    IndexerError::NotSupportedError(
+        "Network, move call and address metrics are computed by the analytical worker, which requires Postgres".into(),
+    )
+}
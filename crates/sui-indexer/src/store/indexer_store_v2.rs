@@ -11,6 +11,7 @@ use sui_types::base_types::{ObjectID, SequenceNumber};
 use sui_types::object::ObjectRead;
 
 use crate::errors::IndexerError;
+use crate::framework::CustomDataToCommit;
 use crate::handlers::{EpochToCommit, TransactionObjectChangesToCommit};
 
 use crate::models_v2::display::StoredDisplay;
@@ -48,6 +49,27 @@ pub trait IndexerStoreV2 {
         checkpoints: Vec<IndexedCheckpoint>,
     ) -> Result<(), IndexerError>;
 
+    /// Whether the data of custom handlers can be committed with the checkpoints. Writers
+    /// with custom handlers are rejected at startup on stores that don't support them.
+    fn supports_custom_handlers(&self) -> bool {
+        false
+    }
+
+    /// Persist checkpoints together with the data indexed from them by custom handlers,
+    /// in a single transaction.
+    async fn persist_checkpoints_with_custom_data(
+        &self,
+        checkpoints: Vec<IndexedCheckpoint>,
+        custom_data: Vec<CustomDataToCommit>,
+    ) -> Result<(), IndexerError> {
+        if !custom_data.is_empty() {
+            return Err(IndexerError::NotSupportedError(
+                "Custom handlers are not supported by this store".to_string(),
+            ));
+        }
+        self.persist_checkpoints(checkpoints).await
+    }
+
     async fn persist_transactions(
         &self,
         transactions: Vec<IndexedTransaction>,
@@ -1,6 +1,7 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+pub use embedded_indexer_store_v2::EmbeddedIndexerStoreV2;
 pub(crate) use indexer_analytical_store::*;
 pub use indexer_store::*;
 pub(crate) use indexer_store_v2::*;
@@ -8,6 +9,7 @@ pub use pg_indexer_analytical_store::PgIndexerAnalyticalStore;
 pub use pg_indexer_store::PgIndexerStore;
 pub use pg_indexer_store_v2::PgIndexerStoreV2;
 
+mod embedded_indexer_store_v2;
 mod indexer_analytical_store;
 mod indexer_store;
 pub mod indexer_store_v2;
@@ -15,7 +17,7 @@ pub mod module_resolver;
 pub(crate) mod module_resolver_v2;
 mod pg_indexer_analytical_store;
 mod pg_indexer_store;
-mod pg_indexer_store_v2;
+pub(crate) mod pg_indexer_store_v2;
 mod pg_partition_manager;
 mod query;
 
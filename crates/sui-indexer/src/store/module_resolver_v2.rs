@@ -12,6 +12,7 @@ use move_binary_format::CompiledModule;
 use move_bytecode_utils::module_cache::GetModule;
 use move_core_types::language_storage::ModuleId;
 use move_core_types::resolver::ModuleResolver;
+use rocksdb::DB;
 use std::sync::{Arc, Mutex};
 use sui_types::base_types::ObjectID;
 use sui_types::move_package::MovePackage;
@@ -19,6 +20,7 @@ use sui_types::move_package::MovePackage;
 use crate::errors::{Context, IndexerError};
 use crate::models_v2::packages::StoredPackage;
 use crate::store::diesel_macro::read_only_blocking;
+use crate::store::embedded_indexer_store_v2::get_embedded_package;
 use crate::PgConnectionPool;
 
 /// A package resolver that reads packages from the database.
@@ -63,6 +65,34 @@ impl ModuleResolver for IndexerStoreModuleResolver {
     }
 }
 
+/// A package resolver that reads packages from the embedded database.
+pub struct EmbeddedStoreModuleResolver {
+    db: Arc<DB>,
+}
+
+impl EmbeddedStoreModuleResolver {
+    pub fn new(db: Arc<DB>) -> Self {
+        Self { db }
+    }
+}
+
+impl ModuleResolver for EmbeddedStoreModuleResolver {
+    type Error = IndexerError;
+
+    fn get_module(&self, id: &ModuleId) -> Result<Option<Vec<u8>>, Self::Error> {
+        let package_id = ObjectID::from(*id.address());
+        let module_name = id.name().to_string();
+
+        let Some(move_package) = get_embedded_package(&self.db, &package_id)? else {
+            return Ok(None);
+        };
+        Ok(move_package
+            .serialized_module_map()
+            .get(&module_name)
+            .cloned())
+    }
+}
+
 /// InterimModuleResolver consists of a backup ModuleResolver
 /// (e.g. IndexerStoreModuleResolver) and an in-mem package cache.
 pub struct InterimModuleResolver<GM> {
@@ -24,6 +24,7 @@ use sui_types::base_types::{ObjectID, SequenceNumber};
 use sui_types::object::ObjectRead;
 
 use crate::errors::{Context, IndexerError};
+use crate::framework::CustomDataToCommit;
 use crate::handlers::EpochToCommit;
 use crate::handlers::TransactionObjectChangesToCommit;
 use crate::metrics::IndexerMetrics;
@@ -319,7 +320,11 @@ impl PgIndexerStoreV2 {
         })
     }
 
-    fn persist_checkpoints(&self, checkpoints: Vec<IndexedCheckpoint>) -> Result<(), IndexerError> {
+    fn persist_checkpoints(
+        &self,
+        checkpoints: Vec<IndexedCheckpoint>,
+        custom_data: Vec<CustomDataToCommit>,
+    ) -> Result<(), IndexerError> {
         if checkpoints.is_empty() {
             return Ok(());
         }
@@ -343,6 +348,15 @@ impl PgIndexerStoreV2 {
                         .map_err(IndexerError::from)
                         .context("Failed to write checkpoints to PostgresDB")?;
                 }
+                for data in &custom_data {
+                    data.commit(conn).map_err(|e| {
+                        IndexerError::PostgresWriteError(format!(
+                            "Failed to write data of custom handler {}: {}",
+                            data.handler_name(),
+                            e
+                        ))
+                    })?;
+                }
                 Ok::<(), IndexerError>(())
             },
             Duration::from_secs(60)
@@ -869,10 +883,25 @@ impl IndexerStoreV2 for PgIndexerStoreV2 {
         &self,
         checkpoints: Vec<IndexedCheckpoint>,
     ) -> Result<(), IndexerError> {
-        self.execute_in_blocking_worker(move |this| this.persist_checkpoints(checkpoints))
+        self.execute_in_blocking_worker(move |this| this.persist_checkpoints(checkpoints, vec![]))
             .await
     }
 
+    fn supports_custom_handlers(&self) -> bool {
+        true
+    }
+
+    async fn persist_checkpoints_with_custom_data(
+        &self,
+        checkpoints: Vec<IndexedCheckpoint>,
+        custom_data: Vec<CustomDataToCommit>,
+    ) -> Result<(), IndexerError> {
+        self.execute_in_blocking_worker(move |this| {
+            this.persist_checkpoints(checkpoints, custom_data)
+        })
+        .await
+    }
+
     async fn persist_transactions(
         &self,
         transactions: Vec<IndexedTransaction>,
@@ -1016,7 +1045,7 @@ impl IndexerStoreV2 for PgIndexerStoreV2 {
 /// Construct deleted objects and mutated objects to commit.
 /// In particular, filter mutated objects updates that would
 /// be override immediately.
-fn make_final_list_of_objects_to_commit(
+pub(crate) fn make_final_list_of_objects_to_commit(
     tx_object_changes: Vec<TransactionObjectChangesToCommit>,
 ) -> Vec<ObjectChangeToCommit> {
     let deleted_objects = tx_object_changes
@@ -1058,7 +1087,26 @@ fn make_final_list_of_objects_to_commit(
 }
 
 #[allow(clippy::large_enum_variant)]
-enum ObjectChangeToCommit {
+#[derive(Clone)]
+pub(crate) enum ObjectChangeToCommit {
     MutatedObject(StoredObject),
     DeletedObject(StoredDeletedObject),
 }
+
+impl ObjectChangeToCommit {
+    pub(crate) fn object_id(&self) -> &[u8] {
+        match self {
+            ObjectChangeToCommit::MutatedObject(o) => &o.object_id,
+            ObjectChangeToCommit::DeletedObject(o) => &o.object_id,
+        }
+    }
+}
+
+impl From<ObjectChangeToCommit> for StoredHistoryObject {
+    fn from(change: ObjectChangeToCommit) -> Self {
+        match change {
+            ObjectChangeToCommit::MutatedObject(o) => o.into(),
+            ObjectChangeToCommit::DeletedObject(o) => o.into(),
+        }
+    }
+}
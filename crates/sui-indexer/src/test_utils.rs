@@ -8,12 +8,13 @@ use tokio::task::JoinHandle;
 
 use std::env;
 use std::net::SocketAddr;
+use std::path::Path;
 use sui_json_rpc_types::SuiTransactionBlockResponse;
 use tracing::info;
 
 use crate::errors::IndexerError;
 use crate::indexer_v2::IndexerV2;
-use crate::store::{PgIndexerStore, PgIndexerStoreV2};
+use crate::store::{EmbeddedIndexerStoreV2, PgIndexerStore, PgIndexerStoreV2};
 use crate::utils::reset_database;
 use crate::{new_pg_connection_pool, Indexer, IndexerConfig};
 use crate::{new_pg_connection_pool_impl, IndexerMetrics};
@@ -89,6 +90,35 @@ pub async fn start_test_indexer_v2(
     (store, handle)
 }
 
+/// Spawns an indexer v2 writer persisting into an embedded database at `path`
+pub async fn start_test_embedded_indexer_v2(
+    path: &Path,
+    rpc_url: String,
+) -> (EmbeddedIndexerStoreV2, JoinHandle<Result<(), IndexerError>>) {
+    let config = IndexerConfig {
+        rpc_client_url: rpc_url,
+        fullnode_sync_worker: true,
+        rpc_server_worker: false,
+        use_v2: true,
+        embedded_db_path: Some(path.to_path_buf()),
+        ..Default::default()
+    };
+
+    let registry = prometheus::Registry::default();
+
+    init_metrics(&registry);
+
+    let indexer_metrics = IndexerMetrics::new(&registry);
+
+    let store = EmbeddedIndexerStoreV2::new(path).unwrap();
+    let store_clone = store.clone();
+    let handle = tokio::spawn(async move {
+        IndexerV2::start_writer(&config, store_clone, indexer_metrics).await
+    });
+
+    (store, handle)
+}
+
 /// Spawns an indexer thread with provided Postgres DB url
 pub async fn start_test_indexer(
     config: IndexerConfig,
@@ -0,0 +1,157 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Passphrase encryption of keystore files.
+//!
+//! The encryption key is derived from the passphrase with Argon2id, and the content of the
+//! keystore, the same JSON array of Base64 encoded keys as a plaintext keystore file, is
+//! encrypted with AES-256-GCM under a fresh nonce on each save.
+
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{Aes256Gcm, Nonce};
+use anyhow::{anyhow, bail, ensure};
+use argon2::{Algorithm, Argon2, Params, Version};
+use fastcrypto::encoding::{Base64, Encoding};
+use rand::{thread_rng, RngCore};
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroizing;
+
+/// Environment variable holding the keystore passphrase, read instead of prompting for it.
+pub const PASSPHRASE_ENV_VAR: &str = "SUI_KEYSTORE_PASSPHRASE";
+
+const KEY_LENGTH: usize = 32;
+const SALT_LENGTH: usize = 16;
+const NONCE_LENGTH: usize = 12;
+
+/// The content of an encrypted keystore file.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct EncryptedKeystore {
+    pub kdf: Kdf,
+    pub cipher: Cipher,
+    /// Base64 encoded ciphertext, including the authentication tag.
+    pub ciphertext: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(tag = "name", rename_all = "snake_case")]
+pub enum Kdf {
+    Argon2id {
+        /// Base64 encoded salt.
+        salt: String,
+        m_cost: u32,
+        t_cost: u32,
+        p_cost: u32,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(tag = "name", rename_all = "snake_case")]
+pub enum Cipher {
+    Aes256Gcm {
+        /// Base64 encoded nonce.
+        nonce: String,
+    },
+}
+
+/// A key derived from a passphrase, encrypting a keystore.
+pub struct KeystoreCipher {
+    kdf: Kdf,
+    key: Zeroizing<[u8; KEY_LENGTH]>,
+}
+
+impl KeystoreCipher {
+    /// Derive a key from `passphrase` with a random salt.
+    pub fn new(passphrase: &str) -> Result<Self, anyhow::Error> {
+        let mut salt = [0u8; SALT_LENGTH];
+        thread_rng().fill_bytes(&mut salt);
+        Self::derive(
+            Kdf::Argon2id {
+                salt: Base64::encode(salt),
+                m_cost: Params::DEFAULT_M_COST,
+                t_cost: Params::DEFAULT_T_COST,
+                p_cost: Params::DEFAULT_P_COST,
+            },
+            passphrase,
+        )
+    }
+
+    fn derive(kdf: Kdf, passphrase: &str) -> Result<Self, anyhow::Error> {
+        let mut key = Zeroizing::new([0u8; KEY_LENGTH]);
+        match &kdf {
+            Kdf::Argon2id {
+                salt,
+                m_cost,
+                t_cost,
+                p_cost,
+            } => {
+                let params = Params::new(*m_cost, *t_cost, *p_cost, Some(KEY_LENGTH))
+                    .map_err(|e| anyhow!("Invalid key derivation parameters: {e}"))?;
+                Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+                    .hash_password_into(passphrase.as_bytes(), &Base64::decode(salt)?, &mut *key)
+                    .map_err(|e| anyhow!("Cannot derive the keystore key: {e}"))?;
+            }
+        }
+        Ok(Self { kdf, key })
+    }
+
+    pub fn encrypt(&self, plaintext: &[u8]) -> Result<EncryptedKeystore, anyhow::Error> {
+        let mut nonce = [0u8; NONCE_LENGTH];
+        thread_rng().fill_bytes(&mut nonce);
+        let ciphertext = Aes256Gcm::new_from_slice(&*self.key)
+            .map_err(|_| anyhow!("Invalid keystore key"))?
+            .encrypt(Nonce::from_slice(&nonce), plaintext)
+            .map_err(|_| anyhow!("Cannot encrypt the keystore"))?;
+        Ok(EncryptedKeystore {
+            kdf: self.kdf.clone(),
+            cipher: Cipher::Aes256Gcm {
+                nonce: Base64::encode(nonce),
+            },
+            ciphertext: Base64::encode(ciphertext),
+        })
+    }
+}
+
+impl EncryptedKeystore {
+    /// Decrypt the keystore with `passphrase`, returning the plaintext and the cipher to encrypt
+    /// it again with the same passphrase.
+    pub fn decrypt(
+        &self,
+        passphrase: &str,
+    ) -> Result<(Zeroizing<Vec<u8>>, KeystoreCipher), anyhow::Error> {
+        let cipher = KeystoreCipher::derive(self.kdf.clone(), passphrase)?;
+        let plaintext = match &self.cipher {
+            Cipher::Aes256Gcm { nonce } => {
+                let nonce = Base64::decode(nonce)?;
+                ensure!(nonce.len() == NONCE_LENGTH, "Invalid keystore nonce");
+                Aes256Gcm::new_from_slice(&*cipher.key)
+                    .map_err(|_| anyhow!("Invalid keystore key"))?
+                    .decrypt(
+                        Nonce::from_slice(&nonce),
+                        Base64::decode(&self.ciphertext)?.as_slice(),
+                    )
+                    .map_err(|_| anyhow!("Cannot decrypt the keystore, wrong passphrase?"))?
+            }
+        };
+        Ok((Zeroizing::new(plaintext), cipher))
+    }
+}
+
+/// Read the keystore passphrase from the `SUI_KEYSTORE_PASSPHRASE` environment variable, or
+/// prompt for it on the terminal.
+pub fn read_passphrase(prompt: &str) -> Result<Zeroizing<String>, anyhow::Error> {
+    if let Ok(passphrase) = std::env::var(PASSPHRASE_ENV_VAR) {
+        return Ok(Zeroizing::new(passphrase));
+    }
+    Ok(Zeroizing::new(rpassword::prompt_password(prompt)?))
+}
+
+/// Prompt for a new keystore passphrase on the terminal, twice.
+pub fn read_new_passphrase() -> Result<Zeroizing<String>, anyhow::Error> {
+    let passphrase = Zeroizing::new(rpassword::prompt_password("New keystore passphrase: ")?);
+    let confirmation = Zeroizing::new(rpassword::prompt_password("Confirm passphrase: ")?);
+    if *passphrase != *confirmation {
+        bail!("The passphrases do not match");
+    }
+    ensure!(!passphrase.is_empty(), "The passphrase cannot be empty");
+    Ok(passphrase)
+}
@@ -0,0 +1,280 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Signing with keys held outside of the keystore, e.g. by a hardware wallet, a KMS or a remote
+//! signing service.
+//!
+//! The keystore talks to the signer with a small JSON protocol. Each request is a single JSON
+//! object, answered by a single JSON object:
+//!
+//! - `{"method": "public_keys"}` is answered by `{"public_keys": [<key>, ...]}`, listing the keys
+//!   the signer can sign with.
+//! - `{"method": "sign_intent_message", "public_key": <key>, "intent_message": <bytes>}` asks for
+//!   a signature over the Blake2b256 digest of the BCS serialized intent message. The message is
+//!   sent in full so the signer can decode it, e.g. to display the transaction for approval.
+//! - `{"method": "sign_hashed", "public_key": <key>, "msg": <bytes>}` asks for a signature over
+//!   the given bytes as is.
+//!
+//! Sign requests are answered by `{"signature": <signature>}`, and any request can be answered by
+//! `{"error": <message>}`. Keys are Base64 encoded `flag || public key`, signatures are Base64
+//! encoded `flag || signature || public key` and bytes are Base64 encoded.
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use anyhow::{anyhow, bail, Context};
+use fastcrypto::encoding::{Base64, Encoding};
+use serde::{Deserialize, Serialize};
+use sui_types::crypto::{EncodeDecodeBase64, PublicKey, Signature};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "method", rename_all = "snake_case")]
+pub enum SignerRequest {
+    PublicKeys,
+    SignIntentMessage {
+        public_key: String,
+        intent_message: String,
+    },
+    SignHashed {
+        public_key: String,
+        msg: String,
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SignerResponse {
+    PublicKeys(Vec<String>),
+    Signature(String),
+    Error(String),
+}
+
+/// A signer holding keys outside of the keystore.
+#[derive(Serialize, Deserialize)]
+pub enum ExternalSigner {
+    /// Run `command` with `args` for each request, writing the request to its stdin and reading
+    /// the response from its stdout.
+    Command {
+        command: String,
+        #[serde(default)]
+        args: Vec<String>,
+    },
+    /// Sign in process with keys held in memory. It is never saved with the client config.
+    #[cfg(test)]
+    #[serde(skip)]
+    Local(tests::LocalSigner),
+}
+
+impl ExternalSigner {
+    pub fn call(&self, request: &SignerRequest) -> Result<SignerResponse, anyhow::Error> {
+        match self {
+            ExternalSigner::Command { command, args } => call_command(command, args, request),
+            #[cfg(test)]
+            ExternalSigner::Local(signer) => Ok(signer.handle(request)),
+        }
+    }
+
+    pub fn public_keys(&self) -> Result<Vec<PublicKey>, anyhow::Error> {
+        match self.call(&SignerRequest::PublicKeys)? {
+            SignerResponse::PublicKeys(keys) => keys
+                .iter()
+                .map(|key| {
+                    PublicKey::decode_base64(key)
+                        .map_err(|e| anyhow!("Invalid public key from external signer: {e}"))
+                })
+                .collect(),
+            SignerResponse::Error(e) => bail!("External signer error: {e}"),
+            response => bail!("Unexpected response from external signer: {response:?}"),
+        }
+    }
+
+    /// Sign the Blake2b256 digest of `intent_message`, the BCS bytes of an intent message.
+    pub fn sign_intent_message(
+        &self,
+        public_key: &PublicKey,
+        intent_message: &[u8],
+    ) -> Result<Signature, anyhow::Error> {
+        self.sign(&SignerRequest::SignIntentMessage {
+            public_key: public_key.encode_base64(),
+            intent_message: Base64::encode(intent_message),
+        })
+    }
+
+    pub fn sign_hashed(
+        &self,
+        public_key: &PublicKey,
+        msg: &[u8],
+    ) -> Result<Signature, anyhow::Error> {
+        self.sign(&SignerRequest::SignHashed {
+            public_key: public_key.encode_base64(),
+            msg: Base64::encode(msg),
+        })
+    }
+
+    fn sign(&self, request: &SignerRequest) -> Result<Signature, anyhow::Error> {
+        match self.call(request)? {
+            SignerResponse::Signature(signature) => Signature::decode_base64(&signature)
+                .map_err(|e| anyhow!("Invalid signature from external signer: {e}")),
+            SignerResponse::Error(e) => bail!("External signer error: {e}"),
+            response => bail!("Unexpected response from external signer: {response:?}"),
+        }
+    }
+}
+
+fn call_command(
+    command: &str,
+    args: &[String],
+    request: &SignerRequest,
+) -> Result<SignerResponse, anyhow::Error> {
+    let mut child = Command::new(command)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()
+        .with_context(|| format!("Cannot start external signer: {command}"))?;
+
+    // Unwrap safe: stdin is piped above. It is dropped after writing, closing the pipe.
+    let mut stdin = child.stdin.take().unwrap();
+    stdin.write_all(&serde_json::to_vec(request)?)?;
+    drop(stdin);
+
+    let output = child
+        .wait_with_output()
+        .with_context(|| format!("Cannot read the output of external signer: {command}"))?;
+    if !output.status.success() {
+        bail!("External signer {command} failed with {}", output.status);
+    }
+    serde_json::from_slice(&output.stdout)
+        .with_context(|| format!("Cannot deserialize the response of external signer: {command}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::key_derive::generate_new_key;
+    use crate::keystore::{AccountKeystore, ExternalKeystore, Keystore};
+    use fastcrypto::hash::HashFunction;
+    use shared_crypto::intent::{Intent, IntentMessage};
+    use signature::Signer;
+    use sui_types::crypto::{DefaultHash, SignatureScheme, SuiKeyPair, SuiSignature};
+
+    /// The signer side of the protocol, signing with keys held in memory.
+    #[derive(Default)]
+    pub struct LocalSigner {
+        keys: Vec<SuiKeyPair>,
+    }
+
+    impl LocalSigner {
+        pub fn new(keys: Vec<SuiKeyPair>) -> Self {
+            Self { keys }
+        }
+
+        pub fn handle(&self, request: &SignerRequest) -> SignerResponse {
+            match self.try_handle(request) {
+                Ok(response) => response,
+                Err(e) => SignerResponse::Error(e.to_string()),
+            }
+        }
+
+        fn try_handle(&self, request: &SignerRequest) -> Result<SignerResponse, anyhow::Error> {
+            let (public_key, msg) = match request {
+                SignerRequest::PublicKeys => {
+                    return Ok(SignerResponse::PublicKeys(
+                        self.keys
+                            .iter()
+                            .map(|key| key.public().encode_base64())
+                            .collect(),
+                    ))
+                }
+                SignerRequest::SignIntentMessage {
+                    public_key,
+                    intent_message,
+                } => {
+                    let mut hasher = DefaultHash::default();
+                    hasher.update(Base64::decode(intent_message)?);
+                    (public_key, hasher.finalize().digest.to_vec())
+                }
+                SignerRequest::SignHashed { public_key, msg } => (public_key, Base64::decode(msg)?),
+            };
+            let key = self
+                .keys
+                .iter()
+                .find(|key| &key.public().encode_base64() == public_key)
+                .ok_or_else(|| anyhow!("Cannot find key {public_key}"))?;
+            let signature: Signature = key.try_sign(&msg)?;
+            Ok(SignerResponse::Signature(signature.encode_base64()))
+        }
+    }
+
+    #[test]
+    fn local_signer_test() {
+        let (_, keypair, _, _) = generate_new_key(SignatureScheme::ED25519, None, None).unwrap();
+        let signer = LocalSigner::new(vec![keypair]);
+        let request: SignerRequest = serde_json::from_str(r#"{"method": "public_keys"}"#).unwrap();
+        assert!(matches!(
+            signer.handle(&request),
+            SignerResponse::PublicKeys(keys) if keys.len() == 1
+        ));
+
+        let request: SignerRequest = serde_json::from_str(
+            r#"{"method": "sign_hashed", "public_key": "AA==", "msg": "aGVsbG8="}"#,
+        )
+        .unwrap();
+        assert!(matches!(signer.handle(&request), SignerResponse::Error(_)));
+    }
+
+    #[test]
+    fn local_signer_is_not_serialized_test() {
+        let (_, keypair, _, _) = generate_new_key(SignatureScheme::ED25519, None, None).unwrap();
+        let keystore =
+            ExternalKeystore::new(ExternalSigner::Local(LocalSigner::new(vec![keypair]))).unwrap();
+        assert!(serde_json::to_string(&keystore).is_err());
+    }
+
+    #[test]
+    fn external_keystore_sign_test() {
+        let (address, keypair, _, _) =
+            generate_new_key(SignatureScheme::ED25519, None, None).unwrap();
+        let (address2, keypair2, _, _) =
+            generate_new_key(SignatureScheme::Secp256k1, None, None).unwrap();
+        let signer = ExternalSigner::Local(LocalSigner::new(vec![
+            SuiKeyPair::decode_base64(&keypair.encode_base64()).unwrap(),
+            keypair2,
+        ]));
+        let mut keystore = Keystore::from(ExternalKeystore::new(signer).unwrap());
+
+        let mut addresses = keystore.addresses();
+        addresses.sort();
+        let mut expected = vec![address, address2];
+        expected.sort();
+        assert_eq!(expected, addresses);
+        assert_eq!(2, keystore.alias_names().len());
+
+        let msg = b"hello".to_vec();
+        let signature = keystore
+            .sign_secure(&address, &msg, Intent::sui_transaction())
+            .unwrap();
+        let intent_msg = IntentMessage::new(Intent::sui_transaction(), &msg);
+        assert_eq!(signature, Signature::new_secure(&intent_msg, &keypair));
+
+        let signature = keystore
+            .sign_secure(&address2, &msg, Intent::sui_transaction())
+            .unwrap();
+        signature
+            .verify_secure(&intent_msg, address2, signature.scheme())
+            .unwrap();
+
+        let signature = keystore.sign_hashed(&address, &msg).unwrap();
+        assert_eq!(signature, Signature::new_hashed(&msg, &keypair));
+
+        // The private keys are not held by the keystore.
+        let (unknown, new_keypair, _, _) =
+            generate_new_key(SignatureScheme::ED25519, None, None).unwrap();
+        assert!(keystore
+            .sign_secure(&unknown, &msg, Intent::sui_transaction())
+            .is_err());
+        assert!(keystore.get_key(&address).is_err());
+        assert!(keystore.add_key(None, new_keypair).is_err());
+    }
+}
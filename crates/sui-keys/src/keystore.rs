@@ -1,11 +1,14 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::encryption::{read_passphrase, EncryptedKeystore, KeystoreCipher};
+use crate::external_signer::ExternalSigner;
 use crate::key_derive::{derive_key_pair_from_path, generate_new_key};
 use crate::random_names::{random_name, random_names};
 use anyhow::{anyhow, bail, ensure, Context};
 use bip32::DerivationPath;
 use bip39::{Language, Mnemonic, Seed};
+use once_cell::sync::OnceCell;
 use rand::{rngs::StdRng, SeedableRng};
 use regex::Regex;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
@@ -21,13 +24,16 @@ use sui_types::base_types::SuiAddress;
 use sui_types::crypto::get_key_pair_from_rng;
 use sui_types::crypto::{
     enum_dispatch, EncodeDecodeBase64, PublicKey, Signature, SignatureScheme, SuiKeyPair,
+    SuiSignature,
 };
+use zeroize::Zeroizing;
 
 #[derive(Serialize, Deserialize)]
 #[enum_dispatch(AccountKeystore)]
 pub enum Keystore {
     File(FileBasedKeystore),
     InMem(InMemKeystore),
+    External(ExternalKeystore),
 }
 #[enum_dispatch]
 pub trait AccountKeystore: Send + Sync {
@@ -148,6 +154,17 @@ impl Display for Keystore {
                 writeln!(writer, "Keystore Type : InMem")?;
                 write!(f, "{}", writer)
             }
+            Keystore::External(external) => {
+                writeln!(writer, "Keystore Type : External")?;
+                match &external.signer {
+                    ExternalSigner::Command { command, .. } => {
+                        write!(writer, "Signer Command : {}", command)?;
+                    }
+                    #[cfg(test)]
+                    ExternalSigner::Local(_) => write!(writer, "Signer : Local")?,
+                }
+                write!(f, "{}", writer)
+            }
         }
     }
 }
@@ -158,11 +175,32 @@ pub struct Alias {
     pub public_key_base64: String,
 }
 
+/// A keystore saved to a file, either as plaintext or encrypted with a passphrase. The keys of
+/// an encrypted keystore are decrypted when first used, prompting for the passphrase unless it
+/// is set in the `SUI_KEYSTORE_PASSPHRASE` environment variable, and kept in memory afterwards.
+/// The aliases and public keys are always saved in plaintext, in a separate aliases file.
 #[derive(Default)]
 pub struct FileBasedKeystore {
-    keys: BTreeMap<SuiAddress, SuiKeyPair>,
+    unlocked: OnceCell<UnlockedKeys>,
     aliases: BTreeMap<SuiAddress, Alias>,
     path: Option<PathBuf>,
+    /// The content of the keystore file, if it is encrypted.
+    encrypted: Option<EncryptedKeystore>,
+}
+
+#[derive(Default)]
+struct UnlockedKeys {
+    keys: BTreeMap<SuiAddress, SuiKeyPair>,
+    /// The cipher encrypting the keystore file, `None` for a plaintext keystore.
+    cipher: Option<KeystoreCipher>,
+}
+
+/// The content of a keystore file.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum KeystoreFile {
+    Plaintext(Vec<String>),
+    Encrypted(EncryptedKeystore),
 }
 
 impl Serialize for FileBasedKeystore {
@@ -193,12 +231,7 @@ impl<'de> Deserialize<'de> for FileBasedKeystore {
 
 impl AccountKeystore for FileBasedKeystore {
     fn sign_hashed(&self, address: &SuiAddress, msg: &[u8]) -> Result<Signature, signature::Error> {
-        Ok(Signature::new_hashed(
-            msg,
-            self.keys.get(address).ok_or_else(|| {
-                signature::Error::from_source(format!("Cannot find key for address: [{address}]"))
-            })?,
-        ))
+        Ok(Signature::new_hashed(msg, self.signing_key(address)?))
     }
     fn sign_secure<T>(
         &self,
@@ -211,9 +244,7 @@ impl AccountKeystore for FileBasedKeystore {
     {
         Ok(Signature::new_secure(
             &IntentMessage::new(intent, msg),
-            self.keys.get(address).ok_or_else(|| {
-                signature::Error::from_source(format!("Cannot find key for address: [{address}]"))
-            })?,
+            self.signing_key(address)?,
         ))
     }
 
@@ -227,7 +258,7 @@ impl AccountKeystore for FileBasedKeystore {
                 public_key_base64: EncodeDecodeBase64::encode_base64(&keypair.public()),
             },
         );
-        self.keys.insert(address, keypair);
+        self.unlocked_mut()?.keys.insert(address, keypair);
         self.save()?;
         Ok(())
     }
@@ -247,7 +278,15 @@ impl AccountKeystore for FileBasedKeystore {
     }
 
     fn keys(&self) -> Vec<PublicKey> {
-        self.keys.values().map(|key| key.public()).collect()
+        match self.unlocked.get() {
+            Some(unlocked) => unlocked.keys.values().map(|key| key.public()).collect(),
+            // Only the public keys of the aliases are known until the keystore is unlocked.
+            None => self
+                .aliases
+                .values()
+                .filter_map(|alias| PublicKey::decode_base64(&alias.public_key_base64).ok())
+                .collect(),
+        }
     }
 
     /// This function returns an error if the provided alias already exists. If the alias
@@ -278,7 +317,7 @@ impl AccountKeystore for FileBasedKeystore {
     }
 
     fn get_key(&self, address: &SuiAddress) -> Result<&SuiKeyPair, anyhow::Error> {
-        match self.keys.get(address) {
+        match self.unlocked()?.keys.get(address) {
             Some(key) => Ok(key),
             None => Err(anyhow!("Cannot find key for address: [{address}]")),
         }
@@ -299,25 +338,27 @@ impl AccountKeystore for FileBasedKeystore {
 
 impl FileBasedKeystore {
     pub fn new(path: &PathBuf) -> Result<Self, anyhow::Error> {
-        let keys = if path.exists() {
+        let mut keystore = Self {
+            path: Some(path.to_path_buf()),
+            ..Default::default()
+        };
+        if path.exists() {
             let reader =
                 BufReader::new(File::open(path).with_context(|| {
                     format!("Cannot open the keystore file: {}", path.display())
                 })?);
-            let kp_strings: Vec<String> = serde_json::from_reader(reader).with_context(|| {
+            let file: KeystoreFile = serde_json::from_reader(reader).with_context(|| {
                 format!("Cannot deserialize the keystore file: {}", path.display(),)
             })?;
-            kp_strings
-                .iter()
-                .map(|kpstr| {
-                    let key = SuiKeyPair::decode_base64(kpstr);
-                    key.map(|k| (SuiAddress::from(&k.public()), k))
-                })
-                .collect::<Result<BTreeMap<_, _>, _>>()
-                .map_err(|e| anyhow!("Invalid keystore file: {}. {}", path.display(), e))?
-        } else {
-            BTreeMap::new()
-        };
+            match file {
+                KeystoreFile::Plaintext(kp_strings) => {
+                    let keys = decode_keys(&kp_strings)
+                        .map_err(|e| anyhow!("Invalid keystore file: {}. {}", path.display(), e))?;
+                    keystore.set_unlocked(UnlockedKeys { keys, cipher: None });
+                }
+                KeystoreFile::Encrypted(encrypted) => keystore.encrypted = Some(encrypted),
+            }
+        }
 
         // check aliases
         let mut aliases_path = path.clone();
@@ -352,9 +393,10 @@ impl FileBasedKeystore {
                         e
                     )
                 })?
-        } else if keys.is_empty() {
+        } else if keystore.unlocked()?.keys.is_empty() {
             BTreeMap::new()
         } else {
+            let keys = &keystore.unlocked()?.keys;
             let names: Vec<String> = random_names(HashSet::new(), keys.len());
             let aliases = keys
                 .iter()
@@ -382,11 +424,8 @@ impl FileBasedKeystore {
             aliases
         };
 
-        Ok(Self {
-            keys,
-            aliases,
-            path: Some(path.to_path_buf()),
-        })
+        keystore.aliases = aliases;
+        Ok(keystore)
     }
 
     pub fn set_path(&mut self, path: &Path) {
@@ -412,16 +451,31 @@ impl FileBasedKeystore {
     }
 
     pub fn save_keystore(&self) -> Result<(), anyhow::Error> {
-        if let Some(path) = &self.path {
-            let store = serde_json::to_string_pretty(
-                &self
+        // The keys of a keystore which is still locked are unchanged, and so is its file.
+        if let (Some(path), Some(unlocked)) = (&self.path, self.unlocked.get()) {
+            // The encoded keys and the JSON holding them are wiped once written or encrypted.
+            let keys: Zeroizing<Vec<String>> = Zeroizing::new(
+                unlocked
                     .keys
                     .values()
                     .map(EncodeDecodeBase64::encode_base64)
-                    .collect::<Vec<_>>(),
-            )
-            .with_context(|| format!("Cannot serialize keystore to file: {}", path.display()))?;
-            fs::write(path, store)?;
+                    .collect(),
+            );
+            let store =
+                Zeroizing::new(serde_json::to_string_pretty(&*keys).with_context(|| {
+                    format!("Cannot serialize keystore to file: {}", path.display())
+                })?);
+            match &unlocked.cipher {
+                Some(cipher) => {
+                    let encrypted =
+                        serde_json::to_string_pretty(&cipher.encrypt(store.as_bytes())?)
+                            .with_context(|| {
+                                format!("Cannot serialize keystore to file: {}", path.display())
+                            })?;
+                    write_atomically(path, encrypted.as_bytes())?
+                }
+                None => write_atomically(path, store.as_bytes())?,
+            }
         }
         Ok(())
     }
@@ -432,9 +486,108 @@ impl FileBasedKeystore {
         Ok(())
     }
 
-    pub fn key_pairs(&self) -> Vec<&SuiKeyPair> {
-        self.keys.values().collect()
+    pub fn key_pairs(&self) -> Result<Vec<&SuiKeyPair>, anyhow::Error> {
+        Ok(self.unlocked()?.keys.values().collect())
+    }
+
+    /// Whether the keystore file is encrypted with a passphrase.
+    pub fn is_encrypted(&self) -> bool {
+        match self.unlocked.get() {
+            Some(unlocked) => unlocked.cipher.is_some(),
+            None => self.encrypted.is_some(),
+        }
+    }
+
+    /// Decrypt the keys of an encrypted keystore with `passphrase`, instead of prompting for it
+    /// when they are first used.
+    pub fn unlock(&self, passphrase: &str) -> Result<(), anyhow::Error> {
+        if let (None, Some(encrypted)) = (self.unlocked.get(), &self.encrypted) {
+            self.set_unlocked(decrypt_keys(encrypted, passphrase)?);
+        }
+        Ok(())
     }
+
+    /// Encrypt the keystore file with a new passphrase, or save it as plaintext if `None`.
+    /// The keystore is unlocked first if it is encrypted.
+    pub fn set_passphrase(&mut self, passphrase: Option<&str>) -> Result<(), anyhow::Error> {
+        let cipher = passphrase.map(KeystoreCipher::new).transpose()?;
+        self.unlocked_mut()?.cipher = cipher;
+        self.save_keystore()
+    }
+
+    fn unlocked(&self) -> Result<&UnlockedKeys, anyhow::Error> {
+        self.unlocked.get_or_try_init(|| match &self.encrypted {
+            Some(encrypted) => {
+                let path = self.path.as_deref().unwrap_or(Path::new(""));
+                let passphrase = read_passphrase(&format!(
+                    "Enter the passphrase of keystore {}: ",
+                    path.display()
+                ))?;
+                decrypt_keys(encrypted, &passphrase)
+            }
+            None => Ok(UnlockedKeys::default()),
+        })
+    }
+
+    fn unlocked_mut(&mut self) -> Result<&mut UnlockedKeys, anyhow::Error> {
+        self.unlocked()?;
+        // Unwrap safe: the keys were unlocked above.
+        Ok(self.unlocked.get_mut().unwrap())
+    }
+
+    fn set_unlocked(&self, unlocked: UnlockedKeys) {
+        // The keys may only have been unlocked concurrently with the same passphrase.
+        let _ = self.unlocked.set(unlocked);
+    }
+
+    fn signing_key(&self, address: &SuiAddress) -> Result<&SuiKeyPair, signature::Error> {
+        self.unlocked()
+            .map_err(|e| signature::Error::from_source(e.to_string()))?
+            .keys
+            .get(address)
+            .ok_or_else(|| {
+                signature::Error::from_source(format!("Cannot find key for address: [{address}]"))
+            })
+    }
+}
+
+fn decode_keys(kp_strings: &[String]) -> Result<BTreeMap<SuiAddress, SuiKeyPair>, anyhow::Error> {
+    kp_strings
+        .iter()
+        .map(|kpstr| {
+            let key = SuiKeyPair::decode_base64(kpstr);
+            key.map(|k| (SuiAddress::from(&k.public()), k))
+        })
+        .collect::<Result<BTreeMap<_, _>, _>>()
+        .map_err(|e| anyhow!("{}", e))
+}
+
+fn decrypt_keys(
+    encrypted: &EncryptedKeystore,
+    passphrase: &str,
+) -> Result<UnlockedKeys, anyhow::Error> {
+    let (plaintext, cipher) = encrypted.decrypt(passphrase)?;
+    let kp_strings: Vec<String> =
+        serde_json::from_slice(&plaintext).context("Cannot deserialize the decrypted keystore")?;
+    Ok(UnlockedKeys {
+        keys: decode_keys(&kp_strings)?,
+        cipher: Some(cipher),
+    })
+}
+
+/// Replace the file at `path` with `contents`, writing them to a temporary file in the same
+/// directory first, so that a failed write cannot leave the file truncated.
+fn write_atomically(path: &Path, contents: &[u8]) -> Result<(), anyhow::Error> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| anyhow!("Invalid keystore path: {}", path.display()))?;
+    let mut tmp_name = file_name.to_os_string();
+    tmp_name.push(".tmp");
+    let tmp_path = path.with_file_name(tmp_name);
+    fs::write(&tmp_path, contents)
+        .with_context(|| format!("Cannot write keystore to file: {}", tmp_path.display()))?;
+    fs::rename(&tmp_path, path)
+        .with_context(|| format!("Cannot replace keystore file: {}", path.display()))
 }
 
 #[derive(Default, Serialize, Deserialize)]
@@ -580,6 +733,237 @@ impl InMemKeystore {
     }
 }
 
+/// A keystore whose keys are held by an external signer, e.g. a hardware wallet or a remote
+/// signing service, see [`ExternalSigner`]. Only the public keys and aliases are stored, in the
+/// client config. When no keys are stored yet, they are fetched from the signer when first used.
+pub struct ExternalKeystore {
+    signer: ExternalSigner,
+    aliases: OnceCell<BTreeMap<SuiAddress, Alias>>,
+}
+
+impl Serialize for ExternalKeystore {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        use serde::ser::SerializeStruct;
+
+        let mut state = serializer.serialize_struct("ExternalKeystore", 2)?;
+        state.serialize_field("signer", &self.signer)?;
+        // The keys are not fetched from the signer only to be saved.
+        state.serialize_field("aliases", self.aliases.get().unwrap_or(&BTreeMap::new()))?;
+        state.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for ExternalKeystore {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        struct Stored {
+            signer: ExternalSigner,
+            #[serde(default)]
+            aliases: BTreeMap<SuiAddress, Alias>,
+        }
+
+        let Stored { signer, aliases } = Stored::deserialize(deserializer)?;
+        let aliases = if aliases.is_empty() {
+            OnceCell::new()
+        } else {
+            OnceCell::with_value(aliases)
+        };
+        Ok(Self { signer, aliases })
+    }
+}
+
+impl AccountKeystore for ExternalKeystore {
+    fn sign_hashed(&self, address: &SuiAddress, msg: &[u8]) -> Result<Signature, signature::Error> {
+        let public_key = self.public_key(address)?;
+        let signature = self
+            .signer
+            .sign_hashed(&public_key, msg)
+            .map_err(|e| signature::Error::from_source(e.to_string()))?;
+        if signature.public_key_bytes() != public_key.as_ref() {
+            return Err(signature::Error::from_source(format!(
+                "External signer signed with a different key than the key of address [{address}]"
+            )));
+        }
+        Ok(signature)
+    }
+
+    fn sign_secure<T>(
+        &self,
+        address: &SuiAddress,
+        msg: &T,
+        intent: Intent,
+    ) -> Result<Signature, signature::Error>
+    where
+        T: Serialize,
+    {
+        let public_key = self.public_key(address)?;
+        let intent_msg = IntentMessage::new(intent, msg);
+        let bytes = bcs::to_bytes(&intent_msg).map_err(signature::Error::from_source)?;
+        let signature = self
+            .signer
+            .sign_intent_message(&public_key, &bytes)
+            .map_err(|e| signature::Error::from_source(e.to_string()))?;
+        signature
+            .verify_secure(&intent_msg, *address, signature.scheme())
+            .map_err(|e| signature::Error::from_source(e.to_string()))?;
+        Ok(signature)
+    }
+
+    fn add_key(
+        &mut self,
+        _alias: Option<String>,
+        _keypair: SuiKeyPair,
+    ) -> Result<(), anyhow::Error> {
+        bail!("Cannot add a key to an external keystore, its keys are managed by the signer")
+    }
+
+    // The keys are listed as empty if they cannot be fetched from the signer, the error is
+    // returned when signing instead.
+    fn aliases(&self) -> Vec<&Alias> {
+        self.aliases_map()
+            .map(|aliases| aliases.values().collect())
+            .unwrap_or_default()
+    }
+
+    fn addresses_with_alias(&self) -> Vec<(&SuiAddress, &Alias)> {
+        self.aliases_map()
+            .map(|aliases| aliases.iter().collect())
+            .unwrap_or_default()
+    }
+
+    fn aliases_mut(&mut self) -> Vec<&mut Alias> {
+        self.aliases_map_mut()
+            .map(|aliases| aliases.values_mut().collect())
+            .unwrap_or_default()
+    }
+
+    fn keys(&self) -> Vec<PublicKey> {
+        self.aliases()
+            .into_iter()
+            .filter_map(|alias| PublicKey::decode_base64(&alias.public_key_base64).ok())
+            .collect()
+    }
+
+    fn get_key(&self, address: &SuiAddress) -> Result<&SuiKeyPair, anyhow::Error> {
+        bail!("The private key of address [{address}] is held by an external signer")
+    }
+
+    /// Get alias of address
+    fn get_alias_by_address(&self, address: &SuiAddress) -> Result<String, anyhow::Error> {
+        match self.aliases_map()?.get(address) {
+            Some(alias) => Ok(alias.alias.clone()),
+            None => bail!("Cannot find alias for address {address}"),
+        }
+    }
+
+    /// This function returns an error if the provided alias already exists. If the alias
+    /// has not already been used, then it returns the alias.
+    /// If no alias has been passed, it will generate a new alias.
+    fn create_alias(&self, alias: Option<String>) -> Result<String, anyhow::Error> {
+        match alias {
+            Some(a) if self.alias_exists(&a) => {
+                bail!("Alias {a} already exists. Please choose another alias.")
+            }
+            Some(a) => validate_alias(&a),
+            None => Ok(random_name(
+                &self
+                    .alias_names()
+                    .into_iter()
+                    .map(|x| x.to_string())
+                    .collect::<HashSet<_>>(),
+            )),
+        }
+    }
+
+    /// Updates an old alias to the new alias. If the new_alias is None,
+    /// it will generate a new random alias. The aliases are saved with the client config.
+    fn update_alias(
+        &mut self,
+        old_alias: &str,
+        new_alias: Option<&str>,
+    ) -> Result<String, anyhow::Error> {
+        self.update_alias_value(old_alias, new_alias)
+    }
+}
+
+impl ExternalKeystore {
+    /// Create a keystore for the keys of `signer`, giving each key a random alias.
+    pub fn new(signer: ExternalSigner) -> Result<Self, anyhow::Error> {
+        let keystore = Self {
+            signer,
+            aliases: OnceCell::new(),
+        };
+        keystore.aliases_map()?;
+        Ok(keystore)
+    }
+
+    /// Fetch the keys of the signer again, keeping the aliases of known keys.
+    pub fn refresh(&mut self) -> Result<(), anyhow::Error> {
+        let aliases = self.fetch_aliases(self.aliases.get().cloned().unwrap_or_default())?;
+        self.aliases = OnceCell::with_value(aliases);
+        Ok(())
+    }
+
+    pub fn signer(&self) -> &ExternalSigner {
+        &self.signer
+    }
+
+    fn fetch_aliases(
+        &self,
+        mut known: BTreeMap<SuiAddress, Alias>,
+    ) -> Result<BTreeMap<SuiAddress, Alias>, anyhow::Error> {
+        let keys = self.signer.public_keys()?;
+        let mut aliases = BTreeMap::new();
+        let mut names = known
+            .values()
+            .map(|alias| alias.alias.clone())
+            .collect::<HashSet<_>>();
+        for key in keys {
+            let address = SuiAddress::from(&key);
+            let alias = match known.remove(&address) {
+                Some(alias) => alias,
+                None => {
+                    let alias = random_name(&names);
+                    names.insert(alias.clone());
+                    Alias {
+                        alias,
+                        public_key_base64: EncodeDecodeBase64::encode_base64(&key),
+                    }
+                }
+            };
+            aliases.insert(address, alias);
+        }
+        Ok(aliases)
+    }
+
+    fn aliases_map(&self) -> Result<&BTreeMap<SuiAddress, Alias>, anyhow::Error> {
+        self.aliases
+            .get_or_try_init(|| self.fetch_aliases(BTreeMap::new()))
+    }
+
+    fn aliases_map_mut(&mut self) -> Result<&mut BTreeMap<SuiAddress, Alias>, anyhow::Error> {
+        self.aliases_map()?;
+        // Unwrap safe: the keys were fetched above.
+        Ok(self.aliases.get_mut().unwrap())
+    }
+
+    fn public_key(&self, address: &SuiAddress) -> Result<PublicKey, signature::Error> {
+        self.aliases_map()
+            .map_err(|e| signature::Error::from_source(e.to_string()))?
+            .get(address)
+            .and_then(|alias| PublicKey::decode_base64(&alias.public_key_base64).ok())
+            .ok_or_else(|| {
+                signature::Error::from_source(format!("Cannot find key for address: [{address}]"))
+            })
+    }
+}
+
 fn validate_alias(alias: &str) -> Result<String, anyhow::Error> {
     let re = Regex::new(r"^[A-Za-z][A-Za-z0-9-_]*$")
         .map_err(|_| anyhow!("Cannot build the regex needed to validate the alias naming"))?;
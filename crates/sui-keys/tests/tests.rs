@@ -9,6 +9,8 @@ use fastcrypto::traits::EncodeDecodeBase64;
 use sui_keys::key_derive::generate_new_key;
 use tempfile::TempDir;
 
+use shared_crypto::intent::Intent;
+use sui_keys::external_signer::{ExternalSigner, SignerResponse};
 use sui_keys::keystore::{AccountKeystore, FileBasedKeystore, InMemKeystore, Keystore};
 use sui_types::crypto::{DefaultHash, SignatureScheme, SuiSignatureInner};
 use sui_types::{
@@ -55,6 +57,22 @@ fn create_alias_keystore_file_test() {
     assert!(keystore_path.exists());
 }
 
+#[test]
+fn save_keystore_replaces_file_test() {
+    let temp_dir = TempDir::new().unwrap();
+    let keystore_path = temp_dir.path().join("sui.keystore");
+    let mut keystore = Keystore::from(FileBasedKeystore::new(&keystore_path).unwrap());
+    for _ in 0..2 {
+        keystore
+            .generate_and_add_new_key(SignatureScheme::ED25519, None, None, None)
+            .unwrap();
+    }
+    assert!(!temp_dir.path().join("sui.keystore.tmp").exists());
+
+    let reloaded = Keystore::from(FileBasedKeystore::new(&keystore_path).unwrap());
+    assert_eq!(reloaded.keys().len(), 2);
+}
+
 #[test]
 fn check_reading_aliases_file_correctly() {
     // when reading the alias file containing alias + public key base 64,
@@ -266,3 +284,98 @@ fn get_alias_by_address_test() {
     let address = generate_new_key(SignatureScheme::ED25519, None, None).unwrap();
     assert!(keystore.get_alias_by_address(&address.0).is_err())
 }
+
+/// A signer running `script` with `sh`.
+fn script_signer(script: &str) -> ExternalSigner {
+    ExternalSigner::Command {
+        command: "sh".to_string(),
+        args: vec!["-c".to_string(), script.to_string()],
+    }
+}
+
+#[test]
+fn external_keystore_serde_test() {
+    let (address, keypair, _, _) = generate_new_key(SignatureScheme::ED25519, None, None).unwrap();
+    let response = serde_json::to_string(&SignerResponse::PublicKeys(vec![keypair
+        .public()
+        .encode_base64()]))
+    .unwrap();
+    let signer = serde_json::to_value(script_signer(&format!("echo '{response}'"))).unwrap();
+
+    // The keys are fetched from the signer when first used, as none are stored.
+    let mut keystore: Keystore =
+        serde_json::from_value(serde_json::json!({ "External": { "signer": signer } })).unwrap();
+    assert_eq!(vec![address], keystore.addresses());
+    assert!(keystore.to_string().contains("External"));
+
+    // Aliases are kept across a round trip.
+    let alias = keystore.get_alias_by_address(&address).unwrap();
+    keystore.update_alias(&alias, Some("ledger")).unwrap();
+    let keystore: Keystore =
+        serde_json::from_str(&serde_json::to_string(&keystore).unwrap()).unwrap();
+    assert_eq!(vec!["ledger"], keystore.alias_names());
+}
+
+#[test]
+fn external_keystore_lazy_load_test() {
+    let (address, _, _, _) = generate_new_key(SignatureScheme::ED25519, None, None).unwrap();
+    let signer = serde_json::to_value(script_signer("exit 1")).unwrap();
+
+    // Loading the keystore does not run the signer.
+    let keystore: Keystore =
+        serde_json::from_value(serde_json::json!({ "External": { "signer": signer } })).unwrap();
+
+    // The signer failure is returned on first use.
+    assert!(keystore.addresses().is_empty());
+    let err = keystore
+        .sign_secure(&address, &b"hello".to_vec(), Intent::sui_transaction())
+        .unwrap_err();
+    assert!(format!("{err:?}").contains("failed with"), "{err:?}");
+}
+
+#[test]
+fn external_signer_command_test() {
+    let (_, keypair, _, _) = generate_new_key(SignatureScheme::ED25519, None, None).unwrap();
+    let public_key = keypair.public();
+
+    // The request is written to the signer's stdin.
+    let signer = script_signer(r#"grep -q '"method":"public_keys"' && echo '{"public_keys": []}'"#);
+    assert!(signer.public_keys().unwrap().is_empty());
+
+    // Malformed output.
+    let err = script_signer("echo not json").public_keys().unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("Cannot deserialize the response of external signer"),
+        "{err}"
+    );
+    let err = script_signer(r#"echo '{"signature": "not base64"}'"#)
+        .sign_hashed(&public_key, b"hello")
+        .unwrap_err();
+    assert!(err.to_string().contains("Invalid signature"), "{err}");
+    let err = script_signer(r#"echo '{"signature": "AA=="}'"#)
+        .public_keys()
+        .unwrap_err();
+    assert!(err.to_string().contains("Unexpected response"), "{err}");
+
+    // Non-zero exit, even with a well formed response.
+    let err = script_signer(r#"echo '{"public_keys": []}'; exit 3"#)
+        .public_keys()
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("failed with exit status: 3"),
+        "{err}"
+    );
+
+    // Missing command.
+    let err = ExternalSigner::Command {
+        command: "/nonexistent/signer".to_string(),
+        args: vec![],
+    }
+    .public_keys()
+    .unwrap_err();
+    assert!(
+        err.to_string().contains("Cannot start external signer"),
+        "{err}"
+    );
+}
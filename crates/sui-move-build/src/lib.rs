@@ -52,6 +52,8 @@ use sui_types::{
 };
 use sui_verifier::verifier as sui_bytecode_verifier;
 
+pub mod on_chain;
+
 #[cfg(test)]
 #[path = "unit_tests/build_tests.rs"]
 mod build_tests;
@@ -589,15 +591,15 @@ impl PackageHooks for SuiPackageHooks {
     }
 
     fn custom_dependency_key(&self) -> Option<String> {
-        None
+        Some(on_chain::ON_CHAIN_DEPENDENCY_KEY.to_string())
     }
 
     fn resolve_custom_dependency(
         &self,
-        _dep_name: move_symbol_pool::Symbol,
-        _info: &CustomDepInfo,
+        dep_name: move_symbol_pool::Symbol,
+        info: &CustomDepInfo,
     ) -> anyhow::Result<()> {
-        Ok(())
+        on_chain::resolve_on_chain_dependency(dep_name, info, None)
     }
 }
 
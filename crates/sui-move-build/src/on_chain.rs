@@ -0,0 +1,182 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Dependencies on packages published on-chain, whose source may not be available.
+//!
+//! An on-chain dependency is declared in the manifest with the URL of the full node to fetch it
+//! from, and the ID of the package:
+//!
+//! ```toml
+//! [dependencies]
+//! MyDep = { on-chain = "https://fullnode.mainnet.sui.io:443", address = "0x..." }
+//! ```
+//!
+//! The bytecode of the package is fetched through the [`OnChainPackageStore`] of the registered
+//! [`OnChainPackageHooks`] and written as a package in the Move cache directory (`$MOVE_HOME`),
+//! which is then built as a bytecode dependency. Package IDs are immutable, so each package is
+//! only fetched once, and packages already in the cache also resolve with [`SuiPackageHooks`].
+//! The manifest of the fetched package sets its `published-at` address so that it is linked and
+//! verified like any other published dependency, binds its runtime ID to the lowercase
+//! dependency name, and declares its own (non-system) dependencies as further on-chain
+//! dependencies.
+
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{anyhow, bail, Context};
+use move_package::{
+    compilation::package_layout::CompiledPackageLayout,
+    package_hooks::PackageHooks,
+    resolution::local_path,
+    source_package::{
+        layout::SourcePackageLayout,
+        parsed_manifest::{CustomDepInfo, DependencyKind},
+    },
+};
+use move_symbol_pool::Symbol;
+use sui_types::{base_types::ObjectID, is_system_package, move_package::MovePackage};
+
+use crate::{SuiPackageHooks, PUBLISHED_AT_MANIFEST_FIELD};
+
+/// The manifest key of on-chain dependencies, holding the URL of the node to fetch them from.
+pub const ON_CHAIN_DEPENDENCY_KEY: &str = "on-chain";
+
+/// Where the packages of on-chain dependencies are fetched from.
+pub trait OnChainPackageStore: Send + Sync {
+    /// Fetch the package at `id` from the node at `node_url`. Dependencies are resolved
+    /// synchronously, possibly from within an async runtime.
+    fn fetch_package(&self, node_url: &str, id: ObjectID) -> anyhow::Result<MovePackage>;
+}
+
+/// Package hooks which resolve on-chain dependencies missing from the cache by fetching them from
+/// `store`, and otherwise behave like [`SuiPackageHooks`].
+pub struct OnChainPackageHooks<S> {
+    store: S,
+}
+
+impl<S: OnChainPackageStore> OnChainPackageHooks<S> {
+    pub fn new(store: S) -> Self {
+        Self { store }
+    }
+}
+
+impl<S: OnChainPackageStore> PackageHooks for OnChainPackageHooks<S> {
+    fn custom_package_info_fields(&self) -> Vec<String> {
+        SuiPackageHooks.custom_package_info_fields()
+    }
+
+    fn custom_dependency_key(&self) -> Option<String> {
+        SuiPackageHooks.custom_dependency_key()
+    }
+
+    fn resolve_custom_dependency(
+        &self,
+        dep_name: Symbol,
+        info: &CustomDepInfo,
+    ) -> anyhow::Result<()> {
+        resolve_on_chain_dependency(dep_name, info, Some(&self.store))
+    }
+}
+
+/// Fetch the on-chain dependency `dep_name` from `store` into the cache, unless it is already
+/// there.
+pub(crate) fn resolve_on_chain_dependency(
+    dep_name: Symbol,
+    info: &CustomDepInfo,
+    store: Option<&dyn OnChainPackageStore>,
+) -> anyhow::Result<()> {
+    let path = local_path(&DependencyKind::Custom(info.clone()));
+    if path.join(SourcePackageLayout::Manifest.path()).exists() {
+        return Ok(());
+    }
+
+    let id = ObjectID::from_hex_literal(info.package_address.as_str())
+        .map_err(|e| anyhow!("Invalid address for on-chain dependency {dep_name}: {e}"))?;
+    let Some(store) = store else {
+        bail!("Cannot fetch on-chain dependency {dep_name}: no package store is configured");
+    };
+    let package = store
+        .fetch_package(info.node_url.as_str(), id)
+        .with_context(|| {
+            format!(
+                "Cannot fetch on-chain dependency {dep_name} at {} from {}",
+                info.package_address, info.node_url
+            )
+        })?;
+
+    write_package(&path, dep_name, info.node_url.as_str(), &package)
+}
+
+/// Write `package` as a bytecode package at `path`. The package is written to a temporary
+/// directory first, so that a failed fetch does not leave an incomplete package in the cache.
+fn write_package(
+    path: &Path,
+    dep_name: Symbol,
+    node_url: &str,
+    package: &MovePackage,
+) -> anyhow::Result<()> {
+    let parent = path
+        .parent()
+        .ok_or_else(|| anyhow!("Invalid cache path {}", path.display()))?;
+    fs::create_dir_all(parent)?;
+    let staging = tempfile::tempdir_in(parent)?;
+
+    let modules_dir = staging
+        .path()
+        .join(CompiledPackageLayout::Root.path())
+        .join(dep_name.as_str())
+        .join(CompiledPackageLayout::CompiledModules.path());
+    fs::create_dir_all(&modules_dir)?;
+    for (name, bytes) in package.serialized_module_map() {
+        fs::write(modules_dir.join(format!("{name}.mv")), bytes)?;
+    }
+
+    let mut manifest = String::new();
+    writeln!(manifest, "[package]")?;
+    writeln!(manifest, "name = \"{dep_name}\"")?;
+    writeln!(
+        manifest,
+        "{PUBLISHED_AT_MANIFEST_FIELD} = \"{}\"",
+        package.id().to_hex_literal()
+    )?;
+    writeln!(manifest, "\n[dependencies]")?;
+    for (runtime_id, upgrade_info) in package.linkage_table() {
+        if is_system_package(*runtime_id) {
+            continue;
+        }
+        let storage_id = upgrade_info.upgraded_id;
+        writeln!(
+            manifest,
+            "{} = {{ {ON_CHAIN_DEPENDENCY_KEY} = \"{node_url}\", address = \"{}\" }}",
+            transitive_dependency_name(storage_id),
+            storage_id.to_hex_literal()
+        )?;
+    }
+    writeln!(manifest, "\n[addresses]")?;
+    writeln!(
+        manifest,
+        "{} = \"{}\"",
+        dep_name.as_str().to_lowercase(),
+        package.original_package_id().to_hex_literal()
+    )?;
+    fs::write(
+        staging.path().join(SourcePackageLayout::Manifest.path()),
+        manifest,
+    )?;
+
+    // Another build may have fetched the same package in the meantime, which is as good.
+    let staging = staging.into_path();
+    if let Err(e) = fs::rename(&staging, path) {
+        fs::remove_dir_all(&staging)?;
+        if !path.join(SourcePackageLayout::Manifest.path()).exists() {
+            return Err(e).with_context(|| format!("Cannot write package to {}", path.display()));
+        }
+    }
+    Ok(())
+}
+
+/// The name of the dependencies of on-chain packages, which are only known by their ID.
+fn transitive_dependency_name(storage_id: ObjectID) -> String {
+    format!("OnChain_{}", storage_id.short_str_lossless())
+}
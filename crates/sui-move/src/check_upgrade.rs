@@ -0,0 +1,730 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Checks an upgrade of a package against the package on-chain before it is submitted, listing
+//! every change that breaks the rules of the upgrade policy rather than only failing on the first.
+
+use std::{collections::BTreeMap, fmt, fs, path::PathBuf};
+
+use anyhow::{anyhow, bail};
+use clap::{Parser, ValueEnum};
+use move_binary_format::{
+    file_format::{
+        Ability, AbilitySet, FunctionDefinitionIndex, StructDefinitionIndex, StructTypeParameter,
+        Visibility,
+    },
+    normalized, CompiledModule,
+};
+use move_cli::base;
+use move_compiler::compiled_unit::NamedCompiledModule;
+use move_core_types::{account_address::AccountAddress, identifier::Identifier};
+use move_ir_types::location::Loc;
+use move_package::BuildConfig as MoveBuildConfig;
+use sui_config::{sui_config_dir, PersistedConfig, SUI_CLIENT_CONFIG};
+use sui_move_build::{on_chain::OnChainPackageStore, BuildConfig, CompiledPackage};
+use sui_sdk::{package_store::RpcPackageStore, sui_client_config::SuiClientConfig};
+use sui_types::{base_types::ObjectID, move_package::UpgradePolicy};
+
+use crate::build::resolve_lock_file_path;
+
+#[derive(Parser)]
+#[group(id = "sui-move-check-upgrade")]
+pub struct CheckUpgrade {
+    /// ID of the package to upgrade.
+    #[clap(long)]
+    pub package_id: ObjectID,
+    /// Upgrade policy to check the upgrade against.
+    #[clap(long, value_enum, default_value_t = Policy::Compatible)]
+    pub policy: Policy,
+    /// URL of the full node to fetch the package from. Defaults to the active environment of the
+    /// client config.
+    #[clap(long)]
+    pub rpc_url: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum Policy {
+    Compatible,
+    Additive,
+    DepOnly,
+}
+
+impl From<Policy> for UpgradePolicy {
+    fn from(policy: Policy) -> Self {
+        match policy {
+            Policy::Compatible => UpgradePolicy::Compatible,
+            Policy::Additive => UpgradePolicy::Additive,
+            Policy::DepOnly => UpgradePolicy::DepOnly,
+        }
+    }
+}
+
+/// A change in a module which the upgrade policy does not allow.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Incompatibility {
+    /// Where the change is in the sources of the upgrade, as `path:line`, unless the change
+    /// removed it.
+    pub location: Option<String>,
+    pub module: String,
+    pub message: String,
+}
+
+/// The result of checking an upgrade.
+#[derive(Debug, Default)]
+pub struct UpgradeCheck {
+    /// The changes breaking the upgrade policy, in the modules the upgrade transaction rejects.
+    pub incompatibilities: Vec<Incompatibility>,
+    /// Modules where the changes found disagree with the check of the upgrade transaction, which
+    /// prevails.
+    pub mismatches: Vec<Incompatibility>,
+}
+
+impl UpgradeCheck {
+    /// Whether the upgrade transaction would accept the upgrade.
+    pub fn is_compatible(&self) -> bool {
+        self.incompatibilities.is_empty()
+    }
+}
+
+impl fmt::Display for Incompatibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(location) = &self.location {
+            write!(f, "{location}: ")?;
+        }
+        write!(f, "{}: {}", self.module, self.message)
+    }
+}
+
+impl CheckUpgrade {
+    pub fn execute(
+        &self,
+        path: Option<PathBuf>,
+        build_config: MoveBuildConfig,
+    ) -> anyhow::Result<()> {
+        let rerooted_path = base::reroot_path(path.clone())?;
+        let build_config = resolve_lock_file_path(build_config, path)?;
+        let pkg = BuildConfig {
+            config: build_config,
+            run_bytecode_verifier: true,
+            print_diags_to_stderr: true,
+        }
+        .build(rerooted_path)?;
+
+        let rpc_url = match &self.rpc_url {
+            Some(rpc_url) => rpc_url.clone(),
+            None => active_rpc_url()?,
+        };
+        let package = RpcPackageStore
+            .fetch_package(&rpc_url, self.package_id)
+            .map_err(|e| {
+                anyhow!(
+                    "Cannot fetch package {} from {rpc_url}: {e}",
+                    self.package_id
+                )
+            })?;
+
+        let local = local_modules(&pkg)?;
+        let existing_modules = package
+            .serialized_module_map()
+            .iter()
+            .map(|(name, bytes)| {
+                Ok((
+                    name.clone(),
+                    CompiledModule::deserialize_with_defaults(bytes)?,
+                ))
+            })
+            .collect::<anyhow::Result<BTreeMap<_, _>>>()?;
+        let existing = existing_modules
+            .iter()
+            .map(|(name, module)| (name.clone(), module))
+            .collect();
+
+        let policy = UpgradePolicy::from(self.policy);
+        let check = check_upgrade(
+            &existing,
+            &local,
+            package.original_package_id().into(),
+            policy,
+        );
+        for mismatch in &check.mismatches {
+            eprintln!("warning: {mismatch}");
+        }
+        if check.is_compatible() {
+            println!(
+                "Upgrade of package {} is compatible with policy {policy}",
+                self.package_id
+            );
+            return Ok(());
+        }
+        for incompatibility in &check.incompatibilities {
+            eprintln!("{incompatibility}");
+        }
+        bail!(
+            "Upgrade of package {} breaks policy {policy} with {} incompatible change(s)",
+            self.package_id,
+            check.incompatibilities.len()
+        )
+    }
+}
+
+/// The modules of the root package of `pkg`, by name.
+fn local_modules(pkg: &CompiledPackage) -> anyhow::Result<BTreeMap<String, LocalModule<'_>>> {
+    let mut local = BTreeMap::new();
+    for unit in pkg.package.root_modules() {
+        let source = fs::read_to_string(&unit.source_path)?;
+        let module = LocalModule {
+            unit: &unit.unit,
+            path: unit.source_path.display().to_string(),
+            source,
+        };
+        local.insert(unit.unit.name.to_string(), module);
+    }
+    Ok(local)
+}
+
+/// The RPC URL of the active environment of the client config.
+fn active_rpc_url() -> anyhow::Result<String> {
+    let config_path = sui_config_dir()?.join(SUI_CLIENT_CONFIG);
+    let config: SuiClientConfig = PersistedConfig::read(&config_path).map_err(|e| {
+        anyhow!(
+            "Cannot read client config at {}, pass --rpc-url instead: {e}",
+            config_path.display()
+        )
+    })?;
+    Ok(config.get_active_env()?.rpc.clone())
+}
+
+/// A module of the upgrade, with the source it was compiled from.
+pub struct LocalModule<'a> {
+    pub unit: &'a NamedCompiledModule,
+    pub path: String,
+    pub source: String,
+}
+
+impl LocalModule<'_> {
+    fn location(&self, loc: Loc) -> String {
+        let start = (loc.start() as usize).min(self.source.len());
+        let line = self.source.as_bytes()[..start]
+            .iter()
+            .filter(|b| **b == b'\n')
+            .count()
+            + 1;
+        format!("{}:{line}", self.path)
+    }
+
+    fn module_location(&self) -> String {
+        self.location(self.unit.source_map.definition_location)
+    }
+
+    fn struct_location(&self, name: &Identifier) -> String {
+        let module = &self.unit.module;
+        module
+            .struct_defs()
+            .iter()
+            .position(|def| {
+                module.identifier_at(module.struct_handle_at(def.struct_handle).name)
+                    == name.as_ident_str()
+            })
+            .and_then(|idx| {
+                self.unit
+                    .source_map
+                    .get_struct_source_map(StructDefinitionIndex(idx as u16))
+                    .ok()
+            })
+            .map(|source_map| self.location(source_map.definition_location))
+            .unwrap_or_else(|| self.module_location())
+    }
+
+    fn function_location(&self, name: &Identifier) -> String {
+        let module = &self.unit.module;
+        module
+            .function_defs()
+            .iter()
+            .position(|def| {
+                module.identifier_at(module.function_handle_at(def.function).name)
+                    == name.as_ident_str()
+            })
+            .and_then(|idx| {
+                self.unit
+                    .source_map
+                    .get_function_source_map(FunctionDefinitionIndex(idx as u16))
+                    .ok()
+            })
+            .map(|source_map| self.location(source_map.definition_location))
+            .unwrap_or_else(|| self.module_location())
+    }
+}
+
+/// Check the upgrade of the `existing` modules of a package with runtime ID `runtime_id` to the
+/// `local` ones under `policy`. The check of the upgrade transaction decides whether each module is
+/// compatible, and comparing its structs and functions finds the changes breaking the policy.
+pub fn check_upgrade(
+    existing: &BTreeMap<String, &CompiledModule>,
+    local: &BTreeMap<String, LocalModule<'_>>,
+    runtime_id: AccountAddress,
+    policy: UpgradePolicy,
+) -> UpgradeCheck {
+    let mut check = UpgradeCheck::default();
+    for (name, existing_module) in existing {
+        let Some(local_module) = local.get(name) else {
+            check.incompatibilities.push(Incompatibility {
+                location: None,
+                module: name.clone(),
+                message: "module was removed".to_string(),
+            });
+            continue;
+        };
+
+        // Upgrades are published at address 0x0, which is substituted by the runtime ID.
+        let mut upgraded = local_module.unit.module.clone();
+        let self_address = upgraded.self_handle().address;
+        if let Some(address) = upgraded
+            .address_identifiers
+            .get_mut(self_address.0 as usize)
+        {
+            if *address == AccountAddress::ZERO {
+                *address = runtime_id;
+            }
+        }
+
+        let existing = normalized::Module::new(existing_module);
+        let upgraded = normalized::Module::new(&upgraded);
+        let mut incompatibilities = vec![];
+        let mut checker = ModuleChecker {
+            existing: &existing,
+            upgraded: &upgraded,
+            local: local_module,
+            module: name,
+            incompatibilities: &mut incompatibilities,
+        };
+        match policy {
+            UpgradePolicy::Compatible => checker.check_compatible(),
+            UpgradePolicy::Additive => checker.check_inclusion(false),
+            UpgradePolicy::DepOnly => checker.check_inclusion(true),
+        }
+
+        let accepted = policy.check_compatibility(&existing, &upgraded).is_ok();
+        match (accepted, incompatibilities.is_empty()) {
+            (true, true) => (),
+            (false, false) => check.incompatibilities.extend(incompatibilities),
+            (false, true) => {
+                let incompatibility = Incompatibility {
+                    location: Some(local_module.module_location()),
+                    module: name.clone(),
+                    message: format!("module breaks policy {policy}"),
+                };
+                check.incompatibilities.push(incompatibility.clone());
+                check.mismatches.push(Incompatibility {
+                    message: format!(
+                        "module breaks policy {policy}, but no incompatible change was found in it"
+                    ),
+                    ..incompatibility
+                });
+            }
+            (true, false) => {
+                let changes: Vec<_> = incompatibilities
+                    .iter()
+                    .map(|incompatibility| incompatibility.message.as_str())
+                    .collect();
+                check.mismatches.push(Incompatibility {
+                    location: Some(local_module.module_location()),
+                    module: name.clone(),
+                    message: format!(
+                        "module is compatible with policy {policy}, but incompatible changes were \
+                         found in it: {}",
+                        changes.join("; ")
+                    ),
+                });
+            }
+        }
+    }
+    check
+}
+
+struct ModuleChecker<'a, 'l> {
+    existing: &'a normalized::Module,
+    upgraded: &'a normalized::Module,
+    local: &'a LocalModule<'l>,
+    module: &'a str,
+    incompatibilities: &'a mut Vec<Incompatibility>,
+}
+
+impl<'a> ModuleChecker<'a, '_> {
+    fn report(&mut self, location: String, message: String) {
+        self.incompatibilities.push(Incompatibility {
+            location: Some(location),
+            module: self.module.to_string(),
+            message,
+        });
+    }
+
+    fn check_address(&mut self) {
+        if self.existing.address != self.upgraded.address {
+            let location = self.local.module_location();
+            self.report(
+                location,
+                format!(
+                    "module address changed from {} to {}, the address of the package should be \
+                     0x0 or its original ID",
+                    self.existing.address.to_hex_literal(),
+                    self.upgraded.address.to_hex_literal()
+                ),
+            );
+        }
+    }
+
+    /// The rules of `UpgradePolicy::Compatible`: struct layouts, abilities and type parameters,
+    /// and public function signatures cannot change.
+    fn check_compatible(&mut self) {
+        self.check_address();
+        let (existing_module, upgraded_module) = (self.existing, self.upgraded);
+
+        for (name, existing) in &existing_module.structs {
+            let Some(upgraded) = upgraded_module.structs.get(name) else {
+                let location = self.local.module_location();
+                self.report(location, format!("struct {name} was removed"));
+                continue;
+            };
+            let location = self.local.struct_location(name);
+            if existing.abilities != upgraded.abilities {
+                self.report(
+                    location.clone(),
+                    format!(
+                        "abilities of struct {name} changed from {} to {}",
+                        format_abilities(existing.abilities),
+                        format_abilities(upgraded.abilities)
+                    ),
+                );
+            }
+            if existing.type_parameters != upgraded.type_parameters {
+                self.report(
+                    location.clone(),
+                    format!(
+                        "type parameters of struct {name} changed from <{}> to <{}>",
+                        format_struct_type_parameters(&existing.type_parameters),
+                        format_struct_type_parameters(&upgraded.type_parameters)
+                    ),
+                );
+            }
+            if existing.fields != upgraded.fields {
+                self.report(
+                    location,
+                    format!(
+                        "layout of struct {name} changed from {{ {} }} to {{ {} }}",
+                        format_fields(&existing.fields),
+                        format_fields(&upgraded.fields)
+                    ),
+                );
+            }
+        }
+
+        for (name, existing) in &existing_module.functions {
+            if existing.visibility != Visibility::Public {
+                continue;
+            }
+            let Some(upgraded) = upgraded_module.functions.get(name) else {
+                let location = self.local.module_location();
+                self.report(location, format!("public function {name} was removed"));
+                continue;
+            };
+            let location = self.local.function_location(name);
+            if upgraded.visibility != Visibility::Public {
+                self.report(
+                    location.clone(),
+                    format!("public function {name} is no longer public"),
+                );
+            }
+            if existing.parameters != upgraded.parameters
+                || existing.return_ != upgraded.return_
+                || !fun_type_parameters_compatible(
+                    &existing.type_parameters,
+                    &upgraded.type_parameters,
+                )
+            {
+                self.report(
+                    location,
+                    format!(
+                        "signature of public function {name} changed from {} to {}",
+                        format_signature(name, existing),
+                        format_signature(name, upgraded)
+                    ),
+                );
+            }
+        }
+    }
+
+    /// The rules of `UpgradePolicy::Additive`, and of `UpgradePolicy::DepOnly` if `equal`: existing
+    /// structs and functions cannot change, and with `DepOnly` none can be added either.
+    fn check_inclusion(&mut self, equal: bool) {
+        self.check_address();
+        let (existing_module, upgraded_module) = (self.existing, self.upgraded);
+        if existing_module.file_format_version > upgraded_module.file_format_version {
+            let location = self.local.module_location();
+            self.report(
+                location,
+                format!(
+                    "bytecode version decreased from {} to {}",
+                    existing_module.file_format_version, upgraded_module.file_format_version
+                ),
+            );
+        }
+
+        for (name, existing) in &existing_module.structs {
+            match upgraded_module.structs.get(name) {
+                Some(upgraded) if upgraded == existing => (),
+                Some(_) => {
+                    let location = self.local.struct_location(name);
+                    self.report(location, format!("struct {name} changed"));
+                }
+                None => {
+                    let location = self.local.module_location();
+                    self.report(location, format!("struct {name} was removed"));
+                }
+            }
+        }
+
+        for (name, existing) in &existing_module.functions {
+            match upgraded_module.functions.get(name) {
+                Some(upgraded) if upgraded == existing => (),
+                Some(_) => {
+                    let location = self.local.function_location(name);
+                    self.report(location, format!("function {name} changed"));
+                }
+                None => {
+                    let location = self.local.module_location();
+                    self.report(location, format!("function {name} was removed"));
+                }
+            }
+        }
+
+        if !equal {
+            return;
+        }
+        for name in upgraded_module.structs.keys() {
+            if !existing_module.structs.contains_key(name) {
+                let location = self.local.struct_location(name);
+                self.report(location, format!("struct {name} was added"));
+            }
+        }
+        for name in upgraded_module.functions.keys() {
+            if !existing_module.functions.contains_key(name) {
+                let location = self.local.function_location(name);
+                self.report(location, format!("function {name} was added"));
+            }
+        }
+        if existing_module.friends.len() != upgraded_module.friends.len() {
+            let location = self.local.module_location();
+            self.report(location, "friend declarations changed".to_string());
+        }
+    }
+}
+
+/// Function type parameters can keep or drop constraints, but cannot be added or removed.
+fn fun_type_parameters_compatible(existing: &[AbilitySet], upgraded: &[AbilitySet]) -> bool {
+    existing.len() == upgraded.len()
+        && existing
+            .iter()
+            .zip(upgraded)
+            .all(|(existing, upgraded)| upgraded.is_subset(*existing))
+}
+
+fn format_abilities(abilities: AbilitySet) -> String {
+    let abilities: Vec<_> = abilities
+        .into_iter()
+        .map(|ability| match ability {
+            Ability::Copy => "copy",
+            Ability::Drop => "drop",
+            Ability::Store => "store",
+            Ability::Key => "key",
+        })
+        .collect();
+    if abilities.is_empty() {
+        "no abilities".to_string()
+    } else {
+        abilities.join(", ")
+    }
+}
+
+fn format_constraints(abilities: AbilitySet) -> String {
+    if abilities == AbilitySet::EMPTY {
+        String::new()
+    } else {
+        format!(": {}", format_abilities(abilities).replace(", ", " + "))
+    }
+}
+
+fn format_struct_type_parameters(type_parameters: &[StructTypeParameter]) -> String {
+    type_parameters
+        .iter()
+        .enumerate()
+        .map(|(i, param)| {
+            let phantom = if param.is_phantom { "phantom " } else { "" };
+            format!("{phantom}T{i}{}", format_constraints(param.constraints))
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn format_fields(fields: &[normalized::Field]) -> String {
+    fields
+        .iter()
+        .map(|field| format!("{}: {}", field.name, field.type_))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn format_signature(name: &Identifier, function: &normalized::Function) -> String {
+    let type_parameters = if function.type_parameters.is_empty() {
+        String::new()
+    } else {
+        let type_parameters: Vec<_> = function
+            .type_parameters
+            .iter()
+            .enumerate()
+            .map(|(i, constraints)| format!("T{i}{}", format_constraints(*constraints)))
+            .collect();
+        format!("<{}>", type_parameters.join(", "))
+    };
+    let parameters: Vec<_> = function.parameters.iter().map(|t| t.to_string()).collect();
+    let return_: Vec<_> = function.return_.iter().map(|t| t.to_string()).collect();
+    let return_ = match return_.len() {
+        0 => String::new(),
+        1 => format!(": {}", return_[0]),
+        _ => format!(": ({})", return_.join(", ")),
+    };
+    format!(
+        "{name}{type_parameters}({}){return_}",
+        parameters.join(", ")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build(package: &str) -> CompiledPackage {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/data/check_upgrade")
+            .join(package);
+        BuildConfig::new_for_testing().build(path).unwrap()
+    }
+
+    /// Check the upgrade of the `base` fixture package to the `upgraded` one under `policy`.
+    fn check(upgraded: &str, policy: UpgradePolicy) -> UpgradeCheck {
+        let base = build("base");
+        let existing = base
+            .package
+            .root_modules()
+            .map(|unit| (unit.unit.name.to_string(), &unit.unit.module))
+            .collect();
+        let upgraded = build(upgraded);
+        let local = local_modules(&upgraded).unwrap();
+        let check = check_upgrade(&existing, &local, AccountAddress::ZERO, policy);
+        assert!(
+            check.mismatches.is_empty(),
+            "Unexpected mismatches: {:?}",
+            check.mismatches
+        );
+        check
+    }
+
+    /// Assert that the only incompatibility of `check` is in `module`, at `location` relative to the
+    /// package, with `message`.
+    fn assert_incompatible(
+        check: &UpgradeCheck,
+        module: &str,
+        location: Option<&str>,
+        message: &str,
+    ) {
+        let [incompatibility] = check.incompatibilities.as_slice() else {
+            panic!(
+                "Expected a single incompatibility, got {:?}",
+                check.incompatibilities
+            );
+        };
+        assert_eq!(incompatibility.module, module);
+        assert_eq!(incompatibility.message, message);
+        match (&incompatibility.location, location) {
+            (Some(actual), Some(expected)) => assert!(
+                actual.ends_with(expected),
+                "Expected location {expected}, got {actual}"
+            ),
+            (actual, expected) => assert_eq!(actual.as_deref(), expected),
+        }
+    }
+
+    #[test]
+    fn test_unchanged() {
+        for policy in [
+            UpgradePolicy::Compatible,
+            UpgradePolicy::Additive,
+            UpgradePolicy::DepOnly,
+        ] {
+            assert!(check("base", policy).is_compatible());
+        }
+    }
+
+    #[test]
+    fn test_struct_layout() {
+        assert_incompatible(
+            &check("struct_layout", UpgradePolicy::Compatible),
+            "m",
+            Some("sources/m.move:5"),
+            "layout of struct S changed from { a: u64 } to { a: u64, b: bool }",
+        );
+        assert_incompatible(
+            &check("struct_layout", UpgradePolicy::Additive),
+            "m",
+            Some("sources/m.move:5"),
+            "struct S changed",
+        );
+    }
+
+    #[test]
+    fn test_abilities() {
+        assert_incompatible(
+            &check("abilities", UpgradePolicy::Compatible),
+            "m",
+            Some("sources/m.move:5"),
+            "abilities of struct S changed from drop, store to copy, drop, store",
+        );
+    }
+
+    #[test]
+    fn test_public_signature() {
+        assert_incompatible(
+            &check("public_signature", UpgradePolicy::Compatible),
+            "m",
+            Some("sources/m.move:9"),
+            "signature of public function f changed from f(u64): u64 to f(u64, u64): u64",
+        );
+    }
+
+    #[test]
+    fn test_removed_module() {
+        for policy in [
+            UpgradePolicy::Compatible,
+            UpgradePolicy::Additive,
+            UpgradePolicy::DepOnly,
+        ] {
+            assert_incompatible(
+                &check("removed_module", policy),
+                "n",
+                None,
+                "module was removed",
+            );
+        }
+    }
+
+    #[test]
+    fn test_added_function() {
+        assert!(check("added_function", UpgradePolicy::Compatible).is_compatible());
+        assert!(check("added_function", UpgradePolicy::Additive).is_compatible());
+        assert_incompatible(
+            &check("added_function", UpgradePolicy::DepOnly),
+            "m",
+            Some("sources/m.move:11"),
+            "function g was added",
+        );
+    }
+}
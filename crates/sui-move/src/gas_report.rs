@@ -0,0 +1,197 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Estimates of what Move unit tests would cost under Sui's gas model, to track the gas usage of
+//! packages over time.
+
+use move_unit_test::gas_report::GasReport as TestsGasReport;
+use serde::Serialize;
+use std::{collections::BTreeMap, fs, path::Path};
+use sui_protocol_config::ProtocolConfig;
+use sui_types::{
+    error::SuiResult,
+    gas::{GasCostSummary, SuiGasStatus, SuiGasStatusAPI},
+    gas_model::units_types::Gas,
+};
+
+/// The statistic holding the number of objects created by a test.
+pub(crate) const CREATED_OBJECTS: &str = "created_objects";
+/// The statistic holding the total size of the objects created by a test, as metered for storage.
+pub(crate) const CREATED_OBJECT_BYTES: &str = "created_object_bytes";
+
+#[derive(Serialize)]
+pub struct GasReport {
+    /// The gas price costs are estimated with, in MIST per gas unit.
+    pub gas_price: u64,
+    pub tests: BTreeMap<String, TestGasReport>,
+}
+
+#[derive(Serialize)]
+pub struct TestGasReport {
+    pub passed: bool,
+    /// The gas units used by the test.
+    pub gas_used: u64,
+    /// The computation cost of the test, in MIST.
+    pub computation_cost: u64,
+    pub created_objects: u64,
+    pub created_object_bytes: u64,
+    /// The cost of storing the objects created by the test, in MIST.
+    pub storage_cost: u64,
+    /// The gas units used by each function called by the test.
+    pub functions: BTreeMap<String, FunctionGasReport>,
+}
+
+#[derive(Serialize)]
+pub struct FunctionGasReport {
+    pub calls: u64,
+    pub gas_used: u64,
+}
+
+impl GasReport {
+    /// Estimate the cost of the tests in `report` at `gas_price`, with the gas model of the latest
+    /// protocol version.
+    pub fn new(report: TestsGasReport, gas_price: u64) -> SuiResult<Self> {
+        let config = ProtocolConfig::get_for_max_version_UNSAFE();
+        let mut tests = BTreeMap::new();
+        for (name, test) in report {
+            let created_objects = test.statistics.get(CREATED_OBJECTS).copied().unwrap_or(0);
+            let created_object_bytes = test
+                .statistics
+                .get(CREATED_OBJECT_BYTES)
+                .copied()
+                .unwrap_or(0);
+            let summary = estimate_cost(test.gas_used, created_object_bytes, gas_price, &config)?;
+            let functions = test
+                .functions
+                .into_iter()
+                .map(|(function, gas)| {
+                    let report = FunctionGasReport {
+                        calls: gas.calls,
+                        gas_used: gas.gas_used,
+                    };
+                    (function, report)
+                })
+                .collect();
+            tests.insert(
+                name,
+                TestGasReport {
+                    passed: test.passed,
+                    gas_used: test.gas_used,
+                    computation_cost: summary.computation_cost,
+                    created_objects,
+                    created_object_bytes,
+                    storage_cost: summary.storage_cost,
+                    functions,
+                },
+            );
+        }
+        Ok(Self { gas_price, tests })
+    }
+
+    /// Print the estimated cost of each test.
+    pub fn print(&self) {
+        let width = self
+            .tests
+            .keys()
+            .map(|name| name.len())
+            .max()
+            .unwrap_or(0)
+            .max("Test Name".len());
+        println!(
+            "\nGas Cost Estimates (gas price: {} MIST):\n",
+            self.gas_price
+        );
+        println!(
+            "┌─{:─^width$}─┬─{:─^15}─┬─{:─^20}─┬─{:─^20}─┐",
+            "", "", "", ""
+        );
+        println!(
+            "│ {:^width$} │ {:^15} │ {:^20} │ {:^20} │",
+            "Test Name", "Gas Used", "Computation (MIST)", "Storage (MIST)"
+        );
+        for (name, test) in &self.tests {
+            println!(
+                "├─{:─^width$}─┼─{:─^15}─┼─{:─^20}─┼─{:─^20}─┤",
+                "", "", "", ""
+            );
+            println!(
+                "│ {:<width$} │ {:^15} │ {:^20} │ {:^20} │",
+                name, test.gas_used, test.computation_cost, test.storage_cost
+            );
+        }
+        println!(
+            "└─{:─^width$}─┴─{:─^15}─┴─{:─^20}─┴─{:─^20}─┘",
+            "", "", "", ""
+        );
+    }
+
+    /// Write the report as JSON to `path`.
+    pub fn write_json(&self, path: &Path) -> anyhow::Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+/// The cost of a transaction using `gas_used` gas units and creating objects of
+/// `created_object_bytes` in total, at `gas_price`.
+fn estimate_cost(
+    gas_used: u64,
+    created_object_bytes: u64,
+    gas_price: u64,
+    config: &ProtocolConfig,
+) -> SuiResult<GasCostSummary> {
+    let mut gas_status = SuiGasStatus::new(config.max_tx_gas(), gas_price, gas_price, config)?;
+    // Going over the budget caps the computation cost at the budget, which is as good an estimate.
+    let _ = gas_status
+        .move_gas_status_mut()
+        .deduct_gas(Gas::new(gas_used).to_unit());
+    let _ = gas_status.bucketize_computation();
+    gas_status.track_storage_mutation(created_object_bytes as usize, 0);
+    Ok(gas_status.summary())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GAS_PRICE: u64 = 1_000;
+
+    #[test]
+    fn estimate_cost_rounds_computation() {
+        let config = ProtocolConfig::get_for_max_version_UNSAFE();
+        let step = config.gas_rounding_step();
+
+        let summary = estimate_cost(1, 0, GAS_PRICE, &config).unwrap();
+        assert_eq!(summary.computation_cost, step * GAS_PRICE);
+        assert_eq!(summary.storage_cost, 0);
+
+        let summary = estimate_cost(step, 0, GAS_PRICE, &config).unwrap();
+        assert_eq!(summary.computation_cost, step * GAS_PRICE);
+
+        let summary = estimate_cost(step + 1, 0, GAS_PRICE, &config).unwrap();
+        assert_eq!(summary.computation_cost, 2 * step * GAS_PRICE);
+    }
+
+    #[test]
+    fn estimate_cost_caps_computation_at_budget() {
+        let config = ProtocolConfig::get_for_max_version_UNSAFE();
+        let summary = estimate_cost(config.max_tx_gas(), 0, GAS_PRICE, &config).unwrap();
+        assert_eq!(summary.computation_cost, config.max_tx_gas());
+    }
+
+    #[test]
+    fn estimate_cost_of_storage() {
+        let config = ProtocolConfig::get_for_max_version_UNSAFE();
+        let summary = estimate_cost(0, 500, GAS_PRICE, &config).unwrap();
+        assert_eq!(
+            summary.storage_cost,
+            500 * config.obj_data_cost_refundable() * config.storage_gas_price()
+        );
+        // the storage cost does not depend on the gas price
+        let summary = estimate_cost(0, 500, 2 * GAS_PRICE, &config).unwrap();
+        assert_eq!(
+            summary.storage_cost,
+            500 * config.obj_data_cost_refundable() * config.storage_gas_price()
+        );
+    }
+}
@@ -12,10 +12,14 @@ use sui_move_build::set_sui_flavor;
 
 #[cfg(feature = "build")]
 pub mod build;
+#[cfg(feature = "check_upgrade")]
+pub mod check_upgrade;
 #[cfg(feature = "coverage")]
 pub mod coverage;
 #[cfg(feature = "disassemble")]
 pub mod disassemble;
+#[cfg(feature = "unit_test")]
+pub mod gas_report;
 pub mod new;
 #[cfg(feature = "prove")]
 pub mod prove;
@@ -26,6 +30,8 @@ pub mod unit_test;
 pub enum Command {
     #[cfg(feature = "build")]
     Build(build::Build),
+    #[cfg(feature = "check_upgrade")]
+    CheckUpgrade(check_upgrade::CheckUpgrade),
     #[cfg(feature = "coverage")]
     Coverage(coverage::Coverage),
     #[cfg(feature = "disassemble")]
@@ -55,6 +61,8 @@ pub fn execute_move_command(
     match command {
         #[cfg(feature = "build")]
         Command::Build(c) => c.execute(package_path, build_config),
+        #[cfg(feature = "check_upgrade")]
+        Command::CheckUpgrade(c) => c.execute(package_path, build_config),
         #[cfg(feature = "coverage")]
         Command::Coverage(c) => c.execute(package_path, build_config),
         #[cfg(feature = "disassemble")]
@@ -72,6 +80,7 @@ pub fn execute_move_command(
                 report_statistics: c.test.report_statistics.clone(),
                 check_stackless_vm: c.test.check_stackless_vm,
                 verbose: c.test.verbose_mode,
+                report_gas: c.test.gas_report || c.gas_report_json.is_some(),
                 ..UnitTestingConfig::default_with_bound(None)
             };
             let result = c.execute(package_path, build_config, unit_test_config)?;
@@ -1,16 +1,28 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::gas_report::{GasReport, CREATED_OBJECTS, CREATED_OBJECT_BYTES};
 use clap::Parser;
 use move_cli::base::{
     self,
     test::{self, UnitTestResult},
 };
 use move_package::BuildConfig;
-use move_unit_test::{extensions::set_extension_hook, UnitTestingConfig};
+use move_unit_test::{
+    extensions::{set_extension_hook, set_statistics_hook},
+    gas_report::GasReport as TestsGasReport,
+    UnitTestingConfig,
+};
 use move_vm_runtime::native_extensions::NativeContextExtensions;
 use once_cell::sync::Lazy;
-use std::{collections::BTreeMap, path::PathBuf, sync::Arc};
+use std::{
+    collections::BTreeMap,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
 use sui_move_build::decorate_warnings;
 use sui_move_natives::{object_runtime::ObjectRuntime, NativesCostTable};
 use sui_protocol_config::ProtocolConfig;
@@ -26,6 +38,9 @@ use sui_types::{
 // Move unit tests will halt after executing this many steps. This is a protection to avoid divergence
 const MAX_UNIT_TEST_INSTRUCTIONS: u64 = 1_000_000;
 
+// The gas price the cost of tests is estimated with in gas reports, unless specified
+const DEFAULT_GAS_REPORT_GAS_PRICE: u64 = 1_000;
+
 #[derive(Parser)]
 #[group(id = "sui-move-test")]
 pub struct Test {
@@ -34,6 +49,13 @@ pub struct Test {
     /// If `true`, disable linters
     #[clap(long, global = true)]
     pub no_lint: bool,
+    /// Write the gas report of the tests as JSON to this file, to track their gas usage over time.
+    /// Implies `--gas-report`
+    #[clap(long = "gas-report-json")]
+    pub gas_report_json: Option<PathBuf>,
+    /// The gas price to estimate the cost of tests with in gas reports, in MIST per gas unit
+    #[clap(long = "gas-price", default_value_t = DEFAULT_GAS_REPORT_GAS_PRICE)]
+    pub gas_price: u64,
 }
 
 impl Test {
@@ -50,12 +72,21 @@ impl Test {
         }
         // find manifest file directory from a given path or (if missing) from current dir
         let rerooted_path = base::reroot_path(path)?;
-        run_move_unit_tests(
+        let (result, gas_report) = run_move_unit_tests_with_gas_report(
             rerooted_path,
             build_config,
             Some(unit_test_config),
             self.test.compute_coverage,
-        )
+        )?;
+
+        if let Some(gas_report) = gas_report {
+            let gas_report = GasReport::new(gas_report, self.gas_price)?;
+            gas_report.print();
+            if let Some(path) = &self.gas_report_json {
+                gas_report.write_json(path)?;
+            }
+        }
+        Ok(result)
     }
 }
 
@@ -83,8 +114,14 @@ impl ChildObjectResolver for DummyChildObjectStore {
 
 static TEST_STORE: Lazy<DummyChildObjectStore> = Lazy::new(|| DummyChildObjectStore {});
 
-static SET_EXTENSION_HOOK: Lazy<()> =
-    Lazy::new(|| set_extension_hook(Box::new(new_testing_object_and_natives_cost_runtime)));
+// Whether the tests being run report their gas usage, to only record the sizes of the objects they
+// create, needed to estimate their storage cost, when it is the case
+static REPORT_GAS: AtomicBool = AtomicBool::new(false);
+
+static SET_EXTENSION_HOOKS: Lazy<()> = Lazy::new(|| {
+    set_extension_hook(Box::new(new_testing_object_and_natives_cost_runtime));
+    set_statistics_hook(Box::new(created_object_statistics));
+});
 
 /// This function returns a result of UnitTestResult. The outer result indicates whether it
 /// successfully started running the test, and the inner result indicatests whether all tests pass.
@@ -94,13 +131,26 @@ pub fn run_move_unit_tests(
     config: Option<UnitTestingConfig>,
     compute_coverage: bool,
 ) -> anyhow::Result<UnitTestResult> {
-    // bind the extension hook if it has not yet been done
-    Lazy::force(&SET_EXTENSION_HOOK);
+    run_move_unit_tests_with_gas_report(path, build_config, config, compute_coverage)
+        .map(|(test_result, _)| test_result)
+}
+
+/// Like `run_move_unit_tests`, but also returns the gas report of the tests if `report_gas` is set
+/// in `config`.
+pub fn run_move_unit_tests_with_gas_report(
+    path: PathBuf,
+    build_config: BuildConfig,
+    config: Option<UnitTestingConfig>,
+    compute_coverage: bool,
+) -> anyhow::Result<(UnitTestResult, Option<TestsGasReport>)> {
+    // bind the extension hooks if it has not yet been done
+    Lazy::force(&SET_EXTENSION_HOOKS);
 
     let config = config
         .unwrap_or_else(|| UnitTestingConfig::default_with_bound(Some(MAX_UNIT_TEST_INSTRUCTIONS)));
+    REPORT_GAS.store(config.report_gas, Ordering::Relaxed);
 
-    let result = move_cli::base::test::run_move_unit_tests(
+    let result = move_cli::base::test::run_move_unit_tests_with_gas_report(
         &path,
         build_config,
         UnitTestingConfig {
@@ -112,13 +162,13 @@ pub fn run_move_unit_tests(
         compute_coverage,
         &mut std::io::stdout(),
     );
-    result.map(|(test_result, warning_diags)| {
+    result.map(|(test_result, warning_diags, gas_report)| {
         if test_result == UnitTestResult::Success {
             if let Some(diags) = warning_diags {
                 decorate_warnings(diags, None);
             }
         }
-        test_result
+        (test_result, gas_report)
     })
 }
 
@@ -128,15 +178,100 @@ fn new_testing_object_and_natives_cost_runtime(ext: &mut NativeContextExtensions
     let metrics = Arc::new(LimitsMetrics::new(&registry));
     let store = Lazy::force(&TEST_STORE);
 
-    ext.add(ObjectRuntime::new(
+    let mut object_runtime = ObjectRuntime::new(
         store,
         BTreeMap::new(),
         false,
         Box::leak(Box::new(ProtocolConfig::get_for_max_version_UNSAFE())), // leak for testing
         metrics,
         0, // epoch id
-    ));
+    );
+    if REPORT_GAS.load(Ordering::Relaxed) {
+        object_runtime.record_created_object_sizes_for_testing();
+    }
+    ext.add(object_runtime);
     ext.add(NativesCostTable::from_protocol_config(
         &ProtocolConfig::get_for_max_version_UNSAFE(),
     ));
 }
+
+fn created_object_statistics(ext: &NativeContextExtensions) -> BTreeMap<String, u64> {
+    let object_runtime: &ObjectRuntime = ext.get();
+    let Some(sizes) = object_runtime.created_object_sizes_for_testing() else {
+        return BTreeMap::new();
+    };
+    BTreeMap::from([
+        (CREATED_OBJECTS.to_owned(), sizes.len() as u64),
+        (
+            CREATED_OBJECT_BYTES.to_owned(),
+            sizes.values().sum::<usize>() as u64,
+        ),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{execute_move_command, Command};
+    use clap::Parser;
+    use std::{fs, path::PathBuf};
+    use sui_protocol_config::ProtocolConfig;
+
+    #[test]
+    fn gas_report_json_test() {
+        let package = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/data/gas_report");
+        let dir = tempfile::tempdir().unwrap();
+        let report_path = dir.path().join("gas_report.json");
+        let command = Command::parse_from([
+            "sui-move",
+            "test",
+            "--gas-report-json",
+            report_path.to_str().unwrap(),
+            "--gas-price",
+            "2000",
+        ]);
+        execute_move_command(
+            Some(package),
+            sui_move_build::BuildConfig::new_for_testing().config,
+            command,
+        )
+        .unwrap();
+
+        let report: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&report_path).unwrap()).unwrap();
+        assert_eq!(report["gas_price"], 2000);
+        let tests = report["tests"].as_object().unwrap();
+        assert_eq!(tests.len(), 2);
+        let test = |name: &str| {
+            tests
+                .iter()
+                .find(|(test, _)| test.ends_with(name))
+                .unwrap_or_else(|| panic!("No report for {name}"))
+                .1
+        };
+
+        let config = ProtocolConfig::get_for_max_version_UNSAFE();
+        let created = test("::objects::test_create_objects");
+        assert_eq!(created["passed"], true);
+        assert_eq!(created["created_objects"], 2);
+        let created_object_bytes = created["created_object_bytes"].as_u64().unwrap();
+        assert!(created_object_bytes > 0);
+        assert_eq!(
+            created["storage_cost"].as_u64().unwrap(),
+            created_object_bytes * config.obj_data_cost_refundable() * config.storage_gas_price()
+        );
+        assert!(created["computation_cost"].as_u64().unwrap() > 0);
+        let functions = created["functions"].as_object().unwrap();
+        let create = functions
+            .iter()
+            .find(|(function, _)| function.ends_with("::objects::create"))
+            .unwrap()
+            .1;
+        assert_eq!(create["calls"], 2);
+
+        let empty = test("::objects::test_no_objects");
+        assert_eq!(empty["passed"], true);
+        assert_eq!(empty["created_objects"], 0);
+        assert_eq!(empty["created_object_bytes"], 0);
+        assert_eq!(empty["storage_cost"], 0);
+    }
+}
@@ -0,0 +1,11 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+module base::m {
+    struct S has drop, store {
+        a: u64,
+        b: bool,
+    }
+
+    public fun f(x: u64): u64 { x }
+}
@@ -0,0 +1,34 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+module gas_report::objects {
+    use sui::object::{Self, UID};
+    use sui::transfer;
+    use sui::tx_context::{Self, TxContext};
+    #[test_only]
+    use sui::test_scenario;
+
+    struct Object has key {
+        id: UID,
+        value: u64,
+    }
+
+    public fun create(value: u64, ctx: &mut TxContext) {
+        transfer::transfer(Object { id: object::new(ctx), value }, tx_context::sender(ctx))
+    }
+
+    #[test]
+    fun test_create_objects() {
+        let scenario_val = test_scenario::begin(@0xA);
+        let scenario = &mut scenario_val;
+        create(1, test_scenario::ctx(scenario));
+        create(2, test_scenario::ctx(scenario));
+        test_scenario::end(scenario_val);
+    }
+
+    #[test]
+    fun test_no_objects() {
+        let scenario_val = test_scenario::begin(@0xA);
+        test_scenario::end(scenario_val);
+    }
+}
@@ -0,0 +1,273 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::config::{AggregationConfig, AggregationMethod};
+use std::collections::HashMap;
+use std::time::SystemTime;
+
+/// A value reported by a single data source.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Observation {
+    pub value: f64,
+    pub timestamp: SystemTime,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RejectionReason {
+    Stale,
+    Deviation,
+}
+
+impl RejectionReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RejectionReason::Stale => "stale",
+            RejectionReason::Deviation => "deviation",
+        }
+    }
+}
+
+/// Why no value is submitted for a feed in a round.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SkipReason {
+    /// Not enough sources returned a fresh value.
+    InsufficientSources { available: usize, required: usize },
+    /// Enough sources returned a fresh value, but too many of them disagree with the median.
+    ExcessiveDeviation { agreeing: usize, required: usize },
+}
+
+impl SkipReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SkipReason::InsufficientSources { .. } => "insufficient_sources",
+            SkipReason::ExcessiveDeviation { .. } => "excessive_deviation",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Aggregate {
+    pub value: f64,
+    /// Sources the value was aggregated from.
+    pub sources: Vec<String>,
+    /// Sources which were discarded.
+    pub rejected: Vec<(String, RejectionReason)>,
+}
+
+/// Aggregates the latest observation of each source into a single value, discarding sources
+/// which are stale or deviate too much from the others.
+pub fn aggregate(
+    config: &AggregationConfig,
+    observations: &HashMap<String, Observation>,
+    now: SystemTime,
+) -> Result<Aggregate, SkipReason> {
+    let required = config.min_sources.max(1);
+    let mut rejected = vec![];
+    let mut fresh = vec![];
+    for (source, observation) in observations {
+        let age = now
+            .duration_since(observation.timestamp)
+            .unwrap_or_default();
+        match config.max_staleness {
+            Some(max_staleness) if age > max_staleness => {
+                rejected.push((source.clone(), RejectionReason::Stale));
+            }
+            _ => fresh.push((source.clone(), observation.value)),
+        }
+    }
+    if fresh.len() < required {
+        return Err(SkipReason::InsufficientSources {
+            available: fresh.len(),
+            required,
+        });
+    }
+
+    let accepted = match config.max_deviation {
+        Some(max_deviation) => {
+            let median = median(&mut fresh.iter().map(|(_, v)| *v).collect::<Vec<_>>());
+            let (accepted, deviating): (Vec<_>, Vec<_>) = fresh
+                .into_iter()
+                .partition(|(_, value)| relative_deviation(*value, median) <= max_deviation);
+            rejected.extend(
+                deviating
+                    .into_iter()
+                    .map(|(source, _)| (source, RejectionReason::Deviation)),
+            );
+            accepted
+        }
+        None => fresh,
+    };
+    if accepted.len() < required {
+        return Err(SkipReason::ExcessiveDeviation {
+            agreeing: accepted.len(),
+            required,
+        });
+    }
+
+    let (mut sources, mut values): (Vec<_>, Vec<_>) = accepted.into_iter().unzip();
+    sources.sort();
+    rejected.sort();
+    let value = match config.method {
+        AggregationMethod::Median => median(&mut values),
+        AggregationMethod::TrimmedMean { trim_ratio } => trimmed_mean(&mut values, trim_ratio),
+    };
+    Ok(Aggregate {
+        value,
+        sources,
+        rejected,
+    })
+}
+
+fn relative_deviation(value: f64, reference: f64) -> f64 {
+    if reference == 0.0 {
+        if value == 0.0 {
+            0.0
+        } else {
+            f64::INFINITY
+        }
+    } else {
+        ((value - reference) / reference).abs()
+    }
+}
+
+/// Median of a non-empty list of values, averaging the two middle values for even lengths.
+fn median(values: &mut [f64]) -> f64 {
+    values.sort_by(f64::total_cmp);
+    let mid = values.len() / 2;
+    if values.len() % 2 == 0 {
+        (values[mid - 1] + values[mid]) / 2.0
+    } else {
+        values[mid]
+    }
+}
+
+/// Mean of a non-empty list of values after discarding `trim_ratio` of the values at each end.
+/// Falls back to the median if trimming would discard every value.
+fn trimmed_mean(values: &mut [f64], trim_ratio: f64) -> f64 {
+    values.sort_by(f64::total_cmp);
+    let trimmed = (values.len() as f64 * trim_ratio.clamp(0.0, 0.5)).floor() as usize;
+    if 2 * trimmed >= values.len() {
+        return median(values);
+    }
+    let kept = &values[trimmed..values.len() - trimmed];
+    kept.iter().sum::<f64>() / kept.len() as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn observations(values: &[(&str, f64, u64)], now: SystemTime) -> HashMap<String, Observation> {
+        values
+            .iter()
+            .map(|(source, value, age_secs)| {
+                (
+                    source.to_string(),
+                    Observation {
+                        value: *value,
+                        timestamp: now - Duration::from_secs(*age_secs),
+                    },
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_median() {
+        let now = SystemTime::now();
+        let config = AggregationConfig::default();
+        let odd = observations(&[("a", 3.0, 0), ("b", 1.0, 0), ("c", 2.0, 0)], now);
+        assert_eq!(aggregate(&config, &odd, now).unwrap().value, 2.0);
+
+        let even = observations(
+            &[("a", 4.0, 0), ("b", 1.0, 0), ("c", 2.0, 0), ("d", 3.0, 0)],
+            now,
+        );
+        let aggregate = aggregate(&config, &even, now).unwrap();
+        assert_eq!(aggregate.value, 2.5);
+        assert_eq!(aggregate.sources, vec!["a", "b", "c", "d"]);
+        assert!(aggregate.rejected.is_empty());
+    }
+
+    #[test]
+    fn test_trimmed_mean() {
+        let now = SystemTime::now();
+        let config = AggregationConfig {
+            method: AggregationMethod::TrimmedMean { trim_ratio: 0.2 },
+            ..Default::default()
+        };
+        let values = observations(
+            &[
+                ("a", 1.0, 0),
+                ("b", 10.0, 0),
+                ("c", 11.0, 0),
+                ("d", 12.0, 0),
+                ("e", 100.0, 0),
+            ],
+            now,
+        );
+        assert_eq!(aggregate(&config, &values, now).unwrap().value, 11.0);
+
+        // Trimming everything falls back to the median.
+        let config = AggregationConfig {
+            method: AggregationMethod::TrimmedMean { trim_ratio: 0.5 },
+            ..Default::default()
+        };
+        let values = observations(&[("a", 1.0, 0), ("b", 3.0, 0)], now);
+        assert_eq!(aggregate(&config, &values, now).unwrap().value, 2.0);
+    }
+
+    #[test]
+    fn test_stale_sources_are_skipped() {
+        let now = SystemTime::now();
+        let config = AggregationConfig {
+            min_sources: 2,
+            max_staleness: Some(Duration::from_secs(10)),
+            ..Default::default()
+        };
+        let values = observations(&[("a", 1.0, 1), ("b", 2.0, 5), ("c", 50.0, 60)], now);
+        let aggregate = aggregate(&config, &values, now).unwrap();
+        assert_eq!(aggregate.value, 1.5);
+        assert_eq!(
+            aggregate.rejected,
+            vec![("c".to_string(), RejectionReason::Stale)]
+        );
+
+        let values = observations(&[("a", 1.0, 1), ("b", 2.0, 20)], now);
+        assert_eq!(
+            super::aggregate(&config, &values, now),
+            Err(SkipReason::InsufficientSources {
+                available: 1,
+                required: 2
+            })
+        );
+    }
+
+    #[test]
+    fn test_deviating_sources_are_skipped() {
+        let now = SystemTime::now();
+        let config = AggregationConfig {
+            min_sources: 2,
+            max_deviation: Some(0.05),
+            ..Default::default()
+        };
+        let values = observations(&[("a", 100.0, 0), ("b", 101.0, 0), ("c", 1000.0, 0)], now);
+        let aggregate = aggregate(&config, &values, now).unwrap();
+        assert_eq!(aggregate.value, 100.5);
+        assert_eq!(
+            aggregate.rejected,
+            vec![("c".to_string(), RejectionReason::Deviation)]
+        );
+
+        // Two sources disagreeing with each other can't be resolved.
+        let values = observations(&[("a", 100.0, 0), ("b", 200.0, 0)], now);
+        assert_eq!(
+            super::aggregate(&config, &values, now),
+            Err(SkipReason::ExcessiveDeviation {
+                agreeing: 0,
+                required: 2
+            })
+        );
+    }
+}
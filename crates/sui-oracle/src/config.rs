@@ -13,6 +13,10 @@ use sui_types::base_types::ObjectID;
 pub struct DataSourceConfig {
     pub url: String,
     pub json_path: String,
+    /// Optional json path to the time the value was observed by the data source, either as
+    /// unix seconds or as an RFC 3339 string. Defaults to the retrieval time.
+    #[serde(default)]
+    pub timestamp_json_path: Option<String>,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -23,6 +27,58 @@ pub struct UploadFeedConfig {
     pub upload_parameters: UploadParameters,
 }
 
+/// A feed whose value is aggregated from several data sources before being uploaded.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct AggregatedUploadFeedConfig {
+    pub submission_interval: Duration,
+    pub data_source_configs: HashMap<String, DataSourceConfig>,
+    #[serde(default)]
+    pub aggregation: AggregationConfig,
+    pub upload_parameters: UploadParameters,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct AggregationConfig {
+    #[serde(default)]
+    pub method: AggregationMethod,
+    /// Minimum number of fresh and agreeing sources required to submit a value.
+    #[serde(default = "default_min_sources")]
+    pub min_sources: usize,
+    /// Values observed longer than this ago are discarded.
+    #[serde(default)]
+    pub max_staleness: Option<Duration>,
+    /// Maximum relative deviation of a source from the median of all sources, e.g. 0.05 for 5%.
+    /// Sources deviating more are discarded.
+    #[serde(default)]
+    pub max_deviation: Option<f64>,
+}
+
+impl Default for AggregationConfig {
+    fn default() -> Self {
+        Self {
+            method: AggregationMethod::default(),
+            min_sources: default_min_sources(),
+            max_staleness: None,
+            max_deviation: None,
+        }
+    }
+}
+
+fn default_min_sources() -> usize {
+    1
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum AggregationMethod {
+    #[default]
+    Median,
+    /// Mean of the values after discarding `trim_ratio` of the values at each end.
+    TrimmedMean { trim_ratio: f64 },
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "kebab-case")]
 pub struct UploadParameters {
@@ -44,6 +100,8 @@ pub struct DownloadFeedConfigs {
 pub struct OracleNodeConfig {
     pub gas_object_id: ObjectID,
     pub upload_feeds: HashMap<String, HashMap<String, UploadFeedConfig>>,
+    #[serde(default)]
+    pub aggregated_upload_feeds: HashMap<String, AggregatedUploadFeedConfig>,
     pub download_feeds: DownloadFeedConfigs,
 
     #[serde(default = "default_metrics_address")]
@@ -1,16 +1,21 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+use aggregation::Observation;
 use chrono::{DateTime, Utc};
-use config::{DownloadFeedConfigs, UploadFeedConfig, UploadParameters};
+use config::{
+    AggregatedUploadFeedConfig, DataSourceConfig, DownloadFeedConfigs, UploadFeedConfig,
+    UploadParameters,
+};
+use futures::future::join_all;
 use metrics::OracleMetrics;
 use mysten_metrics::monitored_scope;
 use once_cell::sync::OnceCell;
 use prometheus::Registry;
 use std::ops::Add;
 use std::str::FromStr;
-use std::sync::Arc;
-use std::time::{Duration, SystemTime};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use std::{collections::HashMap, time::Instant};
 use sui_json_rpc_types::SuiTransactionBlockResponse;
 use sui_json_rpc_types::{
@@ -37,6 +42,7 @@ use tap::tap::TapFallible;
 use sui_sdk::wallet_context::WalletContext;
 use sui_types::base_types::{random_object_ref, ObjectID, ObjectRef};
 use tracing::{debug, error, info, warn};
+pub mod aggregation;
 pub mod config;
 mod metrics;
 
@@ -48,6 +54,7 @@ static STALE_OBJ_ERROR: OnceCell<String> = OnceCell::new();
 
 pub struct OracleNode {
     upload_feeds: HashMap<String, HashMap<String, UploadFeedConfig>>,
+    aggregated_upload_feeds: HashMap<String, AggregatedUploadFeedConfig>,
     gas_obj_id: ObjectID,
     download_feeds: DownloadFeedConfigs,
     wallet_ctx: WalletContext,
@@ -57,6 +64,7 @@ pub struct OracleNode {
 impl OracleNode {
     pub fn new(
         upload_feeds: HashMap<String, HashMap<String, UploadFeedConfig>>,
+        aggregated_upload_feeds: HashMap<String, AggregatedUploadFeedConfig>,
         gas_obj_id: ObjectID,
         download_feeds: DownloadFeedConfigs,
         wallet_ctx: WalletContext,
@@ -64,6 +72,7 @@ impl OracleNode {
     ) -> Self {
         Self {
             upload_feeds,
+            aggregated_upload_feeds,
             gas_obj_id,
             download_feeds,
             wallet_ctx,
@@ -79,6 +88,7 @@ impl OracleNode {
         let wallet_ctx = Arc::new(self.wallet_ctx);
         DataProviderRunner::new(
             self.upload_feeds,
+            self.aggregated_upload_feeds,
             self.gas_obj_id,
             wallet_ctx,
             client.clone(),
@@ -118,12 +128,14 @@ impl OracleNode {
 
 struct DataProviderRunner {
     providers: Vec<Arc<DataProvider>>,
+    aggregated_providers: Vec<Arc<AggregatedDataProvider>>,
     uploader: OnChainDataUploader,
 }
 
 impl DataProviderRunner {
     pub async fn new(
         upload_feeds: HashMap<String, HashMap<String, UploadFeedConfig>>,
+        aggregated_upload_feeds: HashMap<String, AggregatedUploadFeedConfig>,
         gas_coin_id: ObjectID,
         wallet_ctx: Arc<WalletContext>,
         client: Arc<SuiClient>,
@@ -160,6 +172,31 @@ impl DataProviderRunner {
                 }
             }
         }
+        let mut aggregated_providers = vec![];
+        for (feed_name, aggregated_feed) in aggregated_upload_feeds {
+            staleness_tolerance.insert(
+                feed_name.to_ascii_lowercase(),
+                aggregated_feed.submission_interval,
+            );
+            let oracle_obj_id = aggregated_feed
+                .upload_parameters
+                .write_data_provider_object_id;
+            aggregated_providers.push(Arc::new(AggregatedDataProvider::new(
+                feed_name,
+                aggregated_feed,
+                sender.clone(),
+                metrics.clone(),
+            )));
+            if let std::collections::hash_map::Entry::Vacant(e) =
+                oracle_object_args.entry(oracle_obj_id)
+            {
+                e.insert(
+                    get_object_arg(client.read_api(), oracle_obj_id, true)
+                        .await
+                        .unwrap(),
+                );
+            }
+        }
         info!("Staleness tolerance: {:?}", staleness_tolerance);
 
         let gas_obj_ref = get_gas_obj_ref(client.read_api(), gas_coin_id, signer_address).await;
@@ -177,6 +214,7 @@ impl DataProviderRunner {
         };
         Self {
             providers,
+            aggregated_providers,
             uploader,
         }
     }
@@ -187,6 +225,11 @@ impl DataProviderRunner {
                 data_provider.run().await;
             });
         }
+        for data_provider in self.aggregated_providers {
+            tokio::spawn(async move {
+                data_provider.run().await;
+            });
+        }
         tokio::spawn(async move {
             self.uploader.run().await;
         });
@@ -278,48 +321,238 @@ impl DataProvider {
     }
 
     async fn retrieve_from_data_source(&self) -> anyhow::Result<f64> {
-        // TODO: support websocket
-        let url = &self.upload_feed.data_source_config.url;
-        let json_path = &self.upload_feed.data_source_config.json_path;
-        let response = reqwest::Client::new().get(url).send().await?;
+        retrieve_from_data_source(&self.upload_feed.data_source_config)
+            .await
+            .map(|observation| observation.value)
+    }
 
-        if !response.status().is_success() {
-            anyhow::bail!("Failed to fetch data: {:?}", response);
-        }
+    async fn send_to_uploader(&self, value: u64) {
+        let _ = self
+            .sender
+            .send(DataPoint {
+                feed_name: make_onchain_feed_name(&self.feed_name, &self.source_name),
+                upload_parameters: self.upload_feed.upload_parameters.clone(),
+                value,
+                retrieval_timestamp: SystemTime::now(),
+                retrieval_instant: Instant::now(),
+            })
+            .await
+            .tap_err(|err| error!("Failed to send data point to uploader: {:?}", err));
+    }
+}
 
-        let json_blob: serde_json::Value = response.json().await.unwrap();
-        let data = jsonpath_lib::select(&json_blob, json_path)?;
+async fn retrieve_from_data_source(config: &DataSourceConfig) -> anyhow::Result<Observation> {
+    // TODO: support websocket
+    let url = &config.url;
+    let json_path = &config.json_path;
+    let response = reqwest::Client::new().get(url).send().await?;
+    let retrieval_timestamp = SystemTime::now();
 
-        if data.is_empty() {
-            anyhow::bail!(
-                "Failed to find data from json blob: {:?} with json path: {:?}",
-                json_blob,
-                json_path
-            );
-        }
-        // Assume there is one single value per request
-        match data[0].as_str() {
-            Some(value_str) => match value_str.parse::<f64>() {
-                Ok(value) => Ok(value),
-                Err(_) => anyhow::bail!(
-                    "Failed to parse data {:?} as f64 from json blob: {:?}",
-                    data[0],
-                    json_blob
-                ),
-            },
+    if !response.status().is_success() {
+        anyhow::bail!("Failed to fetch data: {:?}", response);
+    }
+
+    let json_blob: serde_json::Value = response.json().await?;
+    let data = jsonpath_lib::select(&json_blob, json_path)?;
+
+    if data.is_empty() {
+        anyhow::bail!(
+            "Failed to find data from json blob: {:?} with json path: {:?}",
+            json_blob,
+            json_path
+        );
+    }
+    // Assume there is one single value per request
+    let value = match data[0].as_str() {
+        Some(value_str) => match value_str.parse::<f64>() {
+            Ok(value) => value,
+            Err(_) => anyhow::bail!(
+                "Failed to parse data {:?} as f64 from json blob: {:?}",
+                data[0],
+                json_blob
+            ),
+        },
+        None => match data[0].as_f64() {
+            Some(value) => value,
             None => anyhow::bail!(
                 "Failed to parse data {:?} as string from json blob: {:?}",
                 data[0],
                 json_blob
             ),
+        },
+    };
+
+    let timestamp = match &config.timestamp_json_path {
+        Some(timestamp_json_path) => {
+            let data = jsonpath_lib::select(&json_blob, timestamp_json_path)?;
+            let Some(timestamp) = data.first() else {
+                anyhow::bail!(
+                    "Failed to find timestamp from json blob: {:?} with json path: {:?}",
+                    json_blob,
+                    timestamp_json_path
+                );
+            };
+            parse_timestamp(timestamp)?
         }
+        None => retrieval_timestamp,
+    };
+    Ok(Observation { value, timestamp })
+}
+
+/// Parses a timestamp given either as unix seconds or as an RFC 3339 string.
+fn parse_timestamp(timestamp: &serde_json::Value) -> anyhow::Result<SystemTime> {
+    let unix_secs = match timestamp {
+        serde_json::Value::Number(n) => n.as_f64(),
+        serde_json::Value::String(s) => match s.parse::<f64>() {
+            Ok(secs) => Some(secs),
+            Err(_) => {
+                let datetime = DateTime::parse_from_rfc3339(s).map_err(|e| {
+                    anyhow::anyhow!("Failed to parse timestamp {:?}: {e}", timestamp)
+                })?;
+                return Ok(SystemTime::from(datetime));
+            }
+        },
+        _ => None,
+    };
+    match unix_secs {
+        Some(secs) if secs >= 0.0 => Ok(UNIX_EPOCH + Duration::from_secs_f64(secs)),
+        _ => anyhow::bail!("Failed to parse timestamp {:?}", timestamp),
     }
+}
 
-    async fn send_to_uploader(&self, value: u64) {
+/// Retrieves a feed from several data sources and uploads their aggregated value, so a
+/// single misbehaving source can't push a wrong value on chain.
+struct AggregatedDataProvider {
+    feed_name: String,
+    upload_feed: AggregatedUploadFeedConfig,
+    sender: tokio::sync::mpsc::Sender<DataPoint>,
+    metrics: Arc<OracleMetrics>,
+    // Latest observation of each source, kept so that a source failing in one round can still
+    // contribute its last value until it becomes stale. Without a staleness bound, a source's
+    // observation is dropped as soon as it fails.
+    observations: Mutex<HashMap<String, Observation>>,
+}
+
+impl AggregatedDataProvider {
+    fn new(
+        feed_name: String,
+        upload_feed: AggregatedUploadFeedConfig,
+        sender: tokio::sync::mpsc::Sender<DataPoint>,
+        metrics: Arc<OracleMetrics>,
+    ) -> Self {
+        Self {
+            feed_name,
+            upload_feed,
+            sender,
+            metrics,
+            observations: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub async fn run(&self) {
+        info!(
+            feed_name = self.feed_name,
+            sources = ?self.upload_feed.data_source_configs.keys().collect::<Vec<_>>(),
+            "Starting AggregatedDataProvider"
+        );
+        let mut interval = tokio::time::interval(self.upload_feed.submission_interval);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+        loop {
+            interval.tick().await;
+            self.run_once().await;
+        }
+    }
+
+    async fn run_once(&self) {
+        debug!(
+            feed_name = self.feed_name,
+            "Running aggregated data provider once."
+        );
+        let results = join_all(self.upload_feed.data_source_configs.iter().map(
+            |(source_name, config)| async move {
+                (source_name, retrieve_from_data_source(config).await)
+            },
+        ))
+        .await;
+
+        for (source_name, result) in results {
+            match result {
+                Ok(observation) => {
+                    self.metrics
+                        .data_source_successes
+                        .with_label_values(&[&self.feed_name, source_name])
+                        .inc();
+                    self.metrics
+                        .data_source_values
+                        .with_label_values(&[&self.feed_name, source_name])
+                        .observe((observation.value * METRICS_MULTIPLIER) as u64);
+                    self.observations
+                        .lock()
+                        .unwrap()
+                        .insert(source_name.clone(), observation);
+                }
+                Err(err) => {
+                    error!(
+                        feed_name = self.feed_name,
+                        source_name, "Failed to retrieve data from data source: {:?}", err
+                    );
+                    self.metrics
+                        .data_source_errors
+                        .with_label_values(&[&self.feed_name, source_name])
+                        .inc();
+                    if self.upload_feed.aggregation.max_staleness.is_none() {
+                        self.observations.lock().unwrap().remove(source_name);
+                    }
+                }
+            }
+        }
+
+        let aggregate = aggregation::aggregate(
+            &self.upload_feed.aggregation,
+            &self.observations.lock().unwrap(),
+            SystemTime::now(),
+        );
+        let aggregate = match aggregate {
+            Ok(aggregate) => aggregate,
+            Err(reason) => {
+                warn!(
+                    feed_name = self.feed_name,
+                    ?reason,
+                    "Not enough agreeing data sources, skipping submission."
+                );
+                self.metrics
+                    .aggregation_skips
+                    .with_label_values(&[&self.feed_name, reason.as_str()])
+                    .inc();
+                return;
+            }
+        };
+        for (source_name, reason) in &aggregate.rejected {
+            warn!(
+                feed_name = self.feed_name,
+                source_name,
+                ?reason,
+                "Discarding value from data source."
+            );
+            self.metrics
+                .data_source_rejections
+                .with_label_values(&[&self.feed_name, source_name, reason.as_str()])
+                .inc();
+        }
+        debug!(
+            feed_name = self.feed_name,
+            value = aggregate.value,
+            sources = ?aggregate.sources,
+            "Aggregated data sources."
+        );
+
+        // TODO: allow more flexible multiplers and data types
+        let value = (aggregate.value * METRICS_MULTIPLIER) as u64;
         let _ = self
             .sender
             .send(DataPoint {
-                feed_name: make_onchain_feed_name(&self.feed_name, &self.source_name),
+                feed_name: self.feed_name.to_ascii_lowercase(),
                 upload_parameters: self.upload_feed.upload_parameters.clone(),
                 value,
                 retrieval_timestamp: SystemTime::now(),
@@ -709,3 +942,190 @@ async fn get_object_arg(
         }
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{AggregationConfig, AggregationMethod};
+    use axum::routing::get;
+    use axum::{Json, Router};
+    use serde_json::json;
+    use std::net::SocketAddr;
+
+    /// Serves `/<source>` with the given json body for each source.
+    fn start_stub_server(sources: Vec<(&'static str, serde_json::Value)>) -> SocketAddr {
+        let mut app = Router::new();
+        for (source, body) in sources {
+            app = app.route(
+                &format!("/{source}"),
+                get(move || async move { Json(body) }),
+            );
+        }
+        let server =
+            axum::Server::bind(&"127.0.0.1:0".parse().unwrap()).serve(app.into_make_service());
+        let addr = server.local_addr();
+        tokio::spawn(server);
+        addr
+    }
+
+    fn aggregated_feed(
+        addr: SocketAddr,
+        sources: &[&str],
+        aggregation: AggregationConfig,
+    ) -> AggregatedUploadFeedConfig {
+        AggregatedUploadFeedConfig {
+            submission_interval: Duration::from_secs(10),
+            data_source_configs: sources
+                .iter()
+                .map(|source| {
+                    (
+                        source.to_string(),
+                        DataSourceConfig {
+                            url: format!("http://{addr}/{source}"),
+                            json_path: "$.price".to_string(),
+                            timestamp_json_path: Some("$.timestamp".to_string()),
+                        },
+                    )
+                })
+                .collect(),
+            aggregation,
+            upload_parameters: UploadParameters {
+                write_package_id: ObjectID::random(),
+                write_module_name: "simple_oracle".to_string(),
+                write_function_name: "submit_data".to_string(),
+                write_data_provider_object_id: ObjectID::random(),
+            },
+        }
+    }
+
+    fn now_secs() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+    }
+
+    #[tokio::test]
+    async fn test_aggregated_data_provider() {
+        let addr = start_stub_server(vec![
+            ("a", json!({"price": "1.00", "timestamp": now_secs()})),
+            ("b", json!({"price": 1.02, "timestamp": now_secs()})),
+            (
+                "c",
+                json!({"price": "1.04", "timestamp": now_secs().to_string()}),
+            ),
+            // Stale source
+            (
+                "d",
+                json!({"price": "1.50", "timestamp": now_secs() - 3600}),
+            ),
+            // Bad source
+            ("e", json!({"price": "95.0", "timestamp": now_secs()})),
+        ]);
+        let aggregation = AggregationConfig {
+            method: AggregationMethod::Median,
+            min_sources: 3,
+            max_staleness: Some(Duration::from_secs(60)),
+            max_deviation: Some(0.1),
+        };
+        let (sender, mut receiver) = tokio::sync::mpsc::channel(10);
+        let metrics = Arc::new(OracleMetrics::new(&Registry::new()));
+        let provider = AggregatedDataProvider::new(
+            "SUI/USDC".to_string(),
+            aggregated_feed(addr, &["a", "b", "c", "d", "e", "missing"], aggregation),
+            sender,
+            metrics.clone(),
+        );
+        provider.run_once().await;
+
+        let data_point = receiver.try_recv().unwrap();
+        assert_eq!(data_point.feed_name, "sui/usdc");
+        assert_eq!(data_point.value, 1_020_000);
+        for (source, reason) in [("d", "stale"), ("e", "deviation")] {
+            assert_eq!(
+                metrics
+                    .data_source_rejections
+                    .with_label_values(&["SUI/USDC", source, reason])
+                    .get(),
+                1
+            );
+        }
+        assert_eq!(
+            metrics
+                .data_source_errors
+                .with_label_values(&["SUI/USDC", "missing"])
+                .get(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn test_aggregated_data_provider_skips_submission() {
+        let addr = start_stub_server(vec![
+            ("a", json!({"price": "1.00", "timestamp": now_secs()})),
+            ("b", json!({"price": "2.00", "timestamp": now_secs()})),
+        ]);
+        let aggregation = AggregationConfig {
+            method: AggregationMethod::Median,
+            min_sources: 2,
+            max_staleness: None,
+            max_deviation: Some(0.1),
+        };
+        let (sender, mut receiver) = tokio::sync::mpsc::channel(10);
+        let metrics = Arc::new(OracleMetrics::new(&Registry::new()));
+        let provider = AggregatedDataProvider::new(
+            "SUI/USDC".to_string(),
+            aggregated_feed(addr, &["a", "b"], aggregation),
+            sender,
+            metrics.clone(),
+        );
+        provider.run_once().await;
+
+        assert!(receiver.try_recv().is_err());
+        assert_eq!(
+            metrics
+                .aggregation_skips
+                .with_label_values(&["SUI/USDC", "excessive_deviation"])
+                .get(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn test_aggregated_data_provider_drops_failed_sources() {
+        let addr = start_stub_server(vec![
+            ("a", json!({"price": "1.00", "timestamp": now_secs()})),
+            ("b", json!({"price": "1.02", "timestamp": now_secs()})),
+        ]);
+        let aggregation = AggregationConfig {
+            method: AggregationMethod::Median,
+            min_sources: 2,
+            max_staleness: None,
+            max_deviation: None,
+        };
+        let (sender, mut receiver) = tokio::sync::mpsc::channel(10);
+        let metrics = Arc::new(OracleMetrics::new(&Registry::new()));
+        let provider = AggregatedDataProvider::new(
+            "SUI/USDC".to_string(),
+            aggregated_feed(addr, &["a", "b", "missing"], aggregation),
+            sender,
+            metrics,
+        );
+        // An observation left from a round in which the source was still up.
+        provider.observations.lock().unwrap().insert(
+            "missing".to_string(),
+            Observation {
+                value: 100.0,
+                timestamp: SystemTime::now() - Duration::from_secs(3600),
+            },
+        );
+        provider.run_once().await;
+
+        assert!(!provider
+            .observations
+            .lock()
+            .unwrap()
+            .contains_key("missing"));
+        assert_eq!(receiver.try_recv().unwrap().value, 1_010_000);
+    }
+}
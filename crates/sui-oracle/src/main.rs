@@ -45,6 +45,7 @@ async fn main() -> anyhow::Result<()> {
 
     OracleNode::new(
         config.upload_feeds,
+        config.aggregated_upload_feeds,
         config.gas_object_id,
         config.download_feeds,
         wallet_ctx,
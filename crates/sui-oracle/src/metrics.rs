@@ -13,6 +13,9 @@ pub struct OracleMetrics {
     pub(crate) data_source_successes: IntCounterVec,
     pub(crate) data_source_errors: IntCounterVec,
     pub(crate) data_staleness: IntCounterVec,
+    pub(crate) data_source_values: HistogramVec,
+    pub(crate) data_source_rejections: IntCounterVec,
+    pub(crate) aggregation_skips: IntCounterVec,
     pub(crate) upload_successes: IntCounterVec,
     pub(crate) upload_data_errors: IntCounterVec,
     pub(crate) download_successes: IntCounterVec,
@@ -50,6 +53,26 @@ impl OracleMetrics {
                 registry,
             )
             .unwrap(),
+            data_source_values: HistogramVec::new_in_registry(
+                "oracle_data_source_values",
+                "Values retrieved from data sources",
+                &["feed", "source"],
+                registry,
+            ),
+            data_source_rejections: register_int_counter_vec_with_registry!(
+                "oracle_data_source_rejections",
+                "Total number of data source values discarded during aggregation",
+                &["feed", "source", "reason"],
+                registry,
+            )
+            .unwrap(),
+            aggregation_skips: register_int_counter_vec_with_registry!(
+                "oracle_aggregation_skips",
+                "Total number of aggregated submissions skipped",
+                &["feed", "reason"],
+                registry,
+            )
+            .unwrap(),
             upload_successes: register_int_counter_vec_with_registry!(
                 "oracle_upload_successes",
                 "Total number of successful data upload",
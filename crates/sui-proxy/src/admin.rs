@@ -1,5 +1,6 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
+use crate::buffer::RemoteWriteBuffer;
 use crate::config::{PeerValidationConfig, RemoteWriteConfig};
 use crate::handlers::publish_metrics;
 use crate::histogram_relay::HistogramRelay;
@@ -7,6 +8,7 @@ use crate::middleware::{
     expect_content_length, expect_mysten_proxy_header, expect_valid_public_key,
 };
 use crate::peers::SuiNodeProvider;
+use crate::relabel::Relabeler;
 use crate::var;
 use anyhow::Result;
 use axum::{extract::DefaultBodyLimit, middleware, routing::post, Extension, Router};
@@ -90,7 +92,9 @@ pub struct Labels {
 pub fn app(
     labels: Labels,
     client: ReqwestClient,
+    buffer: Option<RemoteWriteBuffer>,
     relay: HistogramRelay,
+    relabeler: Relabeler,
     allower: Option<SuiNodeProvider>,
 ) -> Router {
     // build our application with a route and our sender mpsc
@@ -110,8 +114,10 @@ pub fn app(
     }
     router
         .layer(Extension(relay))
+        .layer(Extension(relabeler))
         .layer(Extension(labels))
         .layer(Extension(client))
+        .layer(Extension(buffer))
         .layer(
             ServiceBuilder::new().layer(
                 TraceLayer::new_for_http().on_response(
@@ -204,8 +210,8 @@ pub fn create_server_cert_default_allow(
     )
 }
 
-/// Verify clients against sui blockchain, clients that are not found in sui_getValidators
-/// will be rejected
+/// Verify clients against sui blockchain and our static peers, clients that are not found in
+/// sui_getValidators or our static peers will be rejected
 pub fn create_server_cert_enforce_peer(
     peer_config: PeerValidationConfig,
 ) -> Result<(ServerConfig, Option<SuiNodeProvider>), sui_tls::rustls::Error> {
@@ -216,8 +222,16 @@ pub fn create_server_cert_enforce_peer(
             "missing certs to initialize server".into(),
         ));
     };
-    let allower = SuiNodeProvider::new(peer_config.url, peer_config.interval);
+    let mut allower = SuiNodeProvider::new(peer_config.url, peer_config.interval)
+        .with_static_peers(peer_config.static_peers)
+        .map_err(|error| sui_tls::rustls::Error::General(error.to_string()))?;
+    if let Some(static_peers_file) = peer_config.static_peers_file {
+        allower = allower
+            .with_static_peers_file(static_peers_file.into())
+            .map_err(|error| sui_tls::rustls::Error::General(error.to_string()))?;
+    }
     allower.poll_peer_list();
+    allower.poll_static_peers_file();
     let c = CertVerifier::new(allower.clone()).rustls_server_config(
         load_certs(&certificate_path),
         load_private_key(&private_key_path),
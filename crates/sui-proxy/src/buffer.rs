@@ -0,0 +1,425 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use crate::admin::ReqwestClient;
+use crate::config::RemoteWriteBufferConfig;
+use crate::consumer::{is_retryable, post_remote_write};
+use anyhow::{Context, Result};
+use axum::body::Bytes;
+use once_cell::sync::Lazy;
+use prometheus::{register_counter_vec, register_int_gauge};
+use prometheus::{CounterVec, IntGauge};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::{debug, error, info};
+
+static BUFFER_BYTES: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "remote_write_buffer_bytes",
+        "The size in bytes of the remote_write requests buffered on disk.",
+    )
+    .unwrap()
+});
+static BUFFER_REQUESTS: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "remote_write_buffer_requests",
+        "The number of remote_write requests buffered on disk.",
+    )
+    .unwrap()
+});
+static BUFFER_OPS: Lazy<CounterVec> = Lazy::new(|| {
+    register_counter_vec!(
+        "remote_write_buffer_operations",
+        "Operations counters and status from operations performed on the remote_write buffer.",
+        &["operation", "status"]
+    )
+    .unwrap()
+});
+
+/// extension of the files holding a single snappy compressed remote_write request
+const REQUEST_EXTENSION: &str = "snappy";
+/// extension of a request that is still being written
+const TMP_EXTENSION: &str = "tmp";
+
+#[derive(Default)]
+struct BufferState {
+    /// sequence number to size in bytes of each buffered request, oldest first
+    requests: BTreeMap<u64, u64>,
+    bytes: u64,
+    next: u64,
+}
+
+impl BufferState {
+    fn update_metrics(&self) {
+        BUFFER_BYTES.set(self.bytes as i64);
+        BUFFER_REQUESTS.set(self.requests.len() as i64);
+    }
+}
+
+/// RemoteWriteBuffer is a write-ahead buffer for remote_write requests that could not be sent.
+/// Each request is kept in its own file, named after its sequence number, so that the buffer
+/// survives restarts and is replayed in the order it was written.
+#[derive(Clone)]
+pub struct RemoteWriteBuffer {
+    path: PathBuf,
+    max_bytes: u64,
+    replay_interval: Duration,
+    state: Arc<Mutex<BufferState>>,
+}
+
+impl RemoteWriteBuffer {
+    /// open the buffer at the configured path, recovering any requests buffered before a restart
+    pub fn new(config: RemoteWriteBufferConfig) -> Result<Self> {
+        let path = PathBuf::from(config.path);
+        fs::create_dir_all(&path)
+            .with_context(|| format!("cannot create remote_write buffer dir {:?}", path))?;
+
+        let mut state = BufferState::default();
+        for entry in fs::read_dir(&path)? {
+            let entry = entry?;
+            let file = entry.path();
+            match file.extension().and_then(|e| e.to_str()) {
+                Some(REQUEST_EXTENSION) => (),
+                // we stopped before this request was completely written
+                Some(TMP_EXTENSION) => {
+                    fs::remove_file(&file)?;
+                    continue;
+                }
+                _ => continue,
+            }
+            let Some(seq) = file
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .and_then(|s| s.parse::<u64>().ok())
+            else {
+                continue;
+            };
+            let size = entry.metadata()?.len();
+            state.requests.insert(seq, size);
+            state.bytes += size;
+            state.next = state.next.max(seq + 1);
+        }
+        if !state.requests.is_empty() {
+            info!(
+                "recovered {} buffered remote_write requests ({} bytes) from {:?}",
+                state.requests.len(),
+                state.bytes,
+                path
+            );
+        }
+        state.update_metrics();
+
+        Ok(Self {
+            path,
+            max_bytes: config.max_bytes,
+            replay_interval: config.replay_interval,
+            state: Arc::new(Mutex::new(state)),
+        })
+    }
+
+    fn request_path(&self, seq: u64, extension: &str) -> PathBuf {
+        self.path.join(format!("{seq:020}.{extension}"))
+    }
+
+    /// true if there is nothing waiting to be replayed
+    pub fn is_empty(&self) -> bool {
+        self.state.lock().unwrap().requests.is_empty()
+    }
+
+    /// the number of buffered requests and their size in bytes
+    pub fn backlog(&self) -> (usize, u64) {
+        let state = self.state.lock().unwrap();
+        (state.requests.len(), state.bytes)
+    }
+
+    /// run file system work on the blocking thread pool, so that it doesn't stall the runtime
+    async fn blocking<T: Send + 'static>(&self, f: impl FnOnce(&Self) -> T + Send + 'static) -> T {
+        let buffer = self.clone();
+        tokio::task::spawn_blocking(move || f(&buffer))
+            .await
+            .expect("remote_write buffer task panicked")
+    }
+
+    /// append a snappy compressed remote_write request to the buffer, dropping the oldest
+    /// requests if needed to stay within max_bytes
+    pub async fn push(&self, compressed: Bytes) -> Result<()> {
+        self.blocking(move |buffer| buffer.push_blocking(&compressed))
+            .await
+    }
+
+    fn push_blocking(&self, compressed: &[u8]) -> Result<()> {
+        let size = compressed.len() as u64;
+        if size > self.max_bytes {
+            BUFFER_OPS.with_label_values(&["push", "too_large"]).inc();
+            anyhow::bail!(
+                "remote_write request of {size} bytes does not fit in a buffer of {} bytes",
+                self.max_bytes
+            );
+        }
+
+        let mut state = self.state.lock().unwrap();
+        while state.bytes + size > self.max_bytes {
+            let Some((seq, evicted)) = state.requests.pop_first() else {
+                break;
+            };
+            if let Err(error) = fs::remove_file(self.request_path(seq, REQUEST_EXTENSION)) {
+                error!("unable to remove evicted remote_write request {seq}: {error}");
+            }
+            state.bytes -= evicted;
+            BUFFER_OPS.with_label_values(&["evict", "success"]).inc();
+        }
+
+        let seq = state.next;
+        let tmp = self.request_path(seq, TMP_EXTENSION);
+        let written = fs::write(&tmp, compressed)
+            .and_then(|_| fs::rename(&tmp, self.request_path(seq, REQUEST_EXTENSION)));
+        if let Err(error) = written {
+            BUFFER_OPS.with_label_values(&["push", "failure"]).inc();
+            state.update_metrics();
+            return Err(error).context("unable to write remote_write request to buffer");
+        }
+        state.next += 1;
+        state.requests.insert(seq, size);
+        state.bytes += size;
+        state.update_metrics();
+        BUFFER_OPS.with_label_values(&["push", "success"]).inc();
+        Ok(())
+    }
+
+    /// the oldest buffered request, if any
+    async fn oldest(&self) -> Option<(u64, Bytes)> {
+        self.blocking(|buffer| buffer.oldest_blocking()).await
+    }
+
+    fn oldest_blocking(&self) -> Option<(u64, Bytes)> {
+        loop {
+            let seq = *self.state.lock().unwrap().requests.keys().next()?;
+            match fs::read(self.request_path(seq, REQUEST_EXTENSION)) {
+                Ok(compressed) => return Some((seq, compressed.into())),
+                Err(error) => {
+                    // there's nothing we can do with a request we can't read, skip it
+                    error!("unable to read buffered remote_write request {seq}: {error}");
+                    BUFFER_OPS.with_label_values(&["read", "failure"]).inc();
+                    self.remove_blocking(seq);
+                }
+            }
+        }
+    }
+
+    async fn remove(&self, seq: u64) {
+        self.blocking(move |buffer| buffer.remove_blocking(seq))
+            .await
+    }
+
+    fn remove_blocking(&self, seq: u64) {
+        let mut state = self.state.lock().unwrap();
+        // the request may have been evicted while we were replaying it
+        let Some(size) = state.requests.remove(&seq) else {
+            return;
+        };
+        if let Err(error) = fs::remove_file(self.request_path(seq, REQUEST_EXTENSION)) {
+            error!("unable to remove replayed remote_write request {seq}: {error}");
+        }
+        state.bytes -= size;
+        state.update_metrics();
+    }
+
+    /// replay buffered requests to remote_write, oldest first, until the buffer is empty or
+    /// remote_write is unavailable again. requests rejected by remote_write for reasons other
+    /// than availability are dropped, since retrying them would never succeed.
+    /// returns the number of requests remote_write accepted
+    pub async fn replay(&self, rc: &ReqwestClient) -> usize {
+        let mut replayed = 0;
+        while let Some((seq, compressed)) = self.oldest().await {
+            match post_remote_write(rc, compressed).await {
+                Ok(response) if response.status().is_success() => {
+                    BUFFER_OPS.with_label_values(&["replay", "success"]).inc();
+                    self.remove(seq).await;
+                    replayed += 1;
+                }
+                Ok(response) if !is_retryable(response.status()) => {
+                    let status = response.status();
+                    let body = response
+                        .text()
+                        .await
+                        .unwrap_or_else(|_| "response body cannot be decoded".into());
+                    error!("DROPPING buffered request {seq}, ({status}) ERROR: {body:?}");
+                    BUFFER_OPS.with_label_values(&["replay", "dropped"]).inc();
+                    self.remove(seq).await;
+                }
+                Ok(response) => {
+                    debug!("remote_write is still unavailable: {}", response.status());
+                    BUFFER_OPS.with_label_values(&["replay", "retry"]).inc();
+                    break;
+                }
+                Err(error) => {
+                    debug!("remote_write is still unavailable: {error}");
+                    BUFFER_OPS.with_label_values(&["replay", "retry"]).inc();
+                    break;
+                }
+            }
+        }
+        replayed
+    }
+
+    /// periodically replay the buffer to remote_write
+    pub fn start_replay(self, rc: ReqwestClient) -> JoinHandle<()> {
+        info!(
+            "Started replaying remote_write buffer {:?} every {:?}",
+            self.path, self.replay_interval
+        );
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(self.replay_interval);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                interval.tick().await;
+                if self.is_empty() {
+                    continue;
+                }
+                let replayed = self.replay(&rc).await;
+                let (requests, bytes) = self.backlog();
+                if replayed > 0 {
+                    info!(
+                        "replayed {replayed} buffered remote_write requests, {requests} requests ({bytes} bytes) remain"
+                    );
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::admin::make_reqwest_client;
+    use crate::config::RemoteWriteConfig;
+    use axum::http::StatusCode;
+    use axum::routing::post;
+    use axum::{Extension, Router};
+    use std::sync::atomic::{AtomicU16, AtomicUsize, Ordering};
+
+    fn buffer(path: &std::path::Path, max_bytes: u64) -> RemoteWriteBuffer {
+        RemoteWriteBuffer::new(RemoteWriteBufferConfig {
+            path: path.to_string_lossy().into(),
+            max_bytes,
+            replay_interval: Duration::from_secs(1),
+        })
+        .unwrap()
+    }
+
+    /// a remote_write that answers with whatever status we set and counts the requests it accepts
+    struct DummyRemoteWrite {
+        status: AtomicU16,
+        accepted: AtomicUsize,
+    }
+
+    async fn run_dummy_remote_write(remote_write: Arc<DummyRemoteWrite>) -> ReqwestClient {
+        async fn handler(Extension(remote_write): Extension<Arc<DummyRemoteWrite>>) -> StatusCode {
+            let status = StatusCode::from_u16(remote_write.status.load(Ordering::SeqCst)).unwrap();
+            if status == StatusCode::OK {
+                remote_write.accepted.fetch_add(1, Ordering::SeqCst);
+            }
+            status
+        }
+
+        let listener = std::net::TcpListener::bind("localhost:0").unwrap();
+        let url = format!(
+            "http://localhost:{}/v1/push",
+            listener.local_addr().unwrap().port()
+        );
+        let app = Router::new()
+            .route("/v1/push", post(handler))
+            .layer(Extension(remote_write));
+        tokio::spawn(async move {
+            axum::Server::from_tcp(listener)
+                .unwrap()
+                .serve(app.into_make_service())
+                .await
+                .unwrap();
+        });
+        make_reqwest_client(
+            RemoteWriteConfig {
+                url,
+                ..Default::default()
+            },
+            "dummy user agent",
+        )
+    }
+
+    #[tokio::test]
+    async fn test_push_and_recover() {
+        let dir = tempfile::tempdir().unwrap();
+        let buffer1 = buffer(dir.path(), 1024);
+        assert!(buffer1.is_empty());
+        buffer1.push(Bytes::from(vec![1; 10])).await.unwrap();
+        buffer1.push(Bytes::from(vec![2; 20])).await.unwrap();
+        assert_eq!(buffer1.backlog(), (2, 30));
+
+        // a partially written request from a crash is discarded
+        fs::write(dir.path().join("00000000000000000002.tmp"), [3; 5]).unwrap();
+
+        let buffer2 = buffer(dir.path(), 1024);
+        assert_eq!(buffer2.backlog(), (2, 30));
+        assert_eq!(
+            buffer2.oldest().await.unwrap(),
+            (0, Bytes::from(vec![1; 10]))
+        );
+        buffer2.push(Bytes::from(vec![4; 5])).await.unwrap();
+        assert!(dir.path().join("00000000000000000002.snappy").exists());
+    }
+
+    #[tokio::test]
+    async fn test_max_bytes() {
+        let dir = tempfile::tempdir().unwrap();
+        let buffer = buffer(dir.path(), 25);
+        buffer.push(Bytes::from(vec![1; 10])).await.unwrap();
+        buffer.push(Bytes::from(vec![2; 10])).await.unwrap();
+        // the oldest request is dropped to make room
+        buffer.push(Bytes::from(vec![3; 10])).await.unwrap();
+        assert_eq!(buffer.backlog(), (2, 20));
+        assert_eq!(
+            buffer.oldest().await.unwrap(),
+            (1, Bytes::from(vec![2; 10]))
+        );
+        // a request larger than the whole buffer is refused
+        assert!(buffer.push(Bytes::from(vec![4; 30])).await.is_err());
+        assert_eq!(buffer.backlog(), (2, 20));
+    }
+
+    #[tokio::test]
+    async fn test_replay() {
+        let remote_write = Arc::new(DummyRemoteWrite {
+            status: AtomicU16::new(StatusCode::SERVICE_UNAVAILABLE.as_u16()),
+            accepted: AtomicUsize::new(0),
+        });
+        let rc = run_dummy_remote_write(remote_write.clone()).await;
+        let dir = tempfile::tempdir().unwrap();
+        let buffer = buffer(dir.path(), 1024);
+        buffer.push(Bytes::from(vec![1; 10])).await.unwrap();
+        buffer.push(Bytes::from(vec![2; 10])).await.unwrap();
+
+        // remote_write is still down, nothing is lost
+        assert_eq!(buffer.replay(&rc).await, 0);
+        assert_eq!(buffer.backlog(), (2, 20));
+
+        // requests remote_write will never accept are dropped
+        remote_write
+            .status
+            .store(StatusCode::BAD_REQUEST.as_u16(), Ordering::SeqCst);
+        assert_eq!(buffer.replay(&rc).await, 0);
+        assert!(buffer.is_empty());
+
+        buffer.push(Bytes::from(vec![3; 10])).await.unwrap();
+        buffer.push(Bytes::from(vec![4; 10])).await.unwrap();
+        remote_write
+            .status
+            .store(StatusCode::OK.as_u16(), Ordering::SeqCst);
+        assert_eq!(buffer.replay(&rc).await, 2);
+        assert_eq!(remote_write.accepted.load(Ordering::SeqCst), 2);
+        assert!(buffer.is_empty());
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+}
@@ -4,6 +4,7 @@ use anyhow::{Context, Result};
 use core::time::Duration;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_with::{serde_as, DurationSeconds};
+use std::collections::BTreeMap;
 use std::net::SocketAddr;
 use tracing::debug;
 
@@ -18,6 +19,9 @@ pub struct ProxyConfig {
     pub json_rpc: PeerValidationConfig,
     pub metrics_address: String,
     pub histogram_address: String,
+    /// rules applied to metrics from peers before they are sent to remote_write
+    #[serde(default)]
+    pub relabel: RelabelConfig,
 }
 
 #[serde_as]
@@ -36,6 +40,27 @@ pub struct RemoteWriteConfig {
     /// <https://docs.rs/reqwest/latest/reqwest/struct.ClientBuilder.html#method.pool_max_idle_per_host>
     #[serde(default = "pool_max_idle_per_host_default")]
     pub pool_max_idle_per_host: usize,
+
+    /// buffer remote_write requests on disk while remote_write is unavailable and replay
+    /// them once it recovers. if not set, requests that cannot be sent are dropped
+    #[serde(default)]
+    pub buffer: Option<RemoteWriteBufferConfig>,
+}
+
+#[serde_as]
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct RemoteWriteBufferConfig {
+    /// directory we keep buffered requests in, it must survive restarts
+    /// please use an absolute path
+    pub path: String,
+    /// the maximum size of the buffer on disk. once reached, the oldest requests are dropped
+    #[serde(default = "buffer_max_bytes_default")]
+    pub max_bytes: u64,
+    /// how often we try to replay buffered requests to remote_write
+    #[serde_as(as = "DurationSeconds<u64>")]
+    #[serde(default = "replay_interval_default")]
+    pub replay_interval: Duration,
 }
 
 #[serde_as]
@@ -43,8 +68,12 @@ pub struct RemoteWriteConfig {
 #[serde(rename_all = "kebab-case")]
 pub struct PeerValidationConfig {
     /// url is the json-rpc url we use to obtain valid peers on the blockchain
-    pub url: String,
+    /// if not provided, only static peers are allowed
+    #[serde(default)]
+    pub url: Option<String>,
+    /// how often we refresh peers from json-rpc and from static_peers_file
     #[serde_as(as = "DurationSeconds<u64>")]
+    #[serde(default = "interval_default")]
     pub interval: Duration,
     /// if certificate_file and private_key are not provided, we'll create a self-signed
     /// cert using this hostname
@@ -57,6 +86,76 @@ pub struct PeerValidationConfig {
     /// private key for tls
     /// please use an absolute path
     pub private_key: Option<String>,
+
+    /// peers that are always allowed, in addition to the peers found on chain
+    #[serde(default)]
+    pub static_peers: Vec<StaticPeer>,
+    /// yaml file with a list of static peers, it is reloaded when it changes
+    /// please use an absolute path
+    pub static_peers_file: Option<String>,
+}
+
+/// StaticPeer is a peer we allow without looking for it on chain
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub struct StaticPeer {
+    /// the name we use as the host label for metrics from this peer
+    pub name: String,
+    /// base64 encoded ed25519 network public key of the peer
+    pub public_key: String,
+    /// the p2p multiaddr of the peer, informational only
+    #[serde(default)]
+    pub p2p_address: Option<String>,
+}
+
+#[serde_as]
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct RelabelConfig {
+    /// metric families with a name matching any of these regexes are dropped
+    #[serde(default)]
+    pub drop_metrics: Vec<String>,
+    /// labels with these names are removed from every metric
+    #[serde(default)]
+    pub drop_labels: Vec<String>,
+    /// labels are renamed from the key to the value
+    #[serde(default)]
+    pub rename_labels: BTreeMap<String, String>,
+    /// the maximum number of distinct series accepted from a single peer within
+    /// cardinality_window. once reached, new series from the peer are dropped
+    #[serde(default)]
+    pub max_series_per_peer: Option<usize>,
+    /// how often the series we've seen from each peer are forgotten
+    #[serde_as(as = "DurationSeconds<u64>")]
+    #[serde(default = "cardinality_window_default")]
+    pub cardinality_window: Duration,
+}
+
+impl Default for RelabelConfig {
+    fn default() -> Self {
+        Self {
+            drop_metrics: vec![],
+            drop_labels: vec![],
+            rename_labels: BTreeMap::new(),
+            max_series_per_peer: None,
+            cardinality_window: cardinality_window_default(),
+        }
+    }
+}
+
+/// the default window after which per peer series counts are reset
+fn cardinality_window_default() -> Duration {
+    Duration::from_secs(3600)
+}
+
+/// the default maximum size of the remote_write buffer, 1GiB
+fn buffer_max_bytes_default() -> u64 {
+    1024 * 1024 * 1024
+}
+
+/// the default interval to replay buffered remote_write requests
+fn replay_interval_default() -> Duration {
+    Duration::from_secs(10)
 }
 
 /// the default idle worker per host (reqwest to remote write url call)
@@ -64,6 +163,11 @@ fn pool_max_idle_per_host_default() -> usize {
     8
 }
 
+/// the default interval to refresh our peers
+fn interval_default() -> Duration {
+    Duration::from_secs(30)
+}
+
 /// the default hostname we will use if not provided
 fn hostname_default() -> Option<String> {
     Some("localhost".to_string())
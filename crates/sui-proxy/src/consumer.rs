@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::admin::ReqwestClient;
+use crate::buffer::RemoteWriteBuffer;
 use crate::prom_to_mimir::Mimir;
 use crate::remote_write::WriteRequest;
 use anyhow::Result;
@@ -26,6 +27,13 @@ static CONSUMER_OPS_SUBMITTED: Lazy<Counter> = Lazy::new(|| {
     )
     .unwrap()
 });
+static CONSUMER_OPS_BUFFERED: Lazy<Counter> = Lazy::new(|| {
+    register_counter!(
+        "consumer_operations_buffered",
+        "Operations counter for the number of metric family types we buffer to submit later, because remote_write is unavailable.",
+    )
+    .unwrap()
+});
 static CONSUMER_OPS: Lazy<CounterVec> = Lazy::new(|| {
     register_counter_vec!(
         "consumer_operations",
@@ -106,6 +114,11 @@ impl ProtobufDecoder {
 }
 
 // populate labels in place for our given metric family data
+/// labels added by populate_labels to every metric, which relabel rules must not produce
+pub const RESERVED_LABELS: [&str; 2] = [NETWORK_LABEL, HOST_LABEL];
+const NETWORK_LABEL: &str = "network";
+const HOST_LABEL: &str = "host";
+
 pub fn populate_labels(
     name: String,               // host field for grafana agent (from chain data)
     network: String,            // network name from ansible (via config)
@@ -119,11 +132,11 @@ pub fn populate_labels(
     // proto::LabelPair doesn't have pub fields so we can't use
     // struct literals to construct
     let mut network_label = proto::LabelPair::default();
-    network_label.set_name("network".into());
+    network_label.set_name(NETWORK_LABEL.into());
     network_label.set_value(network);
 
     let mut host_label = proto::LabelPair::default();
-    host_label.set_name("host".into());
+    host_label.set_name(HOST_LABEL.into());
     host_label.set_value(name);
 
     let labels = vec![network_label, host_label];
@@ -267,14 +280,43 @@ async fn convert(
     Ok(result)
 }
 
+/// post a snappy compressed remote_write request to our remote_write url
+pub(crate) async fn post_remote_write(
+    rc: &ReqwestClient,
+    compressed: Bytes,
+) -> reqwest::Result<reqwest::Response> {
+    rc.client
+        .post(rc.settings.url.to_owned())
+        .header(reqwest::header::CONTENT_ENCODING, "snappy")
+        .header(reqwest::header::CONTENT_TYPE, "application/x-protobuf")
+        .header("X-Prometheus-Remote-Write-Version", "0.1.0")
+        .basic_auth(
+            rc.settings.username.to_owned(),
+            Some(rc.settings.password.to_owned()),
+        )
+        .body(compressed)
+        .send()
+        .await
+}
+
+/// a request that failed with this status may succeed later, once remote_write recovers
+pub(crate) fn is_retryable(status: reqwest::StatusCode) -> bool {
+    status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS
+}
+
 /// convert_to_remote_write is an expensive method due to the time it takes to submit to mimir.
 /// other operations here are optimized for async, within reason.  The post process uses a single
 /// connection to mimir and thus incurs the seriliaztion delay for each metric family sent. Possible
 /// future optimizations would be to use multiple tcp connections to mimir, within reason. Nevertheless
 /// we await on each post of each metric family so it shouldn't block any other async work in a
 /// significant way.
+///
+/// If a buffer is provided, requests that fail because mimir is unavailable are written to it
+/// to be replayed later instead of being dropped. While the buffer has a backlog, new requests
+/// are appended to it so that mimir receives samples in order.
 pub async fn convert_to_remote_write(
     rc: ReqwestClient,
+    buffer: Option<RemoteWriteBuffer>,
     node_metric: NodeMetric,
 ) -> (StatusCode, &'static str) {
     let timer = CONSUMER_OPERATION_DURATION
@@ -289,53 +331,73 @@ pub async fn convert_to_remote_write(
         }
     };
 
-    // a counter so we don't iterate the node data 2x
-    let mut mf_cnt = 0;
+    // once we buffer a request, we buffer the rest so they stay in order
+    let mut buffering = buffer.as_ref().map_or(false, |b| !b.is_empty());
+    // counters so we don't iterate the node data 2x
+    let mut submitted = 0;
+    let mut buffered = 0;
     for request in remote_write_protos {
-        mf_cnt += 1;
-        let compressed = match encode_compress(&request) {
-            Ok(compressed) => compressed,
+        let compressed: Bytes = match encode_compress(&request) {
+            Ok(compressed) => compressed.into(),
             Err(error) => return error,
         };
 
-        let response = match rc
-            .client
-            .post(rc.settings.url.to_owned())
-            .header(reqwest::header::CONTENT_ENCODING, "snappy")
-            .header(reqwest::header::CONTENT_TYPE, "application/x-protobuf")
-            .header("X-Prometheus-Remote-Write-Version", "0.1.0")
-            .basic_auth(
-                rc.settings.username.to_owned(),
-                Some(rc.settings.password.to_owned()),
+        let unavailable = if buffering {
+            (
+                StatusCode::SERVICE_UNAVAILABLE,
+                "remote_write has a backlog",
             )
-            .body(compressed)
-            .send()
-            .await
-        {
-            Ok(response) => response,
-            Err(error) => {
-                CONSUMER_OPS
-                    .with_label_values(&["check_response", "INTERNAL_SERVER_ERROR"])
-                    .inc();
-                error!("DROPPING METRICS due to post error: {error}");
-                timer.stop_and_discard();
-                return (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    "DROPPING METRICS due to post error",
-                );
+        } else {
+            match post_remote_write(&rc, compressed.clone()).await {
+                Ok(response) => {
+                    let status = response.status();
+                    match check_response(request, response).await {
+                        Ok(_) => {
+                            submitted += 1;
+                            continue;
+                        }
+                        Err(err) if is_retryable(status) => err,
+                        Err(err) => {
+                            timer.stop_and_discard();
+                            return err;
+                        }
+                    }
+                }
+                Err(error) => {
+                    CONSUMER_OPS
+                        .with_label_values(&["check_response", "INTERNAL_SERVER_ERROR"])
+                        .inc();
+                    error!("post error: {error}");
+                    (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        "DROPPING METRICS due to post error",
+                    )
+                }
             }
         };
 
-        match check_response(request, response).await {
-            Ok(_) => (),
-            Err(err) => {
-                timer.stop_and_discard();
-                return err;
-            }
+        let Some(buffer) = &buffer else {
+            error!("DROPPING METRICS, remote_write is unavailable");
+            timer.stop_and_discard();
+            return unavailable;
+        };
+        if let Err(error) = buffer.push(compressed).await {
+            error!("DROPPING METRICS, unable to buffer them: {error}");
+            timer.stop_and_discard();
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "DROPPING METRICS, unable to buffer them",
+            );
         }
+        buffering = true;
+        buffered += 1;
     }
-    CONSUMER_OPS_SUBMITTED.inc_by(mf_cnt as f64);
+    CONSUMER_OPS_SUBMITTED.inc_by(submitted as f64);
+    CONSUMER_OPS_BUFFERED.inc_by(buffered as f64);
     timer.observe_duration();
+    if buffering {
+        return (StatusCode::ACCEPTED, "buffered");
+    }
     (StatusCode::CREATED, "created")
 }
 
@@ -1,10 +1,12 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 use crate::admin::{Labels, ReqwestClient};
+use crate::buffer::RemoteWriteBuffer;
 use crate::consumer::{convert_to_remote_write, populate_labels, NodeMetric};
 use crate::histogram_relay::HistogramRelay;
 use crate::middleware::LenDelimProtobuf;
 use crate::peers::SuiPeer;
+use crate::relabel::Relabeler;
 use axum::{
     extract::{ConnectInfo, Extension},
     http::StatusCode,
@@ -44,11 +46,13 @@ static HTTP_HANDLER_DURATION: Lazy<HistogramVec> = Lazy::new(|| {
 pub async fn publish_metrics(
     Extension(labels): Extension<Labels>,
     Extension(client): Extension<ReqwestClient>,
+    Extension(buffer): Extension<Option<RemoteWriteBuffer>>,
     ConnectInfo(addr): ConnectInfo<SocketAddr>,
     Extension(SuiPeer {
         name, public_key, ..
     }): Extension<SuiPeer>,
     Extension(relay): Extension<HistogramRelay>,
+    Extension(relabeler): Extension<Relabeler>,
     LenDelimProtobuf(data): LenDelimProtobuf,
 ) -> (StatusCode, &'static str) {
     HANDLER_HITS
@@ -57,10 +61,12 @@ pub async fn publish_metrics(
     let timer = HTTP_HANDLER_DURATION
         .with_label_values(&["publish_metrics", &name])
         .start_timer();
+    let data = relabeler.apply(&name, data);
     let data = populate_labels(name, labels.network, labels.inventory_hostname, data);
     relay.submit(data.clone());
     let response = convert_to_remote_write(
         client.clone(),
+        buffer,
         NodeMetric {
             data,
             peer_addr: Multiaddr::from(addr.ip()),
@@ -1,6 +1,7 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 pub mod admin;
+pub mod buffer;
 pub mod config;
 pub mod consumer;
 pub mod handlers;
@@ -9,6 +10,7 @@ pub mod metrics;
 pub mod middleware;
 pub mod peers;
 pub mod prom_to_mimir;
+pub mod relabel;
 pub mod remote_write;
 
 /// var extracts environment variables at runtime with a default fallback value
@@ -37,6 +39,7 @@ mod tests {
     use crate::admin::Labels;
     use crate::histogram_relay::HistogramRelay;
     use crate::prom_to_mimir::tests::*;
+    use crate::relabel::Relabeler;
 
     use crate::{admin::CertKeyPair, config::RemoteWriteConfig, peers::SuiNodeProvider};
     use axum::http::{header, StatusCode};
@@ -89,7 +92,7 @@ mod tests {
             tokio::spawn(async move { run_dummy_remote_write(dummy_remote_write_listener).await });
 
         // init the tls config and allower
-        let mut allower = SuiNodeProvider::new("".into(), Duration::from_secs(30));
+        let mut allower = SuiNodeProvider::new(None, Duration::from_secs(30));
         let tls_config = CertVerifier::new(allower.clone())
             .rustls_server_config(
                 vec![server_priv_cert.rustls_certificate()],
@@ -113,7 +116,9 @@ mod tests {
                 inventory_hostname: "ansible_inventory_name".into(),
             },
             client,
+            None,
             HistogramRelay::new(),
+            Relabeler::default(),
             Some(allower.clone()),
         );
 
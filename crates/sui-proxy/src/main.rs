@@ -9,8 +9,10 @@ use sui_proxy::{
         app, create_server_cert_default_allow, create_server_cert_enforce_peer,
         make_reqwest_client, server, Labels,
     },
+    buffer::RemoteWriteBuffer,
     config::load,
     histogram_relay, metrics,
+    relabel::Relabeler,
 };
 use sui_tls::TlsAcceptor;
 use telemetry_subscribers::TelemetryConfig;
@@ -86,7 +88,17 @@ async fn main() -> Result<()> {
     let histogram_listener = std::net::TcpListener::bind(config.histogram_address).unwrap();
     let metrics_listener = std::net::TcpListener::bind(config.metrics_address).unwrap();
     let acceptor = TlsAcceptor::new(tls_config);
+    let buffer = config
+        .remote_write
+        .buffer
+        .clone()
+        .map(RemoteWriteBuffer::new)
+        .transpose()?;
     let client = make_reqwest_client(config.remote_write, APP_USER_AGENT);
+    if let Some(buffer) = &buffer {
+        buffer.clone().start_replay(client.clone());
+    }
+    let relabeler = Relabeler::new(config.relabel)?;
     let histogram_relay = histogram_relay::start_prometheus_server(histogram_listener);
     let registry_service = metrics::start_prometheus_server(metrics_listener);
     let prometheus_registry = registry_service.default_registry();
@@ -103,7 +115,9 @@ async fn main() -> Result<()> {
             inventory_hostname: config.inventory_hostname,
         },
         client,
+        buffer,
         histogram_relay,
+        relabeler,
         allower,
     );
 
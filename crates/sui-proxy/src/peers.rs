@@ -1,14 +1,17 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
-use anyhow::{bail, Context, Result};
+use crate::config::StaticPeer;
+use anyhow::{anyhow, bail, Context, Result};
 use fastcrypto::ed25519::Ed25519PublicKey;
+use fastcrypto::encoding::{Base64, Encoding};
 use fastcrypto::traits::ToFromBytes;
 use multiaddr::Multiaddr;
 use once_cell::sync::Lazy;
 use prometheus::{register_counter_vec, register_histogram_vec};
 use prometheus::{CounterVec, HistogramVec};
 use serde::Deserialize;
-use std::time::Duration;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
 use std::{
     collections::HashMap,
     sync::{Arc, RwLock},
@@ -37,6 +40,14 @@ static JSON_RPC_DURATION: Lazy<HistogramVec> = Lazy::new(|| {
     )
     .unwrap()
 });
+static STATIC_PEERS_RELOAD: Lazy<CounterVec> = Lazy::new(|| {
+    register_counter_vec!(
+        "static_peers_reload",
+        "Number of successful/failed reloads of the static peers file.",
+        &["status"]
+    )
+    .unwrap()
+});
 
 /// SuiNods a mapping of public key to SuiPeer data
 pub type SuiPeers = Arc<RwLock<HashMap<Ed25519PublicKey, SuiPeer>>>;
@@ -53,38 +64,63 @@ pub struct SuiPeer {
 /// sui_getValidators.  The node name, public key and other info is extracted from the chain and stored in this
 /// data structure.  We pass this struct to the tls verifier and it depends on the state contained within.
 /// Handlers also use this data in an Extractor extension to check incoming clients on the http api against known keys.
+///
+/// Static peers from our config and from a static peers file are allowed in addition to the peers found on chain,
+/// which lets the proxy run on private networks, or without a sui rpc at all.
 #[derive(Debug, Clone)]
 pub struct SuiNodeProvider {
     nodes: SuiPeers,
-    rpc_url: String,
+    static_nodes: SuiPeers,
+    static_peers: Vec<StaticPeer>,
+    static_peers_file: Option<PathBuf>,
+    rpc_url: Option<String>,
     rpc_poll_interval: Duration,
 }
 
 impl Allower for SuiNodeProvider {
     fn allowed(&self, key: &Ed25519PublicKey) -> bool {
         self.nodes.read().unwrap().contains_key(key)
+            || self.static_nodes.read().unwrap().contains_key(key)
     }
 }
 
 impl SuiNodeProvider {
-    pub fn new(rpc_url: String, rpc_poll_interval: Duration) -> Self {
+    pub fn new(rpc_url: Option<String>, rpc_poll_interval: Duration) -> Self {
         let nodes = Arc::new(RwLock::new(HashMap::new()));
+        let static_nodes = Arc::new(RwLock::new(HashMap::new()));
         Self {
             nodes,
+            static_nodes,
+            static_peers: vec![],
+            static_peers_file: None,
             rpc_url,
             rpc_poll_interval,
         }
     }
 
+    /// allow the given peers regardless of the state of the sui blockchain
+    pub fn with_static_peers(mut self, static_peers: Vec<StaticPeer>) -> Result<Self> {
+        self.static_peers = static_peers;
+        self.reload_static_peers()?;
+        Ok(self)
+    }
+
+    /// allow the peers listed in a yaml file regardless of the state of the sui blockchain.
+    /// call poll_static_peers_file to pick up changes to the file.
+    pub fn with_static_peers_file(mut self, path: PathBuf) -> Result<Self> {
+        self.static_peers_file = Some(path);
+        self.reload_static_peers()?;
+        Ok(self)
+    }
+
     /// get is used to retrieve peer info in our handlers
     pub fn get(&self, key: &Ed25519PublicKey) -> Option<SuiPeer> {
         debug!("look for {:?}", key);
         if let Some(v) = self.nodes.read().unwrap().get(key) {
-            return Some(SuiPeer {
-                name: v.name.to_owned(),
-                p2p_address: v.p2p_address.to_owned(),
-                public_key: v.public_key.to_owned(),
-            });
+            return Some(v.to_owned());
+        }
+        if let Some(v) = self.static_nodes.read().unwrap().get(key) {
+            return Some(v.to_owned());
         }
         None
     }
@@ -164,10 +200,13 @@ impl SuiNodeProvider {
 
     /// poll_peer_list will act as a refresh interval for our cache
     pub fn poll_peer_list(&self) {
-        info!("Started polling for peers using rpc: {}", self.rpc_url);
+        let Some(rpc_url) = self.rpc_url.to_owned() else {
+            info!("no rpc url configured, peers will not be discovered on chain");
+            return;
+        };
+        info!("Started polling for peers using rpc: {}", rpc_url);
 
         let rpc_poll_interval = self.rpc_poll_interval;
-        let rpc_url = self.rpc_url.to_owned();
         let nodes = self.nodes.clone();
         tokio::spawn(async move {
             let mut interval = tokio::time::interval(rpc_poll_interval);
@@ -200,6 +239,97 @@ impl SuiNodeProvider {
     }
 }
 
+impl SuiNodeProvider {
+    /// replace our static peers with those from our config and our static peers file
+    fn reload_static_peers(&self) -> Result<()> {
+        let mut static_peers = self.static_peers.clone();
+        if let Some(path) = &self.static_peers_file {
+            static_peers.extend(load_static_peers_file(path)?);
+        }
+        let peers = static_peers
+            .iter()
+            .map(|peer| {
+                let peer = decode_static_peer(peer)?;
+                Ok((peer.public_key.clone(), peer))
+            })
+            .collect::<Result<HashMap<_, _>>>()?;
+        let mut allow = self.static_nodes.write().unwrap();
+        *allow = peers;
+        info!("{} static peers are on the allow list", allow.len());
+        Ok(())
+    }
+
+    /// poll_static_peers_file reloads our static peers whenever the static peers file is modified
+    pub fn poll_static_peers_file(&self) {
+        let Some(path) = self.static_peers_file.to_owned() else {
+            return;
+        };
+        info!("Started watching static peers file: {:?}", path);
+
+        let provider = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(provider.rpc_poll_interval);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+            let mut last_modified = modified(&path).ok();
+
+            loop {
+                interval.tick().await;
+
+                let modified = match modified(&path) {
+                    Ok(modified) => modified,
+                    Err(error) => {
+                        error!("unable to check static peers file {:?}: {error}", path);
+                        continue;
+                    }
+                };
+                if last_modified == Some(modified) {
+                    continue;
+                }
+                match provider.reload_static_peers() {
+                    Ok(()) => {
+                        last_modified = Some(modified);
+                        STATIC_PEERS_RELOAD.with_label_values(&["success"]).inc();
+                    }
+                    Err(error) => {
+                        // keep the peers we have, and try again on the next tick
+                        STATIC_PEERS_RELOAD.with_label_values(&["failed"]).inc();
+                        error!("unable to reload static peers file {:?}: {error}", path)
+                    }
+                }
+            }
+        });
+    }
+}
+
+fn modified(path: &PathBuf) -> Result<SystemTime> {
+    Ok(std::fs::metadata(path)?.modified()?)
+}
+
+/// load_static_peers_file reads a yaml list of StaticPeer
+fn load_static_peers_file(path: &PathBuf) -> Result<Vec<StaticPeer>> {
+    let file = std::fs::File::open(path).context(format!("cannot open {:?}", path))?;
+    serde_yaml::from_reader(file).context(format!("cannot decode static peers in {:?}", path))
+}
+
+/// decode_static_peer turns a static peer from our config into a SuiPeer we can allow
+fn decode_static_peer(peer: &StaticPeer) -> Result<SuiPeer> {
+    let public_key_bytes = Base64::decode(&peer.public_key)
+        .map_err(|error| anyhow!("unable to decode public key for {}: {error}", peer.name))?;
+    let public_key = Ed25519PublicKey::from_bytes(&public_key_bytes)
+        .map_err(|error| anyhow!("invalid ed25519 public key for {}: {error}", peer.name))?;
+    let p2p_address = match &peer.p2p_address {
+        Some(p2p_address) => p2p_address
+            .parse()
+            .map_err(|error| anyhow!("unable to decode multiaddr for {}: {error}", peer.name))?,
+        None => Multiaddr::empty(),
+    };
+    Ok(SuiPeer {
+        name: peer.name.to_owned(),
+        p2p_address,
+        public_key,
+    })
+}
+
 /// extract will get the network pubkey bytes from a SuiValidatorSummary type.  This type comes from a
 /// full node rpc result.  See get_validators for details.  The key here, if extracted successfully, will
 /// ultimately be stored in the allow list and let us communicate with those actual peers via tls.
@@ -283,4 +413,80 @@ mod tests {
         let peers = extract(deserialized.result);
         assert_eq!(peers.count(), 1, "peers should have been a length of 1");
     }
+
+    fn static_peer(name: &str) -> (Ed25519PublicKey, StaticPeer) {
+        let CertKeyPair(_, public_key) = generate_self_cert(name.into());
+        let peer = StaticPeer {
+            name: name.into(),
+            public_key: Base64::encode(public_key.as_bytes()),
+            p2p_address: Some("/ip4/127.0.0.1/tcp/10000".into()),
+        };
+        (public_key, peer)
+    }
+
+    #[test]
+    fn static_peers_are_allowed() {
+        let (public_key, peer) = static_peer("static-node");
+        let (unknown_key, _) = static_peer("unknown-node");
+
+        let allower = SuiNodeProvider::new(None, Duration::from_secs(30))
+            .with_static_peers(vec![peer])
+            .unwrap();
+        assert!(allower.allowed(&public_key));
+        assert!(!allower.allowed(&unknown_key));
+        assert_eq!(allower.get(&public_key).unwrap().name, "static-node");
+    }
+
+    #[test]
+    fn invalid_static_peers_are_rejected() {
+        let (_, mut peer) = static_peer("static-node");
+        peer.public_key = "not base64!".into();
+        assert!(SuiNodeProvider::new(None, Duration::from_secs(30))
+            .with_static_peers(vec![peer])
+            .is_err());
+    }
+
+    #[test]
+    fn static_peers_file_is_combined_with_static_peers() {
+        let (config_key, config_peer) = static_peer("config-node");
+        let (file_key, file_peer) = static_peer("file-node");
+        let (new_file_key, new_file_peer) = static_peer("new-file-node");
+
+        let file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(
+            file.path(),
+            serde_yaml::to_string(&vec![file_peer]).unwrap(),
+        )
+        .unwrap();
+
+        let mut allower = SuiNodeProvider::new(None, Duration::from_secs(30))
+            .with_static_peers(vec![config_peer])
+            .unwrap()
+            .with_static_peers_file(file.path().to_path_buf())
+            .unwrap();
+        assert!(allower.allowed(&config_key));
+        assert!(allower.allowed(&file_key));
+
+        // rpc driven peers are kept alongside static peers
+        let (rpc_key, _) = static_peer("rpc-node");
+        allower.get_mut().write().unwrap().insert(
+            rpc_key.clone(),
+            SuiPeer {
+                name: "rpc-node".into(),
+                p2p_address: Multiaddr::empty(),
+                public_key: rpc_key.clone(),
+            },
+        );
+
+        std::fs::write(
+            file.path(),
+            serde_yaml::to_string(&vec![new_file_peer]).unwrap(),
+        )
+        .unwrap();
+        allower.reload_static_peers().unwrap();
+        assert!(allower.allowed(&config_key));
+        assert!(!allower.allowed(&file_key));
+        assert!(allower.allowed(&new_file_key));
+        assert!(allower.allowed(&rpc_key));
+    }
 }
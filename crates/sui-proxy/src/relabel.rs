@@ -0,0 +1,383 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+use crate::config::RelabelConfig;
+use crate::consumer::RESERVED_LABELS;
+use anyhow::{bail, Context, Result};
+use once_cell::sync::Lazy;
+use prometheus::proto::{self, MetricFamily};
+use prometheus::{register_counter_vec, CounterVec};
+use regex::RegexSet;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+static RELABEL_DROPPED: Lazy<CounterVec> = Lazy::new(|| {
+    register_counter_vec!(
+        "relabel_dropped",
+        "Number of metrics dropped by relabel rules before remote_write.",
+        &["peer", "reason"]
+    )
+    .unwrap()
+});
+
+/// the series we have accepted from a single peer in the current cardinality window
+struct PeerSeries {
+    series: HashSet<u64>,
+    since: Instant,
+}
+
+/// Relabeler applies the relabel rules from our config to metrics pushed by peers, so that
+/// we control what, and how much, is forwarded to remote_write
+#[derive(Clone)]
+pub struct Relabeler {
+    drop_metrics: RegexSet,
+    drop_labels: HashSet<String>,
+    rename_labels: BTreeMap<String, String>,
+    max_series_per_peer: Option<usize>,
+    cardinality_window: Duration,
+    peers: Arc<Mutex<HashMap<String, PeerSeries>>>,
+}
+
+impl Default for Relabeler {
+    /// a relabeler that forwards everything as is
+    fn default() -> Self {
+        Self::new(RelabelConfig::default()).expect("default relabel config is valid")
+    }
+}
+
+impl Relabeler {
+    pub fn new(config: RelabelConfig) -> Result<Self> {
+        validate_rename_labels(&config.rename_labels)?;
+        Ok(Self {
+            drop_metrics: RegexSet::new(&config.drop_metrics)
+                .context("invalid drop-metrics regex")?,
+            drop_labels: config.drop_labels.into_iter().collect(),
+            rename_labels: config.rename_labels,
+            max_series_per_peer: config.max_series_per_peer,
+            cardinality_window: config.cardinality_window,
+            peers: Arc::new(Mutex::new(HashMap::new())),
+        })
+    }
+
+    /// apply our rules to the metric families received from peer, in order: drop metric
+    /// families by name, drop and rename labels, then enforce the per peer series limit.
+    /// metric families without any metric left are removed.
+    pub fn apply(&self, peer: &str, data: Vec<MetricFamily>) -> Vec<MetricFamily> {
+        let mut data = data;
+        let before = data.len();
+        data.retain(|mf| !self.drop_metrics.is_match(mf.get_name()));
+        if before != data.len() {
+            debug!(
+                "dropped {} metric families from {peer}",
+                before - data.len()
+            );
+            RELABEL_DROPPED
+                .with_label_values(&[peer, "metric_name"])
+                .inc_by((before - data.len()) as f64);
+        }
+
+        if !self.drop_labels.is_empty() || !self.rename_labels.is_empty() {
+            for mf in data.iter_mut() {
+                for m in mf.mut_metric() {
+                    self.relabel(m.mut_label());
+                }
+            }
+        }
+
+        if let Some(max_series) = self.max_series_per_peer {
+            self.limit_cardinality(peer, max_series, &mut data);
+        }
+        data.retain(|mf| !mf.get_metric().is_empty());
+        data
+    }
+
+    fn relabel(&self, labels: &mut protobuf::RepeatedField<proto::LabelPair>) {
+        labels.retain(|label| !self.drop_labels.contains(label.get_name()));
+        if self.rename_labels.is_empty() {
+            return;
+        }
+        // a label keeps its name if it isn't renamed, or if the metric already has a label it
+        // would be renamed to, so that renaming never produces duplicate label names
+        let mut kept: HashSet<String> = labels
+            .iter()
+            .filter(|label| !self.rename_labels.contains_key(label.get_name()))
+            .map(|label| label.get_name().to_owned())
+            .collect();
+        loop {
+            let skipped: Vec<String> = labels
+                .iter()
+                .filter(|label| !kept.contains(label.get_name()))
+                .filter(|label| kept.contains(&self.rename_labels[label.get_name()]))
+                .map(|label| label.get_name().to_owned())
+                .collect();
+            if skipped.is_empty() {
+                break;
+            }
+            kept.extend(skipped);
+        }
+        for label in labels.iter_mut() {
+            if !kept.contains(label.get_name()) {
+                label.set_name(self.rename_labels[label.get_name()].to_owned());
+            }
+        }
+    }
+
+    /// drop metrics that would take the number of distinct series from peer above
+    /// max_series in the current window. series we've already accepted are always kept
+    fn limit_cardinality(&self, peer: &str, max_series: usize, data: &mut [MetricFamily]) {
+        let mut peers = self.peers.lock().unwrap();
+        let peer_series = peers.entry(peer.to_owned()).or_insert_with(|| PeerSeries {
+            series: HashSet::new(),
+            since: Instant::now(),
+        });
+        if peer_series.since.elapsed() >= self.cardinality_window {
+            peer_series.series.clear();
+            peer_series.since = Instant::now();
+        }
+
+        let mut dropped = 0;
+        for mf in data.iter_mut() {
+            let name = mf.get_name().to_owned();
+            mf.mut_metric().retain(|m| {
+                let key = series_key(&name, m.get_label());
+                if peer_series.series.contains(&key) {
+                    return true;
+                }
+                if peer_series.series.len() < max_series {
+                    peer_series.series.insert(key);
+                    return true;
+                }
+                dropped += 1;
+                false
+            });
+        }
+        if dropped > 0 {
+            debug!("{peer} exceeded {max_series} series, dropped {dropped} metrics");
+            RELABEL_DROPPED
+                .with_label_values(&[peer, "cardinality"])
+                .inc_by(dropped as f64);
+        }
+    }
+}
+
+/// renamed labels must not produce duplicate label names: each target name is used once, and
+/// never for a label that populate_labels adds to every metric
+fn validate_rename_labels(rename_labels: &BTreeMap<String, String>) -> Result<()> {
+    let mut targets = HashSet::new();
+    for (from, to) in rename_labels {
+        if RESERVED_LABELS.contains(&to.as_str()) {
+            bail!("rename-labels cannot rename {from} to reserved label {to}");
+        }
+        if !targets.insert(to) {
+            bail!("rename-labels renames more than one label to {to}");
+        }
+    }
+    Ok(())
+}
+
+/// a series is identified by its metric name and label set, regardless of label order
+fn series_key(name: &str, labels: &[proto::LabelPair]) -> u64 {
+    let mut labels: Vec<_> = labels
+        .iter()
+        .map(|l| (l.get_name(), l.get_value()))
+        .collect();
+    labels.sort();
+    let mut hasher = DefaultHasher::new();
+    name.hash(&mut hasher);
+    labels.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prom_to_mimir::tests::{
+        create_counter, create_labels, create_metric_counter, create_metric_family,
+    };
+    use protobuf::RepeatedField;
+
+    fn counter_family(name: &str, series: Vec<Vec<(&str, &str)>>) -> MetricFamily {
+        create_metric_family(
+            name,
+            "some help this is",
+            None,
+            RepeatedField::from_vec(
+                series
+                    .into_iter()
+                    .map(|labels| {
+                        create_metric_counter(
+                            RepeatedField::from_vec(create_labels(labels)),
+                            create_counter(1.0),
+                        )
+                    })
+                    .collect(),
+            ),
+        )
+    }
+
+    #[test]
+    fn test_default_forwards_everything() {
+        let data = vec![
+            counter_family("go_goroutines", vec![vec![("pod", "a")]]),
+            counter_family("foo_metric", vec![vec![("some", "label")]]),
+        ];
+        let relabeled = Relabeler::default().apply("validator-0", data.clone());
+        assert_eq!(relabeled, data);
+    }
+
+    #[test]
+    fn test_drop_metrics() {
+        let relabeler = Relabeler::new(RelabelConfig {
+            drop_metrics: vec!["^go_.*".into(), "^process_cpu_seconds_total$".into()],
+            ..Default::default()
+        })
+        .unwrap();
+        let relabeled = relabeler.apply(
+            "validator-0",
+            vec![
+                counter_family("go_goroutines", vec![vec![]]),
+                counter_family("process_cpu_seconds_total", vec![vec![]]),
+                counter_family("foo_metric", vec![vec![]]),
+            ],
+        );
+        assert_eq!(relabeled.len(), 1);
+        assert_eq!(relabeled[0].get_name(), "foo_metric");
+    }
+
+    #[test]
+    fn test_invalid_regex() {
+        assert!(Relabeler::new(RelabelConfig {
+            drop_metrics: vec!["(".into()],
+            ..Default::default()
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn test_drop_and_rename_labels() {
+        let relabeler = Relabeler::new(RelabelConfig {
+            drop_labels: vec!["pod".into()],
+            rename_labels: BTreeMap::from([("instance".into(), "node".into())]),
+            ..Default::default()
+        })
+        .unwrap();
+        let relabeled = relabeler.apply(
+            "validator-0",
+            vec![counter_family(
+                "foo_metric",
+                vec![vec![("pod", "a"), ("instance", "b"), ("some", "label")]],
+            )],
+        );
+        assert_eq!(
+            relabeled[0].get_metric()[0].get_label(),
+            &create_labels(vec![("node", "b"), ("some", "label")])
+        );
+    }
+
+    #[test]
+    fn test_rename_to_existing_label() {
+        let relabeler = Relabeler::new(RelabelConfig {
+            rename_labels: BTreeMap::from([
+                ("instance".into(), "node".into()),
+                ("pod".into(), "instance".into()),
+            ]),
+            ..Default::default()
+        })
+        .unwrap();
+        let relabeled = relabeler.apply(
+            "validator-0",
+            vec![counter_family(
+                "foo_metric",
+                vec![
+                    // node is already present, so instance keeps its name, and pod can't take it
+                    vec![("instance", "a"), ("node", "b"), ("pod", "c")],
+                    vec![("instance", "a"), ("pod", "c")],
+                ],
+            )],
+        );
+        assert_eq!(
+            relabeled[0].get_metric()[0].get_label(),
+            &create_labels(vec![("instance", "a"), ("node", "b"), ("pod", "c")])
+        );
+        assert_eq!(
+            relabeled[0].get_metric()[1].get_label(),
+            &create_labels(vec![("node", "a"), ("instance", "c")])
+        );
+    }
+
+    #[test]
+    fn test_duplicate_rename_targets() {
+        assert!(Relabeler::new(RelabelConfig {
+            rename_labels: BTreeMap::from([
+                ("instance".into(), "node".into()),
+                ("pod".into(), "node".into()),
+            ]),
+            ..Default::default()
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn test_reserved_rename_targets() {
+        for reserved in RESERVED_LABELS {
+            assert!(Relabeler::new(RelabelConfig {
+                rename_labels: BTreeMap::from([("instance".into(), reserved.into())]),
+                ..Default::default()
+            })
+            .is_err());
+        }
+        // renaming away from a reserved label is fine
+        assert!(Relabeler::new(RelabelConfig {
+            rename_labels: BTreeMap::from([("host".into(), "peer_host".into())]),
+            ..Default::default()
+        })
+        .is_ok());
+    }
+
+    #[test]
+    fn test_max_series_per_peer() {
+        let relabeler = Relabeler::new(RelabelConfig {
+            max_series_per_peer: Some(2),
+            ..Default::default()
+        })
+        .unwrap();
+        let data = vec![
+            counter_family("foo_metric", vec![vec![("id", "1")], vec![("id", "2")]]),
+            counter_family("bar_metric", vec![vec![("id", "1")]]),
+        ];
+
+        // the first two series are accepted, the third is over the limit
+        let relabeled = relabeler.apply("validator-0", data.clone());
+        assert_eq!(relabeled.len(), 1);
+        assert_eq!(relabeled[0].get_name(), "foo_metric");
+        assert_eq!(relabeled[0].get_metric().len(), 2);
+
+        // series we've already accepted keep flowing
+        let relabeled = relabeler.apply("validator-0", data.clone());
+        assert_eq!(relabeled.len(), 1);
+        assert_eq!(relabeled[0].get_metric().len(), 2);
+
+        // limits are per peer
+        let relabeled = relabeler.apply("validator-1", data[1..].to_vec());
+        assert_eq!(relabeled.len(), 1);
+        assert_eq!(relabeled[0].get_name(), "bar_metric");
+    }
+
+    #[test]
+    fn test_cardinality_window() {
+        let relabeler = Relabeler::new(RelabelConfig {
+            max_series_per_peer: Some(1),
+            cardinality_window: Duration::ZERO,
+            ..Default::default()
+        })
+        .unwrap();
+        let foo = vec![counter_family("foo_metric", vec![vec![]])];
+        let bar = vec![counter_family("bar_metric", vec![vec![]])];
+        assert_eq!(relabeler.apply("validator-0", foo).len(), 1);
+        // the window has already elapsed, so the new series replaces the old one
+        assert_eq!(relabeler.apply("validator-0", bar).len(), 1);
+    }
+}
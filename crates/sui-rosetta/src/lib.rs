@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use std::net::SocketAddr;
+use std::path::PathBuf;
 use std::sync::Arc;
 
 use axum::routing::post;
@@ -14,7 +15,7 @@ use mysten_metrics::spawn_monitored_task;
 use sui_sdk::SuiClient;
 
 use crate::errors::Error;
-use crate::state::{CheckpointBlockProvider, OnlineServerContext};
+use crate::state::{CheckpointBlockProvider, CheckpointFileBlockProvider, OnlineServerContext};
 use crate::types::{Currency, SuiEnv};
 
 /// This lib implements the Rosetta online and offline server defined by the [Rosetta API Spec](https://www.rosetta-api.org/docs/Reference.html)
@@ -46,6 +47,20 @@ impl RosettaOnlineServer {
         }
     }
 
+    /// Creates an online server which reconstructs blocks from the checkpoint files in
+    /// `checkpoint_path` instead of querying the full node.
+    pub fn new_with_checkpoint_files(
+        env: SuiEnv,
+        client: SuiClient,
+        checkpoint_path: PathBuf,
+    ) -> Self {
+        let blocks = Arc::new(CheckpointFileBlockProvider::new(checkpoint_path));
+        Self {
+            env,
+            context: OnlineServerContext::new(client, blocks),
+        }
+    }
+
     pub fn serve(self, addr: SocketAddr) -> JoinHandle<hyper::Result<()>> {
         // Online endpoints
         let app = Router::new()
@@ -46,6 +46,9 @@ pub enum RosettaServerCommand {
         full_node_url: String,
         #[clap(long, default_value = "/data")]
         data_path: PathBuf,
+        /// Serve blocks from the checkpoint files in this directory instead of the full node.
+        #[clap(long)]
+        checkpoint_path: Option<PathBuf>,
     },
     StartOnlineServer {
         #[clap(long, default_value = "localnet")]
@@ -56,6 +59,9 @@ pub enum RosettaServerCommand {
         node_config: Option<PathBuf>,
         #[clap(long, default_value = "/data")]
         data_path: PathBuf,
+        /// Serve blocks from the checkpoint files in this directory instead of the full node.
+        #[clap(long)]
+        checkpoint_path: Option<PathBuf>,
     },
     StartOfflineServer {
         #[clap(long, default_value = "localnet")]
@@ -136,6 +142,7 @@ impl RosettaServerCommand {
                 addr,
                 full_node_url,
                 data_path,
+                checkpoint_path,
             } => {
                 info!(
                     "Starting Rosetta Online Server with remove Sui full node [{full_node_url}]."
@@ -143,7 +150,7 @@ impl RosettaServerCommand {
                 let sui_client = wait_for_sui_client(full_node_url).await;
                 let rosetta_path = data_path.join("rosetta_db");
                 info!("Rosetta db path : {rosetta_path:?}");
-                let rosetta = RosettaOnlineServer::new(env, sui_client);
+                let rosetta = online_server(env, sui_client, checkpoint_path);
                 rosetta.serve(addr).await??;
             }
 
@@ -152,6 +159,7 @@ impl RosettaServerCommand {
                 addr,
                 node_config,
                 data_path,
+                checkpoint_path,
             } => {
                 info!("Starting Rosetta Online Server with embedded Sui full node.");
                 info!("Data directory path: {data_path:?}");
@@ -176,7 +184,7 @@ impl RosettaServerCommand {
 
                 let rosetta_path = data_path.join("rosetta_db");
                 info!("Rosetta db path : {rosetta_path:?}");
-                let rosetta = RosettaOnlineServer::new(env, sui_client);
+                let rosetta = online_server(env, sui_client, checkpoint_path);
                 rosetta.serve(addr).await??;
             }
         };
@@ -184,6 +192,20 @@ impl RosettaServerCommand {
     }
 }
 
+fn online_server(
+    env: SuiEnv,
+    sui_client: SuiClient,
+    checkpoint_path: Option<PathBuf>,
+) -> RosettaOnlineServer {
+    match checkpoint_path {
+        Some(path) => {
+            info!("Serving blocks from checkpoint files in {path:?}");
+            RosettaOnlineServer::new_with_checkpoint_files(env, sui_client, path)
+        }
+        None => RosettaOnlineServer::new(env, sui_client),
+    }
+}
+
 async fn wait_for_sui_client(rpc_address: String) -> SuiClient {
     loop {
         match SuiClientBuilder::default()
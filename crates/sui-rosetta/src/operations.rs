@@ -15,18 +15,20 @@ use serde::Serialize;
 
 use sui_json_rpc_types::SuiProgrammableMoveCall;
 use sui_json_rpc_types::SuiProgrammableTransactionBlock;
-use sui_json_rpc_types::{BalanceChange, SuiArgument};
+use sui_json_rpc_types::{BalanceChange, SuiArgument, SuiExecutionStatus};
 use sui_json_rpc_types::{SuiCallArg, SuiCommand};
 use sui_sdk::rpc_types::{
     SuiTransactionBlockData, SuiTransactionBlockDataAPI, SuiTransactionBlockEffectsAPI,
     SuiTransactionBlockKind, SuiTransactionBlockResponse,
 };
 use sui_types::base_types::{ObjectID, SequenceNumber, SuiAddress};
+use sui_types::effects::TransactionEffectsAPI;
+use sui_types::full_checkpoint_content::CheckpointTransaction;
 use sui_types::gas_coin::{GasCoin, GAS};
 use sui_types::governance::{ADD_STAKE_FUN_NAME, WITHDRAW_STAKE_FUN_NAME};
 use sui_types::object::Owner;
 use sui_types::sui_system_state::SUI_SYSTEM_MODULE_NAME;
-use sui_types::transaction::TransactionData;
+use sui_types::transaction::{TransactionData, TransactionDataAPI};
 use sui_types::{SUI_SYSTEM_ADDRESS, SUI_SYSTEM_PACKAGE_ID};
 
 use crate::types::{
@@ -521,26 +523,9 @@ impl TryFrom<SuiTransactionBlockResponse> for Operations {
 
         let status = Some(effect.into_status().into());
         let ops: Operations = tx.data.try_into()?;
-        let ops = ops.set_status(status).into_iter();
 
-        // We will need to subtract the operation amounts from the actual balance
-        // change amount extracted from event to prevent double counting.
-        let mut accounted_balances =
-            ops.as_ref()
-                .iter()
-                .fold(HashMap::new(), |mut balances, op| {
-                    if let (Some(acc), Some(amount), Some(OperationStatus::Success)) =
-                        (&op.account, &op.amount, &op.status)
-                    {
-                        *balances.entry(acc.address).or_default() -= amount.value;
-                    }
-                    balances
-                });
-
-        let mut principal_amounts = 0;
-        let mut reward_amounts = 0;
         // Extract balance change from unstake events
-
+        let mut unstake_amounts = vec![];
         if let Some(events) = response.events {
             for event in events.data {
                 if is_unstake_event(&event.type_) {
@@ -557,12 +542,101 @@ impl TryFrom<SuiTransactionBlockResponse> for Operations {
                     if let (Some(principal_amount), Some(reward_amount)) =
                         (principal_amount, reward_amount)
                     {
-                        principal_amounts += principal_amount;
-                        reward_amounts += reward_amount;
+                        unstake_amounts.push((principal_amount, reward_amount));
                     }
                 }
             }
         }
+
+        Ok(Self::with_balance_changes(
+            ops,
+            sender,
+            gas_owner,
+            gas_used,
+            status,
+            unstake_amounts,
+            &response
+                .balance_changes
+                .ok_or_else(|| anyhow!("Response balance changes should not be empty."))?,
+        ))
+    }
+}
+
+impl Operations {
+    /// Build the operations of an executed transaction from its checkpoint content,
+    /// `balance_changes` must be computed from the transaction's input and output objects.
+    pub fn from_checkpoint_transaction(
+        tx: &CheckpointTransaction,
+        balance_changes: &[BalanceChange],
+    ) -> Result<Self, Error> {
+        let data = tx.transaction.data().transaction_data();
+        let sender = data.sender();
+        let effects = &tx.effects;
+        let gas_owner = effects.gas_object().1.get_owner_address()?;
+        let gas_summary = effects.gas_cost_summary();
+        let gas_used = gas_summary.storage_rebate as i128
+            - gas_summary.storage_cost as i128
+            - gas_summary.computation_cost as i128;
+
+        let status = Some(SuiExecutionStatus::from(effects.status().clone()).into());
+        let ops: Operations = data.clone().try_into()?;
+
+        let mut unstake_amounts = vec![];
+        if let Some(events) = &tx.events {
+            for event in &events.data {
+                if is_unstake_event(&event.type_) {
+                    let event: UnstakingRequestEvent = bcs::from_bytes(&event.contents)?;
+                    unstake_amounts
+                        .push((event.principal_amount as i128, event.reward_amount as i128));
+                }
+            }
+        }
+
+        Ok(Self::with_balance_changes(
+            ops,
+            sender,
+            gas_owner,
+            gas_used,
+            status,
+            unstake_amounts,
+            balance_changes,
+        ))
+    }
+
+    fn with_balance_changes(
+        ops: Operations,
+        sender: SuiAddress,
+        gas_owner: SuiAddress,
+        gas_used: i128,
+        status: Option<OperationStatus>,
+        unstake_amounts: Vec<(i128, i128)>,
+        balance_changes: &[BalanceChange],
+    ) -> Self {
+        let ops = ops.set_status(status).into_iter();
+
+        // We will need to subtract the operation amounts from the actual balance
+        // change amount extracted from event to prevent double counting.
+        let mut accounted_balances =
+            ops.as_ref()
+                .iter()
+                .fold(HashMap::new(), |mut balances, op| {
+                    if let (Some(acc), Some(amount), Some(OperationStatus::Success)) =
+                        (&op.account, &op.amount, &op.status)
+                    {
+                        *balances.entry(acc.address).or_default() -= amount.value;
+                    }
+                    balances
+                });
+
+        let (principal_amounts, reward_amounts) = unstake_amounts.into_iter().fold(
+            (0, 0),
+            |(principal_amounts, reward_amounts), (principal_amount, reward_amount)| {
+                (
+                    principal_amounts + principal_amount,
+                    reward_amounts + reward_amount,
+                )
+            },
+        );
         let staking_balance = if principal_amounts != 0 {
             *accounted_balances.entry(sender).or_default() -= principal_amounts;
             *accounted_balances.entry(sender).or_default() -= reward_amounts;
@@ -578,21 +652,32 @@ impl TryFrom<SuiTransactionBlockResponse> for Operations {
         let coin_change_operations = Self::process_balance_change(
             gas_owner,
             gas_used,
-            &response
-                .balance_changes
-                .ok_or_else(|| anyhow!("Response balance changes should not be empty."))?,
+            balance_changes,
             status,
             accounted_balances,
         );
 
-        Ok(ops
-            .into_iter()
+        ops.into_iter()
             .chain(coin_change_operations)
             .chain(staking_balance)
-            .collect())
+            .collect()
     }
 }
 
+/// Rust representation of `sui_system::validator::UnstakingRequestEvent`, used to read
+/// unstake amounts from BCS encoded events when the parsed JSON is not available.
+#[derive(Deserialize)]
+#[allow(dead_code)]
+struct UnstakingRequestEvent {
+    pool_id: ObjectID,
+    validator_address: SuiAddress,
+    staker_address: SuiAddress,
+    stake_activation_epoch: u64,
+    unstaking_epoch: u64,
+    principal_amount: u64,
+    reward_amount: u64,
+}
+
 fn is_unstake_event(tag: &StructTag) -> bool {
     tag.address == SUI_SYSTEM_ADDRESS
         && tag.module.as_ident_str() == ident_str!("validator")
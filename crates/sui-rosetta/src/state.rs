@@ -6,17 +6,37 @@ use crate::types::{
     Block, BlockHash, BlockIdentifier, BlockResponse, Transaction, TransactionIdentifier,
 };
 use crate::Error;
+use anyhow::anyhow;
 use async_trait::async_trait;
-use std::sync::Arc;
+use std::collections::{BTreeMap, HashMap};
+use std::ffi::OsStr;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use sui_json_rpc::{get_balance_changes_from_effect, ObjectProvider};
 use sui_json_rpc_types::SuiTransactionBlockResponseOptions;
 use sui_sdk::rpc_types::Checkpoint;
 use sui_sdk::SuiClient;
+use sui_storage::blob::Blob;
+use sui_types::base_types::{ObjectID, SequenceNumber};
+use sui_types::digests::CheckpointDigest;
+use sui_types::error::SuiError;
+use sui_types::full_checkpoint_content::CheckpointData;
 use sui_types::messages_checkpoint::CheckpointSequenceNumber;
+use sui_types::object::Object;
+use sui_types::transaction::TransactionDataAPI;
+use tokio::fs;
 
 #[cfg(test)]
 #[path = "unit_tests/balance_changing_tx_tests.rs"]
 mod balance_changing_tx_tests;
 
+#[cfg(test)]
+#[path = "unit_tests/checkpoint_file_tests.rs"]
+mod checkpoint_file_tests;
+
+const CHECKPOINT_FILE_EXTENSION: &str = "chk";
+
 #[derive(Clone)]
 pub struct OnlineServerContext {
     pub client: SuiClient,
@@ -134,31 +154,13 @@ impl CheckpointBlockProvider {
             }
         }
 
-        // previous digest should only be None for genesis block.
-        if checkpoint.previous_digest.is_none() && index != 0 {
-            return Err(Error::DataError(format!(
-                "Previous digest is None for checkpoint [{index}], digest: [{hash:?}]"
-            )));
-        }
-
-        let parent_block_identifier = checkpoint
-            .previous_digest
-            .map(|hash| BlockIdentifier {
-                index: index - 1,
-                hash,
-            })
-            .unwrap_or_else(|| BlockIdentifier { index, hash });
-
-        Ok(BlockResponse {
-            block: Block {
-                block_identifier: BlockIdentifier { index, hash },
-                parent_block_identifier,
-                timestamp: checkpoint.timestamp_ms,
-                transactions,
-                metadata: None,
-            },
-            other_transactions: vec![],
-        })
+        block_response(
+            index,
+            hash,
+            checkpoint.previous_digest,
+            checkpoint.timestamp_ms,
+            transactions,
+        )
     }
 
     async fn create_block_identifier(
@@ -176,3 +178,368 @@ impl CheckpointBlockProvider {
         })
     }
 }
+
+/// A [BlockProvider] which reconstructs blocks from a local directory of checkpoint files,
+/// in the format consumed by the data ingestion `LocalReader` (`<sequence_number>.chk`).
+/// Historic blocks can be served from these files without a fullnode keeping full history.
+#[derive(Clone)]
+pub struct CheckpointFileBlockProvider {
+    path: PathBuf,
+    digest_index: Arc<Mutex<CheckpointDigestIndex>>,
+}
+
+#[derive(Default)]
+struct CheckpointDigestIndex {
+    digests: BTreeMap<CheckpointSequenceNumber, CheckpointDigest>,
+    sequence_numbers: HashMap<CheckpointDigest, CheckpointSequenceNumber>,
+    /// The oldest and latest checkpoint files seen in the directory.
+    range: Option<(CheckpointSequenceNumber, CheckpointSequenceNumber)>,
+}
+
+impl CheckpointDigestIndex {
+    fn insert(&mut self, seq_number: CheckpointSequenceNumber, digest: CheckpointDigest) {
+        self.digests.insert(seq_number, digest);
+        self.sequence_numbers.insert(digest, seq_number);
+    }
+}
+
+#[async_trait]
+impl BlockProvider for CheckpointFileBlockProvider {
+    async fn get_block_by_index(&self, index: u64) -> Result<BlockResponse, Error> {
+        let checkpoint = self.read_checkpoint(index).await?;
+        self.create_block_response(checkpoint).await
+    }
+
+    async fn get_block_by_hash(&self, hash: BlockHash) -> Result<BlockResponse, Error> {
+        let index = self.find_sequence_number(&hash).await?;
+        self.get_block_by_index(index).await
+    }
+
+    async fn current_block(&self) -> Result<BlockResponse, Error> {
+        let checkpoint = self.latest_sequence_number().await?;
+        self.get_block_by_index(checkpoint).await
+    }
+
+    async fn genesis_block_identifier(&self) -> Result<BlockIdentifier, Error> {
+        match self.create_block_identifier(0).await {
+            Err(Error::BlockNotFound { .. }) => Err(Error::DataError(format!(
+                "Genesis checkpoint file is missing from {:?}, the checkpoint files must start \
+                 from checkpoint 0 to serve the genesis block",
+                self.path
+            ))),
+            result => result,
+        }
+    }
+
+    async fn oldest_block_identifier(&self) -> Result<BlockIdentifier, Error> {
+        let checkpoint = self.oldest_sequence_number().await?;
+        self.create_block_identifier(checkpoint).await
+    }
+
+    async fn current_block_identifier(&self) -> Result<BlockIdentifier, Error> {
+        let checkpoint = self.latest_sequence_number().await?;
+        self.create_block_identifier(checkpoint).await
+    }
+
+    async fn create_block_identifier(
+        &self,
+        checkpoint: CheckpointSequenceNumber,
+    ) -> Result<BlockIdentifier, Error> {
+        let hash = self.checkpoint_digest(checkpoint).await?;
+        Ok(BlockIdentifier {
+            index: checkpoint,
+            hash,
+        })
+    }
+}
+
+impl CheckpointFileBlockProvider {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            digest_index: Default::default(),
+        }
+    }
+
+    fn checkpoint_file(&self, seq_number: CheckpointSequenceNumber) -> PathBuf {
+        self.path
+            .join(format!("{seq_number}.{CHECKPOINT_FILE_EXTENSION}"))
+    }
+
+    async fn checkpoint_file_exists(&self, seq_number: CheckpointSequenceNumber) -> bool {
+        fs::metadata(self.checkpoint_file(seq_number)).await.is_ok()
+    }
+
+    async fn read_checkpoint(
+        &self,
+        seq_number: CheckpointSequenceNumber,
+    ) -> Result<CheckpointData, Error> {
+        let file = self.checkpoint_file(seq_number);
+        let bytes = match fs::read(&file).await {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                return Err(Error::BlockNotFound {
+                    index: Some(seq_number),
+                    hash: None,
+                })
+            }
+            Err(e) => return Err(anyhow!("Failed to read checkpoint file {file:?}: {e}").into()),
+        };
+        let checkpoint = Blob::from_bytes::<CheckpointData>(&bytes)?;
+        let summary = &checkpoint.checkpoint_summary;
+        if summary.sequence_number != seq_number {
+            return Err(Error::DataError(format!(
+                "Checkpoint file {file:?} contains checkpoint [{}]",
+                summary.sequence_number
+            )));
+        }
+        self.digest_index
+            .lock()
+            .unwrap()
+            .insert(seq_number, *summary.digest());
+        Ok(checkpoint)
+    }
+
+    /// Scans the directory for the oldest and latest checkpoint files.
+    async fn scan_checkpoint_files(
+        &self,
+    ) -> Result<Option<(CheckpointSequenceNumber, CheckpointSequenceNumber)>, Error> {
+        let mut entries = fs::read_dir(&self.path)
+            .await
+            .map_err(|e| anyhow!("Failed to read checkpoint directory {:?}: {e}", self.path))?;
+        let mut range: Option<(CheckpointSequenceNumber, CheckpointSequenceNumber)> = None;
+        while let Some(entry) = entries.next_entry().await.map_err(anyhow::Error::from)? {
+            let path = entry.path();
+            if path.extension() != Some(OsStr::new(CHECKPOINT_FILE_EXTENSION)) {
+                continue;
+            }
+            if let Some(seq_number) = path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .and_then(|s| s.parse().ok())
+            {
+                range = Some(match range {
+                    Some((oldest, latest)) => (oldest.min(seq_number), latest.max(seq_number)),
+                    None => (seq_number, seq_number),
+                });
+            }
+        }
+        Ok(range)
+    }
+
+    /// Returns the oldest and latest checkpoint files in the directory. Files are written in
+    /// order and only pruned from the oldest end, so the directory is only rescanned once the
+    /// cached oldest file is gone, and newer files are found by probing past the cached latest.
+    async fn checkpoint_range(
+        &self,
+    ) -> Result<Option<(CheckpointSequenceNumber, CheckpointSequenceNumber)>, Error> {
+        let cached = self.digest_index.lock().unwrap().range;
+        let range = match cached {
+            Some((oldest, latest)) if self.checkpoint_file_exists(oldest).await => {
+                Some((oldest, latest))
+            }
+            _ => self.scan_checkpoint_files().await?,
+        };
+        let range = match range {
+            Some((oldest, mut latest)) => {
+                while self.checkpoint_file_exists(latest + 1).await {
+                    latest += 1;
+                }
+                Some((oldest, latest))
+            }
+            None => None,
+        };
+        self.digest_index.lock().unwrap().range = range;
+        Ok(range)
+    }
+
+    async fn latest_sequence_number(&self) -> Result<CheckpointSequenceNumber, Error> {
+        self.checkpoint_range()
+            .await?
+            .map(|(_, latest)| latest)
+            .ok_or_else(|| Error::DataError(format!("No checkpoint files in {:?}", self.path)))
+    }
+
+    async fn oldest_sequence_number(&self) -> Result<CheckpointSequenceNumber, Error> {
+        self.checkpoint_range()
+            .await?
+            .map(|(oldest, _)| oldest)
+            .ok_or_else(|| Error::DataError(format!("No checkpoint files in {:?}", self.path)))
+    }
+
+    async fn checkpoint_digest(
+        &self,
+        seq_number: CheckpointSequenceNumber,
+    ) -> Result<CheckpointDigest, Error> {
+        if let Some(digest) = self.digest_index.lock().unwrap().digests.get(&seq_number) {
+            return Ok(*digest);
+        }
+        Ok(*self
+            .read_checkpoint(seq_number)
+            .await?
+            .checkpoint_summary
+            .digest())
+    }
+
+    /// Looks up the sequence number of a checkpoint by digest. Each checkpoint file is only read
+    /// once to index its digest, so a lookup only reads the files added since the last one.
+    async fn find_sequence_number(
+        &self,
+        hash: &BlockHash,
+    ) -> Result<CheckpointSequenceNumber, Error> {
+        if let Some(seq_number) = self.digest_index.lock().unwrap().sequence_numbers.get(hash) {
+            return Ok(*seq_number);
+        }
+        self.index_checkpoint_files().await?;
+        self.digest_index
+            .lock()
+            .unwrap()
+            .sequence_numbers
+            .get(hash)
+            .copied()
+            .ok_or(Error::BlockNotFound {
+                index: None,
+                hash: Some(*hash),
+            })
+    }
+
+    /// Indexes the digests of the checkpoint files which have not been read yet.
+    async fn index_checkpoint_files(&self) -> Result<(), Error> {
+        let Some((oldest, latest)) = self.checkpoint_range().await? else {
+            return Ok(());
+        };
+        let unindexed: Vec<_> = {
+            let index = self.digest_index.lock().unwrap();
+            (oldest..=latest)
+                .filter(|seq_number| !index.digests.contains_key(seq_number))
+                .collect()
+        };
+        for seq_number in unindexed {
+            match self.read_checkpoint(seq_number).await {
+                // The file may have been pruned since the directory was scanned.
+                Ok(_) | Err(Error::BlockNotFound { .. }) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+
+    async fn create_block_response(
+        &self,
+        checkpoint: CheckpointData,
+    ) -> Result<BlockResponse, Error> {
+        let summary = &checkpoint.checkpoint_summary;
+        let index = summary.sequence_number;
+        let hash = *summary.digest();
+        let objects = CheckpointObjectProvider::new(&checkpoint);
+        let mut transactions = vec![];
+        for tx in &checkpoint.transactions {
+            let balance_changes = get_balance_changes_from_effect(
+                &objects,
+                &tx.effects,
+                tx.transaction
+                    .data()
+                    .transaction_data()
+                    .input_objects()
+                    .map_err(SuiError::from)?,
+                None,
+            )
+            .await?;
+            transactions.push(Transaction {
+                transaction_identifier: TransactionIdentifier {
+                    hash: *tx.transaction.digest(),
+                },
+                operations: Operations::from_checkpoint_transaction(tx, &balance_changes)?,
+                related_transactions: vec![],
+                metadata: None,
+            })
+        }
+
+        block_response(
+            index,
+            hash,
+            summary.previous_digest,
+            summary.timestamp_ms,
+            transactions,
+        )
+    }
+}
+
+/// Serves the input and output objects of a checkpoint for balance change calculation.
+struct CheckpointObjectProvider {
+    objects: HashMap<(ObjectID, SequenceNumber), Object>,
+}
+
+impl CheckpointObjectProvider {
+    fn new(checkpoint: &CheckpointData) -> Self {
+        let objects = checkpoint
+            .all_objects()
+            .into_iter()
+            .map(|o| ((o.id(), o.version()), o.clone()))
+            .collect();
+        Self { objects }
+    }
+}
+
+#[async_trait]
+impl ObjectProvider for CheckpointObjectProvider {
+    type Error = Error;
+
+    async fn get_object(
+        &self,
+        id: &ObjectID,
+        version: &SequenceNumber,
+    ) -> Result<Object, Self::Error> {
+        self.objects.get(&(*id, *version)).cloned().ok_or_else(|| {
+            Error::DataError(format!(
+                "Object [{id}] at version [{version}] not found in checkpoint data"
+            ))
+        })
+    }
+
+    async fn find_object_lt_or_eq_version(
+        &self,
+        id: &ObjectID,
+        version: &SequenceNumber,
+    ) -> Result<Option<Object>, Self::Error> {
+        Ok(self
+            .objects
+            .iter()
+            .filter(|((object_id, object_version), _)| object_id == id && object_version <= version)
+            .max_by_key(|((_, object_version), _)| *object_version)
+            .map(|(_, o)| o.clone()))
+    }
+}
+
+fn block_response(
+    index: CheckpointSequenceNumber,
+    hash: BlockHash,
+    previous_digest: Option<CheckpointDigest>,
+    timestamp: u64,
+    transactions: Vec<Transaction>,
+) -> Result<BlockResponse, Error> {
+    // previous digest should only be None for genesis block.
+    if previous_digest.is_none() && index != 0 {
+        return Err(Error::DataError(format!(
+            "Previous digest is None for checkpoint [{index}], digest: [{hash:?}]"
+        )));
+    }
+
+    let parent_block_identifier = previous_digest
+        .map(|hash| BlockIdentifier {
+            index: index - 1,
+            hash,
+        })
+        .unwrap_or_else(|| BlockIdentifier { index, hash });
+
+    Ok(BlockResponse {
+        block: Block {
+            block_identifier: BlockIdentifier { index, hash },
+            parent_block_identifier,
+            timestamp,
+            transactions,
+            metadata: None,
+        },
+        other_transactions: vec![],
+    })
+}
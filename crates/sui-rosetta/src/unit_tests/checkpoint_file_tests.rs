@@ -0,0 +1,300 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::path::Path;
+use std::time::Duration;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use sui_json_rpc_types::{SuiTransactionBlockResponse, SuiTransactionBlockResponseOptions};
+use sui_sdk::SuiClient;
+use sui_storage::blob::{Blob, BlobEncoding};
+use sui_types::base_types::TransactionDigest;
+use sui_types::crypto::KeypairTraits;
+use sui_types::full_checkpoint_content::CheckpointData;
+use sui_types::gas::GasCostSummary;
+use sui_types::messages_checkpoint::{
+    CertifiedCheckpointSummary, CheckpointContents, CheckpointDigest, CheckpointSummary,
+    SignedCheckpointSummary,
+};
+use sui_types::transaction::{TEST_ONLY_GAS_UNIT_FOR_STAKING, TEST_ONLY_GAS_UNIT_FOR_TRANSFER};
+use sui_types::utils::make_committee_key;
+use test_cluster::TestClusterBuilder;
+
+use crate::errors::Error;
+use crate::operations::Operations;
+use crate::state::{BlockProvider, CheckpointFileBlockProvider};
+use crate::types::OperationType;
+
+const RNG_SEED: [u8; 32] = [
+    21, 23, 199, 200, 234, 250, 252, 178, 94, 15, 202, 178, 62, 186, 88, 137, 233, 192, 130, 157,
+    179, 179, 65, 9, 31, 249, 221, 123, 225, 112, 199, 247,
+];
+
+/// Writes empty checkpoints `start..end` to `path`, returning their digests.
+fn write_checkpoints(path: &Path, start: u64, end: u64) -> Vec<CheckpointDigest> {
+    let mut rng = StdRng::from_seed(RNG_SEED);
+    let (keys, committee) = make_committee_key(&mut rng);
+    let mut previous_digest = None;
+    let mut digests = vec![];
+    for seq_number in 0..end {
+        let contents = CheckpointContents::new_with_digests_only_for_tests(vec![]);
+        let summary = CheckpointSummary::new(
+            0,
+            seq_number,
+            0,
+            &contents,
+            previous_digest,
+            GasCostSummary::default(),
+            None,
+            seq_number * 1000,
+        );
+        let sign_infos: Vec<_> = keys
+            .iter()
+            .map(|k| {
+                let name = k.public().into();
+                SignedCheckpointSummary::sign(committee.epoch, &summary, k, name)
+            })
+            .collect();
+        let checkpoint_summary =
+            CertifiedCheckpointSummary::new(summary, sign_infos, &committee).unwrap();
+        previous_digest = Some(*checkpoint_summary.digest());
+
+        if seq_number < start {
+            continue;
+        }
+        digests.push(*checkpoint_summary.digest());
+        let checkpoint_data = CheckpointData {
+            checkpoint_summary,
+            checkpoint_contents: contents,
+            transactions: vec![],
+        };
+        let bytes = Blob::encode(&checkpoint_data, BlobEncoding::Bcs)
+            .unwrap()
+            .to_bytes();
+        std::fs::write(path.join(format!("{seq_number}.chk")), bytes).unwrap();
+    }
+    digests
+}
+
+#[tokio::test]
+async fn test_blocks_from_checkpoint_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let digests = write_checkpoints(dir.path(), 0, 5);
+    let blocks = CheckpointFileBlockProvider::new(dir.path().to_path_buf());
+
+    let block = blocks.get_block_by_index(3).await.unwrap().block;
+    assert_eq!(3, block.block_identifier.index);
+    assert_eq!(digests[3], block.block_identifier.hash);
+    assert_eq!(2, block.parent_block_identifier.index);
+    assert_eq!(digests[2], block.parent_block_identifier.hash);
+    assert_eq!(3000, block.timestamp);
+
+    let block = blocks.get_block_by_hash(digests[1]).await.unwrap().block;
+    assert_eq!(1, block.block_identifier.index);
+
+    let genesis = blocks.genesis_block_identifier().await.unwrap();
+    assert_eq!(0, genesis.index);
+    assert_eq!(digests[0], genesis.hash);
+
+    let current = blocks.current_block_identifier().await.unwrap();
+    assert_eq!(4, current.index);
+    assert_eq!(digests[4], current.hash);
+
+    assert!(matches!(
+        blocks.get_block_by_index(5).await,
+        Err(Error::BlockNotFound { index: Some(5), .. })
+    ));
+}
+
+#[tokio::test]
+async fn test_pruned_checkpoint_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let digests = write_checkpoints(dir.path(), 10, 15);
+    let blocks = CheckpointFileBlockProvider::new(dir.path().to_path_buf());
+
+    let oldest = blocks.oldest_block_identifier().await.unwrap();
+    assert_eq!(10, oldest.index);
+    assert_eq!(digests[0], oldest.hash);
+
+    let current = blocks.current_block().await.unwrap().block;
+    assert_eq!(14, current.block_identifier.index);
+
+    assert!(matches!(
+        blocks.genesis_block_identifier().await,
+        Err(Error::DataError(_))
+    ));
+    assert!(matches!(
+        blocks.get_block_by_hash(CheckpointDigest::random()).await,
+        Err(Error::BlockNotFound { hash: Some(_), .. })
+    ));
+
+    // The cached range follows checkpoints being written and pruned.
+    let new_digests = write_checkpoints(dir.path(), 15, 17);
+    std::fs::remove_file(dir.path().join("10.chk")).unwrap();
+    std::fs::remove_file(dir.path().join("11.chk")).unwrap();
+    let oldest = blocks.oldest_block_identifier().await.unwrap();
+    assert_eq!(12, oldest.index);
+    assert_eq!(digests[2], oldest.hash);
+    let current = blocks.current_block_identifier().await.unwrap();
+    assert_eq!(16, current.index);
+    assert_eq!(new_digests[1], current.hash);
+}
+
+#[tokio::test]
+async fn test_checkpoint_files_indexed_once() {
+    let dir = tempfile::tempdir().unwrap();
+    let digests = write_checkpoints(dir.path(), 0, 5);
+    let blocks = CheckpointFileBlockProvider::new(dir.path().to_path_buf());
+
+    assert_eq!(1, blocks.find_sequence_number(&digests[1]).await.unwrap());
+
+    // Lookups after the first one are answered from the index, without reading the files again.
+    for seq_number in 0..5 {
+        std::fs::remove_file(dir.path().join(format!("{seq_number}.chk"))).unwrap();
+    }
+    assert_eq!(3, blocks.find_sequence_number(&digests[3]).await.unwrap());
+    assert!(matches!(
+        blocks
+            .find_sequence_number(&CheckpointDigest::random())
+            .await,
+        Err(Error::BlockNotFound { hash: Some(_), .. })
+    ));
+
+    // Checkpoints written after the index was built are indexed on the next miss.
+    let digests = write_checkpoints(dir.path(), 5, 7);
+    assert_eq!(6, blocks.find_sequence_number(&digests[1]).await.unwrap());
+}
+
+/// Operations built from checkpoint files match the ones built from the fullnode's responses, for
+/// transactions with balance changes, gas and unstake events.
+#[tokio::test]
+async fn test_operations_from_checkpoint_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let network = TestClusterBuilder::new()
+        .with_data_ingestion_dir(dir.path().to_path_buf())
+        .build()
+        .await;
+    let client = network.wallet.get_client().await.unwrap();
+    let rgp = network.get_reference_gas_price().await;
+    let sender = network.get_address_0();
+    let recipient = network.get_address_1();
+    let validator = client
+        .governance_api()
+        .get_latest_sui_system_state()
+        .await
+        .unwrap()
+        .active_validators[0]
+        .sui_address;
+    let coins = client
+        .coin_read_api()
+        .get_coins(sender, None, None, None)
+        .await
+        .unwrap()
+        .data;
+
+    let transfer = client
+        .transaction_builder()
+        .transfer_sui(
+            sender,
+            coins[0].coin_object_id,
+            rgp * TEST_ONLY_GAS_UNIT_FOR_TRANSFER,
+            recipient,
+            Some(50000),
+        )
+        .await
+        .unwrap();
+    let transfer = network.sign_and_execute_transaction(&transfer).await.digest;
+
+    let stake = client
+        .transaction_builder()
+        .request_add_stake(
+            sender,
+            vec![coins[1].coin_object_id],
+            Some(1_000_000_000),
+            validator,
+            None,
+            rgp * TEST_ONLY_GAS_UNIT_FOR_STAKING,
+        )
+        .await
+        .unwrap();
+    let stake = network.sign_and_execute_transaction(&stake).await.digest;
+
+    // The stake can only be withdrawn once it is active, in the next epoch.
+    network.trigger_reconfiguration().await;
+    let staked_sui =
+        client.governance_api().get_stakes(sender).await.unwrap()[0].stakes[0].staked_sui_id;
+    let unstake = client
+        .transaction_builder()
+        .request_withdraw_stake(
+            sender,
+            staked_sui,
+            None,
+            rgp * TEST_ONLY_GAS_UNIT_FOR_STAKING,
+        )
+        .await
+        .unwrap();
+    let unstake = network.sign_and_execute_transaction(&unstake).await.digest;
+
+    let blocks = CheckpointFileBlockProvider::new(dir.path().to_path_buf());
+    for (digest, operation_type) in [
+        (transfer, OperationType::Gas),
+        (stake, OperationType::Stake),
+        (unstake, OperationType::StakePrinciple),
+    ] {
+        let response = checkpointed_transaction(&client, digest).await;
+        let checkpoint = response.checkpoint.unwrap();
+        let expected = Operations::try_from(response).unwrap();
+
+        let block = tokio::time::timeout(Duration::from_secs(60), async {
+            // Checkpoint files may still be being written by the validators.
+            loop {
+                if let Ok(block) = blocks.get_block_by_index(checkpoint).await {
+                    return block.block;
+                }
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+        })
+        .await
+        .unwrap();
+        let tx = block
+            .transactions
+            .into_iter()
+            .find(|tx| tx.transaction_identifier.hash == digest)
+            .unwrap();
+
+        assert!(tx
+            .operations
+            .clone()
+            .into_iter()
+            .any(|op| op.type_ == operation_type));
+        assert_eq!(expected, tx.operations);
+    }
+}
+
+/// The response of transaction `digest` once it is included in a checkpoint.
+async fn checkpointed_transaction(
+    client: &SuiClient,
+    digest: TransactionDigest,
+) -> SuiTransactionBlockResponse {
+    let options = SuiTransactionBlockResponseOptions::new()
+        .with_input()
+        .with_effects()
+        .with_events()
+        .with_balance_changes();
+    tokio::time::timeout(Duration::from_secs(60), async {
+        loop {
+            let response = client
+                .read_api()
+                .get_transaction_with_options(digest, options.clone())
+                .await
+                .unwrap();
+            if response.checkpoint.is_some() {
+                return response;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    })
+    .await
+    .unwrap()
+}
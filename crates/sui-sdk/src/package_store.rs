@@ -0,0 +1,47 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::anyhow;
+use sui_json_rpc_types::{SuiObjectDataOptions, SuiRawData};
+use sui_move_build::on_chain::OnChainPackageStore;
+use sui_types::base_types::ObjectID;
+use sui_types::move_package::MovePackage;
+
+use crate::SuiClientBuilder;
+
+/// Store which fetches packages from a full node over JSON-RPC, e.g. to resolve the on-chain
+/// dependencies of Move packages.
+#[derive(Default)]
+pub struct RpcPackageStore;
+
+impl RpcPackageStore {
+    pub async fn fetch(&self, rpc_url: &str, id: ObjectID) -> anyhow::Result<MovePackage> {
+        let client = SuiClientBuilder::default().build(rpc_url).await?;
+        let data = client
+            .read_api()
+            .get_object_with_options(id, SuiObjectDataOptions::new().with_bcs())
+            .await?
+            .into_object()
+            .map_err(|_| anyhow!("Package {id} not found"))?;
+        let Some(SuiRawData::Package(package)) = data.bcs else {
+            return Err(anyhow!("Object {id} is not a package"));
+        };
+        Ok(package.to_move_package(u64::MAX)?)
+    }
+}
+
+impl OnChainPackageStore for RpcPackageStore {
+    fn fetch_package(&self, node_url: &str, id: ObjectID) -> anyhow::Result<MovePackage> {
+        // Dependencies may be resolved from within an async runtime, which cannot be blocked on,
+        // so the package is fetched on a thread of its own.
+        let node_url = node_url.to_owned();
+        std::thread::spawn(move || -> anyhow::Result<MovePackage> {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()?;
+            runtime.block_on(RpcPackageStore.fetch(&node_url, id))
+        })
+        .join()
+        .map_err(|_| anyhow!("Package fetch panicked"))?
+    }
+}
@@ -15,6 +15,17 @@ use sui_types::base_types::*;
 #[serde_as]
 #[derive(Serialize, Deserialize)]
 pub struct SuiClientConfig {
+    /// The keystore signing the transactions of the client. Besides a keystore file, keys can be
+    /// held by an external signer, e.g. a hardware wallet, with:
+    /// ```yaml
+    /// keystore:
+    ///   External:
+    ///     signer:
+    ///       Command:
+    ///         command: /path/to/signer
+    ///         args: []
+    /// ```
+    /// where the signer program implements the protocol of `sui_keys::external_signer`.
     pub keystore: Keystore,
     pub envs: Vec<SuiEnv>,
     pub active_env: Option<String>,
@@ -29,6 +29,8 @@ pub struct WalletContext {
 }
 
 impl WalletContext {
+    /// Load the wallet from its config. An encrypted keystore is unlocked when a private key is
+    /// first used, and stays unlocked for the lifetime of the context.
     pub async fn new(
         config_path: &Path,
         request_timeout: Option<std::time::Duration>,
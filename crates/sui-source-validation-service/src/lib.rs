@@ -34,7 +34,8 @@ use move_core_types::account_address::AccountAddress;
 use move_package::BuildConfig as MoveBuildConfig;
 use move_symbol_pool::Symbol;
 use sui_move::build::resolve_lock_file_path;
-use sui_move_build::{BuildConfig, SuiPackageHooks};
+use sui_move_build::{on_chain::OnChainPackageHooks, BuildConfig};
+use sui_sdk::package_store::RpcPackageStore;
 use sui_sdk::rpc_types::{SuiTransactionBlockEffects, TransactionFilter};
 use sui_sdk::types::base_types::ObjectID;
 use sui_sdk::SuiClientBuilder;
@@ -147,7 +148,9 @@ pub async fn verify_package(
     network: &Network,
     package_path: impl AsRef<Path>,
 ) -> anyhow::Result<(Network, AddressLookup)> {
-    move_package::package_hooks::register_package_hooks(Box::new(SuiPackageHooks));
+    move_package::package_hooks::register_package_hooks(Box::new(OnChainPackageHooks::new(
+        RpcPackageStore,
+    )));
     let mut config = resolve_lock_file_path(
         MoveBuildConfig::default(),
         Some(package_path.as_ref().to_path_buf()),
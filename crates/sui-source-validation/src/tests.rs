@@ -3,13 +3,18 @@
 
 use expect_test::expect;
 use move_core_types::account_address::AccountAddress;
+use move_package::package_hooks::PackageHooks;
+use move_package::source_package::parsed_manifest::CustomDepInfo;
+use move_symbol_pool::Symbol;
 use std::collections::HashMap;
 use std::{fs, io, path::Path};
 use std::{path::PathBuf, str};
 use sui_json_rpc_types::{
     get_new_package_obj_from_response, get_new_package_upgrade_cap_from_response,
 };
+use sui_move_build::on_chain::OnChainPackageHooks;
 use sui_move_build::{BuildConfig, CompiledPackage, SuiPackageHooks};
+use sui_sdk::package_store::RpcPackageStore;
 use sui_sdk::wallet_context::WalletContext;
 use sui_test_transaction_builder::{make_publish_transaction, make_publish_transaction_with_deps};
 use sui_types::base_types::ObjectID;
@@ -109,6 +114,55 @@ async fn successful_verification_unpublished_deps() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn successful_verification_on_chain_deps() -> anyhow::Result<()> {
+    let mut cluster = TestClusterBuilder::new().build().await;
+    let rpc_url = cluster.rpc_url().to_string();
+    let context = &mut cluster.wallet;
+
+    let b_ref = {
+        let fixtures = tempfile::tempdir()?;
+        let b_src = copy_published_package(&fixtures, "b", SuiAddress::ZERO).await?;
+        publish_package(context, b_src).await.0
+    };
+
+    // Depend on `b` by its on-chain ID, without its source.
+    let fixtures = tempfile::tempdir()?;
+    let a_src = copy_published_package(&fixtures, "a", SuiAddress::ZERO).await?;
+    let toml = fs::read_to_string(a_src.join("Move.toml"))?.replace(
+        r#"b = { local = "../b" }"#,
+        &format!(
+            r#"b = {{ on-chain = "{rpc_url}", address = "{}" }}"#,
+            b_ref.0
+        ),
+    );
+    fs::write(a_src.join("Move.toml"), toml)?;
+
+    // Fetch `b` into the cache through the hooks directly, rather than registering them, as
+    // concurrent tests register hooks which can't fetch packages.
+    let b_dep = CustomDepInfo {
+        node_url: Symbol::from(rpc_url.as_str()),
+        package_address: Symbol::from(b_ref.0.to_string()),
+        package_name: Symbol::from("b"),
+        subdir: PathBuf::new(),
+    };
+    OnChainPackageHooks::new(RpcPackageStore)
+        .resolve_custom_dependency(Symbol::from("b"), &b_dep)?;
+    let a_pkg = compile_package(a_src);
+    assert_eq!(
+        a_pkg.dependency_ids.published.get(&Symbol::from("b")),
+        Some(&b_ref.0)
+    );
+
+    let client = context.get_client().await?;
+    let verifier = BytecodeSourceVerifier::new(client.read_api());
+
+    // The fetched bytecode matches the on-chain package
+    verifier.verify_package_deps(&a_pkg).await.unwrap();
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn successful_verification_module_ordering() -> anyhow::Result<()> {
     let mut cluster = TestClusterBuilder::new().build().await;
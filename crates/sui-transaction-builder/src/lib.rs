@@ -30,7 +30,8 @@ use sui_types::object::{Object, Owner};
 use sui_types::programmable_transaction_builder::ProgrammableTransactionBuilder;
 use sui_types::sui_system_state::SUI_SYSTEM_MODULE_NAME;
 use sui_types::transaction::{
-    Argument, CallArg, Command, InputObjectKind, ObjectArg, TransactionData, TransactionKind,
+    Argument, CallArg, Command, InputObjectKind, ObjectArg, ProgrammableTransaction,
+    TransactionData, TransactionKind,
 };
 use sui_types::{coin, fp_ensure, SUI_FRAMEWORK_PACKAGE_ID, SUI_SYSTEM_PACKAGE_ID};
 
@@ -651,7 +652,20 @@ impl TransactionBuilder {
                 }
             };
         }
-        let pt = builder.finish();
+        self.programmable_transaction(signer, builder.finish(), gas, gas_budget)
+            .await
+    }
+
+    /// Build the transaction data for an already constructed programmable transaction.
+    /// If `gas` is not provided, a gas coin of the signer that is not an input of the
+    /// transaction is selected.
+    pub async fn programmable_transaction(
+        &self,
+        signer: SuiAddress,
+        pt: ProgrammableTransaction,
+        gas: Option<ObjectID>,
+        gas_budget: u64,
+    ) -> anyhow::Result<TransactionData> {
         let all_inputs = pt.input_objects()?;
         let inputs = all_inputs
             .iter()
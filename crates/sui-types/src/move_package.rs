@@ -14,6 +14,9 @@ use derive_more::Display;
 use fastcrypto::hash::HashFunction;
 use move_binary_format::access::ModuleAccess;
 use move_binary_format::binary_views::BinaryIndexedView;
+use move_binary_format::compatibility::{Compatibility, InclusionCheck};
+use move_binary_format::errors::PartialVMResult;
+use move_binary_format::file_format::AbilitySet;
 use move_binary_format::file_format::CompiledModule;
 use move_binary_format::normalized;
 use move_core_types::language_storage::ModuleId;
@@ -131,6 +134,32 @@ impl UpgradePolicy {
     pub fn is_valid_policy(policy: &u8) -> bool {
         Self::try_from(*policy).is_ok()
     }
+
+    /// Check that this policy allows `cur_module` to be upgraded to `new_module`. These are the
+    /// rules the upgrade transaction enforces, and tools checking upgrades ahead of time should
+    /// use them rather than a copy.
+    pub fn check_compatibility(
+        &self,
+        cur_module: &normalized::Module,
+        new_module: &normalized::Module,
+    ) -> PartialVMResult<()> {
+        match self {
+            UpgradePolicy::Additive => InclusionCheck::Subset.check(cur_module, new_module),
+            UpgradePolicy::DepOnly => InclusionCheck::Equal.check(cur_module, new_module),
+            UpgradePolicy::Compatible => {
+                let compatibility = Compatibility {
+                    check_struct_and_pub_function_linking: true,
+                    check_struct_layout: true,
+                    check_friend_linking: false,
+                    check_private_entry_linking: false,
+                    disallowed_new_abilities: AbilitySet::ALL,
+                    disallow_change_struct_type_params: true,
+                };
+
+                compatibility.check(cur_module, new_module)
+            }
+        }
+    }
 }
 
 impl TryFrom<u8> for UpgradePolicy {
@@ -147,6 +147,22 @@ impl MoveObject {
         }
     }
 
+    /// Create an object of any type for tests, which are responsible for `has_public_transfer`
+    /// matching the abilities of `type_`, and for `contents` being a valid value of it.
+    pub fn new_for_testing(
+        type_: MoveObjectType,
+        has_public_transfer: bool,
+        version: SequenceNumber,
+        contents: Vec<u8>,
+    ) -> Self {
+        Self {
+            type_,
+            has_public_transfer,
+            version,
+            contents,
+        }
+    }
+
     pub fn type_(&self) -> &MoveObjectType {
         &self.type_
     }
@@ -417,6 +417,14 @@ impl PoolTokenExchangeRate {
             self.pool_token_amount as f64 / self.sui_amount as f64
         }
     }
+
+    pub fn sui_amount(&self) -> u64 {
+        self.sui_amount
+    }
+
+    pub fn pool_token_amount(&self) -> u64 {
+        self.pool_token_amount
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
@@ -1436,6 +1436,11 @@ pub struct TransactionDataV1 {
 }
 
 impl TransactionData {
+    /// The digest of the transaction, as it is computed once the transaction is signed.
+    pub fn digest(&self) -> TransactionDigest {
+        TransactionDigest::new(default_hash(self))
+    }
+
     fn new_system_transaction(kind: TransactionKind) -> Self {
         // assert transaction kind if a system transaction
         assert!(kind.is_system_tx());
@@ -2174,7 +2179,7 @@ impl Message for SenderSignedData {
     const SCOPE: IntentScope = IntentScope::SenderSignedTransaction;
 
     fn digest(&self) -> Self::DigestType {
-        TransactionDigest::new(default_hash(&self.intent_message().value))
+        self.intent_message().value.digest()
     }
 
     fn verify_user_input(&self) -> SuiResult {
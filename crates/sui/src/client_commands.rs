@@ -30,9 +30,9 @@ use shared_crypto::intent::Intent;
 use sui_execution::verifier::VerifierOverrides;
 use sui_json::SuiJsonValue;
 use sui_json_rpc_types::{
-    DynamicFieldPage, SuiData, SuiObjectData, SuiObjectResponse, SuiObjectResponseQuery,
-    SuiParsedData, SuiRawData, SuiTransactionBlockEffectsAPI, SuiTransactionBlockResponse,
-    SuiTransactionBlockResponseOptions,
+    DevInspectResults, DryRunTransactionBlockResponse, DynamicFieldPage, SuiData, SuiObjectData,
+    SuiObjectResponse, SuiObjectResponseQuery, SuiParsedData, SuiRawData,
+    SuiTransactionBlockEffectsAPI, SuiTransactionBlockResponse, SuiTransactionBlockResponseOptions,
 };
 use sui_json_rpc_types::{SuiExecutionStatus, SuiObjectDataOptions};
 use sui_keys::keystore::AccountKeystore;
@@ -46,13 +46,14 @@ use sui_sdk::wallet_context::WalletContext;
 use sui_sdk::SuiClient;
 use sui_types::{
     base_types::{ObjectID, SequenceNumber, SuiAddress},
-    crypto::SignatureScheme,
+    crypto::{PublicKey, SignatureScheme},
     digests::TransactionDigest,
     dynamic_field::DynamicFieldInfo,
     error::SuiError,
     gas_coin::GasCoin,
     metrics::BytecodeVerifierMetrics,
     move_package::UpgradeCap,
+    multisig::{MultiSigPublicKey, ThresholdUnit, WeightUnit},
     object::Owner,
     parse_sui_type_tag,
     signature::GenericSignature,
@@ -68,13 +69,47 @@ use tabled::{
 };
 use tracing::info;
 
+use crate::client_multisig::{MultisigCommand, MultisigOutput};
+use crate::client_ptb::{build_ptb, parse_ptb};
+use crate::transaction_bundle::TransactionBundle;
+use crate::transaction_file::{decode_tx_data, TransactionFile};
+
 macro_rules! serialize_or_execute {
-    ($tx_data:expr, $serialize_unsigned:expr, $serialize_signed:expr, $context:expr, $result_variant:ident) => {{
-        assert!(
-            !$serialize_unsigned || !$serialize_signed,
-            "Cannot specify both --serialize-unsigned-transaction and --serialize-signed-transaction"
+    ($tx_data:expr, $serialize_unsigned:expr, $serialize_signed:expr, $simulation:expr, $context:expr, $result_variant:ident) => {{
+        let simulation: SimulationArgs = $simulation;
+        let modes = [
+            $serialize_unsigned,
+            $serialize_signed,
+            simulation.dry_run,
+            simulation.dev_inspect,
+        ];
+        ensure!(
+            modes.iter().filter(|mode| **mode).count() <= 1,
+            "Cannot specify more than one of --serialize-unsigned-transaction, \
+            --serialize-signed-transaction, --dry-run and --dev-inspect"
         );
-        if $serialize_unsigned {
+        if simulation.dry_run {
+            let response = $context
+                .get_client()
+                .await?
+                .read_api()
+                .dry_run_transaction_block($tx_data)
+                .await?;
+            SuiClientCommandResult::DryRun(response)
+        } else if simulation.dev_inspect {
+            let response = $context
+                .get_client()
+                .await?
+                .read_api()
+                .dev_inspect_transaction_block(
+                    $tx_data.sender(),
+                    $tx_data.kind().clone(),
+                    Some($tx_data.gas_price().into()),
+                    None,
+                )
+                .await?;
+            SuiClientCommandResult::DevInspect(response)
+        } else if $serialize_unsigned {
             SuiClientCommandResult::SerializedUnsignedTransaction($tx_data)
         } else {
             let signature = $context.config.keystore.sign_secure(
@@ -107,6 +142,21 @@ macro_rules! serialize_or_execute {
     }};
 }
 
+/// Ways to preview a transaction instead of executing it.
+#[derive(Args, Clone, Copy, Debug, Default)]
+pub struct SimulationArgs {
+    /// Instead of executing the transaction, dry run it and print out the effects, events,
+    /// object changes, balance changes and gas cost it would have.
+    #[clap(long, required = false)]
+    pub dry_run: bool,
+
+    /// Instead of executing the transaction, run it in dev inspect mode and print out the
+    /// effects, events and the return values of each command. This allows calling any Move
+    /// function, including non-entry functions, and skips some transaction checks.
+    #[clap(long, required = false)]
+    pub dev_inspect: bool,
+}
+
 #[derive(Parser)]
 #[clap(rename_all = "kebab-case")]
 pub enum SuiClientCommands {
@@ -162,12 +212,46 @@ pub enum SuiClientCommands {
         /// (SenderSignedData) using base64 encoding, and print out the string.
         #[clap(long, required = false)]
         serialize_signed_transaction: bool,
+
+        #[clap(flatten)]
+        simulation: SimulationArgs,
     },
 
     /// Query the chain identifier from the rpc endpoint.
     #[clap(name = "chain-identifier")]
     ChainIdentifier,
 
+    /// Create a bundle file from an unsigned transaction, to sign it offline with
+    /// `sui keytool sign-bundle` and execute it with `sui client execute-bundle`.
+    #[clap(name = "create-bundle")]
+    CreateBundle {
+        /// BCS serialized transaction data bytes without its type tag, as base-64 encoded string,
+        /// e.g. the output of a transaction command with `--serialize-unsigned-transaction`.
+        #[clap(long)]
+        tx_bytes: String,
+
+        /// A description of the purpose of the transaction, for the signers to review.
+        #[clap(long)]
+        description: Option<String>,
+
+        /// The public keys of the sender, if it is a multisig address, `flag || pk` in Base64.
+        /// Its keys then sign the bundle, until their weight reaches the threshold.
+        #[clap(long, num_args(1..), requires_all = ["multisig_weights", "multisig_threshold"])]
+        multisig_pks: Vec<PublicKey>,
+
+        /// The weights of the multisig public keys, in the same order.
+        #[clap(long, num_args(1..), requires = "multisig_pks")]
+        multisig_weights: Vec<WeightUnit>,
+
+        /// The threshold of the multisig.
+        #[clap(long, requires = "multisig_pks")]
+        multisig_threshold: Option<ThresholdUnit>,
+
+        /// The bundle file to create.
+        #[clap(long)]
+        file: PathBuf,
+    },
+
     /// Query a dynamic field by its address.
     #[clap(name = "dynamic-field")]
     DynamicFieldQuery {
@@ -185,6 +269,14 @@ pub enum SuiClientCommands {
     /// List all Sui environments
     Envs,
 
+    /// Execute the transaction of a signed bundle file, after checking that the objects it uses
+    /// did not change since the bundle was created.
+    #[clap(name = "execute-bundle")]
+    ExecuteBundle {
+        #[clap(long)]
+        file: PathBuf,
+    },
+
     /// Execute a Signed Transaction. This is useful when the user prefers to sign elsewhere and use this command to execute.
     ExecuteSignedTx {
         /// BCS serialized transaction data bytes without its type tag, as base-64 encoded string.
@@ -194,6 +286,12 @@ pub enum SuiClientCommands {
         /// A list of Base64 encoded signatures `flag || signature || pubkey`.
         #[clap(long)]
         signatures: Vec<String>,
+
+        /// Instead of executing the transaction, dry run it and print out the effects, events,
+        /// object changes, balance changes and gas cost it would have. The signatures are not
+        /// checked.
+        #[clap(long, required = false)]
+        dry_run: bool,
     },
 
     /// Obtain all gas objects owned by the address.
@@ -229,6 +327,17 @@ pub enum SuiClientCommands {
         /// (SenderSignedData) using base64 encoding, and print out the string.
         #[clap(long, required = false)]
         serialize_signed_transaction: bool,
+
+        #[clap(flatten)]
+        simulation: SimulationArgs,
+    },
+
+    /// Propose, sign and execute transactions sent by a multisig address, collecting the partial
+    /// signatures of its keys in a proposal file.
+    #[clap(name = "multisig")]
+    Multisig {
+        #[clap(subcommand)]
+        cmd: MultisigCommand,
     },
 
     /// Generate new address and keypair with keypair scheme flag {ed25519 | secp256k1 | secp256r1}
@@ -308,6 +417,9 @@ pub enum SuiClientCommands {
         /// (SenderSignedData) using base64 encoding, and print out the string.
         #[clap(long, required = false)]
         serialize_signed_transaction: bool,
+
+        #[clap(flatten)]
+        simulation: SimulationArgs,
     },
 
     /// Pay all residual SUI coins to the recipient with input coins, after deducting the gas cost.
@@ -334,6 +446,9 @@ pub enum SuiClientCommands {
         /// (SenderSignedData) using base64 encoding, and print out the string.
         #[clap(long, required = false)]
         serialize_signed_transaction: bool,
+
+        #[clap(flatten)]
+        simulation: SimulationArgs,
     },
 
     /// Pay SUI coins to recipients following following specified amounts, with input coins.
@@ -365,6 +480,42 @@ pub enum SuiClientCommands {
         /// (SenderSignedData) using base64 encoding, and print out the string.
         #[clap(long, required = false)]
         serialize_signed_transaction: bool,
+
+        #[clap(flatten)]
+        simulation: SimulationArgs,
+    },
+
+    /// Compose a programmable transaction block from a sequence of commands, separated by
+    /// newlines or `;`. Available commands are `split-coins`, `merge-coins`, `transfer-objects`,
+    /// `move-call`, `make-move-vec`, `publish` and `upgrade`, e.g.
+    /// `let coins = split-coins gas [1000, 2000]; transfer-objects [coins.0, coins.1] @0x42`.
+    #[clap(name = "ptb")]
+    Ptb {
+        /// The commands of the transaction block
+        #[clap(required = true, num_args(1..))]
+        commands: Vec<String>,
+
+        /// ID of the gas object for gas payment, in 20 bytes Hex string
+        /// If not provided, a gas object with at least gas_budget value will be selected
+        #[clap(long)]
+        gas: Option<ObjectID>,
+
+        /// Gas budget for this transaction
+        #[clap(long)]
+        gas_budget: u64,
+
+        /// Instead of executing the transaction, serialize the bcs bytes of the unsigned transaction data
+        /// (TransactionData) using base64 encoding, and print out the string.
+        #[clap(long, required = false)]
+        serialize_unsigned_transaction: bool,
+
+        /// Instead of executing the transaction, serialize the bcs bytes of the signed transaction data
+        /// (SenderSignedData) using base64 encoding, and print out the string.
+        #[clap(long, required = false)]
+        serialize_signed_transaction: bool,
+
+        #[clap(flatten)]
+        simulation: SimulationArgs,
     },
 
     /// Publish Move modules
@@ -405,6 +556,9 @@ pub enum SuiClientCommands {
         /// (SenderSignedData) using base64 encoding, and print out the string.
         #[clap(long, required = false)]
         serialize_signed_transaction: bool,
+
+        #[clap(flatten)]
+        simulation: SimulationArgs,
     },
 
     /// Split a coin object into multiple coins.
@@ -436,6 +590,9 @@ pub enum SuiClientCommands {
         /// (SenderSignedData) using base64 encoding, and print out the string.
         #[clap(long, required = false)]
         serialize_signed_transaction: bool,
+
+        #[clap(flatten)]
+        simulation: SimulationArgs,
     },
 
     /// Switch active address and network(e.g., devnet, local rpc server)
@@ -488,6 +645,9 @@ pub enum SuiClientCommands {
         /// (SenderSignedData) using base64 encoding, and print out the string.
         #[clap(long, required = false)]
         serialize_signed_transaction: bool,
+
+        #[clap(flatten)]
+        simulation: SimulationArgs,
     },
 
     /// Transfer SUI, and pay gas with the same SUI coin object.
@@ -520,6 +680,9 @@ pub enum SuiClientCommands {
         /// (SenderSignedData) using base64 encoding, and print out the string.
         #[clap(long, required = false)]
         serialize_signed_transaction: bool,
+
+        #[clap(flatten)]
+        simulation: SimulationArgs,
     },
 
     /// Upgrade Move modules
@@ -564,6 +727,9 @@ pub enum SuiClientCommands {
         /// (SenderSignedData) using base64 encoding, and print out the string.
         #[clap(long, required = false)]
         serialize_signed_transaction: bool,
+
+        #[clap(flatten)]
+        simulation: SimulationArgs,
     },
 
     /// Run the bytecode verifier on the package
@@ -724,6 +890,7 @@ impl SuiClientCommands {
                 with_unpublished_dependencies,
                 serialize_unsigned_transaction,
                 serialize_signed_transaction,
+                simulation,
             } => {
                 let sender = context.try_get_object_owner(&gas).await?;
                 let sender = sender.unwrap_or(context.active_address()?);
@@ -798,6 +965,7 @@ impl SuiClientCommands {
                     data,
                     serialize_unsigned_transaction,
                     serialize_signed_transaction,
+                    simulation,
                     context,
                     Upgrade
                 )
@@ -811,6 +979,7 @@ impl SuiClientCommands {
                 with_unpublished_dependencies,
                 serialize_unsigned_transaction,
                 serialize_signed_transaction,
+                simulation,
             } => {
                 if build_config.test_mode {
                     return Err(SuiError::ModulePublishFailure {
@@ -854,6 +1023,7 @@ impl SuiClientCommands {
                     data,
                     serialize_unsigned_transaction,
                     serialize_signed_transaction,
+                    simulation,
                     context,
                     Publish
                 )
@@ -938,6 +1108,7 @@ impl SuiClientCommands {
                 args,
                 serialize_unsigned_transaction,
                 serialize_signed_transaction,
+                simulation,
             } => {
                 let tx_data = construct_move_call_transaction(
                     package, &module, &function, type_args, gas, gas_budget, args, context,
@@ -947,6 +1118,7 @@ impl SuiClientCommands {
                     tx_data,
                     serialize_unsigned_transaction,
                     serialize_signed_transaction,
+                    simulation,
                     context,
                     Call
                 )
@@ -959,6 +1131,7 @@ impl SuiClientCommands {
                 gas_budget,
                 serialize_unsigned_transaction,
                 serialize_signed_transaction,
+                simulation,
             } => {
                 let from = context.get_object_owner(&object_id).await?;
                 let client = context.get_client().await?;
@@ -970,6 +1143,7 @@ impl SuiClientCommands {
                     data,
                     serialize_unsigned_transaction,
                     serialize_signed_transaction,
+                    simulation,
                     context,
                     Transfer
                 )
@@ -982,6 +1156,7 @@ impl SuiClientCommands {
                 amount,
                 serialize_unsigned_transaction,
                 serialize_signed_transaction,
+                simulation,
             } => {
                 let from = context.get_object_owner(&object_id).await?;
 
@@ -994,6 +1169,7 @@ impl SuiClientCommands {
                     data,
                     serialize_unsigned_transaction,
                     serialize_signed_transaction,
+                    simulation,
                     context,
                     TransferSui
                 )
@@ -1007,6 +1183,7 @@ impl SuiClientCommands {
                 gas_budget,
                 serialize_unsigned_transaction,
                 serialize_signed_transaction,
+                simulation,
             } => {
                 ensure!(
                     !input_coins.is_empty(),
@@ -1034,6 +1211,7 @@ impl SuiClientCommands {
                     data,
                     serialize_unsigned_transaction,
                     serialize_signed_transaction,
+                    simulation,
                     context,
                     Pay
                 )
@@ -1046,6 +1224,7 @@ impl SuiClientCommands {
                 gas_budget,
                 serialize_unsigned_transaction,
                 serialize_signed_transaction,
+                simulation,
             } => {
                 ensure!(
                     !input_coins.is_empty(),
@@ -1073,6 +1252,7 @@ impl SuiClientCommands {
                     data,
                     serialize_unsigned_transaction,
                     serialize_signed_transaction,
+                    simulation,
                     context,
                     PaySui
                 )
@@ -1084,6 +1264,7 @@ impl SuiClientCommands {
                 gas_budget,
                 serialize_unsigned_transaction,
                 serialize_signed_transaction,
+                simulation,
             } => {
                 ensure!(
                     !input_coins.is_empty(),
@@ -1100,11 +1281,40 @@ impl SuiClientCommands {
                     data,
                     serialize_unsigned_transaction,
                     serialize_signed_transaction,
+                    simulation,
                     context,
                     PayAllSui
                 )
             }
 
+            SuiClientCommands::Ptb {
+                commands,
+                gas,
+                gas_budget,
+                serialize_unsigned_transaction,
+                serialize_signed_transaction,
+                simulation,
+            } => {
+                let statements = parse_ptb(&commands.join("\n"))?;
+                let sender = context.try_get_object_owner(&gas).await?;
+                let sender = sender.unwrap_or(context.active_address()?);
+
+                let client = context.get_client().await?;
+                let pt = build_ptb(&client, statements).await?;
+                let data = client
+                    .transaction_builder()
+                    .programmable_transaction(sender, pt, gas, gas_budget)
+                    .await?;
+                serialize_or_execute!(
+                    data,
+                    serialize_unsigned_transaction,
+                    serialize_signed_transaction,
+                    simulation,
+                    context,
+                    Ptb
+                )
+            }
+
             SuiClientCommands::Objects { address } => {
                 let address = address.unwrap_or(context.active_address()?);
                 let client = context.get_client().await?;
@@ -1186,6 +1396,7 @@ impl SuiClientCommands {
                 gas_budget,
                 serialize_unsigned_transaction,
                 serialize_signed_transaction,
+                simulation,
             } => {
                 let signer = context.get_object_owner(&coin_id).await?;
                 let client = context.get_client().await?;
@@ -1213,6 +1424,7 @@ impl SuiClientCommands {
                     data,
                     serialize_unsigned_transaction,
                     serialize_signed_transaction,
+                    simulation,
                     context,
                     SplitCoin
                 )
@@ -1224,6 +1436,7 @@ impl SuiClientCommands {
                 gas_budget,
                 serialize_unsigned_transaction,
                 serialize_signed_transaction,
+                simulation,
             } => {
                 let client = context.get_client().await?;
                 let signer = context.get_object_owner(&primary_coin).await?;
@@ -1235,10 +1448,14 @@ impl SuiClientCommands {
                     data,
                     serialize_unsigned_transaction,
                     serialize_signed_transaction,
+                    simulation,
                     context,
                     MergeCoin
                 )
             }
+            SuiClientCommands::Multisig { cmd } => {
+                SuiClientCommandResult::Multisig(cmd.execute(context).await?)
+            }
             SuiClientCommands::Switch { address, env } => {
                 match (address, &env) {
                     (None, Some(env)) => {
@@ -1259,16 +1476,51 @@ impl SuiClientCommands {
                 SuiClientCommandResult::ActiveAddress(context.active_address().ok())
             }
 
+            SuiClientCommands::CreateBundle {
+                tx_bytes,
+                description,
+                multisig_pks,
+                multisig_weights,
+                multisig_threshold,
+                file,
+            } => {
+                let multisig_pk = multisig_threshold
+                    .map(|threshold| {
+                        MultiSigPublicKey::new(multisig_pks, multisig_weights, threshold)
+                    })
+                    .transpose()?;
+                let bundle =
+                    TransactionBundle::new(&decode_tx_data(&tx_bytes)?, description, multisig_pk)?;
+                bundle.create(&file)?;
+                SuiClientCommandResult::CreateBundle(bundle)
+            }
+            SuiClientCommands::ExecuteBundle { file } => {
+                let bundle = TransactionBundle::read(&file)?;
+                let transaction = bundle.transaction()?;
+                bundle.check_objects(&context.get_client().await?).await?;
+                let response = context.execute_transaction_may_fail(transaction).await?;
+                SuiClientCommandResult::ExecuteBundle(response)
+            }
             SuiClientCommands::ExecuteSignedTx {
                 tx_bytes,
                 signatures,
+                dry_run,
             } => {
-                let data = bcs::from_bytes(
+                let data: TransactionData = bcs::from_bytes(
                     &Base64::try_from(tx_bytes)
                         .map_err(|e| anyhow!(e))?
                         .to_vec()
                         .map_err(|e| anyhow!(e))?,
                 )?;
+                if dry_run {
+                    let response = context
+                        .get_client()
+                        .await?
+                        .read_api()
+                        .dry_run_transaction_block(data)
+                        .await?;
+                    return Ok(SuiClientCommandResult::DryRun(response));
+                }
 
                 let mut sigs = Vec::new();
                 for sig in signatures {
@@ -1377,7 +1629,7 @@ fn compile_package_simple(
     )?)
 }
 
-async fn compile_package(
+pub(crate) async fn compile_package(
     client: &SuiClient,
     build_config: MoveBuildConfig,
     package_path: PathBuf,
@@ -1616,6 +1868,20 @@ impl Display for SuiClientCommandResult {
             SuiClientCommandResult::Call(response) => {
                 write!(writer, "{}", response)?;
             }
+            SuiClientCommandResult::DryRun(response) => {
+                writeln!(
+                    writer,
+                    "Dry run completed, the transaction was not executed."
+                )?;
+                write!(writer, "{}", response)?;
+            }
+            SuiClientCommandResult::DevInspect(response) => {
+                writeln!(
+                    writer,
+                    "Dev inspect completed, the transaction was not executed."
+                )?;
+                write!(writer, "{}", response)?;
+            }
             SuiClientCommandResult::SerializedUnsignedTransaction(tx_data) => {
                 writeln!(
                     writer,
@@ -1645,6 +1911,12 @@ impl Display for SuiClientCommandResult {
             SuiClientCommandResult::PayAllSui(response) => {
                 write!(writer, "{}", response)?;
             }
+            SuiClientCommandResult::Ptb(response) => {
+                write!(writer, "{}", response)?;
+            }
+            SuiClientCommandResult::Multisig(output) => {
+                write!(writer, "{}", output)?;
+            }
             SuiClientCommandResult::SyncClientState => {
                 writeln!(writer, "Client state sync complete.")?;
             }
@@ -1669,6 +1941,12 @@ impl Display for SuiClientCommandResult {
             SuiClientCommandResult::ExecuteSignedTx(response) => {
                 write!(writer, "{}", response)?;
             }
+            SuiClientCommandResult::CreateBundle(bundle) => {
+                write!(writer, "{}", bundle)?;
+            }
+            SuiClientCommandResult::ExecuteBundle(response) => {
+                write!(writer, "{}", response)?;
+            }
             SuiClientCommandResult::ActiveEnv(env) => {
                 write!(writer, "{}", env.as_deref().unwrap_or("None"))?;
             }
@@ -1836,8 +2114,9 @@ impl SuiClientCommandResult {
         use SuiClientCommandResult::*;
         match self {
             Upgrade(b) | Publish(b) | TransactionBlock(b) | Call(b) | Transfer(b)
-            | TransferSui(b) | Pay(b) | PaySui(b) | PayAllSui(b) | SplitCoin(b) | MergeCoin(b)
-            | ExecuteSignedTx(b) => Some(b),
+            | TransferSui(b) | Pay(b) | PaySui(b) | PayAllSui(b) | Ptb(b) | SplitCoin(b)
+            | MergeCoin(b) | ExecuteSignedTx(b) | ExecuteBundle(b) => Some(b),
+            Multisig(MultisigOutput::Execute(b)) => Some(b),
             _ => None,
         }
     }
@@ -1976,11 +2255,16 @@ pub enum SuiClientCommandResult {
     Addresses(AddressesOutput),
     Call(SuiTransactionBlockResponse),
     ChainIdentifier(String),
+    CreateBundle(TransactionBundle),
+    DevInspect(DevInspectResults),
+    DryRun(DryRunTransactionBlockResponse),
     DynamicFieldQuery(DynamicFieldPage),
     Envs(Vec<SuiEnv>, Option<String>),
+    ExecuteBundle(SuiTransactionBlockResponse),
     ExecuteSignedTx(SuiTransactionBlockResponse),
     Gas(Vec<GasCoin>),
     MergeCoin(SuiTransactionBlockResponse),
+    Multisig(MultisigOutput),
     NewAddress(NewAddressOutput),
     NewEnv(SuiEnv),
     Object(SuiObjectResponse),
@@ -1988,6 +2272,7 @@ pub enum SuiClientCommandResult {
     Pay(SuiTransactionBlockResponse),
     PayAllSui(SuiTransactionBlockResponse),
     PaySui(SuiTransactionBlockResponse),
+    Ptb(SuiTransactionBlockResponse),
     Publish(SuiTransactionBlockResponse),
     RawObject(SuiObjectResponse),
     SerializedSignedTransaction(SenderSignedData),
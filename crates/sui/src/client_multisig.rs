@@ -0,0 +1,351 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! A workflow to sign and execute transactions sent by a multisig address.
+//!
+//! A proposal file holds the transaction, the multisig public key and the partial signatures
+//! collected so far. It is created with `sui client multisig propose` from the output of any
+//! transaction command run with `--serialize-unsigned-transaction`, passed around the signers
+//! to `sui client multisig sign`, and executed with `sui client multisig execute` once the
+//! weight of its signatures reaches the threshold.
+
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+
+use anyhow::{bail, ensure};
+use clap::*;
+use serde::{Deserialize, Serialize};
+use sui_json_rpc_types::SuiTransactionBlockResponse;
+use sui_sdk::wallet_context::WalletContext;
+use sui_types::base_types::SuiAddress;
+use sui_types::crypto::{EncodeDecodeBase64, PublicKey, Signature, SuiSignature};
+use sui_types::digests::TransactionDigest;
+use sui_types::multisig::{MultiSig, MultiSigPublicKey, ThresholdUnit, WeightUnit};
+use sui_types::signature::GenericSignature;
+use sui_types::transaction::{Transaction, TransactionData, TransactionDataAPI};
+use tabled::{
+    builder::Builder as TableBuilder,
+    settings::{style::HorizontalLine, Panel as TablePanel, Style as TableStyle},
+};
+
+use crate::transaction_file::{decode_tx_data, encode_tx_data, verify_signature, TransactionFile};
+
+#[cfg(test)]
+#[path = "unit_tests/client_multisig_tests.rs"]
+mod client_multisig_tests;
+
+#[derive(Subcommand)]
+#[clap(rename_all = "kebab-case")]
+pub enum MultisigCommand {
+    /// Create a proposal file for a transaction sent by a multisig address, e.g. the output of
+    /// a transaction command run with `--serialize-unsigned-transaction`.
+    Propose {
+        /// BCS serialized transaction data bytes without its type tag, as base-64 encoded string.
+        #[clap(long)]
+        tx_bytes: String,
+        /// The public keys of the multisig, `flag || pk` in Base64.
+        /// See `sui keytool list` for example public keys.
+        #[clap(long, num_args(1..))]
+        pks: Vec<PublicKey>,
+        /// The weights of the public keys, in the same order.
+        #[clap(long, num_args(1..))]
+        weights: Vec<WeightUnit>,
+        #[clap(long)]
+        threshold: ThresholdUnit,
+        /// The proposal file to create.
+        #[clap(long)]
+        file: PathBuf,
+    },
+    /// Add partial signatures to a proposal file, with every key of the keystore taking part in
+    /// the multisig, or only with the key of the given address.
+    Sign {
+        #[clap(long)]
+        file: PathBuf,
+        #[clap(long)]
+        address: Option<SuiAddress>,
+    },
+    /// Show which keys signed a proposal, and the weight remaining to reach the threshold.
+    Status {
+        #[clap(long)]
+        file: PathBuf,
+    },
+    /// Execute the transaction of a proposal, once the weight of its signatures reaches the
+    /// threshold.
+    Execute {
+        #[clap(long)]
+        file: PathBuf,
+    },
+}
+
+impl MultisigCommand {
+    pub async fn execute(
+        self,
+        context: &mut WalletContext,
+    ) -> Result<MultisigOutput, anyhow::Error> {
+        Ok(match self {
+            MultisigCommand::Propose {
+                tx_bytes,
+                pks,
+                weights,
+                threshold,
+                file,
+            } => {
+                let tx_data = decode_tx_data(&tx_bytes)?;
+                let multisig_pk = MultiSigPublicKey::new(pks, weights, threshold)?;
+                let proposal = MultisigProposal::new(&tx_data, multisig_pk)?;
+                proposal.create(&file)?;
+                MultisigOutput::Status(proposal.status()?)
+            }
+            MultisigCommand::Sign { file, address } => {
+                let proposal = MultisigProposal::sign(&file, &context.config.keystore, address)?;
+                MultisigOutput::Status(proposal.status()?)
+            }
+            MultisigCommand::Status { file } => {
+                MultisigOutput::Status(MultisigProposal::read(&file)?.status()?)
+            }
+            MultisigCommand::Execute { file } => {
+                let proposal = MultisigProposal::read(&file)?;
+                let transaction = Transaction::from_generic_sig_data(
+                    proposal.tx_data()?,
+                    vec![proposal.combine()?],
+                );
+                let response = context.execute_transaction_may_fail(transaction).await?;
+                MultisigOutput::Execute(response)
+            }
+        })
+    }
+}
+
+/// A transaction sent by a multisig address, with the partial signatures collected so far.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct MultisigProposal {
+    /// BCS serialized transaction data bytes, as base-64 encoded string.
+    pub tx_bytes: String,
+    pub multisig_pk: MultiSigPublicKey,
+    #[serde(default)]
+    pub signatures: Vec<Signature>,
+}
+
+impl MultisigProposal {
+    pub fn new(
+        tx_data: &TransactionData,
+        multisig_pk: MultiSigPublicKey,
+    ) -> Result<Self, anyhow::Error> {
+        let address = SuiAddress::from(&multisig_pk);
+        ensure!(
+            tx_data.sender() == address,
+            "The transaction is sent by {}, not by the multisig address {address}",
+            tx_data.sender()
+        );
+        Ok(Self {
+            tx_bytes: encode_tx_data(tx_data)?,
+            multisig_pk,
+            signatures: vec![],
+        })
+    }
+
+    pub fn address(&self) -> SuiAddress {
+        SuiAddress::from(&self.multisig_pk)
+    }
+
+    /// The keys of the multisig, in order, and whether they signed.
+    pub fn signers(&self) -> Vec<MultisigSigner> {
+        self.multisig_pk
+            .pubkeys()
+            .iter()
+            .map(|(pk, weight)| MultisigSigner {
+                address: SuiAddress::from(pk),
+                public_base64_key: pk.encode_base64(),
+                weight: *weight,
+                signed: self
+                    .signatures
+                    .iter()
+                    .any(|s| s.public_key_bytes() == pk.as_ref()),
+            })
+            .collect()
+    }
+
+    pub fn signed_weight(&self) -> ThresholdUnit {
+        self.signers()
+            .iter()
+            .filter(|signer| signer.signed)
+            .map(|signer| signer.weight as ThresholdUnit)
+            .sum()
+    }
+
+    /// Combine the partial signatures into the multisig of the transaction.
+    pub fn combine(&self) -> Result<GenericSignature, anyhow::Error> {
+        let threshold = *self.multisig_pk.threshold();
+        let signed_weight = self.signed_weight();
+        ensure!(
+            signed_weight >= threshold,
+            "The signatures have a weight of {signed_weight}, below the threshold of {threshold}"
+        );
+        // The signatures must be in the order of the keys of the multisig.
+        let mut signatures = self
+            .signatures
+            .iter()
+            .map(|signature| {
+                let index = self.multisig_pk.get_index(&signer_public_key(signature)?);
+                Ok((index, GenericSignature::from(signature.clone())))
+            })
+            .collect::<Result<Vec<_>, anyhow::Error>>()?;
+        signatures.sort_by_key(|(index, _)| *index);
+        let multisig = MultiSig::combine(
+            signatures.into_iter().map(|(_, s)| s).collect(),
+            self.multisig_pk.clone(),
+        )?;
+        Ok(multisig.into())
+    }
+
+    pub fn status(&self) -> Result<MultisigStatus, anyhow::Error> {
+        let threshold = *self.multisig_pk.threshold();
+        let signed_weight = self.signed_weight();
+        Ok(MultisigStatus {
+            multisig_address: self.address(),
+            tx_digest: self.tx_data()?.digest(),
+            threshold,
+            signed_weight,
+            remaining_weight: threshold.saturating_sub(signed_weight),
+            signers: self.signers(),
+        })
+    }
+}
+
+impl TransactionFile for MultisigProposal {
+    const KIND: &'static str = "proposal";
+
+    fn tx_bytes(&self) -> &str {
+        &self.tx_bytes
+    }
+
+    fn unsigned_signers(&self) -> Result<Vec<SuiAddress>, anyhow::Error> {
+        Ok(self
+            .signers()
+            .into_iter()
+            .filter(|signer| !signer.signed)
+            .map(|signer| signer.address)
+            .collect())
+    }
+
+    /// Add the partial signature of one of the keys of the multisig, after verifying it.
+    fn add_signature(&mut self, signature: Signature) -> Result<(), anyhow::Error> {
+        let public_key = signer_public_key(&signature)?;
+        ensure!(
+            self.multisig_pk.get_index(&public_key).is_some(),
+            "Key {} is not part of the multisig",
+            public_key.encode_base64()
+        );
+        if self
+            .signatures
+            .iter()
+            .any(|s| s.public_key_bytes() == signature.public_key_bytes())
+        {
+            bail!("Key {} already signed", public_key.encode_base64());
+        }
+        verify_signature(&self.tx_data()?, &signature, SuiAddress::from(&public_key))?;
+        self.signatures.push(signature);
+        Ok(())
+    }
+
+    /// Check that the transaction is sent by the multisig address, and that the partial
+    /// signatures are valid signatures of its keys.
+    fn validate(&self) -> Result<(), anyhow::Error> {
+        let tx_data = self.tx_data()?;
+        ensure!(
+            tx_data.sender() == self.address(),
+            "The transaction is sent by {}, not by the multisig address {}",
+            tx_data.sender(),
+            self.address()
+        );
+        for signature in &self.signatures {
+            let public_key = signer_public_key(signature)?;
+            ensure!(
+                self.multisig_pk.get_index(&public_key).is_some(),
+                "Key {} is not part of the multisig",
+                public_key.encode_base64()
+            );
+            verify_signature(&tx_data, signature, SuiAddress::from(&public_key))?;
+        }
+        Ok(())
+    }
+}
+
+fn signer_public_key(signature: &Signature) -> Result<PublicKey, anyhow::Error> {
+    Ok(GenericSignature::from(signature.clone()).to_public_key()?)
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MultisigSigner {
+    pub address: SuiAddress,
+    pub public_base64_key: String,
+    pub weight: WeightUnit,
+    pub signed: bool,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MultisigStatus {
+    pub multisig_address: SuiAddress,
+    pub tx_digest: TransactionDigest,
+    pub threshold: ThresholdUnit,
+    pub signed_weight: ThresholdUnit,
+    pub remaining_weight: ThresholdUnit,
+    pub signers: Vec<MultisigSigner>,
+}
+
+impl Display for MultisigStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut builder = TableBuilder::default();
+        builder.set_header(vec!["address", "public key", "weight", "signed"]);
+        for signer in &self.signers {
+            builder.push_record(vec![
+                signer.address.to_string(),
+                signer.public_base64_key.clone(),
+                signer.weight.to_string(),
+                if signer.signed { "*" } else { "" }.to_string(),
+            ]);
+        }
+        let mut table = builder.build();
+        table.with(TablePanel::header(format!(
+            "Multisig {} for transaction {}",
+            self.multisig_address, self.tx_digest
+        )));
+        table.with(TableStyle::rounded().horizontals([HorizontalLine::new(
+            2,
+            TableStyle::modern().get_horizontal(),
+        )]));
+        writeln!(f, "{}", table)?;
+        if self.remaining_weight == 0 {
+            write!(
+                f,
+                "Signed weight {} reaches the threshold of {}, the transaction can be executed.",
+                self.signed_weight, self.threshold
+            )
+        } else {
+            write!(
+                f,
+                "Signed weight {} of threshold {}, {} remaining.",
+                self.signed_weight, self.threshold, self.remaining_weight
+            )
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum MultisigOutput {
+    Execute(SuiTransactionBlockResponse),
+    Status(MultisigStatus),
+}
+
+impl Display for MultisigOutput {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MultisigOutput::Execute(response) => write!(f, "{}", response),
+            MultisigOutput::Status(status) => write!(f, "{}", status),
+        }
+    }
+}
@@ -0,0 +1,711 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! A small command language to compose programmable transaction blocks from the CLI, e.g.
+//!
+//! ```text
+//! let coins = split-coins gas [1000, 2000]
+//! transfer-objects [coins.0, coins.1] @0x42
+//! ```
+//!
+//! Statements are separated by newlines or `;`, and the result of a command can be bound to a
+//! name with `let`. `result` refers to the result of the previous command, and `name.N` to the
+//! N-th value of a command returning several values.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail, ensure};
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::TypeTag;
+use move_core_types::u256::U256;
+use move_package::BuildConfig as MoveBuildConfig;
+use sui_json_rpc_types::{SuiMoveNormalizedType, SuiObjectDataOptions};
+use sui_sdk::SuiClient;
+use sui_types::base_types::{ObjectID, SuiAddress};
+use sui_types::move_package::UpgradeCap;
+use sui_types::object::Owner;
+use sui_types::parse_sui_type_tag;
+use sui_types::programmable_transaction_builder::ProgrammableTransactionBuilder;
+use sui_types::transaction::{Argument, Command, ObjectArg, ProgrammableTransaction};
+use sui_types::SUI_FRAMEWORK_PACKAGE_ID;
+
+use crate::client_commands::compile_package;
+
+#[cfg(test)]
+#[path = "unit_tests/client_ptb_tests.rs"]
+mod client_ptb_tests;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PtbArg {
+    /// `gas`, the gas coin.
+    Gas,
+    /// A variable bound with `let`, or `result`, optionally indexing into a command returning
+    /// several values, e.g. `coins.1`.
+    Var(String, Option<u16>),
+    /// `@0x...`, an address passed as a pure value.
+    Address(SuiAddress),
+    /// `0x...`, an object input.
+    Object(ObjectID),
+    /// A number with an optional type suffix, e.g. `1000` or `5u8`. Defaults to `u64`.
+    Number(String, Option<String>),
+    Bool(bool),
+    String(String),
+    /// `vector[...]`, a vector of pure values.
+    Vector(Vec<PtbArg>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PtbCommand {
+    SplitCoins {
+        coin: PtbArg,
+        amounts: Vec<PtbArg>,
+    },
+    MergeCoins {
+        coin: PtbArg,
+        coins: Vec<PtbArg>,
+    },
+    TransferObjects {
+        objects: Vec<PtbArg>,
+        recipient: PtbArg,
+    },
+    MoveCall {
+        package: ObjectID,
+        module: String,
+        function: String,
+        type_args: Vec<TypeTag>,
+        args: Vec<PtbArg>,
+    },
+    MakeMoveVec {
+        type_arg: Option<TypeTag>,
+        elements: Vec<PtbArg>,
+    },
+    Publish {
+        package_path: PathBuf,
+    },
+    Upgrade {
+        package_path: PathBuf,
+        upgrade_capability: ObjectID,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PtbStatement {
+    pub assign: Option<String>,
+    pub command: PtbCommand,
+}
+
+/// Parse the statements of a programmable transaction block.
+pub fn parse_ptb(input: &str) -> Result<Vec<PtbStatement>, anyhow::Error> {
+    let mut parser = Parser { input, pos: 0 };
+    let mut statements = vec![];
+    loop {
+        parser.skip_separators();
+        if parser.peek().is_none() {
+            break;
+        }
+        statements.push(parser.statement()?);
+        parser.skip_whitespace();
+        match parser.peek() {
+            None | Some(';') | Some('\n') => {}
+            Some(c) => return Err(parser.error(&format!("unexpected '{c}'"))),
+        }
+    }
+    ensure!(
+        !statements.is_empty(),
+        "A programmable transaction block needs at least one command"
+    );
+    Ok(statements)
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<char> {
+        self.input[self.pos..].chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn error(&self, message: &str) -> anyhow::Error {
+        anyhow!("Invalid PTB command at position {}: {}", self.pos, message)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(c) if c.is_whitespace() && c != '\n') {
+            self.bump();
+        }
+    }
+
+    fn skip_separators(&mut self) {
+        while matches!(self.peek(), Some(c) if c.is_whitespace() || c == ';') {
+            self.bump();
+        }
+    }
+
+    fn at_statement_end(&mut self) -> bool {
+        self.skip_whitespace();
+        matches!(self.peek(), None | Some(';') | Some('\n'))
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), anyhow::Error> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(c) if c == expected => {
+                self.bump();
+                Ok(())
+            }
+            _ => Err(self.error(&format!("expected '{expected}'"))),
+        }
+    }
+
+    fn take_while(&mut self, f: impl Fn(char) -> bool) -> &'a str {
+        let start = self.pos;
+        while matches!(self.peek(), Some(c) if f(c)) {
+            self.bump();
+        }
+        &self.input[start..self.pos]
+    }
+
+    fn word(&mut self) -> Result<&'a str, anyhow::Error> {
+        self.skip_whitespace();
+        let word = self.take_while(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+        if word.is_empty() {
+            return Err(self.error("expected a name"));
+        }
+        Ok(word)
+    }
+
+    fn hex(&mut self) -> Result<ObjectID, anyhow::Error> {
+        let literal = self.take_while(|c| c.is_ascii_alphanumeric());
+        ObjectID::from_hex_literal(literal)
+            .map_err(|e| self.error(&format!("invalid hex literal '{literal}': {e}")))
+    }
+
+    fn statement(&mut self) -> Result<PtbStatement, anyhow::Error> {
+        let start = self.pos;
+        let assign = if self.word()? == "let" {
+            let name = self.word()?.to_string();
+            self.expect('=')?;
+            Some(name)
+        } else {
+            self.pos = start;
+            None
+        };
+        Ok(PtbStatement {
+            assign,
+            command: self.command()?,
+        })
+    }
+
+    fn command(&mut self) -> Result<PtbCommand, anyhow::Error> {
+        let command = match self.word()? {
+            "split-coins" => PtbCommand::SplitCoins {
+                coin: self.arg()?,
+                amounts: self.list()?,
+            },
+            "merge-coins" => PtbCommand::MergeCoins {
+                coin: self.arg()?,
+                coins: self.list()?,
+            },
+            "transfer-objects" => PtbCommand::TransferObjects {
+                objects: self.list()?,
+                recipient: self.arg()?,
+            },
+            "move-call" => {
+                self.skip_whitespace();
+                let package = self.hex()?;
+                self.expect(':')?;
+                self.expect(':')?;
+                let module = self.word()?.to_string();
+                self.expect(':')?;
+                self.expect(':')?;
+                let function = self.word()?.to_string();
+                let type_args = self.type_args()?;
+                let mut args = vec![];
+                while !self.at_statement_end() {
+                    args.push(self.arg()?);
+                }
+                PtbCommand::MoveCall {
+                    package,
+                    module,
+                    function,
+                    type_args,
+                    args,
+                }
+            }
+            "make-move-vec" => {
+                let type_arg = match self.type_args()?.as_slice() {
+                    [] => None,
+                    [type_arg] => Some(type_arg.clone()),
+                    _ => return Err(self.error("make-move-vec takes a single type argument")),
+                };
+                PtbCommand::MakeMoveVec {
+                    type_arg,
+                    elements: self.list()?,
+                }
+            }
+            "publish" => PtbCommand::Publish {
+                package_path: self.path()?,
+            },
+            "upgrade" => {
+                let package_path = self.path()?;
+                let upgrade_capability = match self.arg()? {
+                    PtbArg::Object(id) => id,
+                    _ => return Err(self.error("expected the upgrade capability object ID")),
+                };
+                PtbCommand::Upgrade {
+                    package_path,
+                    upgrade_capability,
+                }
+            }
+            command => return Err(self.error(&format!("unknown command '{command}'"))),
+        };
+        Ok(command)
+    }
+
+    fn arg(&mut self) -> Result<PtbArg, anyhow::Error> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('@') => {
+                self.bump();
+                Ok(PtbArg::Address(self.hex()?.into()))
+            }
+            Some('"') => Ok(PtbArg::String(self.string()?)),
+            Some(c) if c.is_ascii_digit() => {
+                if self.input[self.pos..].starts_with("0x") {
+                    return Ok(PtbArg::Object(self.hex()?));
+                }
+                let digits = self.take_while(|c| c.is_ascii_digit()).to_string();
+                let suffix = self.take_while(|c| c.is_ascii_alphanumeric());
+                match suffix {
+                    "" => Ok(PtbArg::Number(digits, None)),
+                    "u8" | "u16" | "u32" | "u64" | "u128" | "u256" => {
+                        Ok(PtbArg::Number(digits, Some(suffix.to_string())))
+                    }
+                    _ => Err(self.error(&format!("invalid number suffix '{suffix}'"))),
+                }
+            }
+            Some(c) if c.is_ascii_alphabetic() || c == '_' => {
+                let word = self.take_while(|c| c.is_ascii_alphanumeric() || c == '_');
+                match word {
+                    "gas" => Ok(PtbArg::Gas),
+                    "true" => Ok(PtbArg::Bool(true)),
+                    "false" => Ok(PtbArg::Bool(false)),
+                    "vector" => Ok(PtbArg::Vector(self.list()?)),
+                    name => {
+                        let name = name.to_string();
+                        let index = if self.peek() == Some('.') {
+                            self.bump();
+                            let index = self.take_while(|c| c.is_ascii_digit());
+                            Some(
+                                index
+                                    .parse()
+                                    .map_err(|_| self.error("expected a result index"))?,
+                            )
+                        } else {
+                            None
+                        };
+                        Ok(PtbArg::Var(name, index))
+                    }
+                }
+            }
+            _ => Err(self.error("expected an argument")),
+        }
+    }
+
+    fn list(&mut self) -> Result<Vec<PtbArg>, anyhow::Error> {
+        self.expect('[')?;
+        let mut args = vec![];
+        self.skip_whitespace();
+        if self.peek() == Some(']') {
+            self.bump();
+            return Ok(args);
+        }
+        loop {
+            args.push(self.arg()?);
+            self.skip_whitespace();
+            match self.bump() {
+                Some(',') => continue,
+                Some(']') => return Ok(args),
+                _ => return Err(self.error("expected ',' or ']'")),
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String, anyhow::Error> {
+        self.expect('"')?;
+        let string = self.take_while(|c| c != '"').to_string();
+        self.expect('"')?;
+        Ok(string)
+    }
+
+    fn path(&mut self) -> Result<PathBuf, anyhow::Error> {
+        self.skip_whitespace();
+        if self.peek() == Some('"') {
+            return Ok(PathBuf::from(self.string()?));
+        }
+        let path = self.take_while(|c| !c.is_whitespace() && c != ';');
+        if path.is_empty() {
+            return Err(self.error("expected a package path"));
+        }
+        Ok(PathBuf::from(path))
+    }
+
+    /// Parse optional type arguments, e.g. `<0x2::sui::SUI, u64>`.
+    fn type_args(&mut self) -> Result<Vec<TypeTag>, anyhow::Error> {
+        self.skip_whitespace();
+        if self.peek() != Some('<') {
+            return Ok(vec![]);
+        }
+        self.bump();
+        let mut type_args = vec![];
+        let mut depth = 0;
+        let mut start = self.pos;
+        loop {
+            match self.bump() {
+                Some('<') => depth += 1,
+                Some('>') if depth > 0 => depth -= 1,
+                Some(c @ ('>' | ',')) => {
+                    let type_arg = self.input[start..self.pos - 1].trim();
+                    type_args.push(parse_sui_type_tag(type_arg).map_err(|e| {
+                        self.error(&format!("invalid type argument '{type_arg}': {e}"))
+                    })?);
+                    if c == '>' {
+                        return Ok(type_args);
+                    }
+                    start = self.pos;
+                }
+                Some(_) => {}
+                None => return Err(self.error("unterminated type arguments")),
+            }
+        }
+    }
+}
+
+/// Build a programmable transaction from parsed statements, resolving object inputs and
+/// compiling packages to publish or upgrade.
+pub async fn build_ptb(
+    client: &SuiClient,
+    statements: Vec<PtbStatement>,
+) -> Result<ProgrammableTransaction, anyhow::Error> {
+    let mut builder = PtbBuilder {
+        client,
+        builder: ProgrammableTransactionBuilder::new(),
+        vars: BTreeMap::new(),
+        last_result: None,
+    };
+    for statement in statements {
+        let result = builder.command(statement.command).await?;
+        if let Some(name) = statement.assign {
+            builder.vars.insert(name, result);
+        }
+        builder.last_result = Some(result);
+    }
+    Ok(builder.builder.finish())
+}
+
+struct PtbBuilder<'a> {
+    client: &'a SuiClient,
+    builder: ProgrammableTransactionBuilder,
+    vars: BTreeMap<String, Argument>,
+    last_result: Option<Argument>,
+}
+
+impl<'a> PtbBuilder<'a> {
+    async fn command(&mut self, command: PtbCommand) -> Result<Argument, anyhow::Error> {
+        let command = match command {
+            PtbCommand::SplitCoins { coin, amounts } => {
+                let coin = self.arg(coin, true).await?;
+                let mut amount_args = vec![];
+                for amount in amounts {
+                    amount_args.push(match amount {
+                        PtbArg::Number(..) => {
+                            let bytes = pure_bytes(&amount, Some("u64"))?;
+                            self.builder.pure_bytes(bytes, false)
+                        }
+                        amount => self.arg(amount, true).await?,
+                    });
+                }
+                Command::SplitCoins(coin, amount_args)
+            }
+            PtbCommand::MergeCoins { coin, coins } => {
+                let coin = self.arg(coin, true).await?;
+                Command::MergeCoins(coin, self.args(coins, true).await?)
+            }
+            PtbCommand::TransferObjects { objects, recipient } => {
+                let objects = self.args(objects, true).await?;
+                Command::TransferObjects(objects, self.arg(recipient, true).await?)
+            }
+            PtbCommand::MoveCall {
+                package,
+                module,
+                function,
+                type_args,
+                args,
+            } => {
+                let args = self
+                    .move_call_args(package, &module, &function, args)
+                    .await?;
+                Command::move_call(
+                    package,
+                    Identifier::from_str(&module)?,
+                    Identifier::from_str(&function)?,
+                    type_args,
+                    args,
+                )
+            }
+            PtbCommand::MakeMoveVec { type_arg, elements } => {
+                Command::MakeMoveVec(type_arg, self.args(elements, true).await?)
+            }
+            PtbCommand::Publish { package_path } => {
+                let (dependencies, modules, _, _) = compile_package(
+                    self.client,
+                    MoveBuildConfig::default(),
+                    package_path,
+                    false,
+                    false,
+                )
+                .await?;
+                Command::Publish(modules, dependencies.published.into_values().collect())
+            }
+            PtbCommand::Upgrade {
+                package_path,
+                upgrade_capability,
+            } => return self.upgrade(package_path, upgrade_capability).await,
+        };
+        Ok(self.builder.command(command))
+    }
+
+    /// Authorize, perform and commit an upgrade, keeping the package's upgrade policy.
+    async fn upgrade(
+        &mut self,
+        package_path: PathBuf,
+        upgrade_capability: ObjectID,
+    ) -> Result<Argument, anyhow::Error> {
+        let (dependencies, modules, compiled_package, package_id) = compile_package(
+            self.client,
+            MoveBuildConfig::default(),
+            package_path,
+            false,
+            false,
+        )
+        .await?;
+        let package_id = package_id.map_err(|e| {
+            anyhow!("Invalid 'published-at' field in manifest of package to be upgraded: {e:?}")
+        })?;
+
+        let data = self
+            .client
+            .read_api()
+            .get_object_with_options(
+                upgrade_capability,
+                SuiObjectDataOptions::default().with_bcs().with_owner(),
+            )
+            .await?
+            .into_object()?;
+        let upgrade_cap: UpgradeCap = data
+            .bcs
+            .as_ref()
+            .ok_or_else(|| anyhow!("Fetch upgrade capability object but no data was returned"))?
+            .try_as_move()
+            .ok_or_else(|| anyhow!("Upgrade capability is not a Move Object"))?
+            .deserialize()?;
+        let cap = self.object(upgrade_capability, true).await?;
+        let policy = self.builder.pure(upgrade_cap.policy)?;
+        let digest = self
+            .builder
+            .pure(compiled_package.get_package_digest(false).to_vec())?;
+
+        let ticket = self.builder.programmable_move_call(
+            SUI_FRAMEWORK_PACKAGE_ID,
+            Identifier::from_str("package")?,
+            Identifier::from_str("authorize_upgrade")?,
+            vec![],
+            vec![cap, policy, digest],
+        );
+        let receipt = self.builder.upgrade(
+            package_id,
+            ticket,
+            dependencies.published.into_values().collect(),
+            modules,
+        );
+        Ok(self.builder.programmable_move_call(
+            SUI_FRAMEWORK_PACKAGE_ID,
+            Identifier::from_str("package")?,
+            Identifier::from_str("commit_upgrade")?,
+            vec![],
+            vec![cap, receipt],
+        ))
+    }
+
+    async fn args(
+        &mut self,
+        args: Vec<PtbArg>,
+        mutable: bool,
+    ) -> Result<Vec<Argument>, anyhow::Error> {
+        let mut arguments = vec![];
+        for arg in args {
+            arguments.push(self.arg(arg, mutable).await?);
+        }
+        Ok(arguments)
+    }
+
+    /// Resolve the arguments of a Move call. Shared objects are only taken mutably if the
+    /// function does not take them by immutable reference, so that read-only uses are not
+    /// sequenced as writes.
+    async fn move_call_args(
+        &mut self,
+        package: ObjectID,
+        module: &str,
+        function: &str,
+        args: Vec<PtbArg>,
+    ) -> Result<Vec<Argument>, anyhow::Error> {
+        if !args.iter().any(|arg| matches!(arg, PtbArg::Object(_))) {
+            return self.args(args, true).await;
+        }
+        let parameters = self
+            .client
+            .read_api()
+            .get_normalized_move_modules_by_package(package)
+            .await?
+            .remove(module)
+            .and_then(|mut normalized| normalized.exposed_functions.remove(function))
+            .ok_or_else(|| anyhow!("Function {package}::{module}::{function} not found"))?
+            .parameters;
+        let mut arguments = vec![];
+        for (i, arg) in args.into_iter().enumerate() {
+            let mutable = !matches!(parameters.get(i), Some(SuiMoveNormalizedType::Reference(_)));
+            arguments.push(self.arg(arg, mutable).await?);
+        }
+        Ok(arguments)
+    }
+
+    /// Resolve an argument. `mutable` only matters for shared objects, which are otherwise taken
+    /// by immutable reference.
+    async fn arg(&mut self, arg: PtbArg, mutable: bool) -> Result<Argument, anyhow::Error> {
+        match arg {
+            PtbArg::Gas => Ok(Argument::GasCoin),
+            PtbArg::Var(name, index) => {
+                let result = match self.vars.get(&name) {
+                    Some(result) => *result,
+                    None if name == "result" => self
+                        .last_result
+                        .ok_or_else(|| anyhow!("'result' used before any command"))?,
+                    None => bail!("Unknown variable '{name}'"),
+                };
+                match (result, index) {
+                    (result, None) => Ok(result),
+                    (Argument::Result(i), Some(j)) => Ok(Argument::NestedResult(i, j)),
+                    (_, Some(_)) => bail!("'{name}' does not hold several values"),
+                }
+            }
+            PtbArg::Object(id) => self.object(id, mutable).await,
+            arg => {
+                let bytes = pure_bytes(&arg, None)?;
+                Ok(self.builder.pure_bytes(bytes, false))
+            }
+        }
+    }
+
+    async fn object(&mut self, id: ObjectID, mutable: bool) -> Result<Argument, anyhow::Error> {
+        let object = self
+            .client
+            .read_api()
+            .get_object_with_options(id, SuiObjectDataOptions::new().with_owner())
+            .await?
+            .into_object()?;
+        let owner = object
+            .owner
+            .ok_or_else(|| anyhow!("Unable to determine ownership of object {id}"))?;
+        let object_arg = match owner {
+            Owner::Shared {
+                initial_shared_version,
+            } => ObjectArg::SharedObject {
+                id,
+                initial_shared_version,
+                mutable,
+            },
+            Owner::AddressOwner(_) | Owner::Immutable => {
+                ObjectArg::ImmOrOwnedObject(object.object_ref())
+            }
+            Owner::ObjectOwner(parent) => bail!(
+                "Object {id} is owned by object {parent}, and cannot be used as a transaction input"
+            ),
+        };
+        self.builder.obj(object_arg)
+    }
+}
+
+/// BCS encode a pure value. Numbers without a suffix take `default_type`, or `u64`. All numbers in
+/// a vector share one type: that of its suffixed numbers, which must agree, or `default_type`.
+fn pure_bytes(arg: &PtbArg, default_type: Option<&str>) -> Result<Vec<u8>, anyhow::Error> {
+    Ok(match arg {
+        PtbArg::Address(address) => bcs::to_bytes(address)?,
+        PtbArg::Bool(b) => bcs::to_bytes(b)?,
+        PtbArg::String(s) => bcs::to_bytes(s)?,
+        PtbArg::Number(digits, suffix) => {
+            let invalid = || anyhow!("Invalid number '{digits}'");
+            match suffix.as_deref().or(default_type).unwrap_or("u64") {
+                "u8" => bcs::to_bytes(&u8::from_str(digits).map_err(|_| invalid())?)?,
+                "u16" => bcs::to_bytes(&u16::from_str(digits).map_err(|_| invalid())?)?,
+                "u32" => bcs::to_bytes(&u32::from_str(digits).map_err(|_| invalid())?)?,
+                "u64" => bcs::to_bytes(&u64::from_str(digits).map_err(|_| invalid())?)?,
+                "u128" => bcs::to_bytes(&u128::from_str(digits).map_err(|_| invalid())?)?,
+                "u256" => bcs::to_bytes(&U256::from_str(digits).map_err(|_| invalid())?)?,
+                suffix => bail!("Invalid number type '{suffix}'"),
+            }
+        }
+        PtbArg::Vector(elements) => {
+            let mut suffix = None;
+            number_suffix(arg, &mut suffix)?;
+            let default_type = suffix.or(default_type);
+            // A vector is its ULEB128 encoded length, followed by its elements.
+            let mut bytes = vec![];
+            let mut len = elements.len();
+            loop {
+                let byte = (len & 0x7f) as u8;
+                len >>= 7;
+                if len == 0 {
+                    bytes.push(byte);
+                    break;
+                }
+                bytes.push(byte | 0x80);
+            }
+            for element in elements {
+                bytes.extend(pure_bytes(element, default_type)?);
+            }
+            bytes
+        }
+        PtbArg::Gas | PtbArg::Var(..) | PtbArg::Object(_) => {
+            bail!("Only pure values can be used in a vector")
+        }
+    })
+}
+
+/// Find the suffix of the numbers in `arg`, failing if they do not all have the same one.
+fn number_suffix<'a>(arg: &'a PtbArg, suffix: &mut Option<&'a str>) -> Result<(), anyhow::Error> {
+    match arg {
+        PtbArg::Number(_, Some(s)) => match suffix {
+            Some(other) if *other != s.as_str() => {
+                bail!("Numbers of different types '{other}' and '{s}' in one vector")
+            }
+            _ => *suffix = Some(s.as_str()),
+        },
+        PtbArg::Vector(elements) => {
+            for element in elements {
+                number_suffix(element, suffix)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
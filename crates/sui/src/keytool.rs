@@ -1,7 +1,9 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
+use crate::transaction_bundle::TransactionBundle;
+use crate::transaction_file::TransactionFile;
 use crate::zklogin_commands_util::{perform_zk_login_test_tx, read_cli_line};
-use anyhow::anyhow;
+use anyhow::{anyhow, bail, ensure};
 use bip32::DerivationPath;
 use clap::*;
 use fastcrypto::ed25519::Ed25519KeyPair;
@@ -29,6 +31,7 @@ use shared_crypto::intent::{Intent, IntentMessage, IntentScope, PersonalMessage}
 use std::fmt::{Debug, Display, Formatter};
 use std::fs;
 use std::path::{Path, PathBuf};
+use sui_keys::encryption::read_new_passphrase;
 use sui_keys::key_derive::generate_new_key;
 use sui_keys::keypair_file::{
     read_authority_keypair_from_file, read_keypair_from_file, write_authority_keypair_to_file,
@@ -68,6 +71,10 @@ pub enum KeyToolCommand {
         /// The alias must start with a letter and can contain only letters, digits, hyphens (-), or underscores (_).
         new_alias: Option<String>,
     },
+    /// Change the passphrase of an encrypted sui.keystore. This prompts for the current
+    /// passphrase, unless it is set in the SUI_KEYSTORE_PASSPHRASE environment variable, and
+    /// then twice for the new one.
+    ChangePassphrase,
     /// Convert private key from wallet format (hex of 32 byte private key) to sui.keystore format
     /// (base64 of 33 byte flag || private key) or vice versa.
     Convert { value: String },
@@ -84,6 +91,17 @@ pub enum KeyToolCommand {
         #[clap(long)]
         tx_bytes: Option<String>,
     },
+    /// Encrypt a plaintext sui.keystore with a passphrase, prompted for twice. Commands then
+    /// prompt for the passphrase when they first use a private key, unless it is set in the
+    /// SUI_KEYSTORE_PASSPHRASE environment variable. The aliases and public keys are kept in
+    /// plaintext.
+    Encrypt,
+    /// Print the private key of an address in sui.keystore, as a Base64 encoded 33-byte
+    /// `flag || privkey`.
+    Export {
+        #[clap(long, value_parser = decode_bytes_hex::<SuiAddress>)]
+        address: SuiAddress,
+    },
     /// Generate a new keypair with key scheme flag {ed25519 | secp256k1 | secp256r1}
     /// with optional derivation path, default to m/44'/784'/0'/0'/0' for ed25519 or
     /// m/54'/784'/0'/0/0 for secp256k1 or m/74'/784'/0'/0/0 for secp256r1. Word
@@ -177,6 +195,16 @@ pub enum KeyToolCommand {
         #[clap(long)]
         intent: Option<Intent>,
     },
+    /// Validate a transaction bundle created by `sui client create-bundle`, print its summary
+    /// and add the signatures of the keys in sui.keystore which must sign its transaction, or
+    /// only of the given address. This does not need network access, so it can run on an offline
+    /// machine. The bundle file is updated in place.
+    SignBundle {
+        #[clap(long)]
+        file: PathBuf,
+        #[clap(long, value_parser = decode_bytes_hex::<SuiAddress>)]
+        address: Option<SuiAddress>,
+    },
     /// Creates a signature by leveraging AWS KMS. Pass in a key-id to leverage Amazon
     /// KMS to sign a message and the base64 pubkey.
     /// Generate PubKey from pem using MystenLabs/base64pemkey
@@ -280,6 +308,12 @@ pub struct DecodedMultiSig {
     weight: String,
 }
 
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeystoreEncryption {
+    addresses: Vec<SuiAddress>,
+}
+
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct DecodedMultiSigOutput {
@@ -406,9 +440,11 @@ pub struct ZkLoginInsecureSignPersonalMessage {
 #[serde(untagged)]
 pub enum CommandOutput {
     Alias(AliasUpdate),
+    ChangePassphrase(KeystoreEncryption),
     Convert(ConvertOutput),
     DecodeMultiSig(DecodedMultiSigOutput),
     DecodeTxBytes(TransactionData),
+    Encrypt(KeystoreEncryption),
     Error(String),
     Generate(Key),
     Import(Key),
@@ -420,6 +456,7 @@ pub enum CommandOutput {
     PrivateKeyBase64(PrivateKeyBase64),
     Show(Key),
     Sign(SignData),
+    SignBundle(TransactionBundle),
     SignKMS(SerializedSig),
     ZkLoginSignAndExecuteTx(ZkLoginSignAndExecuteTx),
     ZkLoginInsecureSignPersonalMessage(ZkLoginInsecureSignPersonalMessage),
@@ -439,6 +476,22 @@ impl KeyToolCommand {
                     new_alias,
                 })
             }
+            KeyToolCommand::ChangePassphrase => {
+                let Keystore::File(keystore) = keystore else {
+                    bail!("Only a keystore file has a passphrase");
+                };
+                ensure!(
+                    keystore.is_encrypted(),
+                    "The keystore is not encrypted, use `sui keytool encrypt` to encrypt it"
+                );
+                // Unlock the keystore with the current passphrase before asking for the new one.
+                keystore.key_pairs()?;
+                let passphrase = read_new_passphrase()?;
+                keystore.set_passphrase(Some(&passphrase))?;
+                CommandOutput::ChangePassphrase(KeystoreEncryption {
+                    addresses: keystore.addresses(),
+                })
+            }
             KeyToolCommand::Convert { value } => {
                 let result = convert_private_key_to_base64(value)?;
                 CommandOutput::Convert(result)
@@ -504,6 +557,26 @@ impl KeyToolCommand {
                 CommandOutput::DecodeTxBytes(tx_data)
             }
 
+            KeyToolCommand::Encrypt => {
+                let Keystore::File(keystore) = keystore else {
+                    bail!("Only a keystore file can be encrypted");
+                };
+                ensure!(
+                    !keystore.is_encrypted(),
+                    "The keystore is already encrypted, use `sui keytool change-passphrase` to change its passphrase"
+                );
+                let passphrase = read_new_passphrase()?;
+                keystore.set_passphrase(Some(&passphrase))?;
+                CommandOutput::Encrypt(KeystoreEncryption {
+                    addresses: keystore.addresses(),
+                })
+            }
+            KeyToolCommand::Export { address } => {
+                let keypair = keystore.get_key(&address)?;
+                CommandOutput::PrivateKeyBase64(PrivateKeyBase64 {
+                    base64: keypair.encode_base64(),
+                })
+            }
             KeyToolCommand::Generate {
                 key_scheme,
                 derivation_path,
@@ -743,6 +816,10 @@ impl KeyToolCommand {
                 })
             }
 
+            KeyToolCommand::SignBundle { file, address } => {
+                CommandOutput::SignBundle(TransactionBundle::sign(&file, keystore, address)?)
+            }
+
             KeyToolCommand::SignKMS {
                 data,
                 keyid,
@@ -1099,6 +1176,20 @@ impl Display for CommandOutput {
                     update.old_alias, update.new_alias
                 )
             }
+            CommandOutput::ChangePassphrase(encryption) => {
+                write!(
+                    formatter,
+                    "Changed the passphrase of the keystore with {} keys",
+                    encryption.addresses.len()
+                )
+            }
+            CommandOutput::Encrypt(encryption) => {
+                write!(
+                    formatter,
+                    "Encrypted the keystore with {} keys",
+                    encryption.addresses.len()
+                )
+            }
             // Sign needs to be manually built because we need to wrap the very long
             // rawTxData string and rawIntentMsg strings into multiple rows due to
             // their lengths, which we cannot do with a JsonTable
@@ -1131,6 +1222,7 @@ impl Display for CommandOutput {
                 table.with(Modify::new(Rows::new(0..)).with(Width::wrap(160).keep_words()));
                 write!(formatter, "{}", table)
             }
+            CommandOutput::SignBundle(bundle) => write!(formatter, "{}", bundle),
             _ => {
                 let json_obj = json![self];
                 let mut table = json_to_table(&json_obj);
@@ -3,11 +3,15 @@
 // SPDX-License-Identifier: Apache-2.0
 
 pub mod client_commands;
+pub mod client_multisig;
+pub mod client_ptb;
 pub mod console;
 pub mod fire_drill;
 pub mod keytool;
 pub mod shell;
 pub mod sui_commands;
+pub mod transaction_bundle;
+pub mod transaction_file;
 pub mod validator_commands;
 pub mod zklogin_commands_util;
 
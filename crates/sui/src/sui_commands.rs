@@ -27,7 +27,8 @@ use sui_config::{
 };
 use sui_keys::keystore::{AccountKeystore, FileBasedKeystore, Keystore};
 use sui_move::{self, execute_move_command};
-use sui_move_build::SuiPackageHooks;
+use sui_move_build::on_chain::OnChainPackageHooks;
+use sui_sdk::package_store::RpcPackageStore;
 use sui_sdk::sui_client_config::{SuiClientConfig, SuiEnv};
 use sui_sdk::wallet_context::WalletContext;
 use sui_swarm::memory::Swarm;
@@ -159,7 +160,9 @@ pub enum SuiCommand {
 
 impl SuiCommand {
     pub async fn execute(self) -> Result<(), anyhow::Error> {
-        move_package::package_hooks::register_package_hooks(Box::new(SuiPackageHooks));
+        move_package::package_hooks::register_package_hooks(Box::new(OnChainPackageHooks::new(
+            RpcPackageStore,
+        )));
         match self {
             SuiCommand::Start {
                 config,
@@ -0,0 +1,364 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! A file format to move a transaction to an offline machine for signing, and back for execution.
+//!
+//! A bundle holds the unsigned transaction data, the owned and immutable object references it
+//! depends on, and a human readable summary of what the transaction does. The summary and object
+//! references are derived from the transaction data, and checked against it when the bundle is
+//! read, so that the reviewer of an air-gapped signer can trust them. Bundles are created with
+//! `sui client create-bundle`, signed with `sui keytool sign-bundle` and executed with
+//! `sui client execute-bundle`, which first checks that the object references are still current.
+//!
+//! The sender of a bundle can be a multisig address, whose keys then sign a multisig proposal
+//! embedded in the bundle, see [`MultisigProposal`].
+
+use std::fmt::{Display, Formatter};
+
+use anyhow::{anyhow, bail, ensure};
+use serde::{Deserialize, Serialize};
+use sui_json_rpc_types::{SuiObjectDataOptions, SuiObjectRef};
+use sui_sdk::SuiClient;
+use sui_types::base_types::{EpochId, SuiAddress};
+use sui_types::crypto::{PublicKey, Signature, SuiSignature};
+use sui_types::multisig::MultiSigPublicKey;
+use sui_types::signature::GenericSignature;
+use sui_types::transaction::{
+    InputObjectKind, Transaction, TransactionData, TransactionDataAPI, TransactionExpiration,
+};
+
+use crate::client_multisig::MultisigProposal;
+use crate::transaction_file::{encode_tx_data, verify_signature, TransactionFile};
+
+#[cfg(test)]
+#[path = "unit_tests/transaction_bundle_tests.rs"]
+mod transaction_bundle_tests;
+
+/// The version of the bundle format written by this tool.
+pub const TRANSACTION_BUNDLE_VERSION: u32 = 1;
+
+const MULTI_GET_OBJECT_LIMIT: usize = 50;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionBundle {
+    pub version: u32,
+    /// Free form description of the purpose of the transaction, given by its creator.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub summary: TransactionSummary,
+    /// The owned and immutable objects read by the transaction, which must not change until it
+    /// is executed.
+    pub objects: Vec<SuiObjectRef>,
+    /// BCS serialized transaction data bytes, as base-64 encoded string.
+    pub tx_bytes: String,
+    #[serde(default)]
+    pub signatures: Vec<Signature>,
+    /// The partial signatures of the sender, if it is a multisig address.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub multisig: Option<MultisigProposal>,
+}
+
+/// A human readable summary of a transaction.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionSummary {
+    pub sender: SuiAddress,
+    pub gas_owner: SuiAddress,
+    pub gas_budget: u64,
+    pub gas_price: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expiration: Option<EpochId>,
+    /// The inputs and commands of the transaction, one per line.
+    pub transaction: Vec<String>,
+}
+
+impl TransactionSummary {
+    pub fn new(tx_data: &TransactionData) -> Self {
+        Self {
+            sender: tx_data.sender(),
+            gas_owner: tx_data.gas_owner(),
+            gas_budget: tx_data.gas_budget(),
+            gas_price: tx_data.gas_price(),
+            expiration: match tx_data.expiration() {
+                TransactionExpiration::None => None,
+                TransactionExpiration::Epoch(epoch) => Some(*epoch),
+            },
+            transaction: tx_data
+                .kind()
+                .to_string()
+                .lines()
+                .map(|line| line.to_string())
+                .collect(),
+        }
+    }
+}
+
+impl TransactionBundle {
+    /// Create a bundle for `tx_data`, with the public key of its sender if it is a multisig
+    /// address.
+    pub fn new(
+        tx_data: &TransactionData,
+        description: Option<String>,
+        multisig_pk: Option<MultiSigPublicKey>,
+    ) -> Result<Self, anyhow::Error> {
+        Ok(Self {
+            version: TRANSACTION_BUNDLE_VERSION,
+            description,
+            summary: TransactionSummary::new(tx_data),
+            objects: required_objects(tx_data)?,
+            tx_bytes: encode_tx_data(tx_data)?,
+            signatures: vec![],
+            multisig: multisig_pk
+                .map(|multisig_pk| MultisigProposal::new(tx_data, multisig_pk))
+                .transpose()?,
+        })
+    }
+
+    /// The addresses which must sign the transaction, the sender and the gas owner if it is
+    /// sponsored, and whether they signed. A multisig sender signed once the weight of its
+    /// partial signatures reaches its threshold.
+    pub fn signers(&self) -> Result<Vec<(SuiAddress, bool)>, anyhow::Error> {
+        let mut signed = self
+            .signatures
+            .iter()
+            .map(signer_of)
+            .collect::<Result<Vec<_>, _>>()?;
+        if let Some(multisig) = &self.multisig {
+            if multisig.signed_weight() >= *multisig.multisig_pk.threshold() {
+                signed.push(multisig.address());
+            }
+        }
+        Ok(self
+            .tx_data()?
+            .signers()
+            .into_iter()
+            .map(|signer| (signer, signed.contains(&signer)))
+            .collect())
+    }
+
+    /// The signed transaction, once all of its signers signed.
+    pub fn transaction(&self) -> Result<Transaction, anyhow::Error> {
+        let missing = self
+            .signers()?
+            .into_iter()
+            .filter(|(_, signed)| !signed)
+            .map(|(address, _)| address.to_string())
+            .collect::<Vec<_>>();
+        ensure!(
+            missing.is_empty(),
+            "The bundle is missing the signatures of {}",
+            missing.join(", ")
+        );
+        let mut signatures = self
+            .multisig
+            .iter()
+            .map(|multisig| multisig.combine())
+            .collect::<Result<Vec<_>, _>>()?;
+        signatures.extend(self.signatures.iter().cloned().map(GenericSignature::from));
+        Ok(Transaction::from_generic_sig_data(
+            self.tx_data()?,
+            signatures,
+        ))
+    }
+
+    /// Check that the objects of the bundle are still at the versions the transaction was
+    /// created with, as the transaction cannot be executed otherwise.
+    pub async fn check_objects(&self, client: &SuiClient) -> Result<(), anyhow::Error> {
+        let mut stale = vec![];
+        for chunk in self.objects.chunks(MULTI_GET_OBJECT_LIMIT) {
+            let resps = client
+                .read_api()
+                .multi_get_object_with_options(
+                    chunk.iter().map(|object| object.object_id).collect(),
+                    SuiObjectDataOptions::default(),
+                )
+                .await?;
+            for (object, resp) in chunk.iter().zip(resps) {
+                match resp.object_ref_if_exists() {
+                    Some(current) if current == object.to_object_ref() => {}
+                    Some((_, version, _)) => stale.push(format!(
+                        "{} is at version {}, not {}",
+                        object.object_id, version, object.version
+                    )),
+                    None => stale.push(format!("{} does not exist", object.object_id)),
+                }
+            }
+        }
+        if !stale.is_empty() {
+            bail!(
+                "The bundle objects changed since it was created:\n{}",
+                stale.join("\n")
+            );
+        }
+        Ok(())
+    }
+}
+
+impl TransactionFile for TransactionBundle {
+    const KIND: &'static str = "bundle";
+
+    fn tx_bytes(&self) -> &str {
+        &self.tx_bytes
+    }
+
+    /// The signers of the transaction which did not sign yet, or the keys of a multisig sender
+    /// which did not sign yet.
+    fn unsigned_signers(&self) -> Result<Vec<SuiAddress>, anyhow::Error> {
+        let mut unsigned = vec![];
+        for (address, signed) in self.signers()? {
+            if signed {
+                continue;
+            }
+            match &self.multisig {
+                Some(multisig) if multisig.address() == address => {
+                    unsigned.extend(multisig.unsigned_signers()?)
+                }
+                _ => unsigned.push(address),
+            }
+        }
+        Ok(unsigned)
+    }
+
+    /// Add the signature of one of the signers of the transaction, or the partial signature of
+    /// a key of its multisig sender, after verifying it.
+    fn add_signature(&mut self, signature: Signature) -> Result<(), anyhow::Error> {
+        let signer = signer_of(&signature)?;
+        let Some((_, signed)) = self
+            .signers()?
+            .into_iter()
+            .find(|(address, _)| *address == signer)
+        else {
+            return match &mut self.multisig {
+                Some(multisig) if multisig.signers().iter().any(|s| s.address == signer) => {
+                    multisig.add_signature(signature)
+                }
+                _ => bail!(
+                    "{signer} is not a signer of the transaction. If the sender is a multisig \
+                    address, its public key must be given when creating the bundle"
+                ),
+            };
+        };
+        ensure!(!signed, "{signer} already signed the transaction");
+        verify_signature(&self.tx_data()?, &signature, signer)?;
+        self.signatures.push(signature);
+        Ok(())
+    }
+
+    /// Check that the bundle has a supported version, and that its summary, object references
+    /// and signatures match its transaction data.
+    fn validate(&self) -> Result<(), anyhow::Error> {
+        ensure!(
+            self.version == TRANSACTION_BUNDLE_VERSION,
+            "Unsupported bundle version {}, expected {}",
+            self.version,
+            TRANSACTION_BUNDLE_VERSION
+        );
+        let tx_data = self.tx_data()?;
+        ensure!(
+            self.summary == TransactionSummary::new(&tx_data),
+            "The bundle summary does not match its transaction"
+        );
+        ensure!(
+            self.objects == required_objects(&tx_data)?,
+            "The bundle objects do not match its transaction"
+        );
+        for signature in &self.signatures {
+            verify_signature(&tx_data, signature, signer_of(signature)?)?;
+        }
+        if let Some(multisig) = &self.multisig {
+            ensure!(
+                multisig.tx_bytes == self.tx_bytes,
+                "The bundle multisig does not match its transaction"
+            );
+            multisig.validate()?;
+        }
+        Ok(())
+    }
+}
+
+/// The address of the key of a single-key signature. The partial signatures of a multisig sender
+/// are held by the multisig proposal of the bundle instead.
+fn signer_of(signature: &Signature) -> Result<SuiAddress, anyhow::Error> {
+    let public_key = PublicKey::try_from_bytes(signature.scheme(), signature.public_key_bytes())
+        .map_err(|e| anyhow!("Invalid public key in signature: {e}"))?;
+    Ok(SuiAddress::from(&public_key))
+}
+
+fn required_objects(tx_data: &TransactionData) -> Result<Vec<SuiObjectRef>, anyhow::Error> {
+    let mut objects = tx_data
+        .input_objects()?
+        .into_iter()
+        .filter_map(|input| match input {
+            InputObjectKind::ImmOrOwnedMoveObject(object_ref) => Some(object_ref.into()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    objects.extend(
+        tx_data
+            .receiving_objects()
+            .into_iter()
+            .map(SuiObjectRef::from),
+    );
+    Ok(objects)
+}
+
+impl Display for TransactionSummary {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Sender: {}", self.sender)?;
+        if self.gas_owner != self.sender {
+            writeln!(f, "Gas Owner: {}", self.gas_owner)?;
+        }
+        writeln!(f, "Gas Budget: {}", self.gas_budget)?;
+        writeln!(f, "Gas Price: {}", self.gas_price)?;
+        if let Some(epoch) = self.expiration {
+            writeln!(f, "Expiration Epoch: {}", epoch)?;
+        }
+        for line in &self.transaction {
+            writeln!(f, "{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for TransactionBundle {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "----- Transaction Bundle (version {}) ----",
+            self.version
+        )?;
+        if let Some(description) = &self.description {
+            writeln!(f, "Description: {}", description)?;
+        }
+        write!(f, "{}", self.summary)?;
+        writeln!(f, "----- Objects ----")?;
+        for object in &self.objects {
+            writeln!(
+                f,
+                "{} version {} digest {}",
+                object.object_id, object.version, object.digest
+            )?;
+        }
+        writeln!(f, "----- Signers ----")?;
+        match self.signers() {
+            Ok(signers) => {
+                for (address, signed) in signers {
+                    let status = if signed { "signed" } else { "not signed" };
+                    writeln!(f, "{}: {}", address, status)?;
+                }
+            }
+            Err(e) => writeln!(f, "Cannot read the signers: {e}")?,
+        }
+        if let Some(multisig) = &self.multisig {
+            writeln!(
+                f,
+                "Multisig {}: signed weight {} of threshold {}",
+                multisig.address(),
+                multisig.signed_weight(),
+                multisig.multisig_pk.threshold()
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,122 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Files holding an unsigned transaction, passed around its signers to collect their signatures,
+//! such as multisig proposals and transaction bundles.
+
+use std::fs;
+use std::path::Path;
+
+use anyhow::{anyhow, ensure, Context};
+use fastcrypto::encoding::{Base64, Encoding};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use shared_crypto::intent::{Intent, IntentMessage};
+use sui_keys::keystore::{AccountKeystore, Keystore};
+use sui_types::base_types::SuiAddress;
+use sui_types::crypto::{Signature, SuiSignature};
+use sui_types::transaction::TransactionData;
+
+pub trait TransactionFile: Serialize + DeserializeOwned {
+    /// The name of the file kind in messages, e.g. "proposal".
+    const KIND: &'static str;
+
+    /// BCS serialized transaction data bytes, as base-64 encoded string.
+    fn tx_bytes(&self) -> &str;
+
+    /// The addresses whose signature the file still needs.
+    fn unsigned_signers(&self) -> Result<Vec<SuiAddress>, anyhow::Error>;
+
+    /// Add a signature of the transaction, after verifying it.
+    fn add_signature(&mut self, signature: Signature) -> Result<(), anyhow::Error>;
+
+    /// Check the content of the file once read.
+    fn validate(&self) -> Result<(), anyhow::Error> {
+        Ok(())
+    }
+
+    fn tx_data(&self) -> Result<TransactionData, anyhow::Error> {
+        decode_tx_data(self.tx_bytes())
+    }
+
+    /// Read a file from `path` and validate it.
+    fn read(path: &Path) -> Result<Self, anyhow::Error> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Cannot read {} file {}", Self::KIND, path.display()))?;
+        let file: Self = serde_json::from_str(&content).with_context(|| {
+            format!("Cannot deserialize {} file {}", Self::KIND, path.display())
+        })?;
+        file.validate()?;
+        Ok(file)
+    }
+
+    fn write(&self, path: &Path) -> Result<(), anyhow::Error> {
+        fs::write(path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Cannot write {} file {}", Self::KIND, path.display()))
+    }
+
+    /// Write a new file to `path`, which must not exist yet.
+    fn create(&self, path: &Path) -> Result<(), anyhow::Error> {
+        ensure!(
+            !path.exists(),
+            "The {} file {} already exists",
+            Self::KIND,
+            path.display()
+        );
+        self.write(path)
+    }
+
+    /// Sign the transaction of the file at `path` with the key of `address`, or with every key
+    /// of the keystore which did not sign yet, and save the signatures to the file.
+    fn sign(
+        path: &Path,
+        keystore: &Keystore,
+        address: Option<SuiAddress>,
+    ) -> Result<Self, anyhow::Error> {
+        let mut file = Self::read(path)?;
+        let tx_data = file.tx_data()?;
+        let signers = match address {
+            Some(address) => vec![address],
+            None => {
+                let addresses = keystore.addresses();
+                file.unsigned_signers()?
+                    .into_iter()
+                    .filter(|address| addresses.contains(address))
+                    .collect()
+            }
+        };
+        ensure!(
+            !signers.is_empty(),
+            "The keystore has no key which must sign the {} and did not sign yet",
+            Self::KIND
+        );
+        for signer in signers {
+            let signature = keystore.sign_secure(&signer, &tx_data, Intent::sui_transaction())?;
+            file.add_signature(signature)?;
+        }
+        file.write(path)?;
+        Ok(file)
+    }
+}
+
+pub fn encode_tx_data(tx_data: &TransactionData) -> Result<String, anyhow::Error> {
+    Ok(Base64::encode(bcs::to_bytes(tx_data)?))
+}
+
+pub fn decode_tx_data(tx_bytes: &str) -> Result<TransactionData, anyhow::Error> {
+    Ok(bcs::from_bytes(
+        &Base64::decode(tx_bytes).map_err(|e| anyhow!(e))?,
+    )?)
+}
+
+/// Verify the signature of `signer` over `tx_data`.
+pub fn verify_signature(
+    tx_data: &TransactionData,
+    signature: &Signature,
+    signer: SuiAddress,
+) -> Result<(), anyhow::Error> {
+    let intent_msg = IntentMessage::new(Intent::sui_transaction(), tx_data);
+    signature
+        .verify_secure(&intent_msg, signer, signature.scheme())
+        .map_err(|e| anyhow!("Invalid signature of {signer}: {e}"))
+}
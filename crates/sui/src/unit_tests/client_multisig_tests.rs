@@ -0,0 +1,139 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use shared_crypto::intent::{Intent, IntentMessage};
+use sui_keys::keystore::{AccountKeystore, InMemKeystore, Keystore};
+use sui_types::base_types::{random_object_ref, SuiAddress};
+use sui_types::crypto::{get_key_pair, Signature, SuiKeyPair};
+use sui_types::multisig::MultiSigPublicKey;
+use sui_types::signature::GenericSignature;
+use sui_types::transaction::{Transaction, TransactionData};
+
+use crate::client_multisig::MultisigProposal;
+use crate::transaction_file::TransactionFile;
+
+fn new_keys(n: usize) -> Vec<SuiKeyPair> {
+    (0..n)
+        .map(|_| SuiKeyPair::Ed25519(get_key_pair().1))
+        .collect()
+}
+
+fn new_proposal(keys: &[SuiKeyPair], threshold: u16) -> (MultisigProposal, TransactionData) {
+    let multisig_pk = MultiSigPublicKey::new(
+        keys.iter().map(|key| key.public()).collect(),
+        vec![1; keys.len()],
+        threshold,
+    )
+    .unwrap();
+    let tx_data = TransactionData::new_transfer_sui(
+        SuiAddress::random_for_testing_only(),
+        SuiAddress::from(&multisig_pk),
+        Some(1000),
+        random_object_ref(),
+        10_000_000,
+        1000,
+    );
+    (
+        MultisigProposal::new(&tx_data, multisig_pk).unwrap(),
+        tx_data,
+    )
+}
+
+fn sign(tx_data: &TransactionData, key: &SuiKeyPair) -> Signature {
+    Signature::new_secure(
+        &IntentMessage::new(Intent::sui_transaction(), tx_data.clone()),
+        key,
+    )
+}
+
+#[test]
+fn test_proposal_sign_and_combine() {
+    let keys = new_keys(3);
+    let (mut proposal, tx_data) = new_proposal(&keys, 2);
+    assert_eq!(proposal.tx_data().unwrap(), tx_data);
+    assert!(proposal.combine().is_err());
+
+    // Sign out of the order of the keys, the multisig still needs them in order.
+    proposal.add_signature(sign(&tx_data, &keys[2])).unwrap();
+    let status = proposal.status().unwrap();
+    assert_eq!(status.signed_weight, 1);
+    assert_eq!(status.remaining_weight, 1);
+    assert!(proposal.combine().is_err());
+
+    proposal.add_signature(sign(&tx_data, &keys[0])).unwrap();
+    let status = proposal.status().unwrap();
+    assert_eq!(status.remaining_weight, 0);
+    assert_eq!(
+        status
+            .signers
+            .iter()
+            .map(|signer| signer.signed)
+            .collect::<Vec<_>>(),
+        vec![true, false, true]
+    );
+
+    let multisig = proposal.combine().unwrap();
+    assert!(matches!(multisig, GenericSignature::MultiSig(_)));
+
+    // The proposal round trips through its file format.
+    let file = tempfile::NamedTempFile::new().unwrap();
+    proposal.write(file.path()).unwrap();
+    assert_eq!(MultisigProposal::read(file.path()).unwrap(), proposal);
+}
+
+#[test]
+fn test_proposal_rejects_invalid_signatures() {
+    let keys = new_keys(2);
+    let (mut proposal, tx_data) = new_proposal(&keys, 2);
+
+    // A key outside of the multisig.
+    let outsider = SuiKeyPair::Ed25519(get_key_pair().1);
+    assert!(proposal.add_signature(sign(&tx_data, &outsider)).is_err());
+
+    // A signature over another transaction.
+    let (_, other_tx_data) = new_proposal(&keys, 1);
+    assert!(proposal
+        .add_signature(sign(&other_tx_data, &keys[0]))
+        .is_err());
+
+    // A key signing twice.
+    proposal.add_signature(sign(&tx_data, &keys[0])).unwrap();
+    assert!(proposal.add_signature(sign(&tx_data, &keys[0])).is_err());
+    assert_eq!(proposal.signatures.len(), 1);
+}
+
+#[test]
+fn test_proposal_sign_with_keystore() {
+    let keystore = Keystore::from(InMemKeystore::new_insecure_for_tests(2));
+    let pks = keystore.keys();
+    let multisig_pk = MultiSigPublicKey::new(pks.clone(), vec![1, 1], 2).unwrap();
+    let tx_data = TransactionData::new_transfer_sui(
+        SuiAddress::random_for_testing_only(),
+        SuiAddress::from(&multisig_pk),
+        Some(1000),
+        random_object_ref(),
+        10_000_000,
+        1000,
+    );
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("proposal.json");
+    let proposal = MultisigProposal::new(&tx_data, multisig_pk).unwrap();
+    proposal.create(&path).unwrap();
+    assert!(proposal.create(&path).is_err());
+
+    // Sign with one key, then with every key which did not sign yet.
+    let first = SuiAddress::from(&pks[0]);
+    let proposal = MultisigProposal::sign(&path, &keystore, Some(first)).unwrap();
+    assert_eq!(
+        proposal.unsigned_signers().unwrap(),
+        vec![SuiAddress::from(&pks[1])]
+    );
+    let proposal = MultisigProposal::sign(&path, &keystore, None).unwrap();
+    assert!(proposal.unsigned_signers().unwrap().is_empty());
+    assert!(MultisigProposal::sign(&path, &keystore, None).is_err());
+
+    // The proposal reports the digest of the executed transaction.
+    let transaction =
+        Transaction::from_generic_sig_data(tx_data, vec![proposal.combine().unwrap()]);
+    assert_eq!(proposal.status().unwrap().tx_digest, *transaction.digest());
+}
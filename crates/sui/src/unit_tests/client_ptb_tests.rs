@@ -0,0 +1,160 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::path::PathBuf;
+
+use sui_types::base_types::{ObjectID, SuiAddress};
+use sui_types::parse_sui_type_tag;
+
+use crate::client_ptb::{parse_ptb, pure_bytes, PtbArg, PtbCommand, PtbStatement};
+
+fn number(digits: &str, suffix: Option<&str>) -> PtbArg {
+    PtbArg::Number(digits.to_string(), suffix.map(|s| s.to_string()))
+}
+
+#[test]
+fn test_parse_split_and_transfer() {
+    let statements = parse_ptb(
+        "let coins = split-coins gas [1000, 2000u64]; transfer-objects [coins.0, coins.1] @0x42",
+    )
+    .unwrap();
+    assert_eq!(
+        statements,
+        vec![
+            PtbStatement {
+                assign: Some("coins".to_string()),
+                command: PtbCommand::SplitCoins {
+                    coin: PtbArg::Gas,
+                    amounts: vec![number("1000", None), number("2000", Some("u64"))],
+                },
+            },
+            PtbStatement {
+                assign: None,
+                command: PtbCommand::TransferObjects {
+                    objects: vec![
+                        PtbArg::Var("coins".to_string(), Some(0)),
+                        PtbArg::Var("coins".to_string(), Some(1)),
+                    ],
+                    recipient: PtbArg::Address(SuiAddress::from(
+                        ObjectID::from_hex_literal("0x42").unwrap()
+                    )),
+                },
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_parse_move_call() {
+    let statements = parse_ptb(
+        "move-call 0x2::coin::from_balance <0x2::sui::SUI> result 0x6 true \"name\" vector[1u8, 2u8]\n\
+         make-move-vec <u64> [1, 2]\n\
+         merge-coins 0x5 [result.0]",
+    )
+    .unwrap();
+    assert_eq!(statements.len(), 3);
+    assert_eq!(
+        statements[0].command,
+        PtbCommand::MoveCall {
+            package: ObjectID::from_hex_literal("0x2").unwrap(),
+            module: "coin".to_string(),
+            function: "from_balance".to_string(),
+            type_args: vec![parse_sui_type_tag("0x2::sui::SUI").unwrap()],
+            args: vec![
+                PtbArg::Var("result".to_string(), None),
+                PtbArg::Object(ObjectID::from_hex_literal("0x6").unwrap()),
+                PtbArg::Bool(true),
+                PtbArg::String("name".to_string()),
+                PtbArg::Vector(vec![number("1", Some("u8")), number("2", Some("u8"))]),
+            ],
+        }
+    );
+    assert_eq!(
+        statements[1].command,
+        PtbCommand::MakeMoveVec {
+            type_arg: Some(parse_sui_type_tag("u64").unwrap()),
+            elements: vec![number("1", None), number("2", None)],
+        }
+    );
+    assert_eq!(
+        statements[2].command,
+        PtbCommand::MergeCoins {
+            coin: PtbArg::Object(ObjectID::from_hex_literal("0x5").unwrap()),
+            coins: vec![PtbArg::Var("result".to_string(), Some(0))],
+        }
+    );
+}
+
+#[test]
+fn test_parse_publish_and_upgrade() {
+    let statements = parse_ptb("let cap = publish ./my_package\nupgrade \"../other\" 0x7").unwrap();
+    assert_eq!(
+        statements[0],
+        PtbStatement {
+            assign: Some("cap".to_string()),
+            command: PtbCommand::Publish {
+                package_path: PathBuf::from("./my_package"),
+            },
+        }
+    );
+    assert_eq!(
+        statements[1].command,
+        PtbCommand::Upgrade {
+            package_path: PathBuf::from("../other"),
+            upgrade_capability: ObjectID::from_hex_literal("0x7").unwrap(),
+        }
+    );
+}
+
+#[test]
+fn test_parse_errors() {
+    assert!(parse_ptb("").is_err());
+    assert!(parse_ptb("split-coin gas [1]").is_err());
+    assert!(parse_ptb("split-coins gas [1").is_err());
+    assert!(parse_ptb("split-coins gas [1u7]").is_err());
+    assert!(parse_ptb("move-call 0x2::coin <u64").is_err());
+    assert!(parse_ptb("upgrade ./pkg gas").is_err());
+    assert!(parse_ptb("transfer-objects [gas] @0x1 extra").is_err());
+}
+
+#[test]
+fn test_pure_bytes() {
+    assert_eq!(
+        pure_bytes(&number("1000", None), None).unwrap(),
+        bcs::to_bytes(&1000u64).unwrap()
+    );
+    assert_eq!(
+        pure_bytes(&number("7", None), Some("u8")).unwrap(),
+        bcs::to_bytes(&7u8).unwrap()
+    );
+    assert_eq!(
+        pure_bytes(&PtbArg::Vector(vec![number("1", Some("u16")); 200]), None).unwrap(),
+        bcs::to_bytes(&vec![1u16; 200]).unwrap()
+    );
+    assert_eq!(
+        pure_bytes(
+            &PtbArg::Vector(vec![number("1", None), number("2", Some("u8"))]),
+            None
+        )
+        .unwrap(),
+        bcs::to_bytes(&vec![1u8, 2u8]).unwrap()
+    );
+    assert_eq!(
+        pure_bytes(
+            &PtbArg::Vector(vec![
+                PtbArg::Vector(vec![number("1", None)]),
+                PtbArg::Vector(vec![number("2", Some("u32"))]),
+            ]),
+            None
+        )
+        .unwrap(),
+        bcs::to_bytes(&vec![vec![1u32], vec![2u32]]).unwrap()
+    );
+    assert!(pure_bytes(&number("256", Some("u8")), None).is_err());
+    assert!(pure_bytes(
+        &PtbArg::Vector(vec![number("1", Some("u8")), number("2", Some("u16"))]),
+        None
+    )
+    .is_err());
+    assert!(pure_bytes(&PtbArg::Vector(vec![PtbArg::Gas]), None).is_err());
+}
@@ -7,6 +7,7 @@ use crate::keytool::read_authority_keypair_from_file;
 use crate::keytool::read_keypair_from_file;
 
 use super::write_keypair_to_file;
+use super::CommandOutput;
 use super::KeyToolCommand;
 use anyhow::Ok;
 use fastcrypto::encoding::Base64;
@@ -509,3 +510,32 @@ async fn test_sign_command() -> Result<(), anyhow::Error> {
     .await?;
     Ok(())
 }
+
+#[test]
+async fn test_export() -> Result<(), anyhow::Error> {
+    let mut keystore = Keystore::from(InMemKeystore::new_insecure_for_tests(1));
+    let address = keystore.addresses()[0];
+    let expected = keystore.get_key(&address)?.encode_base64();
+
+    let output = KeyToolCommand::Export { address }
+        .execute(&mut keystore)
+        .await?;
+    let CommandOutput::PrivateKeyBase64(exported) = output else {
+        panic!("Expected a private key");
+    };
+    assert_eq!(exported.base64, expected);
+
+    assert!(KeyToolCommand::Export {
+        address: SuiAddress::random_for_testing_only()
+    }
+    .execute(&mut keystore)
+    .await
+    .is_err());
+
+    // Only a keystore file can be encrypted.
+    assert!(KeyToolCommand::Encrypt
+        .execute(&mut keystore)
+        .await
+        .is_err());
+    Ok(())
+}
@@ -0,0 +1,116 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use shared_crypto::intent::{Intent, IntentMessage};
+use sui_types::base_types::{random_object_ref, SuiAddress};
+use sui_types::crypto::{get_key_pair, Signature, SuiKeyPair};
+use sui_types::multisig::MultiSigPublicKey;
+use sui_types::signature::GenericSignature;
+use sui_types::transaction::TransactionData;
+
+use crate::transaction_bundle::TransactionBundle;
+use crate::transaction_file::TransactionFile;
+
+fn new_keys(n: usize) -> Vec<SuiKeyPair> {
+    (0..n)
+        .map(|_| SuiKeyPair::Ed25519(get_key_pair().1))
+        .collect()
+}
+
+fn transfer_from(sender: SuiAddress) -> TransactionData {
+    TransactionData::new_transfer_sui(
+        SuiAddress::random_for_testing_only(),
+        sender,
+        Some(1000),
+        random_object_ref(),
+        10_000_000,
+        1000,
+    )
+}
+
+fn sign(tx_data: &TransactionData, key: &SuiKeyPair) -> Signature {
+    Signature::new_secure(
+        &IntentMessage::new(Intent::sui_transaction(), tx_data.clone()),
+        key,
+    )
+}
+
+#[test]
+fn test_bundle_single_signer() {
+    let key = SuiKeyPair::Ed25519(get_key_pair().1);
+    let sender = SuiAddress::from(&key.public());
+    let tx_data = transfer_from(sender);
+    let mut bundle = TransactionBundle::new(&tx_data, None, None).unwrap();
+    assert_eq!(bundle.unsigned_signers().unwrap(), vec![sender]);
+    assert!(bundle.transaction().is_err());
+
+    // Only the sender signs.
+    let outsider = SuiKeyPair::Ed25519(get_key_pair().1);
+    let err = bundle.add_signature(sign(&tx_data, &outsider)).unwrap_err();
+    assert!(err.to_string().contains("is not a signer"), "{err}");
+
+    bundle.add_signature(sign(&tx_data, &key)).unwrap();
+    assert!(bundle.add_signature(sign(&tx_data, &key)).is_err());
+    assert!(bundle.unsigned_signers().unwrap().is_empty());
+    assert_eq!(bundle.signers().unwrap(), vec![(sender, true)]);
+    let transaction = bundle.transaction().unwrap();
+    assert!(matches!(
+        transaction.data().tx_signatures(),
+        [GenericSignature::Signature(_)]
+    ));
+}
+
+#[test]
+fn test_bundle_multisig_sender() {
+    let keys = new_keys(3);
+    let multisig_pk = MultiSigPublicKey::new(
+        keys.iter().map(|key| key.public()).collect(),
+        vec![1, 1, 1],
+        2,
+    )
+    .unwrap();
+    let sender = SuiAddress::from(&multisig_pk);
+    let tx_data = transfer_from(sender);
+
+    // Without its public key, the keys of a multisig sender cannot sign.
+    let mut bundle = TransactionBundle::new(&tx_data, None, None).unwrap();
+    let err = bundle.add_signature(sign(&tx_data, &keys[0])).unwrap_err();
+    assert!(err.to_string().contains("multisig"), "{err}");
+
+    // The keys of the multisig sign until their weight reaches the threshold.
+    let mut bundle = TransactionBundle::new(&tx_data, None, Some(multisig_pk)).unwrap();
+    let key_addresses = keys
+        .iter()
+        .map(|key| SuiAddress::from(&key.public()))
+        .collect::<Vec<_>>();
+    assert_eq!(bundle.unsigned_signers().unwrap(), key_addresses);
+    bundle.add_signature(sign(&tx_data, &keys[2])).unwrap();
+    assert_eq!(bundle.signers().unwrap(), vec![(sender, false)]);
+    assert!(bundle.transaction().is_err());
+    assert_eq!(
+        bundle.unsigned_signers().unwrap(),
+        key_addresses[..2].to_vec()
+    );
+
+    bundle.add_signature(sign(&tx_data, &keys[0])).unwrap();
+    assert_eq!(bundle.signers().unwrap(), vec![(sender, true)]);
+    assert!(bundle.unsigned_signers().unwrap().is_empty());
+    assert!(bundle.signatures.is_empty());
+    let transaction = bundle.transaction().unwrap();
+    assert!(matches!(
+        transaction.data().tx_signatures(),
+        [GenericSignature::MultiSig(_)]
+    ));
+
+    // The partial signatures are validated when the bundle is read.
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("bundle.json");
+    bundle.create(&path).unwrap();
+    assert_eq!(TransactionBundle::read(&path).unwrap(), bundle);
+
+    let mut tampered = bundle.clone();
+    let multisig = tampered.multisig.as_mut().unwrap();
+    multisig.signatures[0] = sign(&transfer_from(sender), &keys[2]);
+    tampered.write(&path).unwrap();
+    assert!(TransactionBundle::read(&path).is_err());
+}
@@ -7,10 +7,25 @@ use crate::validator_commands::{
 use anyhow::Ok;
 use fastcrypto::encoding::{Base64, Encoding};
 use shared_crypto::intent::{Intent, IntentMessage};
-use sui_types::crypto::SuiKeyPair;
+use sui_keys::keystore::AccountKeystore;
+use sui_types::base_types::ObjectID;
+use sui_types::crypto::{EncodeDecodeBase64, SuiKeyPair};
 use sui_types::transaction::TransactionData;
 use sui_types::{base_types::SuiAddress, crypto::Signature, transaction::Transaction};
-use test_cluster::TestClusterBuilder;
+use test_cluster::{TestCluster, TestClusterBuilder};
+
+/// Make the wallet of `test_cluster` act as its first validator, returning its address.
+fn use_validator_wallet(test_cluster: &mut TestCluster) -> SuiAddress {
+    let keypair: &SuiKeyPair = test_cluster.swarm.config().validator_configs[0]
+        .account_key_pair
+        .keypair();
+    let validator_address = SuiAddress::from(&keypair.public());
+    let keypair = SuiKeyPair::decode_base64(&keypair.encode_base64()).unwrap();
+    let context = test_cluster.wallet_mut();
+    context.config.keystore.add_key(None, keypair).unwrap();
+    context.config.active_address = Some(validator_address);
+    validator_address
+}
 
 #[tokio::test]
 async fn test_print_raw_rgp_txn() -> Result<(), anyhow::Error> {
@@ -65,3 +80,175 @@ async fn test_print_raw_rgp_txn() -> Result<(), anyhow::Error> {
     assert_eq!(summary.next_epoch_gas_price, 42);
     Ok(())
 }
+
+#[tokio::test]
+async fn test_display_staking_pool_and_list_stakes() -> Result<(), anyhow::Error> {
+    let test_cluster = TestClusterBuilder::new().build().await;
+    let validator_address = test_cluster.swarm.config().validator_configs[0].sui_address();
+    let mut context = test_cluster.wallet;
+    let sui_client = context.get_client().await?;
+    let (_, summary) = get_validator_summary(&sui_client, validator_address)
+        .await?
+        .unwrap();
+
+    let response = SuiValidatorCommand::DisplayStakingPool {
+        validator_address: Some(validator_address),
+        epochs: None,
+    }
+    .execute(&mut context)
+    .await?;
+    let SuiValidatorCommandResponse::DisplayStakingPool(pool) = response else {
+        panic!("Expected DisplayStakingPool");
+    };
+    assert_eq!(pool.staking_pool_id, summary.staking_pool_id);
+    assert_eq!(pool.sui_balance, summary.staking_pool_sui_balance);
+    // The pool is activated at genesis, with a rate recorded for epoch 0.
+    assert_eq!(
+        pool.exchange_rates.first().map(|(epoch, _)| *epoch),
+        Some(0)
+    );
+
+    // The genesis stake of the validator is owned by the validator itself.
+    let response = SuiValidatorCommand::ListStakes {
+        address: Some(validator_address),
+    }
+    .execute(&mut context)
+    .await?;
+    let SuiValidatorCommandResponse::ListStakes(stakes) = response else {
+        panic!("Expected ListStakes");
+    };
+    assert!(stakes
+        .iter()
+        .any(|stake| stake.staking_pool == summary.staking_pool_id));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_display_staking_pool_exchange_rates() -> Result<(), anyhow::Error> {
+    let test_cluster = TestClusterBuilder::new().build().await;
+    let validator_address = test_cluster.swarm.config().validator_configs[0].sui_address();
+    test_cluster.trigger_reconfiguration().await;
+    test_cluster.trigger_reconfiguration().await;
+    let mut context = test_cluster.wallet;
+
+    // A rate is recorded for each epoch since genesis, the most recent ones are displayed.
+    for (epochs, expected) in [
+        (None, vec![0, 1, 2]),
+        (Some(2), vec![1, 2]),
+        (Some(0), vec![]),
+    ] {
+        let response = SuiValidatorCommand::DisplayStakingPool {
+            validator_address: Some(validator_address),
+            epochs,
+        }
+        .execute(&mut context)
+        .await?;
+        let SuiValidatorCommandResponse::DisplayStakingPool(pool) = response else {
+            panic!("Expected DisplayStakingPool");
+        };
+        let displayed = pool
+            .exchange_rates
+            .iter()
+            .map(|(epoch, _)| *epoch)
+            .collect::<Vec<_>>();
+        assert_eq!(displayed, expected);
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_update_commission_rate() -> Result<(), anyhow::Error> {
+    let mut test_cluster = TestClusterBuilder::new().build().await;
+    let validator_address = use_validator_wallet(&mut test_cluster);
+    let mut context = test_cluster.wallet;
+    let sui_client = context.get_client().await?;
+
+    // The rate is at most 20%.
+    let err = SuiValidatorCommand::UpdateCommissionRate {
+        commission_rate: 2001,
+        gas_budget: None,
+    }
+    .execute(&mut context)
+    .await
+    .err()
+    .unwrap();
+    assert!(err.to_string().contains("above the maximum"), "{err}");
+
+    let response = SuiValidatorCommand::UpdateCommissionRate {
+        commission_rate: 2000,
+        gas_budget: None,
+    }
+    .execute(&mut context)
+    .await?;
+    let SuiValidatorCommandResponse::UpdateCommissionRate(response) = response else {
+        panic!("Expected UpdateCommissionRate");
+    };
+    assert_eq!(response.status_ok(), Some(true));
+
+    // The new rate applies from the next epoch.
+    let (_, summary) = get_validator_summary(&sui_client, validator_address)
+        .await?
+        .unwrap();
+    assert_eq!(summary.next_epoch_commission_rate, 2000);
+    assert_ne!(summary.commission_rate, 2000);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_withdraw_stake() -> Result<(), anyhow::Error> {
+    let mut test_cluster = TestClusterBuilder::new().build().await;
+    let validator_address = use_validator_wallet(&mut test_cluster);
+    let mut context = test_cluster.wallet;
+    let sui_client = context.get_client().await?;
+
+    // Stakes must exist and be owned by the sender.
+    let err = SuiValidatorCommand::WithdrawStake {
+        staked_sui_ids: vec![ObjectID::random()],
+        all: false,
+        gas_budget: None,
+    }
+    .execute(&mut context)
+    .await
+    .err()
+    .unwrap();
+    assert!(err.to_string().contains("does not exist"), "{err}");
+
+    // Withdraw the genesis stake of the validator.
+    let stakes = sui_client
+        .governance_api()
+        .get_stakes(validator_address)
+        .await?;
+    assert!(!stakes.is_empty());
+    let response = SuiValidatorCommand::WithdrawStake {
+        staked_sui_ids: vec![],
+        all: true,
+        gas_budget: None,
+    }
+    .execute(&mut context)
+    .await?;
+    let SuiValidatorCommandResponse::WithdrawStake(response) = response else {
+        panic!("Expected WithdrawStake");
+    };
+    assert_eq!(response.status_ok(), Some(true));
+    assert!(sui_client
+        .governance_api()
+        .get_stakes(validator_address)
+        .await?
+        .is_empty());
+
+    // Nothing is left to withdraw.
+    let err = SuiValidatorCommand::WithdrawStake {
+        staked_sui_ids: vec![],
+        all: true,
+        gas_budget: None,
+    }
+    .execute(&mut context)
+    .await
+    .err()
+    .unwrap();
+    assert!(
+        err.to_string().contains("does not own any StakedSui"),
+        "{err}"
+    );
+    Ok(())
+}
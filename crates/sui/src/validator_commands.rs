@@ -3,6 +3,7 @@
 
 use anyhow::{anyhow, bail, Result};
 use move_core_types::ident_str;
+use move_core_types::language_storage::TypeTag;
 use std::{
     collections::{BTreeMap, HashSet},
     fmt::{self, Debug, Display, Formatter, Write},
@@ -12,13 +13,16 @@ use std::{
 use sui_genesis_builder::validator_info::GenesisValidatorInfo;
 
 use sui_types::{
-    base_types::{ObjectID, ObjectRef, SuiAddress},
+    base_types::{EpochId, ObjectID, ObjectRef, SuiAddress},
     crypto::{AuthorityPublicKey, NetworkPublicKey, Signable, DEFAULT_EPOCH_ID},
+    dynamic_field::{derive_dynamic_field_id, Field},
     multiaddr::Multiaddr,
     object::Owner,
+    programmable_transaction_builder::ProgrammableTransactionBuilder,
     sui_system_state::{
         sui_system_state_inner_v1::{UnverifiedValidatorOperationCapV1, ValidatorV1},
         sui_system_state_summary::{SuiSystemStateSummary, SuiValidatorSummary},
+        PoolTokenExchangeRate,
     },
     SUI_SYSTEM_PACKAGE_ID,
 };
@@ -35,7 +39,8 @@ use fastcrypto::{
 use serde::Serialize;
 use shared_crypto::intent::{Intent, IntentMessage, IntentScope};
 use sui_json_rpc_types::{
-    SuiObjectDataOptions, SuiTransactionBlockResponse, SuiTransactionBlockResponseOptions,
+    DelegatedStake, StakeStatus, SuiObjectDataOptions, SuiTransactionBlockResponse,
+    SuiTransactionBlockResponseOptions,
 };
 use sui_keys::keystore::AccountKeystore;
 use sui_keys::{
@@ -51,13 +56,18 @@ use sui_types::crypto::{
     generate_proof_of_possession, get_authority_key_pair, AuthorityPublicKeyBytes,
 };
 use sui_types::crypto::{AuthorityKeyPair, NetworkKeyPair, SignatureScheme, SuiKeyPair};
-use sui_types::transaction::{CallArg, ObjectArg, Transaction, TransactionData};
+use sui_types::transaction::{
+    CallArg, ObjectArg, Transaction, TransactionData, TransactionDataAPI,
+};
 
 #[path = "unit_tests/validator_tests.rs"]
 #[cfg(test)]
 mod validator_tests;
 
 const DEFAULT_GAS_BUDGET: u64 = 200_000_000; // 0.2 SUI
+const MAX_COMMISSION_RATE: u64 = 2_000; // 20%, see `validator.move`
+const DEFAULT_EXCHANGE_RATE_EPOCHS: usize = 10;
+const MULTI_GET_OBJECT_LIMIT: usize = 50;
 
 #[derive(Parser)]
 #[clap(rename_all = "kebab-case")]
@@ -119,6 +129,44 @@ pub enum SuiValidatorCommand {
         #[clap(name = "gas-budget", long)]
         gas_budget: Option<u64>,
     },
+    /// Update commission rate of the active validator. Effectuate from next epoch.
+    #[clap(name = "update-commission-rate")]
+    UpdateCommissionRate {
+        /// Commission rate in basis points, e.g. 200 for 2%. At most 2000.
+        #[clap(name = "commission-rate")]
+        commission_rate: u64,
+        /// Gas budget for this transaction.
+        #[clap(name = "gas-budget", long)]
+        gas_budget: Option<u64>,
+    },
+    /// Display the staking pool of a validator: its balances, pending stake and withdrawals,
+    /// and the exchange rates of the most recent epochs.
+    #[clap(name = "display-staking-pool")]
+    DisplayStakingPool {
+        #[clap(name = "validator-address")]
+        validator_address: Option<SuiAddress>,
+        /// Number of most recent epochs to display the exchange rate of.
+        #[clap(name = "epochs", long)]
+        epochs: Option<usize>,
+    },
+    /// List the StakedSui objects owned by an address, e.g. the staking rewards of a validator.
+    #[clap(name = "list-stakes")]
+    ListStakes {
+        #[clap(name = "address")]
+        address: Option<SuiAddress>,
+    },
+    /// Withdraw StakedSui objects owned by the sender, with their rewards.
+    #[clap(name = "withdraw-stake")]
+    WithdrawStake {
+        #[clap(name = "staked-sui-id", num_args(1..), required_unless_present = "all")]
+        staked_sui_ids: Vec<ObjectID>,
+        /// Withdraw all the StakedSui objects owned by the sender.
+        #[clap(name = "all", long, conflicts_with = "staked-sui-id")]
+        all: bool,
+        /// Gas budget for this transaction.
+        #[clap(name = "gas-budget", long)]
+        gas_budget: Option<u64>,
+    },
     /// Report or un-report a validator.
     #[clap(name = "report-validator")]
     ReportValidator {
@@ -175,6 +223,10 @@ pub enum SuiValidatorCommandResponse {
     LeaveCommittee(SuiTransactionBlockResponse),
     UpdateMetadata(SuiTransactionBlockResponse),
     UpdateGasPrice(SuiTransactionBlockResponse),
+    UpdateCommissionRate(SuiTransactionBlockResponse),
+    DisplayStakingPool(StakingPoolSummary),
+    ListStakes(Vec<DelegatedStake>),
+    WithdrawStake(SuiTransactionBlockResponse),
     ReportValidator(SuiTransactionBlockResponse),
     SerializedPayload(String),
     DisplayGasPriceUpdateRawTxn {
@@ -391,6 +443,72 @@ impl SuiValidatorCommand {
                 SuiValidatorCommandResponse::UpdateGasPrice(resp)
             }
 
+            SuiValidatorCommand::UpdateCommissionRate {
+                commission_rate,
+                gas_budget,
+            } => {
+                if commission_rate > MAX_COMMISSION_RATE {
+                    bail!(
+                        "Commission rate {commission_rate} is above the maximum of {MAX_COMMISSION_RATE}."
+                    );
+                }
+                // Only an active validator can request a new commission rate.
+                let _status =
+                    check_status(context, HashSet::from([ValidatorStatus::Active])).await?;
+                let gas_budget = gas_budget.unwrap_or(DEFAULT_GAS_BUDGET);
+                let args = vec![CallArg::Pure(bcs::to_bytes(&commission_rate).unwrap())];
+                let resp =
+                    call_0x5(context, "request_set_commission_rate", args, gas_budget).await?;
+                SuiValidatorCommandResponse::UpdateCommissionRate(resp)
+            }
+
+            SuiValidatorCommand::DisplayStakingPool {
+                validator_address,
+                epochs,
+            } => {
+                let validator_address = validator_address.unwrap_or(context.active_address()?);
+                let sui_client = context.get_client().await?;
+                let (status, summary) = get_validator_summary(&sui_client, validator_address)
+                    .await?
+                    .ok_or_else(|| {
+                        anyhow!("{validator_address} is not an active or pending Validator.")
+                    })?;
+                let current_epoch = sui_client
+                    .governance_api()
+                    .get_latest_sui_system_state()
+                    .await?
+                    .epoch;
+                let exchange_rates = get_exchange_rates(
+                    &sui_client,
+                    &summary,
+                    current_epoch,
+                    epochs.unwrap_or(DEFAULT_EXCHANGE_RATE_EPOCHS),
+                )
+                .await?;
+                SuiValidatorCommandResponse::DisplayStakingPool(StakingPoolSummary::new(
+                    status,
+                    summary,
+                    exchange_rates,
+                ))
+            }
+
+            SuiValidatorCommand::ListStakes { address } => {
+                let address = address.unwrap_or(context.active_address()?);
+                let sui_client = context.get_client().await?;
+                let stakes = sui_client.governance_api().get_stakes(address).await?;
+                SuiValidatorCommandResponse::ListStakes(stakes)
+            }
+
+            SuiValidatorCommand::WithdrawStake {
+                staked_sui_ids,
+                all,
+                gas_budget,
+            } => {
+                let gas_budget = gas_budget.unwrap_or(DEFAULT_GAS_BUDGET);
+                let resp = withdraw_stakes(context, staked_sui_ids, all, gas_budget).await?;
+                SuiValidatorCommandResponse::WithdrawStake(resp)
+            }
+
             SuiValidatorCommand::ReportValidator {
                 operation_cap_id,
                 reportee_address,
@@ -600,27 +718,44 @@ async fn construct_unsigned_0x5_txn(
     function: &'static str,
     call_args: Vec<CallArg>,
     gas_budget: u64,
+) -> anyhow::Result<TransactionData> {
+    construct_unsigned_0x5_ptb(context, sender, vec![(function, call_args)], gas_budget).await
+}
+
+/// Construct a transaction making several calls to `sui_system`, each with the system state
+/// object as first argument.
+async fn construct_unsigned_0x5_ptb(
+    context: &mut WalletContext,
+    sender: SuiAddress,
+    calls: Vec<(&'static str, Vec<CallArg>)>,
+    gas_budget: u64,
 ) -> anyhow::Result<TransactionData> {
     let sui_client = context.get_client().await?;
-    let mut args = vec![CallArg::SUI_SYSTEM_MUT];
-    args.extend(call_args);
+    let mut builder = ProgrammableTransactionBuilder::new();
+    for (function, call_args) in calls {
+        let mut args = vec![CallArg::SUI_SYSTEM_MUT];
+        args.extend(call_args);
+        builder.move_call(
+            SUI_SYSTEM_PACKAGE_ID,
+            ident_str!("sui_system").to_owned(),
+            ident_str!(function).to_owned(),
+            vec![],
+            args,
+        )?;
+    }
     let rgp = sui_client
         .governance_api()
         .get_reference_gas_price()
         .await?;
 
     let gas_obj_ref = get_gas_obj_ref(sender, &sui_client, gas_budget).await?;
-    TransactionData::new_move_call(
+    Ok(TransactionData::new_programmable(
         sender,
-        SUI_SYSTEM_PACKAGE_ID,
-        ident_str!("sui_system").to_owned(),
-        ident_str!(function).to_owned(),
-        vec![],
-        gas_obj_ref,
-        args,
+        vec![gas_obj_ref],
+        builder.finish(),
         gas_budget,
         rgp,
-    )
+    ))
 }
 
 async fn call_0x5(
@@ -632,11 +767,18 @@ async fn call_0x5(
     let sender = context.active_address()?;
     let tx_data =
         construct_unsigned_0x5_txn(context, sender, function, call_args, gas_budget).await?;
-    let signature =
-        context
-            .config
-            .keystore
-            .sign_secure(&sender, &tx_data, Intent::sui_transaction())?;
+    execute_0x5_txn(context, tx_data).await
+}
+
+async fn execute_0x5_txn(
+    context: &mut WalletContext,
+    tx_data: TransactionData,
+) -> anyhow::Result<SuiTransactionBlockResponse> {
+    let signature = context.config.keystore.sign_secure(
+        &tx_data.sender(),
+        &tx_data,
+        Intent::sui_transaction(),
+    )?;
     let transaction = Transaction::from_data(tx_data, vec![signature]);
     let sui_client = context.get_client().await?;
     sui_client
@@ -652,6 +794,232 @@ async fn call_0x5(
         .map_err(|err| anyhow::anyhow!(err.to_string()))
 }
 
+async fn withdraw_stakes(
+    context: &mut WalletContext,
+    staked_sui_ids: Vec<ObjectID>,
+    all: bool,
+    gas_budget: u64,
+) -> anyhow::Result<SuiTransactionBlockResponse> {
+    let sender = context.active_address()?;
+    let sui_client = context.get_client().await?;
+    let staked_sui_ids = if all {
+        sui_client
+            .governance_api()
+            .get_stakes(sender)
+            .await?
+            .into_iter()
+            .flat_map(|delegated_stake| delegated_stake.stakes)
+            .map(|stake| stake.staked_sui_id)
+            .collect()
+    } else {
+        staked_sui_ids
+    };
+    if staked_sui_ids.is_empty() {
+        bail!("{sender} does not own any StakedSui to withdraw.");
+    }
+
+    let mut calls = vec![];
+    for chunk in staked_sui_ids.chunks(MULTI_GET_OBJECT_LIMIT) {
+        let resps = sui_client
+            .read_api()
+            .multi_get_object_with_options(
+                chunk.to_vec(),
+                SuiObjectDataOptions::default().with_owner(),
+            )
+            .await?;
+        for (staked_sui_id, resp) in chunk.iter().zip(resps) {
+            let obj_ref = resp
+                .object_ref_if_exists()
+                .ok_or_else(|| anyhow!("StakedSui {staked_sui_id} does not exist"))?;
+            let owner = resp.owner();
+            if owner != Some(Owner::AddressOwner(sender)) {
+                bail!("StakedSui {staked_sui_id} is not owned by {sender} but {owner:?}");
+            }
+            calls.push((
+                "request_withdraw_stake",
+                vec![CallArg::Object(ObjectArg::ImmOrOwnedObject(obj_ref))],
+            ));
+        }
+    }
+    let tx_data = construct_unsigned_0x5_ptb(context, sender, calls, gas_budget).await?;
+    execute_0x5_txn(context, tx_data).await
+}
+
+/// Read the exchange rates of the last `epochs` epochs up to `current_epoch` from the
+/// `exchange_rates` table of the staking pool of a validator, in epoch order. The table fields
+/// are read directly, their IDs derive from the table ID and the epoch. Epochs without a rate,
+/// e.g. before the pool was activated, are skipped.
+async fn get_exchange_rates(
+    sui_client: &SuiClient,
+    summary: &SuiValidatorSummary,
+    current_epoch: EpochId,
+    epochs: usize,
+) -> anyhow::Result<Vec<(EpochId, PoolTokenExchangeRate)>> {
+    let Some(activation_epoch) = summary.staking_pool_activation_epoch else {
+        return Ok(vec![]);
+    };
+    let first_epoch = (current_epoch + 1)
+        .saturating_sub(epochs as u64)
+        .max(activation_epoch);
+    let fields = (first_epoch..=current_epoch)
+        .map(|epoch| {
+            derive_dynamic_field_id(
+                summary.exchange_rates_id,
+                &TypeTag::U64,
+                &bcs::to_bytes(&epoch)?,
+            )
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut exchange_rates = vec![];
+    for chunk in fields.chunks(MULTI_GET_OBJECT_LIMIT) {
+        let resps = sui_client
+            .read_api()
+            .multi_get_object_with_options(
+                chunk.to_vec(),
+                SuiObjectDataOptions::default().with_bcs(),
+            )
+            .await?;
+        for (field_id, resp) in chunk.iter().zip(resps) {
+            if resp.data.is_none() {
+                continue;
+            }
+            let bcs = resp
+                .move_object_bcs()
+                .ok_or_else(|| anyhow!("Object {} does not return bcs bytes", field_id))?;
+            let field =
+                bcs::from_bytes::<Field<EpochId, PoolTokenExchangeRate>>(bcs).map_err(|e| {
+                    anyhow!(
+                        "Can't convert bcs bytes of object {} to PoolTokenExchangeRate: {}",
+                        field_id,
+                        e,
+                    )
+                })?;
+            exchange_rates.push((field.name, field.value));
+        }
+    }
+    Ok(exchange_rates)
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StakingPoolSummary {
+    pub validator_address: SuiAddress,
+    pub status: ValidatorStatus,
+    pub staking_pool_id: ObjectID,
+    pub activation_epoch: Option<EpochId>,
+    pub sui_balance: u64,
+    pub rewards_pool: u64,
+    pub pool_token_balance: u64,
+    pub pending_stake: u64,
+    pub pending_total_sui_withdraw: u64,
+    pub pending_pool_token_withdraw: u64,
+    pub commission_rate: u64,
+    pub next_epoch_commission_rate: u64,
+    pub next_epoch_stake: u64,
+    pub exchange_rates: Vec<(EpochId, PoolTokenExchangeRate)>,
+}
+
+impl StakingPoolSummary {
+    fn new(
+        status: ValidatorStatus,
+        summary: SuiValidatorSummary,
+        exchange_rates: Vec<(EpochId, PoolTokenExchangeRate)>,
+    ) -> Self {
+        Self {
+            validator_address: summary.sui_address,
+            status,
+            staking_pool_id: summary.staking_pool_id,
+            activation_epoch: summary.staking_pool_activation_epoch,
+            sui_balance: summary.staking_pool_sui_balance,
+            rewards_pool: summary.rewards_pool,
+            pool_token_balance: summary.pool_token_balance,
+            pending_stake: summary.pending_stake,
+            pending_total_sui_withdraw: summary.pending_total_sui_withdraw,
+            pending_pool_token_withdraw: summary.pending_pool_token_withdraw,
+            commission_rate: summary.commission_rate,
+            next_epoch_commission_rate: summary.next_epoch_commission_rate,
+            next_epoch_stake: summary.next_epoch_stake,
+            exchange_rates,
+        }
+    }
+}
+
+impl Display for StakingPoolSummary {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "----- Staking Pool of {} ({:?}) ----",
+            self.validator_address, self.status
+        )?;
+        writeln!(f, "Staking pool ID: {}", self.staking_pool_id)?;
+        if let Some(epoch) = self.activation_epoch {
+            writeln!(f, "Activation epoch: {epoch}")?;
+        }
+        writeln!(f, "SUI balance: {}", self.sui_balance)?;
+        writeln!(f, "Rewards pool: {}", self.rewards_pool)?;
+        writeln!(f, "Pool token balance: {}", self.pool_token_balance)?;
+        writeln!(f, "Pending stake: {}", self.pending_stake)?;
+        writeln!(
+            f,
+            "Pending withdraw: {} SUI, {} pool tokens",
+            self.pending_total_sui_withdraw, self.pending_pool_token_withdraw
+        )?;
+        writeln!(
+            f,
+            "Commission rate: {} bps, {} bps from next epoch",
+            self.commission_rate, self.next_epoch_commission_rate
+        )?;
+        writeln!(f, "Next epoch stake: {}", self.next_epoch_stake)?;
+        writeln!(f, "\n----- Exchange Rates ----")?;
+        writeln!(
+            f,
+            "{:>8} {:>20} {:>20} {:>12}",
+            "epoch", "SUI amount", "pool token amount", "rate"
+        )?;
+        for (epoch, rate) in &self.exchange_rates {
+            writeln!(
+                f,
+                "{:>8} {:>20} {:>20} {:>12.8}",
+                epoch,
+                rate.sui_amount(),
+                rate.pool_token_amount(),
+                rate.rate()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn write_stakes(stakes: &[DelegatedStake]) -> Result<String, fmt::Error> {
+    let mut writer = String::new();
+    if stakes.is_empty() {
+        writeln!(writer, "No stakes found.")?;
+    }
+    for delegated_stake in stakes {
+        writeln!(
+            writer,
+            "----- Stakes with {} (staking pool {}) ----",
+            delegated_stake.validator_address, delegated_stake.staking_pool
+        )?;
+        for stake in &delegated_stake.stakes {
+            let status = match &stake.status {
+                StakeStatus::Pending => "pending".to_string(),
+                StakeStatus::Active { estimated_reward } => {
+                    format!("active, estimated reward {estimated_reward}")
+                }
+                StakeStatus::Unstaked => "unstaked".to_string(),
+            };
+            writeln!(
+                writer,
+                "{}: principal {}, from epoch {} ({})",
+                stake.staked_sui_id, stake.principal, stake.stake_active_epoch, status
+            )?;
+        }
+    }
+    Ok(writer)
+}
+
 impl Display for SuiValidatorCommandResponse {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         let mut writer = String::new();
@@ -673,6 +1041,18 @@ impl Display for SuiValidatorCommandResponse {
             SuiValidatorCommandResponse::UpdateGasPrice(response) => {
                 write!(writer, "{}", write_transaction_response(response)?)?;
             }
+            SuiValidatorCommandResponse::UpdateCommissionRate(response) => {
+                write!(writer, "{}", write_transaction_response(response)?)?;
+            }
+            SuiValidatorCommandResponse::DisplayStakingPool(summary) => {
+                write!(writer, "{}", summary)?;
+            }
+            SuiValidatorCommandResponse::ListStakes(stakes) => {
+                write!(writer, "{}", write_stakes(stakes)?)?;
+            }
+            SuiValidatorCommandResponse::WithdrawStake(response) => {
+                write!(writer, "{}", write_transaction_response(response)?)?;
+            }
             SuiValidatorCommandResponse::ReportValidator(response) => {
                 write!(writer, "{}", write_transaction_response(response)?)?;
             }
@@ -747,7 +1127,7 @@ impl SuiValidatorCommandResponse {
     }
 }
 
-#[derive(Debug, Hash, PartialEq, Eq)]
+#[derive(Debug, Hash, PartialEq, Eq, Serialize)]
 pub enum ValidatorStatus {
     Active,
     Pending,
@@ -8,6 +8,7 @@ use std::str::FromStr;
 use std::{fmt::Write, fs::read_dir, path::PathBuf, str, thread, time::Duration};
 
 use expect_test::expect;
+use fastcrypto::encoding::{Base64, Encoding};
 use move_package::BuildConfig as MoveBuildConfig;
 use serde_json::json;
 use sui_test_transaction_builder::batch_make_transfer_transactions;
@@ -21,7 +22,8 @@ use tokio::time::sleep;
 
 use sui::client_commands::SwitchResponse;
 use sui::{
-    client_commands::{SuiClientCommandResult, SuiClientCommands},
+    client_commands::{SimulationArgs, SuiClientCommandResult, SuiClientCommands},
+    keytool::{CommandOutput, KeyToolCommand},
     sui_commands::SuiCommand,
 };
 use sui_config::{
@@ -30,8 +32,9 @@ use sui_config::{
 };
 use sui_json::SuiJsonValue;
 use sui_json_rpc_types::{
-    OwnedObjectRef, SuiObjectData, SuiObjectDataFilter, SuiObjectDataOptions, SuiObjectResponse,
-    SuiObjectResponseQuery, SuiTransactionBlockEffects, SuiTransactionBlockEffectsAPI,
+    OwnedObjectRef, SuiObjectData, SuiObjectDataFilter, SuiObjectDataOptions, SuiObjectRef,
+    SuiObjectResponse, SuiObjectResponseQuery, SuiTransactionBlockEffects,
+    SuiTransactionBlockEffectsAPI,
 };
 use sui_keys::keystore::AccountKeystore;
 use sui_macros::sim_test;
@@ -333,6 +336,7 @@ async fn test_gas_command() -> Result<(), anyhow::Error> {
         gas_budget: rgp * TEST_ONLY_GAS_UNIT_FOR_TRANSFER,
         serialize_unsigned_transaction: false,
         serialize_signed_transaction: false,
+        simulation: SimulationArgs::default(),
     }
     .execute(context)
     .await?;
@@ -384,6 +388,7 @@ async fn test_move_call_args_linter_command() -> Result<(), anyhow::Error> {
         with_unpublished_dependencies: false,
         serialize_unsigned_transaction: false,
         serialize_signed_transaction: false,
+        simulation: SimulationArgs::default(),
     }
     .execute(context)
     .await?;
@@ -465,6 +470,7 @@ async fn test_move_call_args_linter_command() -> Result<(), anyhow::Error> {
         gas_budget: TEST_ONLY_GAS_UNIT_FOR_OBJECT_BASICS * rgp,
         serialize_unsigned_transaction: false,
         serialize_signed_transaction: false,
+        simulation: SimulationArgs::default(),
     }
     .execute(context)
     .await?;
@@ -504,6 +510,7 @@ async fn test_move_call_args_linter_command() -> Result<(), anyhow::Error> {
         gas_budget: TEST_ONLY_GAS_UNIT_FOR_OBJECT_BASICS * rgp,
         serialize_unsigned_transaction: false,
         serialize_signed_transaction: false,
+        simulation: SimulationArgs::default(),
     }
     .execute(context)
     .await;
@@ -530,6 +537,7 @@ async fn test_move_call_args_linter_command() -> Result<(), anyhow::Error> {
         gas_budget: TEST_ONLY_GAS_UNIT_FOR_OBJECT_BASICS * rgp,
         serialize_unsigned_transaction: false,
         serialize_signed_transaction: false,
+        simulation: SimulationArgs::default(),
     }
     .execute(context)
     .await;
@@ -558,6 +566,7 @@ async fn test_move_call_args_linter_command() -> Result<(), anyhow::Error> {
         gas_budget: rgp * TEST_ONLY_GAS_UNIT_FOR_OBJECT_BASICS,
         serialize_unsigned_transaction: false,
         serialize_signed_transaction: false,
+        simulation: SimulationArgs::default(),
     }
     .execute(context)
     .await?;
@@ -605,6 +614,7 @@ async fn test_package_publish_command() -> Result<(), anyhow::Error> {
         with_unpublished_dependencies: false,
         serialize_unsigned_transaction: false,
         serialize_signed_transaction: false,
+        simulation: SimulationArgs::default(),
     }
     .execute(context)
     .await?;
@@ -672,6 +682,7 @@ async fn test_delete_shared_object() -> Result<(), anyhow::Error> {
         with_unpublished_dependencies: false,
         serialize_unsigned_transaction: false,
         serialize_signed_transaction: false,
+        simulation: SimulationArgs::default(),
     }
     .execute(context)
     .await?;
@@ -705,6 +716,7 @@ async fn test_delete_shared_object() -> Result<(), anyhow::Error> {
         args: vec![],
         serialize_unsigned_transaction: false,
         serialize_signed_transaction: false,
+        simulation: SimulationArgs::default(),
     }
     .execute(context)
     .await?;
@@ -727,6 +739,7 @@ async fn test_delete_shared_object() -> Result<(), anyhow::Error> {
         args: vec![SuiJsonValue::from_str(&shared_id.to_string()).unwrap()],
         serialize_unsigned_transaction: false,
         serialize_signed_transaction: false,
+        simulation: SimulationArgs::default(),
     }
     .execute(context)
     .await?;
@@ -779,6 +792,7 @@ async fn test_receive_argument() -> Result<(), anyhow::Error> {
         with_unpublished_dependencies: false,
         serialize_unsigned_transaction: false,
         serialize_signed_transaction: false,
+        simulation: SimulationArgs::default(),
     }
     .execute(context)
     .await?;
@@ -812,6 +826,7 @@ async fn test_receive_argument() -> Result<(), anyhow::Error> {
         args: vec![],
         serialize_unsigned_transaction: false,
         serialize_signed_transaction: false,
+        simulation: SimulationArgs::default(),
     }
     .execute(context)
     .await?;
@@ -853,6 +868,7 @@ async fn test_receive_argument() -> Result<(), anyhow::Error> {
         ],
         serialize_unsigned_transaction: false,
         serialize_signed_transaction: false,
+        simulation: SimulationArgs::default(),
     }
     .execute(context)
     .await?;
@@ -905,6 +921,7 @@ async fn test_receive_argument_by_immut_ref() -> Result<(), anyhow::Error> {
         with_unpublished_dependencies: false,
         serialize_unsigned_transaction: false,
         serialize_signed_transaction: false,
+        simulation: SimulationArgs::default(),
     }
     .execute(context)
     .await?;
@@ -938,6 +955,7 @@ async fn test_receive_argument_by_immut_ref() -> Result<(), anyhow::Error> {
         args: vec![],
         serialize_unsigned_transaction: false,
         serialize_signed_transaction: false,
+        simulation: SimulationArgs::default(),
     }
     .execute(context)
     .await?;
@@ -979,6 +997,7 @@ async fn test_receive_argument_by_immut_ref() -> Result<(), anyhow::Error> {
         ],
         serialize_unsigned_transaction: false,
         serialize_signed_transaction: false,
+        simulation: SimulationArgs::default(),
     }
     .execute(context)
     .await?;
@@ -1031,6 +1050,7 @@ async fn test_receive_argument_by_mut_ref() -> Result<(), anyhow::Error> {
         with_unpublished_dependencies: false,
         serialize_unsigned_transaction: false,
         serialize_signed_transaction: false,
+        simulation: SimulationArgs::default(),
     }
     .execute(context)
     .await?;
@@ -1064,6 +1084,7 @@ async fn test_receive_argument_by_mut_ref() -> Result<(), anyhow::Error> {
         args: vec![],
         serialize_unsigned_transaction: false,
         serialize_signed_transaction: false,
+        simulation: SimulationArgs::default(),
     }
     .execute(context)
     .await?;
@@ -1105,6 +1126,7 @@ async fn test_receive_argument_by_mut_ref() -> Result<(), anyhow::Error> {
         ],
         serialize_unsigned_transaction: false,
         serialize_signed_transaction: false,
+        simulation: SimulationArgs::default(),
     }
     .execute(context)
     .await?;
@@ -1159,6 +1181,7 @@ async fn test_package_publish_command_with_unpublished_dependency_succeeds(
         with_unpublished_dependencies,
         serialize_unsigned_transaction: false,
         serialize_signed_transaction: false,
+        simulation: SimulationArgs::default(),
     }
     .execute(context)
     .await?;
@@ -1227,6 +1250,7 @@ async fn test_package_publish_command_with_unpublished_dependency_fails(
         with_unpublished_dependencies,
         serialize_unsigned_transaction: false,
         serialize_signed_transaction: false,
+        simulation: SimulationArgs::default(),
     }
     .execute(context)
     .await;
@@ -1273,6 +1297,7 @@ async fn test_package_publish_command_non_zero_unpublished_dep_fails() -> Result
         with_unpublished_dependencies,
         serialize_unsigned_transaction: false,
         serialize_signed_transaction: false,
+        simulation: SimulationArgs::default(),
     }
     .execute(context)
     .await;
@@ -1328,6 +1353,7 @@ async fn test_package_publish_command_failure_invalid() -> Result<(), anyhow::Er
         with_unpublished_dependencies,
         serialize_unsigned_transaction: false,
         serialize_signed_transaction: false,
+        simulation: SimulationArgs::default(),
     }
     .execute(context)
     .await;
@@ -1370,6 +1396,7 @@ async fn test_package_publish_nonexistent_dependency() -> Result<(), anyhow::Err
         with_unpublished_dependencies: false,
         serialize_unsigned_transaction: false,
         serialize_signed_transaction: false,
+        simulation: SimulationArgs::default(),
     }
     .execute(context)
     .await;
@@ -1413,6 +1440,7 @@ async fn test_package_publish_test_flag() -> Result<(), anyhow::Error> {
         with_unpublished_dependencies: false,
         serialize_unsigned_transaction: false,
         serialize_signed_transaction: false,
+        simulation: SimulationArgs::default(),
     }
     .execute(context)
     .await;
@@ -1468,6 +1496,7 @@ async fn test_package_upgrade_command() -> Result<(), anyhow::Error> {
         with_unpublished_dependencies: false,
         serialize_unsigned_transaction: false,
         serialize_signed_transaction: false,
+        simulation: SimulationArgs::default(),
     }
     .execute(context)
     .await?;
@@ -1540,6 +1569,7 @@ async fn test_package_upgrade_command() -> Result<(), anyhow::Error> {
         with_unpublished_dependencies: false,
         serialize_unsigned_transaction: false,
         serialize_signed_transaction: false,
+        simulation: SimulationArgs::default(),
     }
     .execute(context)
     .await?;
@@ -1602,6 +1632,7 @@ async fn test_native_transfer() -> Result<(), anyhow::Error> {
         gas_budget: rgp * TEST_ONLY_GAS_UNIT_FOR_TRANSFER,
         serialize_unsigned_transaction: false,
         serialize_signed_transaction: false,
+        simulation: SimulationArgs::default(),
     }
     .execute(context)
     .await?;
@@ -1707,6 +1738,7 @@ async fn test_native_transfer() -> Result<(), anyhow::Error> {
         gas_budget: rgp * TEST_ONLY_GAS_UNIT_FOR_TRANSFER,
         serialize_unsigned_transaction: false,
         serialize_signed_transaction: false,
+        simulation: SimulationArgs::default(),
     }
     .execute(context)
     .await?;
@@ -1998,6 +2030,7 @@ async fn test_merge_coin() -> Result<(), anyhow::Error> {
         gas_budget: rgp * TEST_ONLY_GAS_UNIT_FOR_GENERIC,
         serialize_unsigned_transaction: false,
         serialize_signed_transaction: false,
+        simulation: SimulationArgs::default(),
     }
     .execute(context)
     .await?;
@@ -2053,6 +2086,7 @@ async fn test_merge_coin() -> Result<(), anyhow::Error> {
         gas_budget: rgp * TEST_ONLY_GAS_UNIT_FOR_GENERIC,
         serialize_unsigned_transaction: false,
         serialize_signed_transaction: false,
+        simulation: SimulationArgs::default(),
     }
     .execute(context)
     .await?;
@@ -2119,6 +2153,7 @@ async fn test_split_coin() -> Result<(), anyhow::Error> {
         count: None,
         serialize_unsigned_transaction: false,
         serialize_signed_transaction: false,
+        simulation: SimulationArgs::default(),
     }
     .execute(context)
     .await?;
@@ -2187,6 +2222,7 @@ async fn test_split_coin() -> Result<(), anyhow::Error> {
         count: Some(3),
         serialize_unsigned_transaction: false,
         serialize_signed_transaction: false,
+        simulation: SimulationArgs::default(),
     }
     .execute(context)
     .await?;
@@ -2258,6 +2294,7 @@ async fn test_split_coin() -> Result<(), anyhow::Error> {
         count: None,
         serialize_unsigned_transaction: false,
         serialize_signed_transaction: false,
+        simulation: SimulationArgs::default(),
     }
     .execute(context)
     .await?;
@@ -2294,6 +2331,225 @@ async fn test_split_coin() -> Result<(), anyhow::Error> {
     Ok(())
 }
 
+#[sim_test]
+async fn test_ptb_split_and_transfer() -> Result<(), anyhow::Error> {
+    let mut test_cluster = TestClusterBuilder::new().build().await;
+    let rgp = test_cluster.get_reference_gas_price().await;
+    let address = test_cluster.get_address_0();
+    let recipient = test_cluster.get_address_1();
+    let context = &mut test_cluster.wallet;
+
+    let resp = SuiClientCommands::Ptb {
+        commands: vec![
+            "let coins = split-coins gas [1000, 10]".to_string(),
+            format!("transfer-objects [coins.0, coins.1] @{recipient}"),
+        ],
+        gas: None,
+        gas_budget: rgp * TEST_ONLY_GAS_UNIT_FOR_SPLIT_COIN,
+        serialize_unsigned_transaction: false,
+        serialize_signed_transaction: false,
+        simulation: SimulationArgs::default(),
+    }
+    .execute(context)
+    .await?;
+
+    let SuiClientCommandResult::Ptb(r) = resp else {
+        panic!("Command failed")
+    };
+    assert!(r.status_ok().unwrap(), "Command failed: {:?}", r);
+    let created = r.effects.unwrap().created().to_vec();
+    assert_eq!(created.len(), 2);
+    let mut values = vec![];
+    for object in created {
+        assert_eq!(object.owner, Owner::AddressOwner(recipient));
+        values.push(get_gas_value(
+            &get_parsed_object_assert_existence(object.reference.object_id, context).await,
+        ));
+    }
+    values.sort();
+    assert_eq!(values, vec![10, 1000]);
+
+    // Serializing the transaction does not execute it.
+    let resp = SuiClientCommands::Ptb {
+        commands: vec![format!("transfer-objects [gas] @{address}")],
+        gas: None,
+        gas_budget: rgp * TEST_ONLY_GAS_UNIT_FOR_TRANSFER,
+        serialize_unsigned_transaction: true,
+        serialize_signed_transaction: false,
+        simulation: SimulationArgs::default(),
+    }
+    .execute(context)
+    .await?;
+    assert!(matches!(
+        resp,
+        SuiClientCommandResult::SerializedUnsignedTransaction(_)
+    ));
+    Ok(())
+}
+
+#[sim_test]
+async fn test_dry_run_and_dev_inspect() -> Result<(), anyhow::Error> {
+    let mut test_cluster = TestClusterBuilder::new().build().await;
+    let rgp = test_cluster.get_reference_gas_price().await;
+    let address = test_cluster.get_address_0();
+    let recipient = test_cluster.get_address_1();
+    let context = &mut test_cluster.wallet;
+
+    let gas = context
+        .get_one_gas_object_owned_by_address(address)
+        .await?
+        .unwrap();
+    let orig_value = get_gas_value(&get_object(gas.0, context).await.unwrap());
+
+    let resp = SuiClientCommands::Ptb {
+        commands: vec![
+            "let coins = split-coins gas [1000, 10]".to_string(),
+            format!("transfer-objects [coins.0, coins.1] @{recipient}"),
+        ],
+        gas: Some(gas.0),
+        gas_budget: rgp * TEST_ONLY_GAS_UNIT_FOR_SPLIT_COIN,
+        serialize_unsigned_transaction: false,
+        serialize_signed_transaction: false,
+        simulation: SimulationArgs {
+            dry_run: true,
+            dev_inspect: false,
+        },
+    }
+    .execute(context)
+    .await?;
+
+    let SuiClientCommandResult::DryRun(r) = resp else {
+        panic!("Expected a dry run response")
+    };
+    assert!(r.effects.status().is_ok(), "Dry run failed: {:?}", r);
+    assert_eq!(r.effects.created().len(), 2);
+    assert!(!r.balance_changes.is_empty());
+
+    let resp = SuiClientCommands::TransferSui {
+        to: recipient,
+        sui_coin_object_id: gas.0,
+        gas_budget: rgp * TEST_ONLY_GAS_UNIT_FOR_TRANSFER,
+        amount: Some(1000),
+        serialize_unsigned_transaction: false,
+        serialize_signed_transaction: false,
+        simulation: SimulationArgs {
+            dry_run: false,
+            dev_inspect: true,
+        },
+    }
+    .execute(context)
+    .await?;
+
+    let SuiClientCommandResult::DevInspect(r) = resp else {
+        panic!("Expected a dev inspect response")
+    };
+    assert!(r.error.is_none(), "Dev inspect failed: {:?}", r);
+
+    // Neither the dry run nor the dev inspect executed the transaction.
+    let object = get_object(gas.0, context).await.unwrap();
+    assert_eq!(object.version, gas.1);
+    assert_eq!(get_gas_value(&object), orig_value);
+
+    // Only one of the modes can be requested at a time.
+    let result = SuiClientCommands::TransferSui {
+        to: recipient,
+        sui_coin_object_id: gas.0,
+        gas_budget: rgp * TEST_ONLY_GAS_UNIT_FOR_TRANSFER,
+        amount: Some(1000),
+        serialize_unsigned_transaction: true,
+        serialize_signed_transaction: false,
+        simulation: SimulationArgs {
+            dry_run: true,
+            dev_inspect: false,
+        },
+    }
+    .execute(context)
+    .await;
+    assert!(result.is_err());
+    Ok(())
+}
+
+#[sim_test]
+async fn test_transaction_bundle() -> Result<(), anyhow::Error> {
+    let mut test_cluster = TestClusterBuilder::new().build().await;
+    let rgp = test_cluster.get_reference_gas_price().await;
+    let address = test_cluster.get_address_0();
+    let recipient = test_cluster.get_address_1();
+    let context = &mut test_cluster.wallet;
+
+    let gas = context
+        .get_one_gas_object_owned_by_address(address)
+        .await?
+        .unwrap();
+    let resp = SuiClientCommands::TransferSui {
+        to: recipient,
+        sui_coin_object_id: gas.0,
+        gas_budget: rgp * TEST_ONLY_GAS_UNIT_FOR_TRANSFER,
+        amount: Some(1000),
+        serialize_unsigned_transaction: true,
+        serialize_signed_transaction: false,
+        simulation: SimulationArgs::default(),
+    }
+    .execute(context)
+    .await?;
+    let SuiClientCommandResult::SerializedUnsignedTransaction(data) = resp else {
+        panic!("Expected an unsigned transaction")
+    };
+
+    let temp_dir = tempfile::tempdir()?;
+    let file = temp_dir.path().join("bundle.json");
+    let resp = SuiClientCommands::CreateBundle {
+        tx_bytes: Base64::encode(bcs::to_bytes(&data)?),
+        description: Some("Send 1000 MIST".to_string()),
+        multisig_pks: vec![],
+        multisig_weights: vec![],
+        multisig_threshold: None,
+        file: file.clone(),
+    }
+    .execute(context)
+    .await?;
+    let SuiClientCommandResult::CreateBundle(bundle) = resp else {
+        panic!("Expected a bundle")
+    };
+    assert_eq!(bundle.summary.sender, address);
+    assert_eq!(bundle.objects, vec![SuiObjectRef::from(gas)]);
+
+    // The bundle cannot be executed before it is signed.
+    let result = SuiClientCommands::ExecuteBundle { file: file.clone() }
+        .execute(context)
+        .await;
+    assert!(result.is_err());
+
+    let output = KeyToolCommand::SignBundle {
+        file: file.clone(),
+        address: None,
+    }
+    .execute(&mut context.config.keystore)
+    .await?;
+    let CommandOutput::SignBundle(bundle) = output else {
+        panic!("Expected a signed bundle")
+    };
+    assert_eq!(bundle.signatures.len(), 1);
+
+    let resp = SuiClientCommands::ExecuteBundle { file: file.clone() }
+        .execute(context)
+        .await?;
+    let SuiClientCommandResult::ExecuteBundle(r) = resp else {
+        panic!("Expected a transaction response")
+    };
+    assert!(r.status_ok().unwrap(), "Command failed: {:?}", r);
+
+    // The gas coin changed when the transaction was executed, so the bundle is stale.
+    let result = SuiClientCommands::ExecuteBundle { file }
+        .execute(context)
+        .await;
+    assert!(result
+        .unwrap_err()
+        .to_string()
+        .contains("changed since it was created"));
+    Ok(())
+}
+
 #[sim_test]
 async fn test_signature_flag() -> Result<(), anyhow::Error> {
     let res = SignatureScheme::from_flag("0");
@@ -2321,12 +2577,33 @@ async fn test_execute_signed_tx() -> Result<(), anyhow::Error> {
     let txn = txns.swap_remove(0);
 
     let (tx_data, signatures) = txn.to_tx_bytes_and_signatures();
-    SuiClientCommands::ExecuteSignedTx {
-        tx_bytes: tx_data.encoded(),
-        signatures: signatures.into_iter().map(|s| s.encoded()).collect(),
+    let tx_bytes = tx_data.encoded();
+    let signatures = signatures
+        .into_iter()
+        .map(|s| s.encoded())
+        .collect::<Vec<_>>();
+
+    let resp = SuiClientCommands::ExecuteSignedTx {
+        tx_bytes: tx_bytes.clone(),
+        signatures: signatures.clone(),
+        dry_run: true,
+    }
+    .execute(context)
+    .await?;
+    let SuiClientCommandResult::DryRun(r) = resp else {
+        panic!("Expected a dry run response")
+    };
+    assert!(r.effects.status().is_ok(), "Dry run failed: {:?}", r);
+
+    // The dry run did not execute the transaction, so it can still be executed.
+    let resp = SuiClientCommands::ExecuteSignedTx {
+        tx_bytes,
+        signatures,
+        dry_run: false,
     }
     .execute(context)
     .await?;
+    assert!(matches!(resp, SuiClientCommandResult::ExecuteSignedTx(_)));
     Ok(())
 }
 
@@ -2362,6 +2639,7 @@ async fn test_serialize_tx() -> Result<(), anyhow::Error> {
         amount: Some(1),
         serialize_unsigned_transaction: true,
         serialize_signed_transaction: false,
+        simulation: SimulationArgs::default(),
     }
     .execute(context)
     .await?;
@@ -2373,6 +2651,7 @@ async fn test_serialize_tx() -> Result<(), anyhow::Error> {
         amount: Some(1),
         serialize_unsigned_transaction: false,
         serialize_signed_transaction: true,
+        simulation: SimulationArgs::default(),
     }
     .execute(context)
     .await?;
@@ -14,7 +14,7 @@ use move_compiler::{
 };
 use move_coverage::coverage_map::{output_map_to_file, CoverageMap};
 use move_package::{compilation::build_plan::BuildPlan, BuildConfig};
-use move_unit_test::UnitTestingConfig;
+use move_unit_test::{gas_report::GasReport, UnitTestingConfig};
 use move_vm_test_utils::gas_schedule::CostTable;
 use std::{
     collections::HashMap,
@@ -69,6 +69,9 @@ pub struct Test {
     /// Collect coverage information for later use with the various `move coverage` subcommands. Currently supported only in debug builds.
     #[clap(long = "coverage")]
     pub compute_coverage: bool,
+    /// Report the gas used by each test, and by each function it calls.
+    #[clap(long = "gas-report")]
+    pub gas_report: bool,
 }
 
 impl Test {
@@ -89,6 +92,7 @@ impl Test {
             check_stackless_vm,
             verbose_mode,
             compute_coverage,
+            gas_report,
         } = self;
         let unit_test_config = UnitTestingConfig {
             gas_limit,
@@ -98,6 +102,7 @@ impl Test {
             report_statistics,
             check_stackless_vm,
             verbose: verbose_mode,
+            report_gas: gas_report,
             ..UnitTestingConfig::default_with_bound(None)
         };
         let result = run_move_unit_tests(
@@ -126,6 +131,29 @@ pub enum UnitTestResult {
 }
 
 pub fn run_move_unit_tests<W: Write + Send>(
+    pkg_path: &Path,
+    build_config: move_package::BuildConfig,
+    unit_test_config: UnitTestingConfig,
+    natives: Vec<NativeFunctionRecord>,
+    cost_table: Option<CostTable>,
+    compute_coverage: bool,
+    writer: &mut W,
+) -> Result<(UnitTestResult, Option<Diagnostics>)> {
+    let (result, warning_diags, _) = run_move_unit_tests_with_gas_report(
+        pkg_path,
+        build_config,
+        unit_test_config,
+        natives,
+        cost_table,
+        compute_coverage,
+        writer,
+    )?;
+    Ok((result, warning_diags))
+}
+
+/// Like `run_move_unit_tests`, but also returns the gas report of the tests if `report_gas` is set
+/// in `unit_test_config`.
+pub fn run_move_unit_tests_with_gas_report<W: Write + Send>(
     pkg_path: &Path,
     mut build_config: move_package::BuildConfig,
     mut unit_test_config: UnitTestingConfig,
@@ -133,7 +161,7 @@ pub fn run_move_unit_tests<W: Write + Send>(
     cost_table: Option<CostTable>,
     compute_coverage: bool,
     writer: &mut W,
-) -> Result<(UnitTestResult, Option<Diagnostics>)> {
+) -> Result<(UnitTestResult, Option<Diagnostics>, Option<GasReport>)> {
     let mut test_plan = None;
     build_config.test_mode = true;
     build_config.dev_mode = true;
@@ -221,13 +249,12 @@ pub fn run_move_unit_tests<W: Write + Send>(
 
     // Run the tests. If any of the tests fail, then we don't produce a coverage report, so cleanup
     // the trace files.
-    if !unit_test_config
-        .run_and_report_unit_tests(test_plan, Some(natives), cost_table, writer)
-        .unwrap()
-        .1
-    {
+    let (_, ok, gas_report) = unit_test_config
+        .run_and_report_unit_tests_with_gas_report(test_plan, Some(natives), cost_table, writer)
+        .unwrap();
+    if !ok {
         cleanup_trace();
-        return Ok((UnitTestResult::Failure, warning_diags));
+        return Ok((UnitTestResult::Failure, warning_diags, gas_report));
     }
 
     // Compute the coverage map. This will be used by other commands after this.
@@ -235,7 +262,7 @@ pub fn run_move_unit_tests<W: Write + Send>(
         let coverage_map = CoverageMap::from_trace_file(trace_path);
         output_map_to_file(coverage_map_path, &coverage_map).unwrap();
     }
-    Ok((UnitTestResult::Success, warning_diags))
+    Ok((UnitTestResult::Success, warning_diags, gas_report))
 }
 
 impl From<UnitTestResult> for ExitStatus {
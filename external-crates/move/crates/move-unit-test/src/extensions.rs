@@ -8,12 +8,16 @@
 
 use move_vm_runtime::native_extensions::NativeContextExtensions;
 use once_cell::sync::Lazy;
-use std::sync::Mutex;
+use std::{collections::BTreeMap, sync::Mutex};
+
+type StatisticsHook = dyn Fn(&NativeContextExtensions<'_>) -> BTreeMap<String, u64> + Send + Sync;
 
 static EXTENSION_HOOK: Lazy<
     Mutex<Option<Box<dyn Fn(&mut NativeContextExtensions<'_>) + Send + Sync>>>,
 > = Lazy::new(|| Mutex::new(None));
 
+static STATISTICS_HOOK: Lazy<Mutex<Option<Box<StatisticsHook>>>> = Lazy::new(|| Mutex::new(None));
+
 /// Sets a hook which is called to populate additional native extensions. This can be used to
 /// get extensions living outside of the Move repo into the unit testing environment.
 ///
@@ -30,6 +34,21 @@ pub fn set_extension_hook(p: Box<dyn Fn(&mut NativeContextExtensions<'_>) + Send
     *EXTENSION_HOOK.lock().unwrap() = Some(p)
 }
 
+/// Sets a hook which is called with the native extensions at the end of each test, to collect
+/// statistics about the test from them, e.g. the objects it created. The statistics are included
+/// in the gas report of the test.
+pub fn set_statistics_hook(p: Box<StatisticsHook>) {
+    *STATISTICS_HOOK.lock().unwrap() = Some(p)
+}
+
+/// Collect the statistics of a test from its native context extensions.
+pub(crate) fn extension_statistics(e: &NativeContextExtensions<'_>) -> BTreeMap<String, u64> {
+    match &*STATISTICS_HOOK.lock().unwrap() {
+        Some(h) => (*h)(e),
+        None => BTreeMap::new(),
+    }
+}
+
 /// Create all available native context extensions.
 #[allow(unused_mut, clippy::let_and_return)]
 pub(crate) fn new_extensions<'a>() -> NativeContextExtensions<'a> {
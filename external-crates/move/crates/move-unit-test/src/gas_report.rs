@@ -0,0 +1,508 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Gas usage of unit tests, broken down by the functions they call.
+
+use move_binary_format::errors::PartialVMResult;
+use move_core_types::{
+    gas_algebra::{InternalGas, NumArgs, NumBytes},
+    language_storage::ModuleId,
+};
+#[cfg(debug_assertions)]
+use move_vm_profiler::GasProfiler;
+use move_vm_test_utils::gas_schedule::{Gas, GasStatus};
+use move_vm_types::{
+    gas::{GasMeter, SimpleInstruction},
+    views::{TypeView, ValueView},
+};
+use std::collections::BTreeMap;
+
+use crate::format_module_id;
+
+/// The gas used by calls to a function during a test.
+#[derive(Debug, Clone, Copy, Default, Ord, PartialOrd, PartialEq, Eq)]
+pub struct FunctionGas {
+    /// The number of times the function was called.
+    pub calls: u64,
+    /// The gas used by these calls, including the gas used by the functions they call.
+    pub gas_used: u64,
+}
+
+/// The gas report of a test.
+#[derive(Debug, Clone)]
+pub struct TestGasReport {
+    pub passed: bool,
+    /// The gas used by the test.
+    pub gas_used: u64,
+    /// The gas used by each function called by the test, by fully qualified function name.
+    pub functions: BTreeMap<String, FunctionGas>,
+    /// Statistics reported by native extensions at the end of the test, see
+    /// `extensions::set_statistics_hook`.
+    pub statistics: BTreeMap<String, u64>,
+}
+
+/// The gas reports of tests, by fully qualified test name.
+pub type GasReport = BTreeMap<String, TestGasReport>;
+
+struct Frame {
+    function: String,
+    gas_left: InternalGas,
+    is_native: bool,
+}
+
+/// A gas meter which records the gas used by each function called, on top of metering
+/// execution like the `GasStatus` it wraps.
+pub(crate) struct FunctionGasMeter<'a> {
+    gas_status: GasStatus<'a>,
+    // whether to record the gas used by functions, or only meter execution
+    enabled: bool,
+    frames: Vec<Frame>,
+    // whether the last charge was for a call, so that a native charge following it is known to
+    // be for the function called
+    call_pending: bool,
+    functions: BTreeMap<String, (u64, InternalGas)>,
+}
+
+impl<'a> FunctionGasMeter<'a> {
+    /// Meter the execution of `function` with `gas_status`, recording the gas used by the
+    /// functions it calls if `enabled`.
+    pub(crate) fn new(
+        gas_status: GasStatus<'a>,
+        module_id: &ModuleId,
+        function: &str,
+        enabled: bool,
+    ) -> Self {
+        let mut meter = Self {
+            gas_status,
+            enabled,
+            frames: vec![],
+            call_pending: false,
+            functions: BTreeMap::new(),
+        };
+        meter.open_frame(module_id, function);
+        meter
+    }
+
+    /// The gas left, in gas units.
+    pub(crate) fn remaining_gas(&self) -> Gas {
+        self.gas_status.remaining_gas()
+    }
+
+    /// The gas used by each function called, closing the frames of functions which did not
+    /// return, e.g. because execution aborted.
+    pub(crate) fn finish(mut self) -> BTreeMap<String, FunctionGas> {
+        while !self.frames.is_empty() {
+            self.close_frame();
+        }
+        self.functions
+            .into_iter()
+            .map(|(function, (calls, gas_used))| {
+                let gas_used: Gas = gas_used.to_unit_round_down();
+                let gas_used = gas_used.into();
+                (function, FunctionGas { calls, gas_used })
+            })
+            .collect()
+    }
+
+    fn open_frame(&mut self, module_id: &ModuleId, function: &str) {
+        if !self.enabled {
+            return;
+        }
+        self.frames.push(Frame {
+            function: format!("{}::{}", format_module_id(module_id), function),
+            gas_left: GasMeter::remaining_gas(&self.gas_status),
+            is_native: false,
+        });
+        self.call_pending = true;
+    }
+
+    fn close_frame(&mut self) {
+        let Some(frame) = self.frames.pop() else {
+            return;
+        };
+        let gas_used = frame
+            .gas_left
+            .saturating_sub(GasMeter::remaining_gas(&self.gas_status));
+        let (calls, total) = self
+            .functions
+            .entry(frame.function)
+            .or_insert((0, InternalGas::new(0)));
+        *calls += 1;
+        *total = *total + gas_used;
+    }
+
+    /// Runs `charge` against the wrapped `GasStatus`, for charges other than calls.
+    fn charge(
+        &mut self,
+        charge: impl FnOnce(&mut GasStatus<'a>) -> PartialVMResult<()>,
+    ) -> PartialVMResult<()> {
+        self.call_pending = false;
+        charge(&mut self.gas_status)
+    }
+}
+
+impl<'a> GasMeter for FunctionGasMeter<'a> {
+    fn charge_simple_instr(&mut self, instr: SimpleInstruction) -> PartialVMResult<()> {
+        self.charge(|g| g.charge_simple_instr(instr))
+    }
+
+    fn charge_pop(&mut self, popped_val: impl ValueView) -> PartialVMResult<()> {
+        self.charge(|g| g.charge_pop(popped_val))
+    }
+
+    fn charge_call(
+        &mut self,
+        module_id: &ModuleId,
+        func_name: &str,
+        args: impl ExactSizeIterator<Item = impl ValueView>,
+        num_locals: NumArgs,
+    ) -> PartialVMResult<()> {
+        self.open_frame(module_id, func_name);
+        self.gas_status
+            .charge_call(module_id, func_name, args, num_locals)
+    }
+
+    fn charge_call_generic(
+        &mut self,
+        module_id: &ModuleId,
+        func_name: &str,
+        ty_args: impl ExactSizeIterator<Item = impl TypeView>,
+        args: impl ExactSizeIterator<Item = impl ValueView>,
+        num_locals: NumArgs,
+    ) -> PartialVMResult<()> {
+        self.open_frame(module_id, func_name);
+        self.gas_status
+            .charge_call_generic(module_id, func_name, ty_args, args, num_locals)
+    }
+
+    fn charge_ld_const(&mut self, size: NumBytes) -> PartialVMResult<()> {
+        self.charge(|g| g.charge_ld_const(size))
+    }
+
+    fn charge_ld_const_after_deserialization(
+        &mut self,
+        val: impl ValueView,
+    ) -> PartialVMResult<()> {
+        self.charge(|g| g.charge_ld_const_after_deserialization(val))
+    }
+
+    fn charge_copy_loc(&mut self, val: impl ValueView) -> PartialVMResult<()> {
+        self.charge(|g| g.charge_copy_loc(val))
+    }
+
+    fn charge_move_loc(&mut self, val: impl ValueView) -> PartialVMResult<()> {
+        self.charge(|g| g.charge_move_loc(val))
+    }
+
+    fn charge_store_loc(&mut self, val: impl ValueView) -> PartialVMResult<()> {
+        self.charge(|g| g.charge_store_loc(val))
+    }
+
+    fn charge_pack(
+        &mut self,
+        is_generic: bool,
+        args: impl ExactSizeIterator<Item = impl ValueView>,
+    ) -> PartialVMResult<()> {
+        self.charge(|g| g.charge_pack(is_generic, args))
+    }
+
+    fn charge_unpack(
+        &mut self,
+        is_generic: bool,
+        args: impl ExactSizeIterator<Item = impl ValueView>,
+    ) -> PartialVMResult<()> {
+        self.charge(|g| g.charge_unpack(is_generic, args))
+    }
+
+    fn charge_read_ref(&mut self, val: impl ValueView) -> PartialVMResult<()> {
+        self.charge(|g| g.charge_read_ref(val))
+    }
+
+    fn charge_write_ref(
+        &mut self,
+        new_val: impl ValueView,
+        old_val: impl ValueView,
+    ) -> PartialVMResult<()> {
+        self.charge(|g| g.charge_write_ref(new_val, old_val))
+    }
+
+    fn charge_eq(&mut self, lhs: impl ValueView, rhs: impl ValueView) -> PartialVMResult<()> {
+        self.charge(|g| g.charge_eq(lhs, rhs))
+    }
+
+    fn charge_neq(&mut self, lhs: impl ValueView, rhs: impl ValueView) -> PartialVMResult<()> {
+        self.charge(|g| g.charge_neq(lhs, rhs))
+    }
+
+    fn charge_vec_pack<'b>(
+        &mut self,
+        ty: impl TypeView + 'b,
+        args: impl ExactSizeIterator<Item = impl ValueView>,
+    ) -> PartialVMResult<()> {
+        self.charge(|g| g.charge_vec_pack(ty, args))
+    }
+
+    fn charge_vec_len(&mut self, ty: impl TypeView) -> PartialVMResult<()> {
+        self.charge(|g| g.charge_vec_len(ty))
+    }
+
+    fn charge_vec_borrow(
+        &mut self,
+        is_mut: bool,
+        ty: impl TypeView,
+        is_success: bool,
+    ) -> PartialVMResult<()> {
+        self.charge(|g| g.charge_vec_borrow(is_mut, ty, is_success))
+    }
+
+    fn charge_vec_push_back(
+        &mut self,
+        ty: impl TypeView,
+        val: impl ValueView,
+    ) -> PartialVMResult<()> {
+        self.charge(|g| g.charge_vec_push_back(ty, val))
+    }
+
+    fn charge_vec_pop_back(
+        &mut self,
+        ty: impl TypeView,
+        val: Option<impl ValueView>,
+    ) -> PartialVMResult<()> {
+        self.charge(|g| g.charge_vec_pop_back(ty, val))
+    }
+
+    fn charge_vec_unpack(
+        &mut self,
+        ty: impl TypeView,
+        expect_num_elements: NumArgs,
+        elems: impl ExactSizeIterator<Item = impl ValueView>,
+    ) -> PartialVMResult<()> {
+        self.charge(|g| g.charge_vec_unpack(ty, expect_num_elements, elems))
+    }
+
+    fn charge_vec_swap(&mut self, ty: impl TypeView) -> PartialVMResult<()> {
+        self.charge(|g| g.charge_vec_swap(ty))
+    }
+
+    fn charge_native_function(
+        &mut self,
+        amount: InternalGas,
+        ret_vals: Option<impl ExactSizeIterator<Item = impl ValueView>>,
+    ) -> PartialVMResult<()> {
+        let result = self.charge(|g| g.charge_native_function(amount, ret_vals));
+        // natives do not drop a frame, so their frame closes once they are charged for
+        if self.frames.last().is_some_and(|frame| frame.is_native) {
+            self.close_frame();
+        }
+        result
+    }
+
+    fn charge_native_function_before_execution(
+        &mut self,
+        ty_args: impl ExactSizeIterator<Item = impl TypeView>,
+        args: impl ExactSizeIterator<Item = impl ValueView>,
+    ) -> PartialVMResult<()> {
+        if self.call_pending {
+            if let Some(frame) = self.frames.last_mut() {
+                frame.is_native = true;
+            }
+        }
+        self.charge(|g| g.charge_native_function_before_execution(ty_args, args))
+    }
+
+    fn charge_drop_frame(
+        &mut self,
+        locals: impl Iterator<Item = impl ValueView>,
+    ) -> PartialVMResult<()> {
+        let result = self.charge(|g| g.charge_drop_frame(locals));
+        self.close_frame();
+        result
+    }
+
+    fn remaining_gas(&self) -> InternalGas {
+        GasMeter::remaining_gas(&self.gas_status)
+    }
+
+    #[cfg(debug_assertions)]
+    fn get_profiler_mut(&mut self) -> Option<&mut GasProfiler> {
+        self.gas_status.get_profiler_mut()
+    }
+
+    #[cfg(debug_assertions)]
+    fn set_profiler(&mut self, profiler: GasProfiler) {
+        self.gas_status.set_profiler(profiler)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use move_core_types::{
+        account_address::AccountAddress, identifier::Identifier, language_storage::TypeTag,
+    };
+    use move_vm_test_utils::gas_schedule::{
+        new_from_instructions, zero_cost_instruction_table, CostTable, GasCost,
+    };
+    use move_vm_types::values::Value;
+    use std::iter;
+
+    struct TestType;
+
+    impl TypeView for TestType {
+        fn to_type_tag(&self) -> TypeTag {
+            TypeTag::U64
+        }
+    }
+
+    /// A cost table where every instruction costs one gas unit.
+    fn cost_table() -> CostTable {
+        new_from_instructions(
+            zero_cost_instruction_table()
+                .into_iter()
+                .map(|(instr, _)| (instr, GasCost::new(1_000, 0)))
+                .collect(),
+        )
+    }
+
+    fn module_id() -> ModuleId {
+        ModuleId::new(AccountAddress::ONE, Identifier::new("m").unwrap())
+    }
+
+    fn new_meter(cost_table: &CostTable, enabled: bool) -> FunctionGasMeter<'_> {
+        FunctionGasMeter::new(
+            GasStatus::new(cost_table, Gas::new(100)),
+            &module_id(),
+            "test",
+            enabled,
+        )
+    }
+
+    fn call(meter: &mut FunctionGasMeter, function: &str) {
+        meter
+            .charge_call(
+                &module_id(),
+                function,
+                iter::empty::<Value>(),
+                NumArgs::new(0),
+            )
+            .unwrap();
+    }
+
+    fn instr(meter: &mut FunctionGasMeter) {
+        meter.charge_simple_instr(SimpleInstruction::Nop).unwrap();
+    }
+
+    fn ret(meter: &mut FunctionGasMeter) {
+        meter.charge_simple_instr(SimpleInstruction::Ret).unwrap();
+        meter.charge_drop_frame(iter::empty::<Value>()).unwrap();
+    }
+
+    /// Calls a Move function which executes one instruction then returns.
+    fn call_move(meter: &mut FunctionGasMeter, function: &str) {
+        call(meter, function);
+        instr(meter);
+        ret(meter);
+    }
+
+    fn call_native(meter: &mut FunctionGasMeter, function: &str, cost: u64, aborts: bool) {
+        call(meter, function);
+        meter
+            .charge_native_function_before_execution(
+                iter::empty::<TestType>(),
+                iter::empty::<Value>(),
+            )
+            .unwrap();
+        let ret_vals = (!aborts).then(iter::empty::<Value>);
+        meter
+            .charge_native_function(Gas::new(cost).to_unit(), ret_vals)
+            .unwrap();
+    }
+
+    fn function_gas(calls: u64, gas_used: u64) -> FunctionGas {
+        FunctionGas { calls, gas_used }
+    }
+
+    #[test]
+    fn test_nested_calls() {
+        let cost_table = cost_table();
+        let mut meter = new_meter(&cost_table, true);
+        instr(&mut meter);
+        call_move(&mut meter, "callee");
+        call_move(&mut meter, "callee");
+        ret(&mut meter);
+
+        assert_eq!(meter.remaining_gas(), Gas::new(92));
+        assert_eq!(
+            meter.finish(),
+            BTreeMap::from([
+                ("0x1::m::callee".to_owned(), function_gas(2, 6)),
+                ("0x1::m::test".to_owned(), function_gas(1, 8)),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_native_calls() {
+        let cost_table = cost_table();
+        let mut meter = new_meter(&cost_table, true);
+        call_native(&mut meter, "native", 5, false);
+        instr(&mut meter);
+        // the native frame is closed, so this call is not mistaken for a native one
+        call_move(&mut meter, "callee");
+        ret(&mut meter);
+
+        assert_eq!(
+            meter.finish(),
+            BTreeMap::from([
+                ("0x1::m::callee".to_owned(), function_gas(1, 3)),
+                ("0x1::m::native".to_owned(), function_gas(1, 6)),
+                ("0x1::m::test".to_owned(), function_gas(1, 11)),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_native_abort() {
+        let cost_table = cost_table();
+        let mut meter = new_meter(&cost_table, true);
+        call_native(&mut meter, "native", 2, true);
+
+        assert_eq!(
+            meter.finish(),
+            BTreeMap::from([
+                ("0x1::m::native".to_owned(), function_gas(1, 3)),
+                ("0x1::m::test".to_owned(), function_gas(1, 3)),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_abort() {
+        let cost_table = cost_table();
+        let mut meter = new_meter(&cost_table, true);
+        instr(&mut meter);
+        call(&mut meter, "callee");
+        instr(&mut meter);
+        meter.charge_simple_instr(SimpleInstruction::Abort).unwrap();
+
+        // the frames of the functions which did not return are closed when finishing
+        assert_eq!(
+            meter.finish(),
+            BTreeMap::from([
+                ("0x1::m::callee".to_owned(), function_gas(1, 3)),
+                ("0x1::m::test".to_owned(), function_gas(1, 4)),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_disabled() {
+        let cost_table = cost_table();
+        let mut meter = new_meter(&cost_table, false);
+        call_move(&mut meter, "callee");
+        ret(&mut meter);
+
+        assert_eq!(meter.remaining_gas(), Gas::new(96));
+        assert!(meter.finish().is_empty());
+    }
+}
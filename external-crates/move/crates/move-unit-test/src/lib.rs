@@ -4,10 +4,11 @@
 
 pub mod cargo_runner;
 pub mod extensions;
+pub mod gas_report;
 pub mod test_reporter;
 pub mod test_runner;
 
-use crate::test_runner::TestRunner;
+use crate::{gas_report::GasReport, test_runner::TestRunner};
 use clap::*;
 use move_command_line_common::files::verify_and_create_named_address_mapping;
 use move_compiler::{
@@ -100,6 +101,10 @@ pub struct UnitTestingConfig {
     /// Verbose mode
     #[clap(short = 'v', long = "verbose")]
     pub verbose: bool,
+
+    /// Report the gas used by each test, and by each function it calls
+    #[clap(long = "gas-report")]
+    pub report_gas: bool,
 }
 
 fn format_module_id(module_id: &ModuleId) -> String {
@@ -123,6 +128,7 @@ impl UnitTestingConfig {
             dep_files: vec![],
             check_stackless_vm: false,
             verbose: false,
+            report_gas: false,
             list: false,
             named_address_values: vec![],
         }
@@ -187,6 +193,24 @@ impl UnitTestingConfig {
         cost_table: Option<CostTable>,
         writer: W,
     ) -> Result<(W, bool)> {
+        let (writer, ok, _) = self.run_and_report_unit_tests_with_gas_report(
+            test_plan,
+            native_function_table,
+            cost_table,
+            writer,
+        )?;
+        Ok((writer, ok))
+    }
+
+    /// Like `run_and_report_unit_tests`, but also returns the gas report of the tests if
+    /// `report_gas` is set.
+    pub fn run_and_report_unit_tests_with_gas_report<W: Write + Send>(
+        &self,
+        test_plan: TestPlan,
+        native_function_table: Option<NativeFunctionTable>,
+        cost_table: Option<CostTable>,
+        writer: W,
+    ) -> Result<(W, bool, Option<GasReport>)> {
         let shared_writer = Mutex::new(writer);
 
         if self.list {
@@ -200,7 +224,7 @@ impl UnitTestingConfig {
                     )?;
                 }
             }
-            return Ok((shared_writer.into_inner().unwrap(), true));
+            return Ok((shared_writer.into_inner().unwrap(), true, None));
         }
 
         writeln!(shared_writer.lock().unwrap(), "Running Move unit tests")?;
@@ -210,6 +234,7 @@ impl UnitTestingConfig {
             self.check_stackless_vm,
             self.verbose,
             self.report_stacktrace_on_abort,
+            self.report_gas,
             test_plan,
             native_function_table,
             cost_table,
@@ -226,9 +251,16 @@ impl UnitTestingConfig {
             test_results.report_statistics(&shared_writer, report_type)?;
         }
 
+        let gas_report = if self.report_gas {
+            test_results.report_gas(&shared_writer)?;
+            Some(test_results.gas_report())
+        } else {
+            None
+        };
+
         let ok = test_results.summarize(&shared_writer)?;
 
         let writer = shared_writer.into_inner().unwrap();
-        Ok((writer, ok))
+        Ok((writer, ok, gas_report))
     }
 }
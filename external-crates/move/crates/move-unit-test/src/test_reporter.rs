@@ -2,7 +2,10 @@
 // Copyright (c) The Move Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::format_module_id;
+use crate::{
+    format_module_id,
+    gas_report::{FunctionGas, GasReport, TestGasReport},
+};
 use codespan_reporting::files::{Files, SimpleFiles};
 use colored::{control, Colorize};
 use move_binary_format::{
@@ -59,6 +62,10 @@ pub struct TestRunInfo {
     pub function_ident: String,
     pub elapsed_time: Duration,
     pub instructions_executed: u64,
+    /// The gas used by each function called by the test, if gas is reported.
+    pub function_gas: BTreeMap<String, FunctionGas>,
+    /// Statistics collected from native extensions at the end of the test, if gas is reported.
+    pub statistics: BTreeMap<String, u64>,
 }
 
 #[derive(Debug, Clone)]
@@ -80,6 +87,8 @@ impl TestRunInfo {
             function_ident,
             elapsed_time,
             instructions_executed,
+            function_gas: BTreeMap::new(),
+            statistics: BTreeMap::new(),
         }
     }
 }
@@ -531,6 +540,101 @@ impl TestResults {
         writeln!(writer.lock().unwrap())
     }
 
+    /// The gas report of each test, by fully qualified test name.
+    pub fn gas_report(&self) -> GasReport {
+        let passed = self
+            .final_statistics
+            .passed
+            .iter()
+            .flat_map(|(module_id, infos)| infos.iter().map(move |info| (module_id, info, true)));
+        let failed = self
+            .final_statistics
+            .failed
+            .iter()
+            .flat_map(|(module_id, failures)| {
+                failures
+                    .iter()
+                    .map(move |failure| (module_id, &failure.test_run_info, false))
+            });
+        passed
+            .chain(failed)
+            .map(|(module_id, info, passed)| {
+                let report = TestGasReport {
+                    passed,
+                    gas_used: info.instructions_executed,
+                    functions: info.function_gas.clone(),
+                    statistics: info.statistics.clone(),
+                };
+                (
+                    format!("{}::{}", format_module_id(module_id), info.function_ident),
+                    report,
+                )
+            })
+            .collect()
+    }
+
+    /// Report the gas used by each test, and by each function it calls, most expensive first.
+    pub fn report_gas<W: Write>(&self, writer: &Mutex<W>) -> Result<()> {
+        writeln!(writer.lock().unwrap(), "\nGas Report:")?;
+
+        for (test_name, report) in self.gas_report() {
+            writeln!(
+                writer.lock().unwrap(),
+                "\n{} (gas used: {})",
+                test_name.bold(),
+                report.gas_used
+            )?;
+            let mut functions: Vec<_> = report.functions.into_iter().collect();
+            functions.sort_by(|(f1, g1), (f2, g2)| g2.gas_used.cmp(&g1.gas_used).then(f1.cmp(f2)));
+            let width = functions
+                .iter()
+                .map(|(function, _)| function.len())
+                .max()
+                .unwrap_or(0)
+                .max("Function".len());
+
+            writeln!(
+                writer.lock().unwrap(),
+                "┌─{:─^width$}─┬─{:─^10}─┬─{:─^15}─┐",
+                "",
+                "",
+                "",
+            )?;
+            writeln!(
+                writer.lock().unwrap(),
+                "│ {:^width$} │ {:^10} │ {:^15} │",
+                "Function",
+                "Calls",
+                "Gas Used",
+            )?;
+            for (function, gas) in functions {
+                writeln!(
+                    writer.lock().unwrap(),
+                    "├─{:─^width$}─┼─{:─^10}─┼─{:─^15}─┤",
+                    "",
+                    "",
+                    "",
+                )?;
+                writeln!(
+                    writer.lock().unwrap(),
+                    "│ {:<width$} │ {:^10} │ {:^15} │",
+                    function,
+                    gas.calls,
+                    gas.gas_used,
+                )?;
+            }
+            writeln!(
+                writer.lock().unwrap(),
+                "└─{:─^width$}─┴─{:─^10}─┴─{:─^15}─┘",
+                "",
+                "",
+                "",
+            )?;
+        }
+
+        writeln!(writer.lock().unwrap())
+    }
+
     /// Returns `true` if all tests passed, `false` if there was a test failure/timeout
     pub fn summarize<W: Write>(self, writer: &Mutex<W>) -> Result<bool> {
         let num_failed_tests = self
@@ -4,6 +4,7 @@
 
 use crate::{
     extensions, format_module_id,
+    gas_report::FunctionGasMeter,
     test_reporter::{
         FailureReason, MoveError, TestFailure, TestResults, TestRunInfo, TestStatistics,
     },
@@ -52,6 +53,7 @@ use move_vm_runtime::native_extensions::NativeContextExtensions;
 /// Test state common to all tests
 pub struct SharedTestingConfig {
     report_stacktrace_on_abort: bool,
+    report_gas: bool,
     execution_bound: u64,
     cost_table: CostTable,
     native_function_table: NativeFunctionTable,
@@ -94,6 +96,7 @@ impl TestRunner {
         check_stackless_vm: bool,
         verbose: bool,
         report_stacktrace_on_abort: bool,
+        report_gas: bool,
         tests: TestPlan,
         // TODO: maybe we should require the clients to always pass in a list of native functions so
         // we don't have to make assumptions about their gas parameters.
@@ -117,6 +120,7 @@ impl TestRunner {
         Ok(Self {
             testing_config: SharedTestingConfig {
                 report_stacktrace_on_abort,
+                report_gas,
                 starting_storage_state,
                 execution_bound,
                 native_function_table,
@@ -229,12 +233,18 @@ impl SharedTestingConfig {
         let extensions = extensions::new_extensions();
         let mut session =
             move_vm.new_session_with_extensions(&self.starting_storage_state, extensions);
-        let mut gas_meter = GasStatus::new(&self.cost_table, Gas::new(self.execution_bound));
+        let mut gas_status = GasStatus::new(&self.cost_table, Gas::new(self.execution_bound));
         #[cfg(debug_assertions)]
-        gas_meter.set_profiler(GasProfiler::init_default_cfg(
+        gas_status.set_profiler(GasProfiler::init_default_cfg(
             function_name.to_owned(),
             self.execution_bound,
         ));
+        let mut gas_meter = FunctionGasMeter::new(
+            gas_status,
+            &test_plan.module_id,
+            function_name,
+            self.report_gas,
+        );
 
         // TODO: collect VM logs if the verbose flag (i.e, `self.verbose`) is set
 
@@ -257,7 +267,7 @@ impl SharedTestingConfig {
                 err.remove_exec_state();
             }
         }
-        let test_run_info = TestRunInfo::new(
+        let mut test_run_info = TestRunInfo::new(
             function_name.to_string(),
             now.elapsed(),
             // TODO(Gas): This doesn't look quite right...
@@ -267,8 +277,14 @@ impl SharedTestingConfig {
                 .unwrap()
                 .into(),
         );
+        test_run_info.function_gas = gas_meter.finish();
         match session.finish_with_extensions().0 {
-            Ok((cs, _, extensions)) => (Ok(cs), Ok(extensions), return_result, test_run_info),
+            Ok((cs, _, extensions)) => {
+                if self.report_gas {
+                    test_run_info.statistics = extensions::extension_statistics(&extensions);
+                }
+                (Ok(cs), Ok(extensions), return_result, test_run_info)
+            }
             Err(err) => (Err(err.clone()), Err(err), return_result, test_run_info),
         }
     }
@@ -0,0 +1,30 @@
+module 0x1::function_gas {
+    use std::hash;
+
+    #[test]
+    fun calls() {
+        callee();
+        callee();
+        nested();
+    }
+
+    #[test]
+    fun native_call() {
+        let _ = hash::sha3_256(b"gas");
+    }
+
+    #[test, expected_failure(abort_code=0, location=0x1::function_gas)]
+    fun aborting_call() {
+        nested_abort();
+    }
+
+    fun callee() {}
+
+    fun nested() {
+        callee();
+    }
+
+    fun nested_abort() {
+        abort 0
+    }
+}
@@ -32,8 +32,9 @@ fn run_test_with_modifiers(
     Ok(results)
 }
 
-// Runs all tests under the test/test_sources directory.
-fn run_test_impl(path: &Path) -> anyhow::Result<()> {
+// Runs all tests under the test/test_sources directory, and under the test/gas_report_sources
+// directory with `report_gas` set.
+fn run_test_impl(path: &Path, report_gas: bool) -> anyhow::Result<()> {
     std::env::set_var("NO_COLOR", "1");
     let update_baseline = read_env_update_baseline();
     let source_files = vec![path.to_str().unwrap().to_owned()];
@@ -46,6 +47,7 @@ fn run_test_impl(path: &Path) -> anyhow::Result<()> {
             .into_iter()
             .collect(),
         report_stacktrace_on_abort: true,
+        report_gas,
 
         ..UnitTestingConfig::default_with_bound(None)
     };
@@ -84,8 +86,20 @@ fn run_test_impl(path: &Path) -> anyhow::Result<()> {
 }
 
 fn run_test(path: &Path) -> datatest_stable::Result<()> {
-    run_test_impl(path)?;
+    run_test_impl(path, false)?;
     Ok(())
 }
 
-datatest_stable::harness!(run_test, "tests/test_sources", r".*\.move$");
+fn run_gas_report_test(path: &Path) -> datatest_stable::Result<()> {
+    run_test_impl(path, true)?;
+    Ok(())
+}
+
+datatest_stable::harness!(
+    run_test,
+    "tests/test_sources",
+    r".*\.move$",
+    run_gas_report_test,
+    "tests/gas_report_sources",
+    r".*\.move$"
+);
@@ -8,7 +8,6 @@ mod checked {
     use crate::gas_charger::GasCharger;
     use move_binary_format::{
         access::ModuleAccess,
-        compatibility::{Compatibility, InclusionCheck},
         errors::{Location, PartialVMResult, VMResult},
         file_format::{AbilitySet, CodeOffset, FunctionDefinitionIndex, LocalIndex, Visibility},
         normalized, CompiledModule,
@@ -664,30 +663,16 @@ mod checked {
         cur_module: &normalized::Module,
         new_module: &normalized::Module,
     ) -> Result<(), ExecutionError> {
-        match policy {
-            UpgradePolicy::Additive => InclusionCheck::Subset.check(cur_module, new_module),
-            UpgradePolicy::DepOnly => InclusionCheck::Equal.check(cur_module, new_module),
-            UpgradePolicy::Compatible => {
-                let compatibility = Compatibility {
-                    check_struct_and_pub_function_linking: true,
-                    check_struct_layout: true,
-                    check_friend_linking: false,
-                    check_private_entry_linking: false,
-                    disallowed_new_abilities: AbilitySet::ALL,
-                    disallow_change_struct_type_params: true,
-                };
-
-                compatibility.check(cur_module, new_module)
-            }
-        }
-        .map_err(|e| {
-            ExecutionError::new_with_source(
-                ExecutionErrorKind::PackageUpgradeError {
-                    upgrade_error: PackageUpgradeError::IncompatibleUpgrade,
-                },
-                e,
-            )
-        })
+        policy
+            .check_compatibility(cur_module, new_module)
+            .map_err(|e| {
+                ExecutionError::new_with_source(
+                    ExecutionErrorKind::PackageUpgradeError {
+                        upgrade_error: PackageUpgradeError::IncompatibleUpgrade,
+                    },
+                    e,
+                )
+            })
     }
 
     fn fetch_package(
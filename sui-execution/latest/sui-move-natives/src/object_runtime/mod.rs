@@ -63,6 +63,9 @@ pub(crate) struct TestInventories {
     pub(crate) taken_immutable_values: BTreeMap<Type, BTreeMap<ObjectID, Value>>,
     // object has been taken from the inventory
     pub(crate) taken: BTreeMap<ObjectID, Owner>,
+    // sizes of the objects created by the transactions ended so far, as metered for storage.
+    // Only recorded when requested, i.e. when reporting gas, as computing them is not free
+    pub(crate) created_object_sizes: Option<BTreeMap<ObjectID, usize>>,
 }
 
 pub struct LoadedRuntimeObject {
@@ -403,6 +406,20 @@ impl<'a> ObjectRuntime<'a> {
         self.state.finish(loaded_child_objects, child_effects)
     }
 
+    /// Record the sizes of the objects created by the transactions of a test scenario, see
+    /// `created_object_sizes_for_testing`.
+    pub fn record_created_object_sizes_for_testing(&mut self) {
+        self.test_inventories
+            .created_object_sizes
+            .get_or_insert_with(BTreeMap::new);
+    }
+
+    /// The sizes of the objects created by the transactions of a test scenario, as metered for
+    /// their storage cost, if they are recorded.
+    pub fn created_object_sizes_for_testing(&self) -> Option<&BTreeMap<ObjectID, usize>> {
+        self.test_inventories.created_object_sizes.as_ref()
+    }
+
     pub(crate) fn all_active_child_objects(
         &self,
     ) -> impl Iterator<Item = (&ObjectID, &Type, Value)> {
@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    get_nth_struct_field, legacy_test_cost,
+    get_nth_struct_field, get_tag_and_layouts, legacy_test_cost,
     object_runtime::{ObjectRuntime, RuntimeResults},
 };
 use linked_hash_map::LinkedHashMap;
@@ -27,7 +27,8 @@ use std::{
     collections::{BTreeMap, BTreeSet, VecDeque},
 };
 use sui_types::{
-    base_types::{ObjectID, SequenceNumber, SuiAddress},
+    base_types::{MoveObjectType, ObjectID, SequenceNumber, SuiAddress},
+    digests::TransactionDigest,
     id::UID,
     object::Owner,
 };
@@ -197,6 +198,24 @@ pub fn end_transaction(
         deleted.push(wrapped)
     }
 
+    // record the size of created objects, to estimate their storage cost in gas reports
+    let object_runtime_ref: &ObjectRuntime = context.extensions().get();
+    let mut created_object_sizes = vec![];
+    if object_runtime_ref
+        .test_inventories
+        .created_object_sizes
+        .is_some()
+    {
+        for id in &created {
+            let Some((ty, value)) = new_object_values.get(id) else {
+                continue;
+            };
+            if let Some(size) = object_size_for_gas_metering(context, ty, value)? {
+                created_object_sizes.push((*id, size));
+            }
+        }
+    }
+
     // new input objects are remaining taken objects not written/deleted
     let object_runtime_ref: &mut ObjectRuntime = context.extensions_mut().get_mut();
     object_runtime_ref.state.input_objects = object_runtime_ref
@@ -205,6 +224,9 @@ pub fn end_transaction(
         .iter()
         .map(|(id, owner)| (*id, *owner))
         .collect::<BTreeMap<_, _>>();
+    if let Some(sizes) = &mut object_runtime_ref.test_inventories.created_object_sizes {
+        sizes.extend(created_object_sizes);
+    }
     // update inventories
     // check for bad updates to immutable values
     for (id, (ty, value)) in new_object_values {
@@ -632,6 +654,33 @@ fn pack_option(opt: Option<Value>) -> Value {
     )]))
 }
 
+/// The size of a new object with type `ty` and value `value`, as computed by
+/// `Object::object_size_for_gas_metering`, or `None` if the value cannot be serialized.
+fn object_size_for_gas_metering(
+    context: &NativeContext,
+    ty: &Type,
+    value: &Value,
+) -> PartialVMResult<Option<usize>> {
+    let Some((tag, layout, _)) = get_tag_and_layouts(context, ty)? else {
+        return Ok(None);
+    };
+    let Some(contents) = value.simple_serialize(&layout) else {
+        return Ok(None);
+    };
+    let Ok(type_size) = bcs::serialized_size(&MoveObjectType::from(tag)) else {
+        return Ok(None);
+    };
+    // the Move object holds its type, `has_public_transfer` flag, version and contents, and the
+    // object its owner, previous transaction and storage rebate
+    let move_object_size = type_size + 1 + 8 + contents.len();
+    Ok(Some(
+        move_object_size
+            + std::mem::size_of::<Owner>()
+            + std::mem::size_of::<TransactionDigest>()
+            + std::mem::size_of::<u64>(),
+    ))
+}
+
 fn find_all_wrapped_objects<'a>(
     context: &NativeContext,
     ids: &mut BTreeSet<ObjectID>,